@@ -2,8 +2,11 @@ arch snes.cpu
 lorom
 
 ; Must match locations in patch/map_tiles.rs
-!item_list_ptrs = $83E000
-!item_list_sizes = $83E00C
+; Dynamic tile data (item and locked door tiles) is all in bank $EA: per-area lists (for the pause map)
+; are referenced by the tables below, and per-room lists (for the HUD minimap) by ExtraRoomData.
+!dynamic_tile_data = $EA0000
+!item_list_ptrs = $EAAE00
+!item_list_sizes = $EAAE0C
 
 !bank_82_freespace_start = $82FD00
 !bank_82_freespace_end = $82FD80
@@ -103,7 +106,7 @@ update_hud_tilemap:
     lda $8F0010,x
     tax            ; x <- extra room data pointer
     lda $B80007,x
-    tax            ; x <- pointer to dynamic tile data (list of 6-byte records) in bank $E4
+    tax            ; x <- pointer to dynamic tile data (list of 6-byte records) in bank $EA
 
     ; $03 <- $703000  (destination tilemap)
     lda #$3000
@@ -111,7 +114,7 @@ update_hud_tilemap:
     lda #$0070
     sta $05
 
-    lda $E40000,x
+    lda.l !dynamic_tile_data,x
     sta $06        ; $06 <- loop counter: size of list
     beq .done      ; if there are no dynamic tiles for this room, then we're done
     inx : inx
@@ -119,18 +122,18 @@ update_hud_tilemap:
 .item_loop:
     lda #$0000
     sep #$20
-    lda $E40000, x
+    lda.l !dynamic_tile_data, x
     phx
     tax
     lda $7ED870, x
     plx
-    and $E40001, x
+    and.l !dynamic_tile_data+1, x
     bne .skip  ; item is collected, so skip overwriting tile
 
     rep #$20
-    lda $E40002, x  ; A <- tilemap offset
+    lda.l !dynamic_tile_data+2, x  ; A <- tilemap offset
     tay
-    lda $E40004, x  ; A <- tilemap word
+    lda.l !dynamic_tile_data+4, x  ; A <- tilemap word
     sta [$03], y
 
 .skip:
@@ -185,27 +188,27 @@ update_pause_tilemap:
     lda $1F5B
     asl
     tax
-    lda !item_list_sizes, x
+    lda.l !item_list_sizes, x
     beq .done  ; If there are no items in this area, then we're done.
     sta $06    ; $06 <- loop counter
-    lda !item_list_ptrs, x
+    lda.l !item_list_ptrs, x
     tax  ; X <- item data offset
 
 .item_loop:
     lda #$0000
     sep #$20
-    lda $830000, x
+    lda.l !dynamic_tile_data, x
     phx
     tax
     lda $7ED870, x
     plx
-    and $830001, x
+    and.l !dynamic_tile_data+1, x
     bne .skip  ; item is collected, so skip overwriting tile
 
     rep #$20
-    lda $830002, x  ; A <- tilemap offset
+    lda.l !dynamic_tile_data+2, x  ; A <- tilemap offset
     tay
-    lda $830004, x  ; A <- tilemap word
+    lda.l !dynamic_tile_data+4, x  ; A <- tilemap word
     sta [$03], y
 
 .skip:
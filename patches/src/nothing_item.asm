@@ -9,7 +9,7 @@ org !bank_84_free_space_start
 dw $EE64, inst        ; PLM $F700 (nothing, for Bomb Torizo Room)
 
 inst:
-    dw $8764, $B100, $0000, $0000, $0000, $0000  ; Load item graphics (zeros, written in patch.rs)
+    dw $8764, $B100, $0000, $0000, $0000, $0000  ; Load item graphics (blank, from gfx/items/nothing.png)
     dw $8A24, .triggered                   ; Set link instruction for when triggered
     dw $86C1, $DF89                        ; Pre-instruction = go to link instruction if triggered
     dw $E04F                               ; Draw item frame 0 (not sure why we need to do this, but it doesn't work otherwise)
@@ -55,6 +55,8 @@ def runAsar(outputFilepath : str, inputFilepath : str):
             '--no-title-check', 
             '--disable-read', 
             '--ips', outputFilepath, 
+            '--symbols=wla', 
+            f'--symbols-path={os.path.splitext(outputFilepath)[0]}.sym', 
             inputFilepath, 
             'dummy.smc'
         ]
@@ -0,0 +1,177 @@
+// On-disk cache of the `GameData` derived from sm-json-data.
+//
+// Parsing the room JSON and deriving the logic graph is the slow part of `GameData::load`,
+// so its result is written to a binary cache file. The cache is keyed by a hash of every input
+// file, together with the crate version and `CACHE_FORMAT_VERSION`, and is transparently rebuilt
+// when anything changes. A cache that cannot be read or written is never fatal: we just fall back
+// to loading from the source data.
+
+use crate::GameData;
+use crate::data_source::DataSource;
+use anyhow::{Context, Result, bail};
+use hashbrown::HashMap;
+use json::JsonValue;
+use log::{info, warn};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+
+const CACHE_MAGIC: &[u8; 8] = b"MRGDATA1";
+
+// Increment this whenever the loading logic changes in a way that affects the cached data,
+// e.g. changes to requirement parsing or to any of the serialized structures.
+pub const CACHE_FORMAT_VERSION: u32 = 2;
+
+pub fn default_cache_path(base_path: &Path) -> PathBuf {
+    base_path.join("../tmp/game_data.cache")
+}
+
+// 64-bit FNV-1a: not cryptographic, but stable across platforms and Rust versions.
+struct Fnv64(u64);
+
+impl Fnv64 {
+    fn new() -> Self {
+        Fnv64(0xcbf29ce484222325)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for &b in data {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+// Compute the cache key from all files that `GameData::load` reads.
+pub fn compute_cache_key(data_source: &dyn DataSource, input_paths: &[String]) -> Result<u64> {
+    let mut hasher = Fnv64::new();
+    hasher.update(&CACHE_FORMAT_VERSION.to_le_bytes());
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    for path in input_paths {
+        let data = data_source
+            .read(path)
+            .with_context(|| format!("Unable to read {path}"))?;
+        // Include the path and length so that moving or concatenating files changes the key.
+        hasher.update(path.as_bytes());
+        hasher.update(&(data.len() as u64).to_le_bytes());
+        hasher.update(&data);
+    }
+    Ok(hasher.0)
+}
+
+fn read_cache(cache_path: &Path, key: u64) -> Result<Option<GameData>> {
+    if !cache_path.exists() {
+        return Ok(None);
+    }
+    let data = std::fs::read(cache_path)?;
+    let header_len = CACHE_MAGIC.len() + 8;
+    if data.len() < header_len || &data[..CACHE_MAGIC.len()] != CACHE_MAGIC {
+        bail!("Not a game data cache file");
+    }
+    let cached_key = u64::from_le_bytes(data[CACHE_MAGIC.len()..header_len].try_into()?);
+    if cached_key != key {
+        return Ok(None);
+    }
+    let game_data: GameData = bincode::deserialize(&data[header_len..])?;
+    Ok(Some(game_data))
+}
+
+fn write_cache(cache_path: &Path, key: u64, game_data: &GameData) -> Result<()> {
+    let mut data = Vec::new();
+    data.extend_from_slice(CACHE_MAGIC);
+    data.extend_from_slice(&key.to_le_bytes());
+    bincode::serialize_into(&mut data, game_data)?;
+    if let Some(dir) = cache_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // Write to a temporary file first, so that concurrent processes never see a partial cache.
+    let tmp_path = cache_path.with_extension(format!("tmp{}", std::process::id()));
+    std::fs::write(&tmp_path, data)?;
+    std::fs::rename(&tmp_path, cache_path)?;
+    Ok(())
+}
+
+// Load the cached game data if it is valid for the given inputs; otherwise build it using
+// `build` and write it to the cache.
+pub fn load_or_build(
+    cache_path: &Path,
+    data_source: &dyn DataSource,
+    input_paths: &[String],
+    build: impl FnOnce() -> Result<GameData>,
+) -> Result<GameData> {
+    let key = compute_cache_key(data_source, input_paths)?;
+    match read_cache(cache_path, key) {
+        Ok(Some(game_data)) => {
+            info!("Loaded game data from cache {}", cache_path.display());
+            return Ok(game_data);
+        }
+        Ok(None) => {
+            info!(
+                "Game data cache {} is missing or stale",
+                cache_path.display()
+            );
+        }
+        Err(e) => {
+            warn!(
+                "Unable to read game data cache {}: {:?}",
+                cache_path.display(),
+                e
+            );
+        }
+    }
+    let game_data = build()?;
+    if let Err(e) = write_cache(cache_path, key, &game_data) {
+        warn!(
+            "Unable to write game data cache {}: {:?}",
+            cache_path.display(),
+            e
+        );
+    }
+    Ok(game_data)
+}
+
+// `JsonValue` does not implement serde traits, so JSON fields are stored as serialized strings.
+pub mod json_value_map {
+    use super::*;
+
+    pub fn serialize<K, S>(map: &HashMap<K, JsonValue>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_map(map.iter().map(|(k, v)| (k, v.dump())))
+    }
+
+    pub fn deserialize<'de, K, D>(deserializer: D) -> Result<HashMap<K, JsonValue>, D::Error>
+    where
+        K: DeserializeOwned + Hash + Eq,
+        D: Deserializer<'de>,
+    {
+        let map: HashMap<K, String> = HashMap::deserialize(deserializer)?;
+        map.into_iter()
+            .map(|(k, v)| {
+                json::parse(&v)
+                    .map(|v| (k, v))
+                    .map_err(serde::de::Error::custom)
+            })
+            .collect()
+    }
+}
+
+pub mod json_value_vec {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(v: &[JsonValue], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(v.iter().map(|x| x.dump()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<JsonValue>, D::Error> {
+        let v: Vec<String> = Vec::deserialize(deserializer)?;
+        v.iter()
+            .map(|x| json::parse(x).map_err(serde::de::Error::custom))
+            .collect()
+    }
+}
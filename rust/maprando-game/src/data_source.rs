@@ -0,0 +1,259 @@
+// Abstraction over where the randomizer's data files come from.
+//
+// All data is addressed by a logical path using '/' separators, relative to a root laid out like
+// the repository:
+//
+//   sm-json-data/...        logic data (rooms, connections, tech, helpers, ...)
+//   room_geometry.json
+//   TitleScreen/Images/...
+//   gfx/...
+//   patches/...             IPS/BPS patches, including Samus sprites and Mosaic tilesets
+//   maps/...                map repositories
+//   data/...                randomizer data (presets, map tiles, start locations, ...)
+//
+// A source can be a directory on disk (`DirSource`), files held in memory (`MemorySource`),
+// which can also be saved to and loaded from a single bundled archive file, or a combination
+// of other sources mounted at different prefixes (`MountSource`). Because the default layout
+// puts `data/` inside the `rust` directory rather than next to the other files, the default
+// source (`default_data_source`) is a `MountSource`.
+
+use anyhow::{Context, Result, bail};
+use json::JsonValue;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const ARCHIVE_MAGIC: &[u8; 8] = b"MRDATA01";
+
+pub trait DataSource: Send + Sync {
+    // Read the file at the given logical path.
+    fn read(&self, path: &str) -> Result<Vec<u8>>;
+
+    // List the logical paths of all files under the given directory (recursively), sorted.
+    // A missing directory gives an empty list.
+    fn list(&self, dir: &str) -> Result<Vec<String>>;
+
+    fn read_to_string(&self, path: &str) -> Result<String> {
+        let data = self.read(path)?;
+        String::from_utf8(data).with_context(|| format!("{path} is not valid UTF-8"))
+    }
+
+    fn read_json(&self, path: &str) -> Result<JsonValue> {
+        let json_str = self.read_to_string(path)?;
+        json::parse(&json_str).with_context(|| format!("unable to parse {path}"))
+    }
+}
+
+fn join_logical(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir.trim_end_matches('/'), name)
+    }
+}
+
+// Files in a directory on disk.
+pub struct DirSource {
+    root: PathBuf,
+}
+
+impl DirSource {
+    pub fn new(root: &Path) -> Self {
+        DirSource {
+            root: root.to_owned(),
+        }
+    }
+
+    fn list_into(&self, dir: &str, out: &mut Vec<String>) -> Result<()> {
+        let path = self.root.join(dir);
+        let entries = path
+            .read_dir()
+            .with_context(|| format!("Unable to read directory {}", path.display()))?;
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name().into_string().unwrap();
+            let logical_path = join_logical(dir, &name);
+            if entry.file_type()?.is_dir() {
+                self.list_into(&logical_path, out)?;
+            } else {
+                out.push(logical_path);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DataSource for DirSource {
+    fn read(&self, path: &str) -> Result<Vec<u8>> {
+        let full_path = self.root.join(path);
+        std::fs::read(&full_path).with_context(|| format!("unable to read {}", full_path.display()))
+    }
+
+    fn list(&self, dir: &str) -> Result<Vec<String>> {
+        let mut out = vec![];
+        if self.root.join(dir).is_dir() {
+            self.list_into(dir, &mut out)?;
+        }
+        out.sort();
+        Ok(out)
+    }
+}
+
+// Files held in memory, e.g. for embedded (wasm) use, or loaded from a bundled archive.
+#[derive(Default)]
+pub struct MemorySource {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl MemorySource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, path: &str, data: Vec<u8>) {
+        self.files.insert(path.to_string(), data);
+    }
+
+    // Copy files from another source: each of `paths` is either a single file, or a directory
+    // whose files are all copied.
+    pub fn add_from(&mut self, source: &dyn DataSource, paths: &[&str]) -> Result<()> {
+        for &path in paths {
+            let files = source.list(path)?;
+            if files.is_empty() {
+                self.files.insert(path.to_string(), source.read(path)?);
+            }
+            for file in files {
+                let data = source.read(&file)?;
+                self.files.insert(file, data);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn from_archive_bytes(data: &[u8]) -> Result<Self> {
+        if data.len() < ARCHIVE_MAGIC.len() || &data[..ARCHIVE_MAGIC.len()] != ARCHIVE_MAGIC {
+            bail!("Not a data archive");
+        }
+        let files: BTreeMap<String, Vec<u8>> = bincode::deserialize(&data[ARCHIVE_MAGIC.len()..])?;
+        Ok(MemorySource { files })
+    }
+
+    pub fn to_archive_bytes(&self) -> Result<Vec<u8>> {
+        let mut data = ARCHIVE_MAGIC.to_vec();
+        bincode::serialize_into(&mut data, &self.files)?;
+        Ok(data)
+    }
+
+    pub fn load_archive(path: &Path) -> Result<Self> {
+        let data = std::fs::read(path)
+            .with_context(|| format!("Unable to read data archive {}", path.display()))?;
+        Self::from_archive_bytes(&data)
+            .with_context(|| format!("Unable to load data archive {}", path.display()))
+    }
+
+    pub fn save_archive(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_archive_bytes()?)
+            .with_context(|| format!("Unable to write data archive {}", path.display()))
+    }
+}
+
+impl DataSource for MemorySource {
+    fn read(&self, path: &str) -> Result<Vec<u8>> {
+        match self.files.get(path) {
+            Some(data) => Ok(data.clone()),
+            None => bail!("{path} not found in data archive"),
+        }
+    }
+
+    fn list(&self, dir: &str) -> Result<Vec<String>> {
+        let prefix = join_logical(dir, "");
+        Ok(self
+            .files
+            .keys()
+            .filter(|k| k.starts_with(&prefix))
+            .cloned()
+            .collect())
+    }
+}
+
+// Other sources mounted at logical path prefixes. Each path is served by the source with the
+// longest matching prefix, which receives the path with the prefix removed.
+#[derive(Default)]
+pub struct MountSource {
+    mounts: Vec<(String, Arc<dyn DataSource>)>,
+}
+
+impl MountSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Mount `source` at `prefix` (a directory such as "data", or "" for the root).
+    pub fn mount(mut self, prefix: &str, source: Arc<dyn DataSource>) -> Self {
+        self.mounts
+            .push((prefix.trim_end_matches('/').to_string(), source));
+        self.mounts.sort_by_key(|(p, _)| std::cmp::Reverse(p.len()));
+        self
+    }
+
+    fn resolve<'a>(&self, path: &'a str) -> Result<(&str, &dyn DataSource, &'a str)> {
+        for (prefix, source) in &self.mounts {
+            if prefix.is_empty() {
+                return Ok((prefix, source.as_ref(), path));
+            }
+            if path == prefix {
+                return Ok((prefix, source.as_ref(), ""));
+            }
+            if let Some(rest) = path.strip_prefix(&join_logical(prefix, "")) {
+                return Ok((prefix, source.as_ref(), rest));
+            }
+        }
+        bail!("No data source mounted for {path}");
+    }
+}
+
+impl DataSource for MountSource {
+    fn read(&self, path: &str) -> Result<Vec<u8>> {
+        let (_, source, rest) = self.resolve(path)?;
+        source.read(rest)
+    }
+
+    fn list(&self, dir: &str) -> Result<Vec<String>> {
+        let (prefix, source, rest) = self.resolve(dir)?;
+        let mut out: Vec<String> = source
+            .list(rest)?
+            .into_iter()
+            .map(|p| join_logical(prefix, &p))
+            .collect();
+        // Files under a more specific mount are hidden from a less specific one:
+        out.retain(|p| {
+            self.resolve(p)
+                .map(|(p2, _, _)| p2 == prefix)
+                .unwrap_or(false)
+        });
+        out.sort();
+        Ok(out)
+    }
+}
+
+// The data source for the standard repository layout, where `base_path` is the `rust` directory
+// (containing `data/`), and everything else is in its parent directory.
+pub fn default_data_source(base_path: &Path) -> Arc<dyn DataSource> {
+    Arc::new(
+        MountSource::new()
+            .mount("", Arc::new(DirSource::new(&base_path.join(".."))))
+            .mount("data", Arc::new(DirSource::new(&base_path.join("data")))),
+    )
+}
+
+// Open the data source for a command-line tool: the bundled archive if one is given, and
+// otherwise the standard layout around `base_path`.
+pub fn open_data_source(
+    base_path: &Path,
+    archive_path: Option<&Path>,
+) -> Result<Arc<dyn DataSource>> {
+    match archive_path {
+        Some(path) => Ok(Arc::new(MemorySource::load_archive(path)?)),
+        None => Ok(default_data_source(base_path)),
+    }
+}
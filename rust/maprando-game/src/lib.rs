@@ -3,14 +3,20 @@
 // TODO: consider removing this later. It's not a bad lint but I don't want to deal with it now.
 #![allow(clippy::too_many_arguments)]
 
+pub mod cache;
+pub mod data_source;
 pub mod glowpatch;
+pub mod lint;
+pub mod requirement_format;
 pub mod smart_xml;
 pub mod util;
 
+use crate::data_source::{DataSource, default_data_source};
 use crate::glowpatch::GlowPatch;
+use crate::lint::LintIssue;
 use anyhow::{Context, Result, bail, ensure};
 use hashbrown::{HashMap, HashSet};
-use image::{Rgb, io::Reader as ImageReader};
+use image::Rgb;
 use json::{self, JsonValue};
 use log::{error, info, warn};
 use ndarray::Array3;
@@ -18,9 +24,9 @@ use num_enum::TryFromPrimitive;
 use serde::{Deserialize, Serialize};
 use std::borrow::ToOwned;
 use std::fmt::{self, Debug, Formatter};
-use std::fs::File;
 use std::hash::Hash;
-use std::path::{Path, PathBuf};
+use std::path::Path;
+use std::sync::Arc;
 use strum::VariantNames;
 use strum_macros::{EnumString, VariantNames};
 
@@ -125,7 +131,7 @@ pub type StepTrailId = i32;
 pub type LinkIdx = i32;
 pub type TraversalId = usize; // Index into Traversal.past_steps
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct IndexedVec<T: Hash + Eq> {
     pub keys: Vec<T>,
     pub index_by_key: HashMap<T, usize>,
@@ -397,6 +403,26 @@ pub enum Requirement {
         can_be_very_patient_tech_id: usize,
         r_mode: bool,
     },
+    KraidFight {
+        can_be_patient_tech_idx: usize,
+        can_be_very_patient_tech_idx: usize,
+        can_be_extremely_patient_tech_idx: usize,
+    },
+    CrocomireFight {
+        can_be_patient_tech_idx: usize,
+        can_be_very_patient_tech_idx: usize,
+        can_be_extremely_patient_tech_idx: usize,
+    },
+    SporeSpawnFight {
+        can_be_patient_tech_idx: usize,
+        can_be_very_patient_tech_idx: usize,
+        can_be_extremely_patient_tech_idx: usize,
+    },
+    GoldenTorizoFight {
+        can_be_patient_tech_idx: usize,
+        can_be_very_patient_tech_idx: usize,
+        can_be_extremely_patient_tech_idx: usize,
+    },
     DoorType {
         room_id: RoomId,
         node_id: NodeId,
@@ -582,7 +608,7 @@ impl Requirement {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Link {
     pub from_vertex_id: VertexId,
     pub to_vertex_id: VertexId,
@@ -714,7 +740,7 @@ pub struct StartLocation {
     pub requires_parsed: Option<Requirement>,
 }
 
-#[derive(Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct HubLocation {
     pub room_id: usize,
     pub node_id: usize,
@@ -780,7 +806,7 @@ fn parse_door_orientation(door_orientation: &str) -> Result<DoorOrientation> {
     })
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GModeRegainMobility {}
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -1202,7 +1228,7 @@ fn compute_runway_effective_length(geom: &RunwayGeometry) -> f32 {
         + 5.0 / 59.0 * geom.gentle_down_tiles
 }
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct LinksDataGroup {
     pub links: Vec<Link>,
     pub links_by_src: Vec<Vec<(LinkIdx, Link)>>,
@@ -1309,10 +1335,15 @@ pub struct TitleScreenData {
 }
 
 pub fn read_image(path: &Path) -> Result<Array3<u8>> {
-    let img = ImageReader::open(path)
-        .with_context(|| format!("Unable to open image: {}", path.display()))?
-        .decode()
-        .with_context(|| format!("Unable to decode image: {}", path.display()))?
+    let data =
+        std::fs::read(path).with_context(|| format!("Unable to open image: {}", path.display()))?;
+    decode_image(&data, &path.display().to_string())
+}
+
+// Decode an image from the contents of an image file; `name` is used only in error messages.
+pub fn decode_image(data: &[u8], name: &str) -> Result<Array3<u8>> {
+    let img = image::load_from_memory(data)
+        .with_context(|| format!("Unable to decode image: {name}"))?
         .to_rgb8();
     let width = img.width() as usize;
     let height = img.height() as usize;
@@ -1351,7 +1382,7 @@ pub struct VertexKey {
     pub actions: Vec<VertexAction>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct NotableInfo {
     pub room_id: RoomId,
     pub notable_id: NotableId,
@@ -1537,7 +1568,7 @@ pub struct VariableWidthFont {
     pub char_isv: IndexedVec<char>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ExitInfo {
     pub vertex_id: VertexId,
     pub exit_condition: ExitCondition,
@@ -1550,9 +1581,14 @@ pub struct ExitInfo {
 // more structured; combine maps with the same keys; also maybe unify the room geometry data
 // with sm-json-data and cut back on the amount of different
 // keys/IDs/indexes for rooms, nodes, and doors.
-#[derive(Default, Clone)]
+//
+// Serialization is used only for the on-disk cache (see `cache.rs`): skipped fields are loaded
+// from the auxiliary data files after the cache is read, so they are always at their defaults
+// at the point where the cache is written.
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct GameData {
-    sm_json_data_path: PathBuf,
+    #[serde(skip)]
+    data_source: Option<Arc<dyn DataSource>>,
     pub tech_isv: IndexedVec<TechId>,
     pub notable_isv: IndexedVec<(RoomId, NotableId)>,
     pub notable_info: Vec<NotableInfo>,
@@ -1562,23 +1598,30 @@ pub struct GameData {
     weapon_categories: HashMap<String, Vec<String>>, // map from weapon category to specific weapons with that category
     enemy_attack_damage: HashMap<(String, String), Capacity>,
     enemy_vulnerabilities: HashMap<String, EnemyVulnerabilities>,
+    #[serde(with = "cache::json_value_map")]
     enemy_json: HashMap<String, JsonValue>,
+    #[serde(with = "cache::json_value_map")]
     weapon_json_map: HashMap<String, JsonValue>,
     non_ammo_weapon_mask: WeaponMask,
+    #[serde(with = "cache::json_value_map")]
     pub tech_json_map: HashMap<TechId, JsonValue>,
     pub tech_names: HashMap<TechId, String>,
     pub tech_id_by_name: HashMap<String, TechId>,
     pub notable_id_by_name: HashMap<(RoomId, String), NotableId>,
+    #[serde(with = "cache::json_value_map")]
     pub helper_json_map: HashMap<String, JsonValue>,
     pub helper_category_map: HashMap<String, String>,
     pub tech_requirement: HashMap<(TechId, bool), Option<Requirement>>,
     pub helpers: HashMap<String, Option<Requirement>>,
     pub numeric_isv: IndexedVec<String>,
+    #[serde(with = "cache::json_value_vec")]
     pub numeric_json: Vec<JsonValue>,
     pub numeric_values: Vec<Numeric>,
+    #[serde(with = "cache::json_value_map")]
     pub room_json_map: HashMap<RoomId, JsonValue>,
     pub room_obstacle_idx_map: HashMap<RoomId, HashMap<String, usize>>,
     pub room_full_area: HashMap<RoomId, String>,
+    #[serde(with = "cache::json_value_map")]
     pub node_json_map: HashMap<(RoomId, NodeId), JsonValue>,
     pub node_spawn_at_map: HashMap<(RoomId, NodeId), NodeId>,
     pub reverse_node_ptr_map: HashMap<NodePtr, (RoomId, NodeId)>,
@@ -1593,6 +1636,7 @@ pub struct GameData {
     pub reverse_door_ptr_pair_map: HashMap<(RoomId, NodeId), DoorPtrPair>,
     pub door_position: HashMap<(RoomId, NodeId), DoorOrientation>,
     pub vertex_isv: IndexedVec<VertexKey>,
+    #[serde(with = "cache::json_value_map")]
     pub grey_lock_map: HashMap<(RoomId, NodeId), JsonValue>,
     pub item_locations: Vec<(RoomId, NodeId)>,
     pub item_vertex_ids: Vec<Vec<VertexId>>,
@@ -1601,6 +1645,7 @@ pub struct GameData {
     pub save_locations: Vec<(RoomId, NodeId)>,
     pub links: Vec<Link>,
     pub base_links_data: LinksDataGroup,
+    #[serde(skip)]
     pub room_geometry: Vec<RoomGeometry>,
     pub room_ptrs: Vec<RoomPtr>,
     pub room_and_door_idxs_by_door_ptr_pair:
@@ -1620,7 +1665,9 @@ pub struct GameData {
     pub area_map_ptrs: Vec<isize>,
     pub tech_description: HashMap<TechId, String>,
     pub tech_dependencies: HashMap<TechId, Vec<TechId>>,
+    #[serde(skip)]
     pub escape_timings: Vec<EscapeTimingRoom>,
+    #[serde(skip)]
     pub start_locations: Vec<StartLocation>,
     pub start_location_id_map: HashMap<(RoomId, NodeId), StartLocationId>,
     pub hub_farms: Vec<(VertexId, Requirement)>,
@@ -1632,12 +1679,20 @@ pub struct GameData {
     pub carry_flash_suit_tech_idx: TechIdx,
     pub carry_blue_suit_tech_idx: TechIdx,
     pub mother_brain_defeated_flag_id: usize,
+    #[serde(skip)]
     pub title_screen_data: TitleScreenData,
+    #[serde(skip)]
     pub room_name_font: VariableWidthFont,
+    #[serde(skip)]
     pub reduced_flashing_patch: GlowPatch,
+    #[serde(skip)]
     pub strat_videos: HashMap<(RoomId, StratId), Vec<StratVideo>>,
+    #[serde(skip)]
     pub map_tile_data: Vec<MapTileData>,
     pub area_order: Vec<String>,
+    // Problems found while loading in lint mode (see `lint.rs`); `None` when not linting.
+    #[serde(skip)]
+    lint_issues: Option<Vec<LintIssue>>,
 }
 
 impl<T: Hash + Eq> IndexedVec<T> {
@@ -1653,14 +1708,8 @@ impl<T: Hash + Eq> IndexedVec<T> {
     }
 }
 
-fn read_json(path: &Path) -> Result<JsonValue> {
-    let file = File::open(path).with_context(|| format!("unable to open {}", path.display()))?;
-    let json_str = std::io::read_to_string(file)
-        .with_context(|| format!("unable to read {}", path.display()))?;
-    let json_data =
-        json::parse(&json_str).with_context(|| format!("unable to parse {}", path.display()))?;
-    Ok(json_data)
-}
+// Logical path of sm-json-data within the data source.
+const SM_JSON_DATA_DIR: &str = "sm-json-data";
 
 // TODO: Take steep slopes into account here:
 pub fn get_effective_runway_length(used_tiles: f32, open_end: f32) -> f32 {
@@ -1691,7 +1740,7 @@ struct RequirementContext<'a> {
 
 impl GameData {
     fn load_tech(&mut self) -> Result<()> {
-        let full_tech_json = read_json(&self.sm_json_data_path.join("tech.json"))?;
+        let full_tech_json = self.read_sm_json("tech.json")?;
         for tech_category in full_tech_json["techCategories"].members() {
             ensure!(tech_category["techs"].is_array());
             for tech_json in tech_category["techs"].members() {
@@ -1850,7 +1899,7 @@ impl GameData {
     }
 
     fn load_items_and_flags(&mut self) -> Result<()> {
-        let item_json = read_json(&self.sm_json_data_path.join("items.json"))?;
+        let item_json = self.read_sm_json("items.json")?;
 
         for item_name in Item::VARIANTS {
             self.item_isv.add(&item_name.to_string());
@@ -1864,7 +1913,7 @@ impl GameData {
     }
 
     fn load_weapons(&mut self) -> Result<()> {
-        let weapons_json = read_json(&self.sm_json_data_path.join("weapons/main.json"))?;
+        let weapons_json = self.read_sm_json("weapons/main.json")?;
         ensure!(weapons_json["weapons"].is_array());
         for weapon_json in weapons_json["weapons"].members() {
             let name = weapon_json["name"].as_str().unwrap();
@@ -1899,7 +1948,7 @@ impl GameData {
 
     fn load_enemies(&mut self) -> Result<()> {
         for file in ["main.json", "bosses/main.json"] {
-            let enemies_json = read_json(&self.sm_json_data_path.join("enemies").join(file))?;
+            let enemies_json = self.read_sm_json(&format!("enemies/{file}"))?;
             ensure!(enemies_json["enemies"].is_array());
             for enemy_json in enemies_json["enemies"].members() {
                 let enemy_name = enemy_json["name"].as_str().unwrap();
@@ -1993,7 +2042,7 @@ impl GameData {
     }
 
     fn load_numerics(&mut self) -> Result<()> {
-        let numerics_json = read_json(&self.sm_json_data_path.join("numerics.json"))?;
+        let numerics_json = self.read_sm_json("numerics.json")?;
         ensure!(numerics_json["numericCategories"].is_array());
         for category_json in numerics_json["numericCategories"].members() {
             let category = category_json["name"].as_str().unwrap();
@@ -2032,7 +2081,7 @@ impl GameData {
     }
 
     fn load_helpers(&mut self) -> Result<()> {
-        let helpers_json = read_json(&self.sm_json_data_path.join("helpers.json"))?;
+        let helpers_json = self.read_sm_json("helpers.json")?;
         ensure!(helpers_json["helperCategories"].is_array());
 
         for (category_idx, category_json) in helpers_json["helperCategories"].members().enumerate()
@@ -2282,10 +2331,23 @@ impl GameData {
     ) -> Result<Vec<Requirement>> {
         let mut reqs: Vec<Requirement> = Vec::new();
         for req_json in req_jsons {
-            reqs.push(
-                self.parse_requirement(req_json, ctx)
-                    .with_context(|| format!("Processing requirement {req_json}"))?,
-            );
+            let res = self
+                .catch_lint_panic(|game_data| game_data.parse_requirement(req_json, ctx))
+                .with_context(|| format!("Processing requirement {req_json}"));
+            match res {
+                Ok(req) => reqs.push(req),
+                Err(e) if self.is_linting() => {
+                    // Report the problem and carry on, treating the requirement as impossible:
+                    self.add_issue(LintIssue {
+                        room_id: (!ctx.strat_name.is_empty()).then_some(ctx.room_id),
+                        strat: (!ctx.strat_name.is_empty()).then(|| ctx.strat_name.to_string()),
+                        message: format!("{e:#}"),
+                        ..LintIssue::default()
+                    });
+                    reqs.push(Requirement::Never);
+                }
+                Err(e) => return Err(e),
+            }
         }
         Ok(reqs)
     }
@@ -2854,6 +2916,40 @@ impl GameData {
                             [&TECH_ID_CAN_BE_VERY_PATIENT],
                         r_mode,
                     });
+                } else if !value.has_key("explicitWeapons") && !value.has_key("excludedWeapons") {
+                    // Strats restricting the weapons that can be used are left to the generic
+                    // enemy kill logic, since the fight models assume any weapon can be used.
+                    let can_be_patient_tech_idx =
+                        self.tech_isv.index_by_key[&TECH_ID_CAN_BE_PATIENT];
+                    let can_be_very_patient_tech_idx =
+                        self.tech_isv.index_by_key[&TECH_ID_CAN_BE_VERY_PATIENT];
+                    let can_be_extremely_patient_tech_idx =
+                        self.tech_isv.index_by_key[&TECH_ID_CAN_BE_EXTREMELY_PATIENT];
+                    if enemy_set.contains("Kraid") {
+                        return Ok(Requirement::KraidFight {
+                            can_be_patient_tech_idx,
+                            can_be_very_patient_tech_idx,
+                            can_be_extremely_patient_tech_idx,
+                        });
+                    } else if enemy_set.contains("Crocomire") {
+                        return Ok(Requirement::CrocomireFight {
+                            can_be_patient_tech_idx,
+                            can_be_very_patient_tech_idx,
+                            can_be_extremely_patient_tech_idx,
+                        });
+                    } else if enemy_set.contains("Spore Spawn") {
+                        return Ok(Requirement::SporeSpawnFight {
+                            can_be_patient_tech_idx,
+                            can_be_very_patient_tech_idx,
+                            can_be_extremely_patient_tech_idx,
+                        });
+                    } else if enemy_set.contains("Golden Torizo") {
+                        return Ok(Requirement::GoldenTorizoFight {
+                            can_be_patient_tech_idx,
+                            can_be_very_patient_tech_idx,
+                            can_be_extremely_patient_tech_idx,
+                        });
+                    }
                 }
 
                 let mut allowed_weapons: WeaponMask = if value.has_key("explicitWeapons") {
@@ -3093,25 +3189,32 @@ impl GameData {
                 return Ok(Requirement::make_and(reqs));
             }
         }
+        if req_json.is_string() {
+            bail!(
+                "Unknown requirement name (not a tech, helper, item, or flag): {}",
+                req_json
+            );
+        }
         bail!("Unable to parse requirement: {}", req_json);
     }
 
-    pub fn load_rooms(&mut self, room_pattern: &str) -> Result<()> {
-        let mut room_json_map: HashMap<usize, JsonValue> = HashMap::new();
-        for entry in glob::glob(room_pattern).unwrap() {
-            if let Ok(path) = entry {
-                let path_str = path.to_str().with_context(|| {
-                    format!("Unable to convert path to string: {}", path.display())
-                })?;
-                if path_str.contains("ceres") || path_str.contains("roomDiagrams") {
-                    continue;
-                }
+    pub fn load_rooms(&mut self) -> Result<()> {
+        let data_source = self.data_source().clone();
+        let paths = self.get_room_paths()?;
+        self.load_room_files(data_source.as_ref(), &paths)
+    }
 
-                let room_json = read_json(&path)?;
-                room_json_map.insert(room_json["id"].as_usize().unwrap(), room_json);
-            } else {
-                bail!("Error processing region path: {}", entry.err().unwrap());
-            }
+    // Load the given room files, which may come from a different source than the rest of the
+    // game data (e.g. for test scenarios).
+    pub fn load_room_files(
+        &mut self,
+        data_source: &dyn DataSource,
+        paths: &[String],
+    ) -> Result<()> {
+        let mut room_json_map: HashMap<usize, JsonValue> = HashMap::new();
+        for path in paths {
+            let room_json = data_source.read_json(path)?;
+            room_json_map.insert(room_json["id"].as_usize().unwrap(), room_json);
         }
 
         let mut room_id_vec: Vec<usize> = room_json_map.keys().cloned().collect();
@@ -4696,33 +4799,41 @@ impl GameData {
 
         // Process strats:
         ensure!(room_json["strats"].is_array());
-        for strat_json in room_json["strats"].members() {
-            self.process_strat(
-                strat_json,
-                room_json,
-                &obstacles_idx_map,
-                &notable_map,
-                &node_implicit_door_unlocks,
-            )
-            .context(format!(
-                "Processing {} strat '{}'",
-                strat_json["link"], strat_json["name"]
-            ))?;
+        for (i, strat_json) in room_json["strats"].members().enumerate() {
+            let res = self
+                .catch_lint_panic(|game_data| {
+                    game_data.process_strat(
+                        strat_json,
+                        room_json,
+                        &obstacles_idx_map,
+                        &notable_map,
+                        &node_implicit_door_unlocks,
+                    )
+                })
+                .context(format!(
+                    "Processing {} strat '{}'",
+                    strat_json["link"], strat_json["name"]
+                ));
+            if let Err(e) = res {
+                if !self.is_linting() {
+                    return Err(e);
+                }
+                self.add_issue(LintIssue {
+                    room_id: Some(room_id),
+                    strat: strat_json["name"].as_str().map(|x| x.to_string()),
+                    json_path: Some(format!("strats[{i}]")),
+                    message: format!("{e:#}"),
+                    ..LintIssue::default()
+                });
+            }
         }
         Ok(())
     }
 
     fn load_connections(&mut self) -> Result<()> {
-        let connection_pattern =
-            self.sm_json_data_path.to_str().unwrap().to_string() + "/connection/**/*.json";
-        for entry in glob::glob(&connection_pattern)? {
-            if let Ok(path) = entry {
-                if !path.to_str().unwrap().contains("ceres") {
-                    self.process_connections(&read_json(&path)?)?;
-                }
-            } else {
-                bail!("Error processing connection path: {}", entry.err().unwrap());
-            }
+        for path in self.get_connection_paths()? {
+            let connection_json = self.data_source().read_json(&path)?;
+            self.process_connections(&connection_json)?;
         }
         Ok(())
     }
@@ -4909,18 +5020,22 @@ impl GameData {
         weapon_mask
     }
 
-    fn load_escape_timings(&mut self, path: &Path) -> Result<()> {
-        let escape_timings_str = std::fs::read_to_string(path)
-            .with_context(|| format!("Unable to load escape timings at {}", path.display()))?;
+    fn load_escape_timings(&mut self, path: &str) -> Result<()> {
+        let escape_timings_str = self
+            .data_source()
+            .read_to_string(path)
+            .with_context(|| format!("Unable to load escape timings at {path}"))?;
         let escape_timing_data: EscapeTimingData = serde_json::from_str(&escape_timings_str)?;
         self.escape_timings = escape_timing_data.rooms;
         assert_eq!(self.escape_timings.len(), self.room_geometry.len());
         Ok(())
     }
 
-    fn load_start_locations(&mut self, path: &Path) -> Result<()> {
-        let start_locations_str = std::fs::read_to_string(path)
-            .with_context(|| format!("Unable to load start locations at {}", path.display()))?;
+    fn load_start_locations(&mut self, path: &str) -> Result<()> {
+        let start_locations_str = self
+            .data_source()
+            .read_to_string(path)
+            .with_context(|| format!("Unable to load start locations at {path}"))?;
         let mut start_locations: Vec<StartLocation> = serde_json::from_str(&start_locations_str)?;
         let mut start_location_id_map: HashMap<(usize, usize), usize> = HashMap::new();
         for (i, loc) in start_locations.iter_mut().enumerate() {
@@ -5007,9 +5122,11 @@ impl GameData {
         Ok(())
     }
 
-    fn load_room_geometry(&mut self, path: &Path) -> Result<()> {
-        let room_geometry_str = std::fs::read_to_string(path)
-            .with_context(|| format!("Unable to load room geometry at {}", path.display()))?;
+    fn load_room_geometry(&mut self, path: &str) -> Result<()> {
+        let room_geometry_str = self
+            .data_source()
+            .read_to_string(path)
+            .with_context(|| format!("Unable to load room geometry at {path}"))?;
         let room_geometry: Vec<RoomGeometry> = serde_json::from_str(&room_geometry_str)?;
         for (room_idx, room) in room_geometry.iter().enumerate() {
             if room.name == "Toilet" {
@@ -5205,18 +5322,15 @@ impl GameData {
         }
     }
 
-    pub fn load_title_screens(&mut self, path: &Path) -> Result<()> {
+    pub fn load_title_screens(&mut self, dir: &str) -> Result<()> {
         info!("Loading title screens");
-        let file_it = path.read_dir().with_context(|| {
-            format!(
-                "Unable to read title screen directory at {}",
-                path.display()
-            )
-        })?;
-        for file in file_it {
-            let file = file?;
-            let filename = file.file_name().into_string().unwrap();
-            let img = read_image(&file.path())?;
+        let paths = self
+            .data_source()
+            .list(dir)
+            .with_context(|| format!("Unable to read title screen directory at {dir}"))?;
+        for path in paths {
+            let filename = path.rsplit('/').next().unwrap();
+            let img = decode_image(&self.data_source().read(&path)?, &path)?;
 
             if filename.starts_with("TL") {
                 self.title_screen_data.top_left.push(img);
@@ -5233,8 +5347,8 @@ impl GameData {
         Ok(())
     }
 
-    pub fn load_room_name_font(&mut self, path: &Path) -> Result<()> {
-        let img = read_image(path)?;
+    pub fn load_room_name_font(&mut self, path: &str) -> Result<()> {
+        let img = decode_image(&self.data_source().read(path)?, path)?;
         let dim = img.dim();
         let char_map = [
             "ABCDEFGHIJKLMNOPQRSTUVWXYZ",
@@ -5287,20 +5401,20 @@ impl GameData {
         Ok(())
     }
 
-    fn load_reduced_flashing_patch(&mut self, path: &Path) -> Result<()> {
-        let reduced_flashing_str = std::fs::read_to_string(path).with_context(|| {
-            format!(
-                "Unable to load reduced flashing patch at {}",
-                path.display()
-            )
-        })?;
+    fn load_reduced_flashing_patch(&mut self, path: &str) -> Result<()> {
+        let reduced_flashing_str = self
+            .data_source()
+            .read_to_string(path)
+            .with_context(|| format!("Unable to load reduced flashing patch at {path}"))?;
         self.reduced_flashing_patch = serde_json::from_str(&reduced_flashing_str)?;
         Ok(())
     }
 
-    fn load_strat_videos(&mut self, path: &Path) -> Result<()> {
-        let strat_videos_str = std::fs::read_to_string(path)
-            .with_context(|| format!("Unable to load strat videos at {}", path.display()))?;
+    fn load_strat_videos(&mut self, path: &str) -> Result<()> {
+        let strat_videos_str = self
+            .data_source()
+            .read_to_string(path)
+            .with_context(|| format!("Unable to load strat videos at {path}"))?;
         let strat_videos: Vec<StratVideo> = serde_json::from_str(&strat_videos_str)?;
         for video in strat_videos {
             self.strat_videos
@@ -5311,9 +5425,11 @@ impl GameData {
         Ok(())
     }
 
-    fn load_map_tile_data(&mut self, path: &Path) -> Result<()> {
-        let map_tile_data_str = std::fs::read_to_string(path)
-            .with_context(|| format!("Unable to load map tile data at {}", path.display()))?;
+    fn load_map_tile_data(&mut self, path: &str) -> Result<()> {
+        let map_tile_data_str = self
+            .data_source()
+            .read_to_string(path)
+            .with_context(|| format!("Unable to load map tile data at {path}"))?;
         let map_tile_data_file: MapTileDataFile = serde_json::from_str(&map_tile_data_str)?;
         self.map_tile_data = map_tile_data_file.rooms;
         for room in &mut self.map_tile_data {
@@ -5382,10 +5498,43 @@ impl GameData {
         out
     }
 
+    pub fn data_source(&self) -> &Arc<dyn DataSource> {
+        self.data_source
+            .as_ref()
+            .expect("game data was not loaded from a data source")
+    }
+
+    fn read_sm_json(&self, path: &str) -> Result<JsonValue> {
+        self.data_source()
+            .read_json(&format!("{SM_JSON_DATA_DIR}/{path}"))
+    }
+
+    // Paths of the room files in sm-json-data, excluding Ceres which is not used.
+    fn get_room_paths(&self) -> Result<Vec<String>> {
+        let mut paths = self
+            .data_source()
+            .list(&format!("{SM_JSON_DATA_DIR}/region"))?;
+        paths.retain(|p| {
+            p.ends_with(".json") && !p.contains("ceres") && !p.contains("roomDiagrams")
+        });
+        Ok(paths)
+    }
+
+    fn get_connection_paths(&self) -> Result<Vec<String>> {
+        let mut paths = self
+            .data_source()
+            .list(&format!("{SM_JSON_DATA_DIR}/connection"))?;
+        paths.retain(|p| p.ends_with(".json") && !p.contains("ceres"));
+        Ok(paths)
+    }
+
     pub fn load_minimal(base_path: &Path) -> Result<GameData> {
-        let sm_json_data_path = base_path.join("../sm-json-data");
+        Self::load_minimal_from_source(default_data_source(base_path))
+    }
+
+    pub fn load_minimal_from_source(data_source: Arc<dyn DataSource>) -> Result<GameData> {
         let mut game_data = GameData {
-            sm_json_data_path,
+            data_source: Some(data_source),
             ..GameData::default()
         };
 
@@ -5399,20 +5548,66 @@ impl GameData {
         Ok(game_data)
     }
 
+    // Load the parts of the game data derived from sm-json-data. This is the slow part of
+    // loading, and its result is what gets cached.
+    fn load_sm_json_data(data_source: Arc<dyn DataSource>) -> Result<GameData> {
+        let mut game_data = Self::load_minimal_from_source(data_source)?;
+        game_data.load_rooms()?;
+        game_data.load_connections()?;
+        game_data.extract_all_tech_dependencies()?;
+        game_data.extract_all_strat_dependencies()?;
+        Ok(game_data)
+    }
+
+    fn get_cache_input_paths(data_source: &dyn DataSource) -> Result<Vec<String>> {
+        let mut paths: Vec<String> = data_source
+            .list(SM_JSON_DATA_DIR)?
+            .into_iter()
+            .filter(|p| p.ends_with(".json"))
+            .collect();
+        for path in [
+            "room_geometry.json",
+            "data/escape_timings.json",
+            "data/start_locations.json",
+            "data/room_name_font.png",
+            "data/reduced_flashing.json",
+            "data/strat_videos.json",
+            "data/map_tiles.json",
+        ] {
+            paths.push(path.to_string());
+        }
+        Ok(paths)
+    }
+
     pub fn load(base_path: &Path) -> Result<GameData> {
-        let mut game_data = Self::load_minimal(base_path)?;
+        Self::load_with_cache(base_path, Some(&cache::default_cache_path(base_path)))
+    }
 
-        let room_geometry_path = base_path.join("../room_geometry.json");
-        let escape_timings_path = base_path.join("data/escape_timings.json");
-        let start_locations_path = base_path.join("data/start_locations.json");
-        let title_screen_path = base_path.join("../TitleScreen/Images");
-        let room_name_font_path = base_path.join("data/room_name_font.png");
-        let reduced_flashing_path = base_path.join("data/reduced_flashing.json");
-        let strat_videos_path = base_path.join("data/strat_videos.json");
-        let map_tile_path = base_path.join("data/map_tiles.json");
+    pub fn load_with_cache(base_path: &Path, cache_path: Option<&Path>) -> Result<GameData> {
+        Self::load_from_source(default_data_source(base_path), cache_path)
+    }
+
+    // Load the game data from the given data source, using the cache at `cache_path` (if given)
+    // for the parts derived from sm-json-data. The cache is rebuilt if it is missing or any of
+    // the input files have changed.
+    pub fn load_from_source(
+        data_source: Arc<dyn DataSource>,
+        cache_path: Option<&Path>,
+    ) -> Result<GameData> {
+        let mut game_data = match cache_path {
+            Some(cache_path) => {
+                let input_paths = Self::get_cache_input_paths(data_source.as_ref())?;
+                cache::load_or_build(cache_path, data_source.as_ref(), &input_paths, || {
+                    Self::load_sm_json_data(data_source.clone())
+                })?
+            }
+            None => Self::load_sm_json_data(data_source.clone())?,
+        };
+        // The data source is not part of the cache:
+        game_data.data_source = Some(data_source);
 
-        game_data.load_reduced_flashing_patch(&reduced_flashing_path)?;
-        game_data.load_strat_videos(&strat_videos_path)?;
+        game_data.load_reduced_flashing_patch("data/reduced_flashing.json")?;
+        game_data.load_strat_videos("data/strat_videos.json")?;
 
         game_data.area_order = vec![
             "Central Crateria",
@@ -5439,20 +5634,13 @@ impl GameData {
         .map(|x| x.to_string())
         .collect();
 
-        let room_pattern =
-            game_data.sm_json_data_path.to_str().unwrap().to_string() + "/region/**/*.json";
-        game_data.load_rooms(&room_pattern)?;
-        game_data.load_connections()?;
-        game_data.extract_all_tech_dependencies()?;
-        game_data.extract_all_strat_dependencies()?;
-
         game_data
-            .load_room_geometry(&room_geometry_path)
+            .load_room_geometry("room_geometry.json")
             .context("Unable to load room geometry")?;
-        game_data.load_escape_timings(&escape_timings_path)?;
-        game_data.load_start_locations(&start_locations_path)?;
+        game_data.load_escape_timings("data/escape_timings.json")?;
+        game_data.load_start_locations("data/start_locations.json")?;
         game_data.load_hub_locations()?;
-        game_data.load_map_tile_data(&map_tile_path)?;
+        game_data.load_map_tile_data("data/map_tiles.json")?;
         game_data.area_names = vec![
             "Crateria",
             "Brinstar",
@@ -5472,8 +5660,8 @@ impl GameData {
             0x1AC000, // Maridia
             0x1AD000, // Tourian
         ];
-        game_data.load_title_screens(&title_screen_path)?;
-        game_data.load_room_name_font(&room_name_font_path)?;
+        game_data.load_title_screens("TitleScreen/Images")?;
+        game_data.load_room_name_font("data/room_name_font.png")?;
 
         // for link in &game_data.links {
         //     let from_vertex_id = link.from_vertex_id;
@@ -0,0 +1,510 @@
+// Lint mode for sm-json-data: instead of stopping at the first error, the loader records every
+// problem it finds (together with where it was found) and keeps going, so that logic changes can
+// be checked in full before they reach the randomizer.
+
+use crate::data_source::DataSource;
+use crate::{
+    ExitCondition, GameData, MainEntranceCondition, NodeId, RoomId, SM_JSON_DATA_DIR,
+    ToiletCondition,
+};
+use anyhow::{Result, anyhow};
+use hashbrown::{HashMap, HashSet};
+use json::JsonValue;
+use std::fmt;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+
+#[derive(Clone, Debug, Default)]
+pub struct LintIssue {
+    pub file: Option<String>,
+    pub room_id: Option<RoomId>,
+    pub room: Option<String>,
+    pub node_id: Option<NodeId>,
+    pub strat: Option<String>,
+    pub json_path: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{file}: ")?;
+        }
+        if let Some(room) = &self.room {
+            write!(f, "room '{room}'")?;
+        } else if let Some(room_id) = self.room_id {
+            write!(f, "room {room_id}")?;
+        }
+        if let Some(node_id) = self.node_id {
+            write!(f, ", node {node_id}")?;
+        }
+        if let Some(strat) = &self.strat {
+            write!(f, ", strat '{strat}'")?;
+        }
+        if let Some(json_path) = &self.json_path {
+            write!(f, " at {json_path}")?;
+        }
+        if self.room.is_some() || self.room_id.is_some() || self.json_path.is_some() {
+            write!(f, ": ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+fn collect_obstacles_cleared(req_json: &JsonValue, out: &mut Vec<String>) {
+    match req_json {
+        JsonValue::Object(obj) => {
+            for (key, value) in obj.iter() {
+                if key == "obstaclesCleared" {
+                    for x in value.members() {
+                        if let Some(s) = x.as_str() {
+                            out.push(s.to_string());
+                        }
+                    }
+                } else {
+                    collect_obstacles_cleared(value, out);
+                }
+            }
+        }
+        JsonValue::Array(arr) => {
+            for x in arr {
+                collect_obstacles_cleared(x, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Whether an exit condition could possibly satisfy an entrance condition, based only on their
+// types. This mirrors the cases accepted by the cross-room requirements in the randomizer.
+fn is_exit_compatible(exit: &ExitCondition, entrance: &MainEntranceCondition) -> bool {
+    use ExitCondition as X;
+    use MainEntranceCondition as E;
+    match entrance {
+        E::ComeInNormally {} => matches!(exit, X::LeaveNormally {}),
+        E::ComeInRunning { .. }
+        | E::ComeInJumping { .. }
+        | E::ComeInShinecharging { .. }
+        | E::ComeInGettingBlueSpeed { .. }
+        | E::ComeInStutterShinecharging { .. }
+        | E::ComeInStutterGettingBlueSpeed { .. }
+        | E::ComeInWithBombBoost {}
+        | E::ComeInWithDoorStuckSetup { .. } => matches!(exit, X::LeaveWithRunway { .. }),
+        E::ComeInSpaceJumping { .. } | E::ComeInBlueSpaceJumping { .. } => {
+            matches!(exit, X::LeaveSpaceJumping { .. })
+        }
+        E::ComeInShinecharged {} | E::ComeInShinechargedJumping {} => matches!(
+            exit,
+            X::LeaveNormally {} | X::LeaveShinecharged { .. } | X::LeaveWithRunway { .. }
+        ),
+        E::ComeInWithSpark { .. } => matches!(
+            exit,
+            X::LeaveNormally {}
+                | X::LeaveShinecharged { .. }
+                | X::LeaveWithRunway { .. }
+                | X::LeaveWithSpark { .. }
+        ),
+        E::ComeInSpeedballing { .. } | E::ComeInWithMockball { .. } => {
+            matches!(
+                exit,
+                X::LeaveWithRunway { .. } | X::LeaveWithMockball { .. }
+            )
+        }
+        E::ComeInWithTemporaryBlue { .. } => matches!(
+            exit,
+            X::LeaveWithRunway { .. } | X::LeaveWithTemporaryBlue { .. }
+        ),
+        E::ComeInSpinning { .. } | E::ComeInBlueSpinning { .. } => {
+            matches!(exit, X::LeaveWithRunway { .. } | X::LeaveSpinning { .. })
+        }
+        E::ComeInWithSpringBallBounce { .. } | E::ComeInWithBlueSpringBallBounce { .. } => {
+            matches!(
+                exit,
+                X::LeaveWithRunway { .. }
+                    | X::LeaveWithMockball { .. }
+                    | X::LeaveWithSpringBallBounce { .. }
+            )
+        }
+        E::ComeInWithRMode {} => matches!(exit, X::LeaveWithGModeSetup { .. }),
+        E::ComeInWithGMode { .. } => {
+            matches!(
+                exit,
+                X::LeaveWithGModeSetup { .. } | X::LeaveWithGMode { .. }
+            )
+        }
+        E::ComeInWithStoredFallSpeed { .. } => matches!(exit, X::LeaveWithStoredFallSpeed { .. }),
+        E::ComeInWithWallJumpBelow { .. } | E::ComeInWithSpaceJumpBelow {} => {
+            matches!(exit, X::LeaveWithDoorFrameBelow { .. })
+        }
+        E::ComeInWithPlatformBelow { .. } => matches!(exit, X::LeaveWithPlatformBelow { .. }),
+        E::ComeInWithSidePlatform { .. } => matches!(exit, X::LeaveWithSidePlatform { .. }),
+        E::ComeInWithGrappleSwing { .. } => matches!(exit, X::LeaveWithGrappleSwing { .. }),
+        E::ComeInWithGrappleJump { .. } => matches!(exit, X::LeaveWithGrappleJump { .. }),
+        E::ComeInWithGrappleTeleport { .. } => matches!(exit, X::LeaveWithGrappleTeleport { .. }),
+        E::ComeInWithSamusEaterTeleport { .. } => {
+            matches!(exit, X::LeaveWithSamusEaterTeleport { .. })
+        }
+        E::ComeInWithSuperSink {} => matches!(exit, X::LeaveWithSuperSink {}),
+    }
+}
+
+fn opposite_orientation(orientation: &str) -> &'static str {
+    match orientation {
+        "left" => "right",
+        "right" => "left",
+        "up" => "down",
+        "down" => "up",
+        _ => "",
+    }
+}
+
+impl GameData {
+    pub(crate) fn is_linting(&self) -> bool {
+        self.lint_issues.is_some()
+    }
+
+    pub(crate) fn add_issue(&mut self, issue: LintIssue) {
+        self.lint_issues.as_mut().unwrap().push(issue);
+    }
+
+    // Run `f`, converting panics into errors while linting, so that problems which the loader
+    // would otherwise treat as fatal (e.g. missing keys) can be reported and skipped.
+    pub(crate) fn catch_lint_panic<T>(
+        &mut self,
+        f: impl FnOnce(&mut GameData) -> Result<T>,
+    ) -> Result<T> {
+        if !self.is_linting() {
+            return f(self);
+        }
+        match std::panic::catch_unwind(AssertUnwindSafe(|| f(self))) {
+            Ok(res) => res,
+            Err(payload) => Err(anyhow!("panic: {}", panic_message(payload.as_ref()))),
+        }
+    }
+
+    fn lint_rooms(&mut self) -> HashMap<RoomId, String> {
+        let mut room_paths: HashMap<RoomId, String> = HashMap::new();
+        let mut room_json_map: HashMap<RoomId, JsonValue> = HashMap::new();
+        let paths = match self.get_room_paths() {
+            Ok(paths) => paths,
+            Err(e) => {
+                self.add_issue(LintIssue {
+                    message: format!("Error listing room files: {e:#}"),
+                    ..LintIssue::default()
+                });
+                vec![]
+            }
+        };
+        for path in paths {
+            match self.data_source().read_json(&path) {
+                Ok(room_json) => {
+                    if let Some(room_id) = room_json["id"].as_usize() {
+                        room_paths.insert(room_id, path);
+                        room_json_map.insert(room_id, room_json);
+                    } else {
+                        self.add_issue(LintIssue {
+                            file: Some(path),
+                            json_path: Some("id".to_string()),
+                            message: "missing or invalid room ID".to_string(),
+                            ..LintIssue::default()
+                        });
+                    }
+                }
+                Err(e) => self.add_issue(LintIssue {
+                    file: Some(path),
+                    message: format!("{e:#}"),
+                    ..LintIssue::default()
+                }),
+            }
+        }
+
+        let mut room_id_vec: Vec<RoomId> = room_json_map.keys().cloned().collect();
+        room_id_vec.sort();
+        for room_id in room_id_vec {
+            let room_json = &room_json_map[&room_id];
+            let res = self
+                .catch_lint_panic(|game_data| game_data.preprocess_room(room_json))
+                .and_then(|preprocessed_room_json| {
+                    self.catch_lint_panic(|game_data| {
+                        game_data.process_room(&preprocessed_room_json)
+                    })
+                });
+            if let Err(e) = res {
+                self.add_issue(LintIssue {
+                    room_id: Some(room_id),
+                    room: room_json["name"].as_str().map(|x| x.to_string()),
+                    message: format!("{e:#}"),
+                    ..LintIssue::default()
+                });
+            }
+        }
+        if let Err(e) = self.catch_lint_panic(|game_data| game_data.populate_target_locations()) {
+            self.add_issue(LintIssue {
+                message: format!("Populating target locations: {e:#}"),
+                ..LintIssue::default()
+            });
+        }
+        room_paths
+    }
+
+    fn lint_connections(&mut self) {
+        let paths = match self.get_connection_paths() {
+            Ok(paths) => paths,
+            Err(e) => {
+                self.add_issue(LintIssue {
+                    message: format!("Error listing connection files: {e:#}"),
+                    ..LintIssue::default()
+                });
+                vec![]
+            }
+        };
+        for path in paths {
+            let connection_json = match self.data_source().read_json(&path) {
+                Ok(x) => x,
+                Err(e) => {
+                    self.add_issue(LintIssue {
+                        file: Some(path),
+                        message: format!("{e:#}"),
+                        ..LintIssue::default()
+                    });
+                    continue;
+                }
+            };
+            for (i, connection) in connection_json["connections"].members().enumerate() {
+                for (j, node) in connection["nodes"].members().enumerate() {
+                    let room_id = node["roomid"].as_usize().unwrap_or(0);
+                    let node_id = node["nodeid"].as_usize().unwrap_or(0);
+                    if !self.node_json_map.contains_key(&(room_id, node_id)) {
+                        self.add_issue(LintIssue {
+                            file: Some(path.clone()),
+                            json_path: Some(format!("connections[{i}].nodes[{j}]")),
+                            message: format!(
+                                "connection references unknown node {node_id} in room {room_id}"
+                            ),
+                            ..LintIssue::default()
+                        });
+                    }
+                }
+            }
+            if let Err(e) =
+                self.catch_lint_panic(|game_data| game_data.process_connections(&connection_json))
+            {
+                self.add_issue(LintIssue {
+                    file: Some(path),
+                    message: format!("{e:#}"),
+                    ..LintIssue::default()
+                });
+            }
+        }
+
+        // Every door node with an address should be matched by a connection:
+        let mut door_nodes: Vec<(RoomId, NodeId)> = self.node_ptr_map.keys().copied().collect();
+        door_nodes.sort();
+        for (room_id, node_id) in door_nodes {
+            let node_json = &self.node_json_map[&(room_id, node_id)];
+            // The West Ocean Bridge doors share addresses with West Ocean, and are connected
+            // separately by the randomizer:
+            if node_json["nodeType"] != "door" || [(32, 7), (32, 8)].contains(&(room_id, node_id)) {
+                continue;
+            }
+            if !self
+                .reverse_door_ptr_pair_map
+                .contains_key(&(room_id, node_id))
+            {
+                self.add_issue(LintIssue {
+                    room_id: Some(room_id),
+                    node_id: Some(node_id),
+                    message: format!(
+                        "door node address {:#x} is not matched by any connection",
+                        self.node_ptr_map[&(room_id, node_id)]
+                    ),
+                    ..LintIssue::default()
+                });
+            }
+        }
+    }
+
+    fn lint_obstacles(&mut self) {
+        let mut room_ids: Vec<RoomId> = self.room_json_map.keys().copied().collect();
+        room_ids.sort();
+        let mut issues = vec![];
+        for room_id in room_ids {
+            let room_json = &self.room_json_map[&room_id];
+            let obstacles: HashSet<String> = room_json["obstacles"]
+                .members()
+                .filter_map(|x| x["id"].as_str().map(|s| s.to_string()))
+                .collect();
+            let mut cleared: HashSet<String> = HashSet::new();
+            for (i, strat_json) in room_json["strats"].members().enumerate() {
+                for x in strat_json["clearsObstacles"].members() {
+                    let obstacle = x.as_str().unwrap_or_default().to_string();
+                    if !obstacles.contains(&obstacle) {
+                        issues.push(LintIssue {
+                            room_id: Some(room_id),
+                            strat: strat_json["name"].as_str().map(|x| x.to_string()),
+                            json_path: Some(format!("strats[{i}].clearsObstacles")),
+                            message: format!("undefined obstacle '{obstacle}'"),
+                            ..LintIssue::default()
+                        });
+                    }
+                    cleared.insert(obstacle);
+                }
+            }
+            for (i, strat_json) in room_json["strats"].members().enumerate() {
+                let mut required = vec![];
+                collect_obstacles_cleared(&strat_json["requires"], &mut required);
+                for obstacle in required {
+                    if !cleared.contains(&obstacle) {
+                        issues.push(LintIssue {
+                            room_id: Some(room_id),
+                            strat: strat_json["name"].as_str().map(|x| x.to_string()),
+                            json_path: Some(format!("strats[{i}].requires")),
+                            message: format!(
+                                "requires obstacle '{obstacle}' to be cleared, but no strat clears it"
+                            ),
+                            ..LintIssue::default()
+                        });
+                    }
+                }
+            }
+        }
+        self.lint_issues.as_mut().unwrap().extend(issues);
+    }
+
+    fn lint_entrance_exit_conditions(&mut self) {
+        let orientation = |game_data: &GameData, key: &(RoomId, NodeId)| -> String {
+            game_data.node_json_map[key]["doorOrientation"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string()
+        };
+        let mut exits: Vec<(String, &ExitCondition)> = vec![];
+        for (key, exit_infos) in &self.node_exit_conditions {
+            for exit_info in exit_infos {
+                exits.push((orientation(self, key), &exit_info.exit_condition));
+            }
+        }
+        let mut entrances: Vec<(String, &MainEntranceCondition)> = vec![];
+        for (key, entrance_conditions) in &self.node_entrance_conditions {
+            for (_, entrance_condition) in entrance_conditions {
+                if entrance_condition.through_toilet != ToiletCondition::Yes {
+                    entrances.push((orientation(self, key), &entrance_condition.main));
+                }
+            }
+        }
+
+        let mut issues = vec![];
+        let mut entrance_keys: Vec<&(RoomId, NodeId)> =
+            self.node_entrance_conditions.keys().collect();
+        entrance_keys.sort();
+        for key in entrance_keys {
+            let target = opposite_orientation(&orientation(self, key));
+            for (vertex_id, entrance_condition) in &self.node_entrance_conditions[key] {
+                if entrance_condition.through_toilet == ToiletCondition::Yes {
+                    // Vertical doors through the Toilet are not checked.
+                    continue;
+                }
+                let found = exits.iter().any(|(o, exit)| {
+                    o == target && is_exit_compatible(exit, &entrance_condition.main)
+                });
+                if !found {
+                    issues.push(LintIssue {
+                        room_id: Some(key.0),
+                        node_id: Some(key.1),
+                        message: format!(
+                            "entrance condition has no matching exit condition on any {target} door (vertex {vertex_id}): {:?}",
+                            entrance_condition.main
+                        ),
+                        ..LintIssue::default()
+                    });
+                }
+            }
+        }
+
+        let mut exit_keys: Vec<&(RoomId, NodeId)> = self.node_exit_conditions.keys().collect();
+        exit_keys.sort();
+        for key in exit_keys {
+            let target = opposite_orientation(&orientation(self, key));
+            for exit_info in &self.node_exit_conditions[key] {
+                let exit = &exit_info.exit_condition;
+                if matches!(exit, ExitCondition::LeaveNormally {}) {
+                    continue;
+                }
+                let found = entrances
+                    .iter()
+                    .any(|(o, entrance)| o == target && is_exit_compatible(exit, entrance));
+                if !found {
+                    issues.push(LintIssue {
+                        room_id: Some(key.0),
+                        node_id: Some(key.1),
+                        message: format!(
+                            "exit condition has no matching entrance condition on any {target} door: {exit:?}"
+                        ),
+                        ..LintIssue::default()
+                    });
+                }
+            }
+        }
+        self.lint_issues.as_mut().unwrap().extend(issues);
+    }
+
+    // Load sm-json-data in lint mode, returning every problem found. An error is returned only
+    // if the global data (items, tech, helpers, weapons, enemies) cannot be loaded at all.
+    pub fn lint(data_source: Arc<dyn DataSource>) -> Result<Vec<LintIssue>> {
+        let mut game_data = GameData::load_minimal_from_source(data_source)?;
+        game_data.lint_issues = Some(vec![]);
+
+        // Panics are reported as issues, so silence the default panic output while linting.
+        let prev_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(|_| {}));
+
+        let room_paths = game_data.lint_rooms();
+        game_data.lint_connections();
+        game_data.lint_obstacles();
+        game_data.lint_entrance_exit_conditions();
+        if let Err(e) =
+            game_data.catch_lint_panic(|game_data| game_data.extract_all_tech_dependencies())
+        {
+            game_data.add_issue(LintIssue {
+                message: format!("Extracting tech dependencies: {e:#}"),
+                ..LintIssue::default()
+            });
+        }
+
+        std::panic::set_hook(prev_hook);
+
+        let mut issues = game_data.lint_issues.take().unwrap();
+        let sm_json_data_prefix = format!("{SM_JSON_DATA_DIR}/");
+        for issue in &mut issues {
+            if let Some(room_id) = issue.room_id {
+                if issue.file.is_none() {
+                    issue.file = room_paths.get(&room_id).cloned();
+                }
+                if issue.room.is_none()
+                    && let Some(room_json) = game_data.room_json_map.get(&room_id)
+                {
+                    issue.room = room_json["name"].as_str().map(|x| x.to_string());
+                }
+            }
+        }
+        // Report files relative to sm-json-data:
+        for issue in &mut issues {
+            if let Some(file) = &issue.file
+                && let Some(rel_path) = file.strip_prefix(&sm_json_data_prefix)
+            {
+                issue.file = Some(rel_path.to_string());
+            }
+        }
+        Ok(issues)
+    }
+}
@@ -0,0 +1,1046 @@
+// Serialization of compiled `Requirement` trees back into sm-json-data syntax.
+//
+// Requirements that came directly from sm-json-data are written back using the same keys that
+// `GameData::parse_requirement` accepts, so in most cases the output can be fed back through the
+// parser. Some requirements only exist after preprocessing (e.g. cross-room requirements derived
+// from entrance/exit conditions, door unlocking, farming); these have no sm-json-data equivalent
+// and are written as a single-key object named after the variant (e.g. `{"damage": {...}}`), or
+// with extra non-standard keys (e.g. `"heated"`) where the sm-json-data form would lose context
+// that the room otherwise provides.
+//
+// The output keeps everything needed to rebuild the requirement exactly, which
+// `Requirement::from_json` does without any room context.
+
+use crate::{
+    BeamType, Capacity, DoorType, EnemyDrop, EnemyVulnerabilities, Float, GameData, Numeric,
+    Requirement, ReserveTriggerHeat, RidleyStuck, TECH_ID_CAN_BE_EXTREMELY_PATIENT,
+    TECH_ID_CAN_BE_PATIENT, TECH_ID_CAN_BE_VERY_PATIENT, TECH_ID_CAN_CARRY_BLUE_SUIT,
+    TECH_ID_CAN_CARRY_FLASH_SUIT, TECH_ID_CAN_SHINESPARK,
+};
+use anyhow::{Context, Result, bail, ensure};
+use json::{self, JsonValue};
+
+// Enemies whose kills are always parsed as boss fights, so a generic `EnemyKill` is never written
+// with their names:
+const FIGHT_ENEMIES: [&str; 5] = [
+    "Phantoon",
+    "Draygon",
+    "Botwoon 1",
+    "Botwoon 2",
+    "Mother Brain 2",
+];
+
+// Enemies whose kills are parsed as boss fights unless the weapons are restricted:
+const UNRESTRICTED_FIGHT_ENEMIES: [&str; 4] =
+    ["Kraid", "Crocomire", "Spore Spawn", "Golden Torizo"];
+
+impl Numeric {
+    pub fn to_json(&self, game_data: &GameData) -> JsonValue {
+        match self {
+            Numeric::Constant(c) => (*c).into(),
+            Numeric::Parameter(idx) => game_data.numeric_isv.keys[*idx as usize].as_str().into(),
+            Numeric::Add(a, b) => {
+                json::object! {"add": [a.to_json(game_data), b.to_json(game_data)]}
+            }
+            Numeric::Mul(a, b) => {
+                json::object! {"mul": [a.to_json(game_data), b.to_json(game_data)]}
+            }
+        }
+    }
+}
+
+fn single_key(key: &str, value: JsonValue) -> JsonValue {
+    let mut out = JsonValue::new_object();
+    out[key] = value;
+    out
+}
+
+fn resource_list(key: &str, resource_type: &str, count: JsonValue) -> JsonValue {
+    let mut resource = JsonValue::new_object();
+    resource["type"] = resource_type.into();
+    resource["count"] = count;
+    single_key(key, JsonValue::Array(vec![resource]))
+}
+
+fn refill_json(resource_type: &str, limit: &Numeric, game_data: &GameData) -> JsonValue {
+    // The parser represents a full refill as a partial refill with a limit of 9999:
+    if limit == &Numeric::Constant(9999) {
+        json::object! {"refill": [resource_type]}
+    } else {
+        json::object! {"partialRefill": {"type": resource_type, "limit": limit.to_json(game_data)}}
+    }
+}
+
+fn runway_json(length_key: &str, used_tiles: f32, heated: bool) -> JsonValue {
+    // With an open end and no slopes, the effective runway length equals the length in tiles.
+    let mut out = JsonValue::new_object();
+    out[length_key] = used_tiles.into();
+    out["openEnd"] = 1.into();
+    if heated {
+        out["heated"] = true.into();
+    }
+    out
+}
+
+fn enemy_drops_json(drops: &[EnemyDrop]) -> JsonValue {
+    let mut out = JsonValue::new_array();
+    for drop in drops {
+        out.push(json::object! {
+            "count": drop.count,
+            "noDrop": drop.nothing_weight.get(),
+            "smallEnergy": drop.small_energy_weight.get(),
+            "bigEnergy": drop.large_energy_weight.get(),
+            "missile": drop.missile_weight.get(),
+            "super": drop.super_weight.get(),
+            "powerBomb": drop.power_bomb_weight.get(),
+        })
+        .unwrap();
+    }
+    out
+}
+
+fn enemies_json(enemy_name: &str, count: usize) -> JsonValue {
+    json::array![JsonValue::Array(vec![enemy_name.into(); count])]
+}
+
+fn boss_kill_json(enemy_name: &str) -> JsonValue {
+    json::object! {"enemyKill": {"enemies": enemies_json(enemy_name, 1)}}
+}
+
+fn door_type_name(door_type: DoorType) -> String {
+    match door_type {
+        DoorType::Beam(beam) => format!("{beam:?}Beam"),
+        other => format!("{other:?}"),
+    }
+}
+
+fn enemy_kill_json(count: usize, vul: &EnemyVulnerabilities, game_data: &GameData) -> JsonValue {
+    // The enemy name is not retained in the compiled requirement, so recover it from the
+    // vulnerabilities. If weapons were restricted, the vulnerabilities will no longer match exactly
+    // and the restriction is expressed with "explicitWeapons".
+    let mut enemy_names: Vec<&String> = game_data
+        .enemy_vulnerabilities
+        .keys()
+        .filter(|x| !FIGHT_ENEMIES.contains(&x.as_str()))
+        .collect();
+    enemy_names.sort();
+    for &name in &enemy_names {
+        if UNRESTRICTED_FIGHT_ENEMIES.contains(&name.as_str()) {
+            // Written with "explicitWeapons" below, so that it's not read back as a boss fight.
+            continue;
+        }
+        if &game_data.enemy_vulnerabilities[name] == vul {
+            return json::object! {"enemyKill": {"enemies": enemies_json(name, count)}};
+        }
+    }
+    for &name in &enemy_names {
+        let base_vul = &game_data.enemy_vulnerabilities[name];
+        if base_vul.hp != vul.hp
+            || vul.non_ammo_vulnerabilities & !base_vul.non_ammo_vulnerabilities != 0
+            || (vul.missile_damage != 0 && vul.missile_damage != base_vul.missile_damage)
+            || (vul.super_damage != 0 && vul.super_damage != base_vul.super_damage)
+            || (vul.power_bomb_damage != 0
+                && vul.power_bomb_damage != base_vul.power_bomb_damage
+                && vul.power_bomb_damage != base_vul.power_bomb_damage / 2)
+        {
+            continue;
+        }
+        let mut weapons = JsonValue::new_array();
+        for (i, weapon_name) in game_data.weapon_isv.keys.iter().enumerate() {
+            if vul.non_ammo_vulnerabilities & (1 << i) != 0 {
+                weapons.push(weapon_name.as_str()).unwrap();
+            }
+        }
+        if vul.missile_damage != 0 {
+            weapons.push("Missile").unwrap();
+        }
+        if vul.super_damage != 0 {
+            weapons.push("Super").unwrap();
+        }
+        if vul.power_bomb_damage != 0 {
+            if vul.power_bomb_damage == base_vul.power_bomb_damage {
+                weapons.push("PowerBomb").unwrap();
+            } else {
+                weapons.push("PowerBombPeriphery").unwrap();
+            }
+        }
+        return json::object! {"enemyKill": {
+            "enemies": enemies_json(name, count),
+            "explicitWeapons": weapons,
+        }};
+    }
+    json::object! {"enemyKill": {
+        "count": count,
+        "hp": vul.hp,
+        "nonAmmoVulnerabilities": vul.non_ammo_vulnerabilities,
+        "missileDamage": vul.missile_damage,
+        "superDamage": vul.super_damage,
+        "powerBombDamage": vul.power_bomb_damage,
+    }}
+}
+
+impl Requirement {
+    // Convert the requirement to sm-json-data style JSON (see the notes at the top of this module).
+    pub fn to_json(&self, game_data: &GameData) -> JsonValue {
+        let num = |n: &Numeric| n.to_json(game_data);
+        match self {
+            Requirement::Free => "free".into(),
+            Requirement::Never => "never".into(),
+            &Requirement::Tech(tech_idx) => {
+                let tech_id = game_data.tech_isv.keys[tech_idx];
+                json::object! {"tech": game_data.tech_names[&tech_id].as_str()}
+            }
+            &Requirement::Notable(notable_idx) => {
+                // Notable names are only unique within a room, so include the room if needed:
+                let info = &game_data.notable_info[notable_idx];
+                if game_data
+                    .notable_info
+                    .iter()
+                    .filter(|x| x.name == info.name)
+                    .count()
+                    > 1
+                {
+                    json::object! {"notable": {"name": info.name.as_str(), "roomId": info.room_id}}
+                } else {
+                    json::object! {"notable": info.name.as_str()}
+                }
+            }
+            &Requirement::Item(item_id) => game_data.item_isv.keys[item_id].as_str().into(),
+            &Requirement::Flag(flag_id) => game_data.flag_isv.keys[flag_id].as_str().into(),
+            &Requirement::NotFlag(flag_id) => {
+                json::object! {"not": game_data.flag_isv.keys[flag_id].as_str()}
+            }
+            Requirement::MotherBrainBarrierClear(i) => {
+                format!("i_MotherBrainBarrier{}Clear", i + 1).into()
+            }
+            Requirement::DisableableETank => json::object! {"disableEquipment": "ETank"},
+            Requirement::Walljump => "canWalljump".into(),
+            Requirement::ShineCharge { used_tiles, heated } => single_key(
+                "canShineCharge",
+                runway_json("usedTiles", used_tiles.get(), *heated),
+            ),
+            Requirement::SpeedBall { used_tiles, heated } => single_key(
+                "speedBall",
+                runway_json("length", used_tiles.get(), *heated),
+            ),
+            Requirement::GetBlueSpeed { used_tiles, heated } => single_key(
+                "getBlueSpeed",
+                runway_json("usedTiles", used_tiles.get(), *heated),
+            ),
+            Requirement::ShineChargeFrames(n) => single_key("shineChargeFrames", num(n)),
+            Requirement::Shinespark {
+                frames,
+                excess_frames,
+                ..
+            } => {
+                let mut spark = json::object! {"frames": *frames};
+                if *excess_frames != 0 {
+                    spark["excessFrames"] = (*excess_frames).into();
+                }
+                single_key("shinespark", spark)
+            }
+            Requirement::HeatFrames(n) => single_key("heatFrames", num(n)),
+            Requirement::SuitlessHeatFrames(n) => single_key("suitlessHeatFrames", num(n)),
+            Requirement::SimpleHeatFrames(n) => single_key("simpleHeatFrames", num(n)),
+            Requirement::HeatFramesWithEnergyDrops(n, drops, buffed_drops) => json::object! {
+                "heatFramesWithEnergyDrops": {
+                    "frames": num(n),
+                    "drops": enemy_drops_json(drops),
+                    "buffedDrops": enemy_drops_json(buffed_drops),
+                }
+            },
+            Requirement::LavaFrames(n) => single_key("lavaFrames", num(n)),
+            Requirement::LavaFramesWithEnergyDrops(n, drops, buffed_drops) => json::object! {
+                "lavaFramesWithEnergyDrops": {
+                    "frames": num(n),
+                    "drops": enemy_drops_json(drops),
+                    "buffedDrops": enemy_drops_json(buffed_drops),
+                }
+            },
+            Requirement::GravitylessLavaFrames(n) => single_key("gravitylessLavaFrames", num(n)),
+            Requirement::AcidFrames(n) => single_key("acidFrames", num(n)),
+            Requirement::GravitylessAcidFrames(n) => single_key("gravitylessAcidFrames", num(n)),
+            Requirement::MetroidFrames(n) => single_key("metroidFrames", num(n)),
+            Requirement::CycleFrames(n) => single_key("cycleFrames", num(n)),
+            Requirement::SimpleCycleFrames(n) => single_key("simpleCycleFrames", num(n)),
+            Requirement::Farm {
+                requirement,
+                enemy_drops,
+                enemy_drops_buffed,
+                full_energy,
+                full_missiles,
+                full_supers,
+                full_power_bombs,
+            } => json::object! {"farm": {
+                "requires": requirement.to_json(game_data),
+                "drops": enemy_drops_json(enemy_drops),
+                "buffedDrops": enemy_drops_json(enemy_drops_buffed),
+                "fullEnergy": *full_energy,
+                "fullMissiles": *full_missiles,
+                "fullSupers": *full_supers,
+                "fullPowerBombs": *full_power_bombs,
+            }},
+            Requirement::Damage {
+                unit_energy,
+                quantity,
+            } => json::object! {"damage": {"unitEnergy": *unit_energy, "quantity": num(quantity)}},
+            Requirement::MissilesAvailable(n) => {
+                resource_list("resourceAvailable", "Missile", num(n))
+            }
+            Requirement::SupersAvailable(n) => resource_list("resourceAvailable", "Super", num(n)),
+            Requirement::PowerBombsAvailable(n) => {
+                resource_list("resourceAvailable", "PowerBomb", num(n))
+            }
+            Requirement::RegularEnergyAvailable(n) => {
+                resource_list("resourceAvailable", "RegularEnergy", num(n))
+            }
+            Requirement::ReserveEnergyAvailable(n) => {
+                resource_list("resourceAvailable", "ReserveEnergy", num(n))
+            }
+            Requirement::EnergyAvailable(n) => resource_list("resourceAvailable", "Energy", num(n)),
+            Requirement::MissilesCapacity(n) => {
+                resource_list("resourceCapacity", "Missile", num(n))
+            }
+            Requirement::SupersCapacity(n) => resource_list("resourceCapacity", "Super", num(n)),
+            Requirement::PowerBombsCapacity(n) => {
+                resource_list("resourceCapacity", "PowerBomb", num(n))
+            }
+            Requirement::RegularEnergyCapacity(n) => {
+                resource_list("resourceCapacity", "RegularEnergy", num(n))
+            }
+            Requirement::ReserveEnergyCapacity(n) => {
+                resource_list("resourceCapacity", "ReserveEnergy", num(n))
+            }
+            Requirement::MissilesMissingAtMost(n) => {
+                resource_list("resourceMissingAtMost", "Missile", num(n))
+            }
+            Requirement::SupersMissingAtMost(n) => {
+                resource_list("resourceMissingAtMost", "Super", num(n))
+            }
+            Requirement::PowerBombsMissingAtMost(n) => {
+                resource_list("resourceMissingAtMost", "PowerBomb", num(n))
+            }
+            Requirement::RegularEnergyMissingAtMost(n) => {
+                resource_list("resourceMissingAtMost", "RegularEnergy", num(n))
+            }
+            Requirement::ReserveEnergyMissingAtMost(n) => {
+                resource_list("resourceMissingAtMost", "ReserveEnergy", num(n))
+            }
+            Requirement::EnergyMissingAtMost(n) => {
+                resource_list("resourceMissingAtMost", "Energy", num(n))
+            }
+            Requirement::Energy(n) => resource_list("resourceConsumed", "Energy", num(n)),
+            Requirement::RegularEnergy(n) => {
+                resource_list("resourceConsumed", "RegularEnergy", num(n))
+            }
+            Requirement::ReserveEnergy(n) => {
+                resource_list("resourceConsumed", "ReserveEnergy", num(n))
+            }
+            Requirement::Missiles(n) => {
+                json::object! {"ammo": {"type": "Missile", "count": num(n)}}
+            }
+            Requirement::Supers(n) => json::object! {"ammo": {"type": "Super", "count": num(n)}},
+            Requirement::PowerBombs(n) => {
+                json::object! {"ammo": {"type": "PowerBomb", "count": num(n)}}
+            }
+            Requirement::EnergyRefill(n) => refill_json("Energy", n, game_data),
+            Requirement::RegularEnergyRefill(n) => refill_json("RegularEnergy", n, game_data),
+            Requirement::ReserveRefill(n) => refill_json("ReserveEnergy", n, game_data),
+            Requirement::MissileRefill(n) => refill_json("Missile", n, game_data),
+            Requirement::SuperRefill(n) => refill_json("Super", n, game_data),
+            Requirement::PowerBombRefill(n) => refill_json("PowerBomb", n, game_data),
+            Requirement::ClimbWithoutLava => "i_ClimbWithoutLava".into(),
+            Requirement::AmmoStationRefill => "i_ammoRefill".into(),
+            Requirement::AmmoStationRefillAll => "i_ammoRefillAll".into(),
+            Requirement::EnergyStationRefill => "i_energyStationRefill".into(),
+            Requirement::RegularEnergyDrain(n) => {
+                resource_list("resourceAtMost", "RegularEnergy", num(n))
+            }
+            Requirement::ReserveEnergyDrain(n) => {
+                resource_list("resourceAtMost", "ReserveEnergy", num(n))
+            }
+            Requirement::MissileDrain(n) => resource_list("resourceAtMost", "Missile", num(n)),
+            Requirement::LowerNorfairElevatorDownFrames => {
+                "i_LowerNorfairElevatorDownwardFrames".into()
+            }
+            Requirement::LowerNorfairElevatorUpFrames => {
+                "i_LowerNorfairElevatorUpwardFrames".into()
+            }
+            Requirement::MainHallElevatorFrames => "i_MainHallElevatorFrames".into(),
+            Requirement::EquipmentScreenCycleFrames => "i_equipmentScreenCycleFrames".into(),
+            Requirement::ShinesparksCostEnergy => "i_ShinesparksCostEnergy".into(),
+            Requirement::AllItemsSpawn => "i_AllItemsSpawn".into(),
+            Requirement::AcidChozoWithoutSpaceJump => "i_AcidChozoWithoutSpaceJump".into(),
+            Requirement::KraidCameraFix => "i_KraidCameraFix".into(),
+            Requirement::CrocomireCameraFix => "i_CrocomireCameraFix".into(),
+            Requirement::SupersDoubleDamageMotherBrain => "i_SupersDoubleDamageMotherBrain".into(),
+            Requirement::BlueGateGlitchLeniency { heated: false } => {
+                "i_blueGateGlitchLeniency".into()
+            }
+            Requirement::BlueGateGlitchLeniency { heated: true } => {
+                "i_heatedBlueGateGlitchLeniency".into()
+            }
+            Requirement::HeatedDoorStuckLeniency { heat_frames } => {
+                json::object! {"heatedDoorStuckLeniency": {"heatFrames": *heat_frames}}
+            }
+            Requirement::ReserveTrigger {
+                min_reserve_energy,
+                max_reserve_energy,
+                heat,
+            } => json::object! {"autoReserveTrigger": {
+                "minReserveEnergy": num(min_reserve_energy),
+                "maxReserveEnergy": num(max_reserve_energy),
+                "implicitHeatFrames": match heat {
+                    ReserveTriggerHeat::No => "no",
+                    ReserveTriggerHeat::Yes => "yes",
+                    ReserveTriggerHeat::Suitless => "suitless",
+                },
+            }},
+            Requirement::EnemyKill { count, vul } => {
+                enemy_kill_json(*count as usize, vul, game_data)
+            }
+            Requirement::PhantoonFight {} => boss_kill_json("Phantoon"),
+            Requirement::DraygonFight { .. } => boss_kill_json("Draygon"),
+            Requirement::RidleyFight {
+                power_bombs,
+                g_mode,
+                stuck,
+                ..
+            } => {
+                let mut ridley = JsonValue::new_object();
+                if !power_bombs {
+                    ridley["powerBombs"] = false.into();
+                }
+                if *g_mode {
+                    ridley["gMode"] = true.into();
+                }
+                match stuck {
+                    RidleyStuck::None => {}
+                    RidleyStuck::Top => ridley["stuck"] = "top".into(),
+                    RidleyStuck::Bottom => ridley["stuck"] = "bottom".into(),
+                }
+                single_key("ridleyKill", ridley)
+            }
+            Requirement::BotwoonFight {
+                second_phase: false,
+            } => boss_kill_json("Botwoon 1"),
+            Requirement::BotwoonFight { second_phase: true } => boss_kill_json("Botwoon 2"),
+            Requirement::MotherBrain2Fight { r_mode, .. } => {
+                let mut out = boss_kill_json("Mother Brain 2");
+                if *r_mode {
+                    out["enemyKill"]["rMode"] = true.into();
+                }
+                out
+            }
+            Requirement::KraidFight { .. } => boss_kill_json("Kraid"),
+            Requirement::CrocomireFight { .. } => boss_kill_json("Crocomire"),
+            Requirement::SporeSpawnFight { .. } => boss_kill_json("Spore Spawn"),
+            Requirement::GoldenTorizoFight { .. } => boss_kill_json("Golden Torizo"),
+            Requirement::DoorType {
+                room_id,
+                node_id,
+                door_type,
+            } => json::object! {"doorType": {
+                "roomId": *room_id,
+                "nodeId": *node_id,
+                "type": door_type_name(*door_type),
+            }},
+            Requirement::DoorUnlocked { room_id, node_id } => {
+                json::object! {"doorUnlocked": {"roomId": *room_id, "nodeId": *node_id}}
+            }
+            Requirement::UnlockDoor {
+                room_id,
+                node_id,
+                requirement_red,
+                requirement_green,
+                requirement_yellow,
+                requirement_charge,
+            } => json::object! {"unlockDoor": {
+                "roomId": *room_id,
+                "nodeId": *node_id,
+                "red": requirement_red.to_json(game_data),
+                "green": requirement_green.to_json(game_data),
+                "yellow": requirement_yellow.to_json(game_data),
+                "charge": requirement_charge.to_json(game_data),
+            }},
+            Requirement::ResetRoom { room_id, node_id } => {
+                json::object! {"resetRoom": {"nodes": [*node_id], "roomId": *room_id}}
+            }
+            Requirement::EscapeMorphLocation => "i_canEscapeMorphLocation".into(),
+            Requirement::EnemyDrops => "i_enemyDrops".into(),
+            Requirement::DoorTransition => json::object! {"doorTransition": {}},
+            Requirement::GainFlashSuit => json::object! {"gainFlashSuit": {}},
+            Requirement::UseFlashSuit { .. } => json::object! {"useFlashSuit": {}},
+            Requirement::NoFlashSuit => json::object! {"noFlashSuit": {}},
+            Requirement::GainBlueSuit => json::object! {"gainBlueSuit": {}},
+            Requirement::HaveBlueSuit { .. } => json::object! {"haveBlueSuit": {}},
+            Requirement::BlueSuitShineCharge { .. } => json::object! {"blueSuitShinecharge": {}},
+            Requirement::NoBlueSuit => json::object! {"noBlueSuit": {}},
+            Requirement::And(reqs) => single_key(
+                "and",
+                JsonValue::Array(reqs.iter().map(|r| r.to_json(game_data)).collect()),
+            ),
+            Requirement::Or(reqs) => single_key(
+                "or",
+                JsonValue::Array(reqs.iter().map(|r| r.to_json(game_data)).collect()),
+            ),
+        }
+    }
+
+    // Convert the requirement to a compact single-line string, e.g.
+    // `Morph & (heatFrames(120) | Varia) & tech(canSpringBallJump)`.
+    pub fn to_compact_string(&self, game_data: &GameData) -> String {
+        let mut out = String::new();
+        write_compact(&self.to_json(game_data), false, &mut out);
+        out
+    }
+
+    // Rebuild a requirement from the output of `to_json`.
+    pub fn from_json(req_json: &JsonValue, game_data: &GameData) -> Result<Requirement> {
+        if let Some(value) = req_json.as_str() {
+            return string_requirement_from_json(value, game_data);
+        }
+        ensure!(
+            req_json.is_object() && req_json.len() == 1,
+            "Expected a string or single-key object: {req_json}"
+        );
+        let (key, value) = req_json.entries().next().unwrap();
+        let num = |x: &JsonValue| game_data.parse_numeric(x);
+        let tech_idx = |tech_id| game_data.tech_isv.index_by_key[&tech_id];
+        Ok(match key {
+            "tech" => {
+                let name = value.as_str().context("Expected tech name")?;
+                let tech_id = *game_data
+                    .tech_id_by_name
+                    .get(name)
+                    .with_context(|| format!("Unknown tech: {name}"))?;
+                Requirement::Tech(tech_idx(tech_id))
+            }
+            "notable" => {
+                let (name, room_id) = match value.as_str() {
+                    Some(name) => (name, None),
+                    None => (
+                        value["name"].as_str().context("Expected notable name")?,
+                        Some(value["roomId"].as_usize().context("Expected roomId")?),
+                    ),
+                };
+                let notable_idx = game_data
+                    .notable_info
+                    .iter()
+                    .position(|x| x.name == name && room_id.is_none_or(|r| x.room_id == r))
+                    .with_context(|| format!("Unknown notable: {name}"))?;
+                Requirement::Notable(notable_idx)
+            }
+            "not" => {
+                let flag = value.as_str().context("Expected flag name")?;
+                Requirement::NotFlag(
+                    *game_data
+                        .flag_isv
+                        .index_by_key
+                        .get(flag)
+                        .with_context(|| format!("Unknown flag: {flag}"))?,
+                )
+            }
+            "disableEquipment" => {
+                ensure!(
+                    value.as_str() == Some("ETank"),
+                    "Unexpected disableEquipment: {value}"
+                );
+                Requirement::DisableableETank
+            }
+            "canShineCharge" => Requirement::ShineCharge {
+                used_tiles: Float::new(get_f32(&value["usedTiles"])?),
+                heated: value["heated"].as_bool().unwrap_or(false),
+            },
+            "speedBall" => Requirement::SpeedBall {
+                used_tiles: Float::new(get_f32(&value["length"])?),
+                heated: value["heated"].as_bool().unwrap_or(false),
+            },
+            "getBlueSpeed" => Requirement::GetBlueSpeed {
+                used_tiles: Float::new(get_f32(&value["usedTiles"])?),
+                heated: value["heated"].as_bool().unwrap_or(false),
+            },
+            "shineChargeFrames" => Requirement::ShineChargeFrames(num(value)?),
+            "shinespark" => Requirement::Shinespark {
+                shinespark_tech_idx: tech_idx(TECH_ID_CAN_SHINESPARK),
+                frames: get_capacity(&value["frames"])?,
+                excess_frames: value["excessFrames"].as_i16().unwrap_or(0),
+            },
+            "heatFrames" => Requirement::HeatFrames(num(value)?),
+            "suitlessHeatFrames" => Requirement::SuitlessHeatFrames(num(value)?),
+            "simpleHeatFrames" => Requirement::SimpleHeatFrames(num(value)?),
+            "heatFramesWithEnergyDrops" => Requirement::HeatFramesWithEnergyDrops(
+                num(&value["frames"])?,
+                enemy_drops_from_json(&value["drops"])?,
+                enemy_drops_from_json(&value["buffedDrops"])?,
+            ),
+            "lavaFrames" => Requirement::LavaFrames(num(value)?),
+            "lavaFramesWithEnergyDrops" => Requirement::LavaFramesWithEnergyDrops(
+                num(&value["frames"])?,
+                enemy_drops_from_json(&value["drops"])?,
+                enemy_drops_from_json(&value["buffedDrops"])?,
+            ),
+            "gravitylessLavaFrames" => Requirement::GravitylessLavaFrames(num(value)?),
+            "acidFrames" => Requirement::AcidFrames(num(value)?),
+            "gravitylessAcidFrames" => Requirement::GravitylessAcidFrames(num(value)?),
+            "metroidFrames" => Requirement::MetroidFrames(num(value)?),
+            "cycleFrames" => Requirement::CycleFrames(num(value)?),
+            "simpleCycleFrames" => Requirement::SimpleCycleFrames(num(value)?),
+            "farm" => Requirement::Farm {
+                requirement: Box::new(Requirement::from_json(&value["requires"], game_data)?),
+                enemy_drops: enemy_drops_from_json(&value["drops"])?,
+                enemy_drops_buffed: enemy_drops_from_json(&value["buffedDrops"])?,
+                full_energy: get_bool(&value["fullEnergy"])?,
+                full_missiles: get_bool(&value["fullMissiles"])?,
+                full_supers: get_bool(&value["fullSupers"])?,
+                full_power_bombs: get_bool(&value["fullPowerBombs"])?,
+            },
+            "damage" => Requirement::Damage {
+                unit_energy: get_capacity(&value["unitEnergy"])?,
+                quantity: num(&value["quantity"])?,
+            },
+            "resourceAvailable"
+            | "resourceCapacity"
+            | "resourceMissingAtMost"
+            | "resourceConsumed"
+            | "resourceAtMost" => {
+                ensure!(value.len() == 1, "Expected a single resource: {value}");
+                let resource_type = value[0]["type"].as_str().context("Expected type")?;
+                let n = num(&value[0]["count"])?;
+                resource_requirement(key, resource_type, n)?
+            }
+            "ammo" => {
+                let n = num(&value["count"])?;
+                match value["type"].as_str() {
+                    Some("Missile") => Requirement::Missiles(n),
+                    Some("Super") => Requirement::Supers(n),
+                    Some("PowerBomb") => Requirement::PowerBombs(n),
+                    _ => bail!("Unexpected ammo type: {value}"),
+                }
+            }
+            "refill" => {
+                ensure!(value.len() == 1, "Expected a single resource: {value}");
+                let resource_type = value[0].as_str().context("Expected resource type")?;
+                refill_requirement(resource_type, Numeric::Constant(9999))?
+            }
+            "partialRefill" => {
+                let resource_type = value["type"].as_str().context("Expected type")?;
+                refill_requirement(resource_type, num(&value["limit"])?)?
+            }
+            "heatedDoorStuckLeniency" => Requirement::HeatedDoorStuckLeniency {
+                heat_frames: get_capacity(&value["heatFrames"])?,
+            },
+            "autoReserveTrigger" => Requirement::ReserveTrigger {
+                min_reserve_energy: num(&value["minReserveEnergy"])?,
+                max_reserve_energy: num(&value["maxReserveEnergy"])?,
+                heat: game_data.parse_reserve_trigger_heat(
+                    value["implicitHeatFrames"]
+                        .as_str()
+                        .context("Expected implicitHeatFrames")?,
+                )?,
+            },
+            "enemyKill" => enemy_kill_from_json(value, game_data)?,
+            "ridleyKill" => Requirement::RidleyFight {
+                can_be_patient_tech_idx: tech_idx(TECH_ID_CAN_BE_PATIENT),
+                can_be_very_patient_tech_idx: tech_idx(TECH_ID_CAN_BE_VERY_PATIENT),
+                can_be_extremely_patient_tech_idx: tech_idx(TECH_ID_CAN_BE_EXTREMELY_PATIENT),
+                power_bombs: value["powerBombs"].as_bool().unwrap_or(true),
+                g_mode: value["gMode"].as_bool().unwrap_or(false),
+                stuck: match value["stuck"].as_str() {
+                    None => RidleyStuck::None,
+                    Some("top") => RidleyStuck::Top,
+                    Some("bottom") => RidleyStuck::Bottom,
+                    Some(other) => bail!("Unexpected Ridley stuck value: {other}"),
+                },
+            },
+            "doorType" => {
+                let type_name = value["type"].as_str().context("Expected door type")?;
+                Requirement::DoorType {
+                    room_id: get_usize(&value["roomId"])?,
+                    node_id: get_usize(&value["nodeId"])?,
+                    door_type: door_type_from_name(type_name)?,
+                }
+            }
+            "doorUnlocked" => Requirement::DoorUnlocked {
+                room_id: get_usize(&value["roomId"])?,
+                node_id: get_usize(&value["nodeId"])?,
+            },
+            "unlockDoor" => {
+                let req = |key: &str| -> Result<Box<Requirement>> {
+                    Ok(Box::new(Requirement::from_json(&value[key], game_data)?))
+                };
+                Requirement::UnlockDoor {
+                    room_id: get_usize(&value["roomId"])?,
+                    node_id: get_usize(&value["nodeId"])?,
+                    requirement_red: req("red")?,
+                    requirement_green: req("green")?,
+                    requirement_yellow: req("yellow")?,
+                    requirement_charge: req("charge")?,
+                }
+            }
+            "resetRoom" => {
+                ensure!(value["nodes"].len() == 1, "Expected a single node: {value}");
+                Requirement::ResetRoom {
+                    room_id: get_usize(&value["roomId"])?,
+                    node_id: get_usize(&value["nodes"][0])?,
+                }
+            }
+            "doorTransition" => Requirement::DoorTransition,
+            "gainFlashSuit" => Requirement::GainFlashSuit,
+            "useFlashSuit" => Requirement::UseFlashSuit {
+                carry_flash_suit_tech_idx: tech_idx(TECH_ID_CAN_CARRY_FLASH_SUIT),
+            },
+            "noFlashSuit" => Requirement::NoFlashSuit,
+            "gainBlueSuit" => Requirement::GainBlueSuit,
+            "haveBlueSuit" => Requirement::HaveBlueSuit {
+                carry_blue_suit_tech_idx: tech_idx(TECH_ID_CAN_CARRY_BLUE_SUIT),
+            },
+            "blueSuitShinecharge" => Requirement::BlueSuitShineCharge {
+                carry_blue_suit_tech_idx: tech_idx(TECH_ID_CAN_CARRY_BLUE_SUIT),
+            },
+            "noBlueSuit" => Requirement::NoBlueSuit,
+            "and" | "or" => {
+                ensure!(value.is_array(), "Expected an array: {value}");
+                let reqs = value
+                    .members()
+                    .map(|x| Requirement::from_json(x, game_data))
+                    .collect::<Result<Vec<_>>>()?;
+                // Built directly rather than with `make_and`/`make_or`, to keep the structure:
+                if key == "and" {
+                    Requirement::And(reqs)
+                } else {
+                    Requirement::Or(reqs)
+                }
+            }
+            _ => bail!("Unrecognized requirement key: {key}"),
+        })
+    }
+}
+
+fn get_f32(value: &JsonValue) -> Result<f32> {
+    // `JsonValue::as_f32` doesn't always give back the f32 that was written, while going through
+    // f64 does:
+    Ok(value
+        .as_f64()
+        .with_context(|| format!("Expected a number: {value}"))? as f32)
+}
+
+fn get_capacity(value: &JsonValue) -> Result<Capacity> {
+    value
+        .as_i16()
+        .with_context(|| format!("Expected an integer: {value}"))
+}
+
+fn get_usize(value: &JsonValue) -> Result<usize> {
+    value
+        .as_usize()
+        .with_context(|| format!("Expected an index: {value}"))
+}
+
+fn get_bool(value: &JsonValue) -> Result<bool> {
+    value
+        .as_bool()
+        .with_context(|| format!("Expected a boolean: {value}"))
+}
+
+fn string_requirement_from_json(value: &str, game_data: &GameData) -> Result<Requirement> {
+    Ok(match value {
+        "free" => Requirement::Free,
+        "never" => Requirement::Never,
+        "canWalljump" => Requirement::Walljump,
+        "i_MotherBrainBarrier1Clear" => Requirement::MotherBrainBarrierClear(0),
+        "i_MotherBrainBarrier2Clear" => Requirement::MotherBrainBarrierClear(1),
+        "i_MotherBrainBarrier3Clear" => Requirement::MotherBrainBarrierClear(2),
+        "i_MotherBrainBarrier4Clear" => Requirement::MotherBrainBarrierClear(3),
+        "i_ClimbWithoutLava" => Requirement::ClimbWithoutLava,
+        "i_ammoRefill" => Requirement::AmmoStationRefill,
+        "i_ammoRefillAll" => Requirement::AmmoStationRefillAll,
+        "i_energyStationRefill" => Requirement::EnergyStationRefill,
+        "i_LowerNorfairElevatorDownwardFrames" => Requirement::LowerNorfairElevatorDownFrames,
+        "i_LowerNorfairElevatorUpwardFrames" => Requirement::LowerNorfairElevatorUpFrames,
+        "i_MainHallElevatorFrames" => Requirement::MainHallElevatorFrames,
+        "i_equipmentScreenCycleFrames" => Requirement::EquipmentScreenCycleFrames,
+        "i_ShinesparksCostEnergy" => Requirement::ShinesparksCostEnergy,
+        "i_AllItemsSpawn" => Requirement::AllItemsSpawn,
+        "i_AcidChozoWithoutSpaceJump" => Requirement::AcidChozoWithoutSpaceJump,
+        "i_KraidCameraFix" => Requirement::KraidCameraFix,
+        "i_CrocomireCameraFix" => Requirement::CrocomireCameraFix,
+        "i_SupersDoubleDamageMotherBrain" => Requirement::SupersDoubleDamageMotherBrain,
+        "i_blueGateGlitchLeniency" => Requirement::BlueGateGlitchLeniency { heated: false },
+        "i_heatedBlueGateGlitchLeniency" => Requirement::BlueGateGlitchLeniency { heated: true },
+        "i_canEscapeMorphLocation" => Requirement::EscapeMorphLocation,
+        "i_enemyDrops" => Requirement::EnemyDrops,
+        _ => {
+            if let Some(&item_id) = game_data.item_isv.index_by_key.get(value) {
+                Requirement::Item(item_id)
+            } else if let Some(&flag_id) = game_data.flag_isv.index_by_key.get(value) {
+                Requirement::Flag(flag_id)
+            } else {
+                bail!("Unrecognized requirement: {value}");
+            }
+        }
+    })
+}
+
+fn resource_requirement(key: &str, resource_type: &str, n: Numeric) -> Result<Requirement> {
+    Ok(match (key, resource_type) {
+        ("resourceAvailable", "Missile") => Requirement::MissilesAvailable(n),
+        ("resourceAvailable", "Super") => Requirement::SupersAvailable(n),
+        ("resourceAvailable", "PowerBomb") => Requirement::PowerBombsAvailable(n),
+        ("resourceAvailable", "RegularEnergy") => Requirement::RegularEnergyAvailable(n),
+        ("resourceAvailable", "ReserveEnergy") => Requirement::ReserveEnergyAvailable(n),
+        ("resourceAvailable", "Energy") => Requirement::EnergyAvailable(n),
+        ("resourceCapacity", "Missile") => Requirement::MissilesCapacity(n),
+        ("resourceCapacity", "Super") => Requirement::SupersCapacity(n),
+        ("resourceCapacity", "PowerBomb") => Requirement::PowerBombsCapacity(n),
+        ("resourceCapacity", "RegularEnergy") => Requirement::RegularEnergyCapacity(n),
+        ("resourceCapacity", "ReserveEnergy") => Requirement::ReserveEnergyCapacity(n),
+        ("resourceMissingAtMost", "Missile") => Requirement::MissilesMissingAtMost(n),
+        ("resourceMissingAtMost", "Super") => Requirement::SupersMissingAtMost(n),
+        ("resourceMissingAtMost", "PowerBomb") => Requirement::PowerBombsMissingAtMost(n),
+        ("resourceMissingAtMost", "RegularEnergy") => Requirement::RegularEnergyMissingAtMost(n),
+        ("resourceMissingAtMost", "ReserveEnergy") => Requirement::ReserveEnergyMissingAtMost(n),
+        ("resourceMissingAtMost", "Energy") => Requirement::EnergyMissingAtMost(n),
+        ("resourceConsumed", "Energy") => Requirement::Energy(n),
+        ("resourceConsumed", "RegularEnergy") => Requirement::RegularEnergy(n),
+        ("resourceConsumed", "ReserveEnergy") => Requirement::ReserveEnergy(n),
+        ("resourceAtMost", "RegularEnergy") => Requirement::RegularEnergyDrain(n),
+        ("resourceAtMost", "ReserveEnergy") => Requirement::ReserveEnergyDrain(n),
+        ("resourceAtMost", "Missile") => Requirement::MissileDrain(n),
+        _ => bail!("Unexpected resource type for {key}: {resource_type}"),
+    })
+}
+
+fn refill_requirement(resource_type: &str, limit: Numeric) -> Result<Requirement> {
+    Ok(match resource_type {
+        "Energy" => Requirement::EnergyRefill(limit),
+        "RegularEnergy" => Requirement::RegularEnergyRefill(limit),
+        "ReserveEnergy" => Requirement::ReserveRefill(limit),
+        "Missile" => Requirement::MissileRefill(limit),
+        "Super" => Requirement::SuperRefill(limit),
+        "PowerBomb" => Requirement::PowerBombRefill(limit),
+        _ => bail!("Unexpected refill resource type: {resource_type}"),
+    })
+}
+
+fn enemy_drops_from_json(value: &JsonValue) -> Result<Vec<EnemyDrop>> {
+    ensure!(value.is_array(), "Expected an array of drops: {value}");
+    value
+        .members()
+        .map(|drop| {
+            Ok(EnemyDrop {
+                nothing_weight: Float::new(get_f32(&drop["noDrop"])?),
+                small_energy_weight: Float::new(get_f32(&drop["smallEnergy"])?),
+                large_energy_weight: Float::new(get_f32(&drop["bigEnergy"])?),
+                missile_weight: Float::new(get_f32(&drop["missile"])?),
+                super_weight: Float::new(get_f32(&drop["super"])?),
+                power_bomb_weight: Float::new(get_f32(&drop["powerBomb"])?),
+                count: get_capacity(&drop["count"])?,
+            })
+        })
+        .collect()
+}
+
+fn door_type_from_name(name: &str) -> Result<DoorType> {
+    Ok(match name {
+        "Blue" => DoorType::Blue,
+        "Red" => DoorType::Red,
+        "Green" => DoorType::Green,
+        "Yellow" => DoorType::Yellow,
+        "Gray" => DoorType::Gray,
+        "Wall" => DoorType::Wall,
+        "ChargeBeam" => DoorType::Beam(BeamType::Charge),
+        "IceBeam" => DoorType::Beam(BeamType::Ice),
+        "WaveBeam" => DoorType::Beam(BeamType::Wave),
+        "SpazerBeam" => DoorType::Beam(BeamType::Spazer),
+        "PlasmaBeam" => DoorType::Beam(BeamType::Plasma),
+        _ => bail!("Unrecognized door type: {name}"),
+    })
+}
+
+fn enemy_kill_from_json(value: &JsonValue, game_data: &GameData) -> Result<Requirement> {
+    if !value.has_key("enemies") {
+        return Ok(Requirement::EnemyKill {
+            count: get_capacity(&value["count"])?,
+            vul: EnemyVulnerabilities {
+                hp: get_capacity(&value["hp"])?,
+                non_ammo_vulnerabilities: get_usize(&value["nonAmmoVulnerabilities"])?,
+                missile_damage: get_capacity(&value["missileDamage"])?,
+                super_damage: get_capacity(&value["superDamage"])?,
+                power_bomb_damage: get_capacity(&value["powerBombDamage"])?,
+            },
+        });
+    }
+    ensure!(
+        value["enemies"].len() == 1,
+        "Expected a single enemy group: {value}"
+    );
+    let group = &value["enemies"][0];
+    let name = group[0].as_str().context("Expected enemy name")?;
+    let count = group.len() as Capacity;
+    let tech_idx = |tech_id| game_data.tech_isv.index_by_key[&tech_id];
+    if !value.has_key("explicitWeapons") {
+        let can_be_patient_tech_idx = tech_idx(TECH_ID_CAN_BE_PATIENT);
+        let can_be_very_patient_tech_idx = tech_idx(TECH_ID_CAN_BE_VERY_PATIENT);
+        let can_be_extremely_patient_tech_idx = tech_idx(TECH_ID_CAN_BE_EXTREMELY_PATIENT);
+        match name {
+            "Phantoon" => return Ok(Requirement::PhantoonFight {}),
+            "Draygon" => {
+                return Ok(Requirement::DraygonFight {
+                    can_be_patient_tech_idx,
+                    can_be_very_patient_tech_idx,
+                    can_be_extremely_patient_tech_idx,
+                });
+            }
+            "Botwoon 1" => {
+                return Ok(Requirement::BotwoonFight {
+                    second_phase: false,
+                });
+            }
+            "Botwoon 2" => return Ok(Requirement::BotwoonFight { second_phase: true }),
+            "Mother Brain 2" => {
+                return Ok(Requirement::MotherBrain2Fight {
+                    can_be_very_patient_tech_id: can_be_very_patient_tech_idx,
+                    r_mode: value["rMode"].as_bool().unwrap_or(false),
+                });
+            }
+            "Kraid" => {
+                return Ok(Requirement::KraidFight {
+                    can_be_patient_tech_idx,
+                    can_be_very_patient_tech_idx,
+                    can_be_extremely_patient_tech_idx,
+                });
+            }
+            "Crocomire" => {
+                return Ok(Requirement::CrocomireFight {
+                    can_be_patient_tech_idx,
+                    can_be_very_patient_tech_idx,
+                    can_be_extremely_patient_tech_idx,
+                });
+            }
+            "Spore Spawn" => {
+                return Ok(Requirement::SporeSpawnFight {
+                    can_be_patient_tech_idx,
+                    can_be_very_patient_tech_idx,
+                    can_be_extremely_patient_tech_idx,
+                });
+            }
+            "Golden Torizo" => {
+                return Ok(Requirement::GoldenTorizoFight {
+                    can_be_patient_tech_idx,
+                    can_be_very_patient_tech_idx,
+                    can_be_extremely_patient_tech_idx,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let base_vul = game_data
+        .enemy_vulnerabilities
+        .get(name)
+        .with_context(|| format!("Unknown enemy: {name}"))?;
+    let mut vul = base_vul.clone();
+    if value.has_key("explicitWeapons") {
+        // The inverse of the weapon list written by `enemy_kill_json`:
+        let weapons: Vec<&str> = value["explicitWeapons"]
+            .members()
+            .filter_map(|x| x.as_str())
+            .collect();
+        vul.non_ammo_vulnerabilities = 0;
+        for &weapon in &weapons {
+            if !["Missile", "Super", "PowerBomb", "PowerBombPeriphery"].contains(&weapon) {
+                let weapon_idx = *game_data
+                    .weapon_isv
+                    .index_by_key
+                    .get(weapon)
+                    .with_context(|| format!("Unknown weapon: {weapon}"))?;
+                vul.non_ammo_vulnerabilities |= 1 << weapon_idx;
+            }
+        }
+        if !weapons.contains(&"Missile") {
+            vul.missile_damage = 0;
+        }
+        if !weapons.contains(&"Super") {
+            vul.super_damage = 0;
+        }
+        if !weapons.contains(&"PowerBomb") {
+            if weapons.contains(&"PowerBombPeriphery") {
+                vul.power_bomb_damage /= 2;
+            } else {
+                vul.power_bomb_damage = 0;
+            }
+        }
+    }
+    Ok(Requirement::EnemyKill { count, vul })
+}
+
+fn write_compact_value(value: &JsonValue, out: &mut String) {
+    match value {
+        JsonValue::Array(members) => {
+            out.push('[');
+            for (i, member) in members.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_compact_value(member, out);
+            }
+            out.push(']');
+        }
+        JsonValue::Object(obj) => {
+            for (i, (key, member)) in obj.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                out.push_str(key);
+                out.push('=');
+                write_compact_value(member, out);
+            }
+        }
+        other => {
+            if let Some(s) = other.as_str() {
+                out.push_str(s);
+            } else {
+                out.push_str(&other.dump());
+            }
+        }
+    }
+}
+
+fn write_compact(req_json: &JsonValue, nested: bool, out: &mut String) {
+    if req_json.is_object() && req_json.len() == 1 {
+        let (key, value) = req_json.entries().next().unwrap();
+        if (key == "and" || key == "or") && value.is_array() {
+            let sep = if key == "and" { " & " } else { " | " };
+            if nested {
+                out.push('(');
+            }
+            for (i, member) in value.members().enumerate() {
+                if i > 0 {
+                    out.push_str(sep);
+                }
+                write_compact(member, true, out);
+            }
+            if nested {
+                out.push(')');
+            }
+            return;
+        }
+        out.push_str(key);
+        out.push('(');
+        if value.is_array() {
+            // Drop the outer brackets, since the parentheses already delimit the list:
+            for (i, member) in value.members().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_compact_value(member, out);
+            }
+        } else {
+            write_compact_value(value, out);
+        }
+        out.push(')');
+        return;
+    }
+    write_compact_value(req_json, out);
+}
@@ -659,3 +659,388 @@ pub fn apply_mother_brain_2_requirement(
         true
     }
 }
+
+pub fn apply_kraid_requirement(
+    inventory: &Inventory,
+    local: &mut LocalState,
+    proficiency: f32,
+    can_be_patient: bool,
+    can_be_very_patient: bool,
+    can_be_extremely_patient: bool,
+    reverse: bool,
+) -> bool {
+    // Kraid only takes damage from shots into his mouth, which he keeps open for roughly a third
+    // of the fight. Uncharged beam is always available, so the fight can always be won given
+    // enough patience.
+    let mut boss_hp: f32 = 1000.0;
+    let mut time: f32 = 0.0; // Cumulative time in seconds for the fight
+    let charge_damage = get_charge_damage(inventory);
+    let beam_damage = get_uncharged_damage(inventory);
+
+    // Assume an accuracy of between 60% (on lowest difficulty) to 100% (on highest):
+    let accuracy = 0.6 + 0.4 * proficiency;
+
+    // Assume a firing rate of between 40% (on lowest difficulty) to 100% (on highest):
+    let firing_rate = 0.4 + 0.6 * proficiency;
+
+    let super_time = 1.5 / firing_rate; // minimum of 1.5 seconds between Super shots
+    let missile_time = 0.5 / firing_rate; // minimum of 0.5 seconds between Missile shots
+    let charge_time = 1.5 / firing_rate; // minimum of 1.5 seconds between charge shots
+
+    // Uncharged shots: up to 4 per second, while the mouth is open.
+    let beam_dps = beam_damage * 4.0 / 3.0 * accuracy * firing_rate;
+
+    // Prioritize using Supers:
+    let supers_available = local.supers_available(inventory, reverse);
+    let supers_to_use = min(
+        supers_available,
+        f32::ceil(boss_hp / (300.0 * accuracy)) as Capacity,
+    );
+    assert!(local.use_supers(supers_to_use, inventory, reverse));
+    boss_hp -= supers_to_use as f32 * 300.0 * accuracy;
+    time += supers_to_use as f32 * super_time;
+
+    // Then use available Missiles:
+    if boss_hp > 0.0 {
+        let missiles_available = local.missiles_available(inventory, reverse);
+        let missiles_to_use = min(
+            missiles_available,
+            f32::ceil(boss_hp / (100.0 * accuracy)) as Capacity,
+        );
+        assert!(local.use_missiles(missiles_to_use, inventory, reverse));
+        boss_hp -= missiles_to_use as f32 * 100.0 * accuracy;
+        time += missiles_to_use as f32 * missile_time;
+    }
+
+    // Then finish with Charge shots, if available, or otherwise uncharged shots:
+    if boss_hp > 0.0 {
+        if charge_damage > 0.0 {
+            let charge_shots_to_use = f32::ceil(boss_hp / (charge_damage * accuracy));
+            time += charge_shots_to_use * charge_time;
+        } else {
+            time += boss_hp / beam_dps;
+        }
+    }
+
+    // For determining if patience tech is required:
+    // `good_time` = hypothetical time based on good execution
+    let good_time = time * firing_rate * accuracy;
+    if good_time >= 90.0 && !can_be_patient
+        || good_time >= 180.0 && !can_be_very_patient
+        || good_time >= 360.0 && !can_be_extremely_patient
+    {
+        // We don't have enough patience to finish the fight:
+        return false;
+    }
+
+    // Assumed rate of damage to Samus (per second) from Kraid's claws and belly spikes,
+    // given minimal dodging skill (calibrated against `boss_simulation`):
+    let base_hit_dps = 3.5;
+    let hit_rate = 1.0 - 0.9 * proficiency;
+    let damage = base_hit_dps * hit_rate * time;
+    // Overflow safeguard - bail here if Samus takes calamitous damage.
+    if damage > 10000.0 {
+        return false;
+    }
+    let energy_used = (damage / suit_damage_factor(inventory) as f32) as Capacity;
+    local.use_energy(energy_used, true, inventory, reverse)
+}
+
+pub fn apply_crocomire_requirement(
+    inventory: &Inventory,
+    local: &mut LocalState,
+    proficiency: f32,
+    can_be_patient: bool,
+    can_be_very_patient: bool,
+    can_be_extremely_patient: bool,
+    reverse: bool,
+) -> bool {
+    // Crocomire can't be damaged directly: shots into his open mouth push him back, until he is
+    // pushed off the ledge into the lava. Uncharged beam shots don't push him back. Meanwhile he
+    // keeps advancing at a steady rate, so each shot only contributes the push-back in excess of
+    // the ground he regains while waiting for the next shot. We express the total push-back needed
+    // in terms of the equivalent damage.
+    let mut boss_hp: f32 = 3000.0;
+    let mut time: f32 = 0.0; // Cumulative time in seconds for the fight
+    let charge_damage = get_charge_damage(inventory);
+
+    // Assume an accuracy of between 60% (on lowest difficulty) to 100% (on highest):
+    let accuracy = 0.6 + 0.4 * proficiency;
+
+    // Assume a firing rate of between 50% (on lowest difficulty) to 100% (on highest):
+    let firing_rate = 0.5 + 0.5 * proficiency;
+
+    // Rate at which Crocomire regains ground, in equivalent damage per second:
+    let advance_rate = 8.0;
+
+    let super_time = 1.0 / firing_rate; // minimum of 1.0 seconds between Super shots
+    let missile_time = 0.4 / firing_rate; // minimum of 0.4 seconds between Missile shots
+    let charge_time = 1.2 / firing_rate; // minimum of 1.2 seconds between charge shots
+
+    let super_push = 300.0 * accuracy - advance_rate * super_time;
+    let missile_push = 100.0 * accuracy - advance_rate * missile_time;
+    let charge_push = charge_damage * accuracy - advance_rate * charge_time;
+
+    // Prioritize using Supers:
+    if super_push > 0.0 {
+        let supers_available = local.supers_available(inventory, reverse);
+        let supers_to_use = min(
+            supers_available,
+            f32::ceil(boss_hp / super_push) as Capacity,
+        );
+        assert!(local.use_supers(supers_to_use, inventory, reverse));
+        boss_hp -= supers_to_use as f32 * super_push;
+        time += supers_to_use as f32 * super_time;
+    }
+
+    // Then use available Missiles:
+    if boss_hp > 0.0 && missile_push > 0.0 {
+        let missiles_available = local.missiles_available(inventory, reverse);
+        let missiles_to_use = min(
+            missiles_available,
+            f32::ceil(boss_hp / missile_push) as Capacity,
+        );
+        assert!(local.use_missiles(missiles_to_use, inventory, reverse));
+        boss_hp -= missiles_to_use as f32 * missile_push;
+        time += missiles_to_use as f32 * missile_time;
+    }
+
+    // Then finish with Charge shots, if strong enough to push him back:
+    if boss_hp > 0.0 && charge_push > 0.0 {
+        let charge_shots_to_use = f32::ceil(boss_hp / charge_push);
+        boss_hp = 0.0;
+        time += charge_shots_to_use * charge_time;
+    }
+
+    if boss_hp > 0.0 {
+        // We don't have enough ammo to finish the fight:
+        return false;
+    }
+
+    // For determining if patience tech is required:
+    // `good_time` = hypothetical time based on good execution
+    let good_time = time * firing_rate * accuracy;
+    if good_time >= 90.0 && !can_be_patient
+        || good_time >= 180.0 && !can_be_very_patient
+        || good_time >= 360.0 && !can_be_extremely_patient
+    {
+        // We don't have enough patience to finish the fight:
+        return false;
+    }
+
+    // Assumed rate of damage to Samus (per second) from Crocomire's projectiles and from being
+    // pushed into the spike wall, given minimal dodging skill:
+    let base_hit_dps = 10.0 * (1.0 - 0.9 * proficiency);
+
+    // Assumed average energy per second gained from shooting Crocomire's projectiles:
+    let farm_rate = 1.0 * (0.25 + 0.75 * proficiency);
+
+    let mut net_dps = base_hit_dps / suit_damage_factor(inventory) as f32 - farm_rate;
+    if net_dps < 0.0 {
+        net_dps = 0.0;
+    }
+    // Overflow safeguard - bail here if Samus takes calamitous damage.
+    if net_dps * time > 10000.0 {
+        return false;
+    }
+    local.use_energy((net_dps * time) as Capacity, true, inventory, reverse)
+}
+
+pub fn apply_spore_spawn_requirement(
+    inventory: &Inventory,
+    local: &mut LocalState,
+    proficiency: f32,
+    can_be_patient: bool,
+    can_be_very_patient: bool,
+    can_be_extremely_patient: bool,
+    reverse: bool,
+) -> bool {
+    // Spore Spawn only takes damage while its core is open, once per cycle of its movement pattern,
+    // which limits how many shots of each type can land per cycle.
+    let mut boss_hp: f32 = 960.0;
+    let mut time: f32 = 0.0; // Cumulative time in seconds for the fight
+    let charge_damage = get_charge_damage(inventory);
+    let beam_damage = get_uncharged_damage(inventory);
+
+    const CYCLE_TIME: f32 = 12.0;
+
+    // Assume an accuracy of between 50% (on lowest difficulty) to 100% (on highest):
+    let accuracy = 0.5 + 0.5 * proficiency;
+
+    // Assume a firing rate of between 50% (on lowest difficulty) to 100% (on highest),
+    // relative to the maximum number of shots that can land while the core is open:
+    let firing_rate = 0.5 + 0.5 * proficiency;
+
+    let super_time = CYCLE_TIME / (1.0 * firing_rate); // up to 1 Super per cycle
+    let missile_time = CYCLE_TIME / (3.0 * firing_rate); // up to 3 Missiles per cycle
+    let charge_time = CYCLE_TIME / (1.0 * firing_rate); // up to 1 charge shot per cycle
+    let beam_time = CYCLE_TIME / (6.0 * firing_rate); // up to 6 uncharged shots per cycle
+
+    // Prioritize using Supers:
+    let supers_available = local.supers_available(inventory, reverse);
+    let supers_to_use = min(
+        supers_available,
+        f32::ceil(boss_hp / (300.0 * accuracy)) as Capacity,
+    );
+    assert!(local.use_supers(supers_to_use, inventory, reverse));
+    boss_hp -= supers_to_use as f32 * 300.0 * accuracy;
+    time += supers_to_use as f32 * super_time;
+
+    // Then use available Missiles:
+    if boss_hp > 0.0 {
+        let missiles_available = local.missiles_available(inventory, reverse);
+        let missiles_to_use = min(
+            missiles_available,
+            f32::ceil(boss_hp / (100.0 * accuracy)) as Capacity,
+        );
+        assert!(local.use_missiles(missiles_to_use, inventory, reverse));
+        boss_hp -= missiles_to_use as f32 * 100.0 * accuracy;
+        time += missiles_to_use as f32 * missile_time;
+    }
+
+    // Then finish with whichever beam attack does more damage per cycle:
+    if boss_hp > 0.0 {
+        let charge_dps = charge_damage * accuracy / charge_time;
+        let beam_dps = beam_damage * accuracy / beam_time;
+        time += boss_hp / f32::max(charge_dps, beam_dps);
+    }
+
+    // For determining if patience tech is required:
+    // `good_time` = hypothetical time based on good execution
+    let good_time = time * firing_rate * accuracy;
+    if good_time >= 90.0 && !can_be_patient
+        || good_time >= 180.0 && !can_be_very_patient
+        || good_time >= 360.0 && !can_be_extremely_patient
+    {
+        // We don't have enough patience to finish the fight:
+        return false;
+    }
+
+    // Assumed rate of damage to Samus (per second) from spores and contact with Spore Spawn,
+    // given minimal dodging skill:
+    let base_hit_dps = 8.0 * (1.0 - 0.9 * proficiency);
+
+    // Assumed average energy per second gained from farming spores:
+    let farm_rate = 3.0 * (0.25 + 0.75 * proficiency);
+
+    let mut net_dps = base_hit_dps / suit_damage_factor(inventory) as f32 - farm_rate;
+    if net_dps < 0.0 {
+        net_dps = 0.0;
+    }
+    // Overflow safeguard - bail here if Samus takes calamitous damage.
+    if net_dps * time > 10000.0 {
+        return false;
+    }
+    local.use_energy((net_dps * time) as Capacity, true, inventory, reverse)
+}
+
+pub fn apply_golden_torizo_requirement(
+    inventory: &Inventory,
+    local: &mut LocalState,
+    proficiency: f32,
+    can_be_patient: bool,
+    can_be_very_patient: bool,
+    can_be_extremely_patient: bool,
+    reverse: bool,
+) -> bool {
+    let mut boss_hp: f32 = 8000.0;
+    let mut time: f32 = 0.0; // Cumulative time in seconds for the fight
+    let charge_damage = get_charge_damage(inventory);
+    let beam_damage = get_uncharged_damage(inventory);
+
+    // Assume an ammo accuracy rate of between 70% (on lowest difficulty) to 100% (on highest):
+    let accuracy = 0.7 + 0.3 * proficiency;
+
+    // Golden Torizo catches Supers fired at it from the front, so they only land when fired
+    // from behind; assume between 25% (on lowest difficulty) to 75% (on highest) land:
+    let super_accuracy = 0.25 + 0.5 * proficiency;
+
+    // Assume a firing rate of between 20% (on lowest difficulty) to 100% (on highest):
+    let firing_rate = 0.2 + 0.8 * proficiency;
+
+    let super_time = 1.0 / firing_rate; // minimum of 1.0 seconds between Super shots
+    let missile_time = 0.34 / firing_rate; // minimum of 0.34 seconds between Missile shots
+    let charge_time = 1.4 / firing_rate; // minimum of 1.4 seconds between charge shots
+    let beam_time = 0.34 / firing_rate; // minimum of 0.34 seconds between uncharged shots
+
+    let charge_dps = charge_damage * accuracy / charge_time;
+    let missiles_dps = 100.0 * accuracy / missile_time;
+
+    // Use Charge first if it's higher DPS than Missiles (which happens with Charge + Plasma),
+    // to minimize damage taken:
+    if charge_dps >= missiles_dps {
+        let charge_shots_to_use = f32::ceil(boss_hp / (charge_damage * accuracy));
+        boss_hp = 0.0;
+        time += charge_shots_to_use * charge_time;
+    }
+
+    // Then use Missiles:
+    if boss_hp > 0.0 {
+        let missiles_available = local.missiles_available(inventory, reverse);
+        let missiles_to_use = min(
+            missiles_available,
+            f32::ceil(boss_hp / (100.0 * accuracy)) as Capacity,
+        );
+        assert!(local.use_missiles(missiles_to_use, inventory, reverse));
+        boss_hp -= missiles_to_use as f32 * 100.0 * accuracy;
+        time += missiles_to_use as f32 * missile_time;
+    }
+
+    // Then use Supers:
+    if boss_hp > 0.0 {
+        let supers_available = local.supers_available(inventory, reverse);
+        let supers_to_use = min(
+            supers_available,
+            f32::ceil(boss_hp / (300.0 * super_accuracy)) as Capacity,
+        );
+        assert!(local.use_supers(supers_to_use, inventory, reverse));
+        boss_hp -= supers_to_use as f32 * 300.0 * super_accuracy;
+        time += supers_to_use as f32 * super_time;
+    }
+
+    // Then finish with Charge shots, if available, or otherwise uncharged shots:
+    if boss_hp > 0.0 {
+        if charge_damage > 0.0 {
+            let charge_shots_to_use = f32::ceil(boss_hp / (charge_damage * accuracy));
+            time += charge_shots_to_use * charge_time;
+        } else {
+            let beam_shots_to_use = f32::ceil(boss_hp / (beam_damage * accuracy));
+            time += beam_shots_to_use * beam_time;
+        }
+    }
+
+    // For determining if patience tech is required:
+    // `good_time` = hypothetical time based on good execution
+    let good_time = time * firing_rate * accuracy;
+    if good_time >= 120.0 && !can_be_patient
+        || good_time >= 180.0 && !can_be_very_patient
+        || good_time >= 360.0 && !can_be_extremely_patient
+    {
+        // We don't have enough patience to finish the fight:
+        return false;
+    }
+
+    let space_jump = inventory.items[Item::SpaceJump as usize];
+    let screw = inventory.items[Item::ScrewAttack as usize];
+
+    // Assumed rate of damage to Samus (per second) from Golden Torizo's eye beams, eggs,
+    // crescent waves and swipes, given minimal dodging skill:
+    let base_hit_dps = 30.0;
+
+    // Multiplier to damage based on items (SpaceJump and Screw) and proficiency (in dodging).
+    // We assume that at Insane level (proficiency=1.0) it is possible to avoid nearly all damage
+    // with either item.
+    let hit_rate = match (space_jump, screw) {
+        (false, false) => 1.0 - 0.85 * proficiency,
+        (true, false) => 0.8 - 0.75 * proficiency,
+        (false, true) => 0.7 - 0.65 * proficiency,
+        (true, true) => 0.6 - 0.6 * proficiency,
+    };
+    let damage = base_hit_dps * hit_rate * time;
+    // Overflow safeguard - bail here if Samus takes calamitous damage.
+    if damage > 10000.0 {
+        return false;
+    }
+    let energy_used = (damage / suit_damage_factor(inventory) as f32) as Capacity;
+    local.use_energy(energy_used, true, inventory, reverse)
+}
@@ -5,11 +5,15 @@ pub fn get_charge_damage(inventory: &Inventory) -> f32 {
     if !inventory.items[Item::Charge as usize] {
         return 0.0;
     }
+    3.0 * get_uncharged_damage(inventory)
+}
+
+pub fn get_uncharged_damage(inventory: &Inventory) -> f32 {
     let plasma = inventory.items[Item::Plasma as usize];
     let spazer = inventory.items[Item::Spazer as usize];
     let wave = inventory.items[Item::Wave as usize];
     let ice = inventory.items[Item::Ice as usize];
-    3.0 * match (plasma, spazer, wave, ice) {
+    match (plasma, spazer, wave, ice) {
         (false, false, false, false) => 20.0,
         (false, false, false, true) => 30.0,
         (false, false, true, false) => 50.0,
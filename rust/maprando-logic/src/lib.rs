@@ -7,7 +7,7 @@ pub mod helpers;
 use maprando_game::{Capacity, GameData, Item, ReserveTriggerHeat, StepTrailId, WeaponMask};
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Inventory {
     pub items: Vec<bool>,
     pub max_energy: Capacity,
@@ -22,7 +22,7 @@ pub struct Inventory {
 }
 
 // TODO: move tech and notable_strats out of this struct, since these do not change from step to step.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GlobalState {
     pub inventory: Inventory,
     pub pool_inventory: Inventory,
@@ -133,3 +133,115 @@ pub fn can_defeat_mother_brain_2(
         JsValue::null()
     }
 }
+
+#[wasm_bindgen]
+pub fn can_defeat_kraid(
+    inventory: JsValue,
+    local: JsValue,
+    proficiency: f32,
+    can_be_patient: bool,
+    can_be_very_patient: bool,
+    can_be_extremely_patient: bool,
+) -> JsValue {
+    let inventory: Inventory = serde_wasm_bindgen::from_value(inventory).unwrap();
+    let mut local =
+        serde_wasm_bindgen::from_value(local).unwrap_or_else(|_| LocalState::full(false));
+
+    if apply_kraid_requirement(
+        &inventory,
+        &mut local,
+        proficiency,
+        can_be_patient,
+        can_be_very_patient,
+        can_be_extremely_patient,
+        false,
+    ) {
+        serde_wasm_bindgen::to_value(&local).unwrap()
+    } else {
+        JsValue::null()
+    }
+}
+
+#[wasm_bindgen]
+pub fn can_defeat_crocomire(
+    inventory: JsValue,
+    local: JsValue,
+    proficiency: f32,
+    can_be_patient: bool,
+    can_be_very_patient: bool,
+    can_be_extremely_patient: bool,
+) -> JsValue {
+    let inventory: Inventory = serde_wasm_bindgen::from_value(inventory).unwrap();
+    let mut local =
+        serde_wasm_bindgen::from_value(local).unwrap_or_else(|_| LocalState::full(false));
+
+    if apply_crocomire_requirement(
+        &inventory,
+        &mut local,
+        proficiency,
+        can_be_patient,
+        can_be_very_patient,
+        can_be_extremely_patient,
+        false,
+    ) {
+        serde_wasm_bindgen::to_value(&local).unwrap()
+    } else {
+        JsValue::null()
+    }
+}
+
+#[wasm_bindgen]
+pub fn can_defeat_spore_spawn(
+    inventory: JsValue,
+    local: JsValue,
+    proficiency: f32,
+    can_be_patient: bool,
+    can_be_very_patient: bool,
+    can_be_extremely_patient: bool,
+) -> JsValue {
+    let inventory: Inventory = serde_wasm_bindgen::from_value(inventory).unwrap();
+    let mut local =
+        serde_wasm_bindgen::from_value(local).unwrap_or_else(|_| LocalState::full(false));
+
+    if apply_spore_spawn_requirement(
+        &inventory,
+        &mut local,
+        proficiency,
+        can_be_patient,
+        can_be_very_patient,
+        can_be_extremely_patient,
+        false,
+    ) {
+        serde_wasm_bindgen::to_value(&local).unwrap()
+    } else {
+        JsValue::null()
+    }
+}
+
+#[wasm_bindgen]
+pub fn can_defeat_golden_torizo(
+    inventory: JsValue,
+    local: JsValue,
+    proficiency: f32,
+    can_be_patient: bool,
+    can_be_very_patient: bool,
+    can_be_extremely_patient: bool,
+) -> JsValue {
+    let inventory: Inventory = serde_wasm_bindgen::from_value(inventory).unwrap();
+    let mut local =
+        serde_wasm_bindgen::from_value(local).unwrap_or_else(|_| LocalState::full(false));
+
+    if apply_golden_torizo_requirement(
+        &inventory,
+        &mut local,
+        proficiency,
+        can_be_patient,
+        can_be_very_patient,
+        can_be_extremely_patient,
+        false,
+    ) {
+        serde_wasm_bindgen::to_value(&local).unwrap()
+    } else {
+        JsValue::null()
+    }
+}
@@ -1,6 +1,5 @@
 use anyhow::Result;
 use askama::Template;
-use glob::glob;
 use hashbrown::{HashMap, HashSet};
 use itertools::Itertools;
 use json::JsonValue;
@@ -28,7 +27,7 @@ use maprando_game::{
     TECH_ID_CAN_WALLJUMP, TechId, VertexKey, parse_speed_booster,
 };
 use maprando_logic::{GlobalState, Inventory};
-use std::{io::Cursor, path::PathBuf};
+use std::io::Cursor;
 
 use super::VersionInfo;
 
@@ -160,30 +159,24 @@ pub struct LogicData {
     pub vanilla_map_png: Vec<u8>, // PNG of vanilla map, to show on logic index page
 }
 
-fn list_room_diagram_files() -> HashMap<usize, String> {
+// List the room diagrams in sm-json-data, with the URL paths that they are served at.
+fn list_room_diagram_files(game_data: &GameData) -> Result<HashMap<usize, String>> {
     let mut out: HashMap<usize, String> = HashMap::new();
-    for entry in glob("../sm-json-data/region/*/roomDiagrams/*.png").unwrap() {
-        match entry {
-            Ok(path) => {
-                let mut new_path = PathBuf::new();
-                new_path = new_path.join("static");
-                for c in path.components().skip(1) {
-                    new_path = new_path.join(c);
-                }
-
-                let path_string = new_path.to_str().unwrap().to_string();
-                let segments: Vec<&str> = path_string.split(['_', '.']).collect();
-                let subregion = segments[0];
-                if subregion == "ceres" {
-                    continue;
-                }
-                let room_id: usize = str::parse(segments[2]).unwrap();
-                out.insert(room_id, path_string);
-            }
-            Err(e) => panic!("Failure reading room diagrams: {e:?}"),
+    for path in game_data.data_source().list("sm-json-data/region")? {
+        let components: Vec<&str> = path.split('/').collect();
+        if components.len() != 5 || components[3] != "roomDiagrams" || !path.ends_with(".png") {
+            continue;
+        }
+        let path_string = format!("static/{path}");
+        let segments: Vec<&str> = path_string.split(['_', '.']).collect();
+        let subregion = segments[0];
+        if subregion == "ceres" {
+            continue;
         }
+        let room_id: usize = str::parse(segments[2]).unwrap();
+        out.insert(room_id, path_string);
     }
-    out
+    Ok(out)
 }
 
 fn make_requires(requires_json: &JsonValue) -> String {
@@ -1075,7 +1068,7 @@ impl LogicData {
         let vanilla_map_data =
             get_vanilla_map_data(vanilla_map, game_data, &preset_data.default_preset)?;
         out.vanilla_map_png = vanilla_map_data.png;
-        let room_diagram_listing = list_room_diagram_files();
+        let room_diagram_listing = list_room_diagram_files(game_data)?;
         let mut room_templates: Vec<RoomTemplate> = vec![];
 
         let items = vec![true; game_data.item_isv.keys.len()];
@@ -22,8 +22,8 @@ use log::{error, info};
 use rand::{RngCore, SeedableRng};
 use serde_derive::{Deserialize, Serialize};
 use serde_variant::to_variant_name;
-use std::time::{Duration, SystemTime};
-use std::{path::Path, time::Instant};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 
 use crate::{
     logic_helper::LogicData,
@@ -34,6 +34,7 @@ use maprando::settings::{ObjectiveGroup, get_objective_groups};
 use maprando::{
     customize::{mosaic::MosaicTheme, samus_sprite::SamusSpriteCategory},
     difficulty::{get_full_global, get_link_difficulty_length},
+    map_areas::validate_area_assignment,
     map_repository::MapRepository,
     preset::PresetData,
     randomize::{
@@ -45,10 +46,13 @@ use maprando::{
     spoiler_log::SpoilerLog,
 };
 use maprando_game::GameData;
+use maprando_game::cache::default_cache_path;
+use maprando_game::data_source::open_data_source;
 use maprando_game::{LinksDataGroup, Map};
 use maprando_game::{NotableId, RoomId, StartLocation, TechId};
 
-const VISUALIZER_PATH: &str = "../visualizer/";
+// Location of the visualizer, relative to `base_path`.
+const VISUALIZER_PATH: &str = "../visualizer";
 
 #[derive(Parser)]
 struct Args {
@@ -66,11 +70,19 @@ struct Args {
     dev: bool,
     #[arg(long, default_value_t = 8080)]
     port: u16,
+    /// Directory containing the randomizer data (`data/`) and web assets (`static/`,
+    /// `maprando-wasm/pkg`), with the other data files (sm-json-data, patches, maps, the
+    /// visualizer, etc.) in its parent directory
+    #[arg(long, default_value = ".")]
+    base_path: PathBuf,
+    /// Bundled data archive to load all data files from, in place of `base_path`
+    #[arg(long)]
+    data_archive: Option<PathBuf>,
 }
 
-fn load_visualizer_files() -> Vec<(String, Vec<u8>)> {
+fn load_visualizer_files(base_path: &Path) -> Vec<(String, Vec<u8>)> {
     let mut files: Vec<(String, Vec<u8>)> = vec![];
-    for entry_res in std::fs::read_dir(VISUALIZER_PATH).unwrap() {
+    for entry_res in std::fs::read_dir(base_path.join(VISUALIZER_PATH)).unwrap() {
         let entry = entry_res.unwrap();
         let name = entry.file_name().to_str().unwrap().to_string();
         let data = std::fs::read(entry.path()).unwrap();
@@ -82,15 +94,12 @@ fn load_visualizer_files() -> Vec<(String, Vec<u8>)> {
 fn build_app_data() -> AppData {
     let start_time = Instant::now();
     let args = Args::parse();
-    let etank_colors_path = Path::new("data/etank_colors.json");
-    let vanilla_map_path = Path::new("../maps/vanilla");
-    let small_maps_path = Path::new("../maps/v119-small-avro");
-    let standard_maps_path = Path::new("../maps/v119-standard-avro");
-    let wild_maps_path = Path::new("../maps/v119-wild-avro");
-    let samus_sprites_path = Path::new("../MapRandoSprites/samus_sprites/manifest.json");
-    let tech_path = Path::new("data/tech_data.json");
-    let notable_path = Path::new("data/notable_data.json");
-    let presets_path = Path::new("data/presets");
+    let etank_colors_path = "data/etank_colors.json";
+    let vanilla_map_path = "maps/vanilla";
+    let small_maps_path = "maps/v119-small-avro";
+    let standard_maps_path = "maps/v119-standard-avro";
+    let wild_maps_path = "maps/v119-wild-avro";
+    let samus_sprites_path = "MapRandoSprites/samus_sprites/manifest.json";
     let mosaic_themes = vec![
         ("OuterCrateria", "Outer Crateria"),
         ("InnerCrateria", "Inner Crateria"),
@@ -115,11 +124,16 @@ fn build_app_data() -> AppData {
     })
     .collect();
 
-    let mut game_data = GameData::load(Path::new(".")).unwrap();
+    let data_source = open_data_source(&args.base_path, args.data_archive.as_deref()).unwrap();
+    let mut game_data = GameData::load_from_source(
+        data_source.clone(),
+        Some(&default_cache_path(&args.base_path)),
+    )
+    .unwrap();
 
     info!("Loading logic preset data");
     let etank_colors: Vec<Vec<String>> =
-        serde_json::from_str(&std::fs::read_to_string(etank_colors_path).unwrap()).unwrap();
+        serde_json::from_str(&data_source.read_to_string(etank_colors_path).unwrap()).unwrap();
     let version_info = VersionInfo {
         version: VERSION,
         dev: args.dev,
@@ -131,7 +145,7 @@ fn build_app_data() -> AppData {
         args.video_storage_url.clone()
     };
 
-    let preset_data = PresetData::load(tech_path, notable_path, presets_path, &game_data).unwrap();
+    let preset_data = PresetData::load(data_source.as_ref(), &game_data).unwrap();
     let global = get_full_global(&game_data);
     game_data.make_links_data(&|link, game_data| {
         get_link_difficulty_length(link, game_data, &preset_data, &global)
@@ -140,19 +154,19 @@ fn build_app_data() -> AppData {
     let map_repositories: HashMap<String, MapRepository> = vec![
         (
             "Vanilla".to_string(),
-            MapRepository::new("Vanilla", vanilla_map_path).unwrap(),
+            MapRepository::new("Vanilla", data_source.clone(), vanilla_map_path).unwrap(),
         ),
         (
             "Small".to_string(),
-            MapRepository::new("Small", small_maps_path).unwrap(),
+            MapRepository::new("Small", data_source.clone(), small_maps_path).unwrap(),
         ),
         (
             "Standard".to_string(),
-            MapRepository::new("Standard", standard_maps_path).unwrap(),
+            MapRepository::new("Standard", data_source.clone(), standard_maps_path).unwrap(),
         ),
         (
             "Wild".to_string(),
-            MapRepository::new("Wild", wild_maps_path).unwrap(),
+            MapRepository::new("Wild", data_source.clone(), wild_maps_path).unwrap(),
         ),
     ]
     .into_iter()
@@ -160,6 +174,7 @@ fn build_app_data() -> AppData {
     let vanilla_map = map_repositories["Vanilla"]
         .get_map_batch(0, &game_data)
         .unwrap()[0]
+        .1
         .clone();
 
     let logic_data = LogicData::new(
@@ -171,14 +186,14 @@ fn build_app_data() -> AppData {
     )
     .unwrap();
     let samus_sprite_categories: Vec<SamusSpriteCategory> =
-        serde_json::from_str(&std::fs::read_to_string(samus_sprites_path).unwrap()).unwrap();
+        serde_json::from_str(&data_source.read_to_string(samus_sprites_path).unwrap()).unwrap();
 
     let app_data = AppData {
         game_data,
         preset_data,
         map_repositories,
         seed_repository: SeedRepository::new(&args.seed_repository_url).unwrap(),
-        visualizer_files: load_visualizer_files(),
+        visualizer_files: load_visualizer_files(&args.base_path),
         video_storage_url,
         video_storage_path: args.video_storage_path.clone(),
         logic_data,
@@ -189,13 +204,49 @@ fn build_app_data() -> AppData {
         static_visualizer: args.static_visualizer,
         etank_colors,
         mosaic_themes,
+        base_path: args.base_path.clone(),
     };
     info!("Start-up time: {:.3}s", start_time.elapsed().as_secs_f32());
     app_data
 }
 
-pub async fn fav_icon() -> actix_web::Result<actix_files::NamedFile> {
-    Ok(NamedFile::open("static/favicon.ico")?)
+pub async fn fav_icon(
+    app_data: actix_web::web::Data<AppData>,
+) -> actix_web::Result<actix_files::NamedFile> {
+    Ok(NamedFile::open(
+        app_data.base_path.join("static/favicon.ico"),
+    )?)
+}
+
+// sm-json-data is served through the data source, so that it is also available when the data
+// comes from a bundled archive.
+#[get("/static/sm-json-data/{path:.*}")]
+async fn sm_json_data_file(
+    path: actix_web::web::Path<String>,
+    app_data: actix_web::web::Data<AppData>,
+) -> impl Responder {
+    let path = path.into_inner();
+    if path.split('/').any(|x| x == "..") {
+        return HttpResponse::NotFound().finish();
+    }
+    match app_data
+        .game_data
+        .data_source()
+        .read(&format!("sm-json-data/{path}"))
+    {
+        Ok(data) => {
+            let ext = Path::new(&path)
+                .extension()
+                .and_then(|x| x.to_str())
+                .unwrap_or("bin");
+            let mime = actix_files::file_extension_to_mime(ext);
+            HttpResponse::Ok().content_type(mime).body(data)
+        }
+        Err(e) => {
+            error!("{e:#}");
+            HttpResponse::NotFound().finish()
+        }
+    }
 }
 
 #[derive(Template)]
@@ -428,6 +479,8 @@ struct SeedData {
     http_headers: serde_json::Map<String, serde_json::Value>,
     random_seed: usize,
     map_seed: usize,
+    #[serde(default)]
+    map_id: Option<String>,
     door_randomization_seed: usize,
     item_placement_seed: usize,
     settings: RandomizerSettings,
@@ -496,6 +549,7 @@ struct RandomizeResponse {
 struct AttemptOutput {
     random_seed: usize,
     map_seed: usize,
+    map_id: String,
     door_randomization_seed: usize,
     item_placement_seed: usize,
     randomization: Randomization,
@@ -507,6 +561,7 @@ struct AttemptOutput {
 enum AttemptError {
     TooManyAttempts,
     TimedOut,
+    NoMatchingMap(String),
 }
 
 fn handle_randomize_request(
@@ -562,7 +617,7 @@ fn handle_randomize_request(
 
     let time_start_attempts = Instant::now();
     let mut attempt_num = 0;
-    let mut map_batch: Vec<Map> = vec![];
+    let mut map_batch: Vec<(String, Map)> = vec![];
     for _ in 0..max_map_attempts {
         let map_seed = (rng.next_u64() & 0xFFFFFFFF) as usize;
         let door_randomization_seed = (rng.next_u64() & 0xFFFFFFFF) as usize;
@@ -572,13 +627,20 @@ fn handle_randomize_request(
             panic!("Unrecognized map layout option: {map_layout}");
         }
 
-        if map_batch.is_empty() {
-            map_batch = app_data.map_repositories[&map_layout]
-                .get_map_batch(map_seed, &app_data.game_data)
-                .unwrap();
-        }
-
-        let mut map = map_batch.pop().unwrap();
+        let map_repository = &app_data.map_repositories[&map_layout];
+        let (map_id, mut map) = if let Some(map_query) = &settings.map_query {
+            match map_repository.select_map(map_seed, map_query, &app_data.game_data) {
+                Ok(x) => x,
+                Err(e) => return Err(AttemptError::NoMatchingMap(e.to_string())),
+            }
+        } else {
+            if map_batch.is_empty() {
+                map_batch = map_repository
+                    .get_map_batch(map_seed, &app_data.game_data)
+                    .unwrap();
+            }
+            map_batch.pop().unwrap()
+        };
         if !assign_map_areas(&mut map, &settings, map_seed, &app_data.game_data) {
             info!("Area assignment failed for map seed={map_seed}");
             continue;
@@ -621,7 +683,7 @@ fn handle_randomize_request(
                 }
             };
             info!(
-                "Successful attempt {attempt_num}/{attempt_num}/{max_attempts}: display_seed={}, random_seed={random_seed}, map_seed={map_seed}, door_randomization_seed={door_randomization_seed}, item_placement_seed={item_placement_seed}",
+                "Successful attempt {attempt_num}/{attempt_num}/{max_attempts}: display_seed={}, random_seed={random_seed}, map_seed={map_seed}, map_id={map_id}, door_randomization_seed={door_randomization_seed}, item_placement_seed={item_placement_seed}",
                 randomization.display_seed,
             );
 
@@ -632,6 +694,7 @@ fn handle_randomize_request(
             let output_result = Ok(AttemptOutput {
                 random_seed,
                 map_seed,
+                map_id,
                 door_randomization_seed,
                 item_placement_seed,
                 randomization,
@@ -679,6 +742,20 @@ async fn randomize(
         return HttpResponse::BadRequest().body("Invalid Ridley proficiency");
     }
 
+    if let Err(e) = validate_area_assignment(
+        &settings.other_settings.area_assignment,
+        &settings.map_layout,
+        &app_data.game_data,
+    ) {
+        return HttpResponse::BadRequest().body(format!("Invalid area assignment: {e}"));
+    }
+
+    if let Some(map_query) = &settings.map_query
+        && let Err(e) = map_query.validate(settings.other_settings.area_assignment.partition)
+    {
+        return HttpResponse::BadRequest().body(format!("Invalid map query: {e}"));
+    }
+
     let settings_copy = settings.clone();
     let app_data_copy = app_data.clone();
     let output_result = actix_web::rt::task::spawn_blocking(|| {
@@ -697,6 +774,9 @@ async fn randomize(
             return HttpResponse::InternalServerError()
                 .body("Failed too many randomization attempts (maximum attempt count reached)");
         }
+        Err(AttemptError::NoMatchingMap(e)) => {
+            return HttpResponse::BadRequest().body(format!("Unable to select map: {e}"));
+        }
     };
 
     let timestamp = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
@@ -714,6 +794,7 @@ async fn randomize(
         http_headers: format_http_headers(&http_req),
         random_seed: output.random_seed,
         map_seed: output.map_seed,
+        map_id: Some(output.map_id.clone()),
         door_randomization_seed: output.door_randomization_seed,
         item_placement_seed: output.item_placement_seed,
         settings: settings.clone(),
@@ -913,6 +994,8 @@ async fn main() {
     let app_data = actix_web::web::Data::new(build_app_data());
 
     let port = app_data.port;
+    let static_path = app_data.base_path.join("static");
+    let wasm_path = app_data.base_path.join("maprando-wasm/pkg");
 
     HttpServer::new(move || {
         let mut app = App::new()
@@ -933,12 +1016,9 @@ async fn main() {
             .service(seed::scope())
             .service(logic::scope())
             .service(upgrade_settings)
-            .service(actix_files::Files::new(
-                "/static/sm-json-data",
-                "../sm-json-data",
-            ))
-            .service(actix_files::Files::new("/static", "static"))
-            .service(actix_files::Files::new("/wasm", "maprando-wasm/pkg"))
+            .service(sm_json_data_file)
+            .service(actix_files::Files::new("/static", &static_path))
+            .service(actix_files::Files::new("/wasm", &wasm_path))
             .route("/favicon.ico", actix_web::web::get().to(fav_icon));
 
         if let Some(path) = &app_data.video_storage_path {
@@ -10,9 +10,10 @@ use maprando::{
     randomize::{DifficultyConfig, ItemPriorityGroup, Randomization, get_starting_items},
     seed_repository::{Seed, SeedFile},
     settings::{
-        AreaAssignmentBaseOrder, AreaAssignmentPreset, CrashFixesPreset, DisableETankSetting,
-        DoorLocksSize, ETankRefill, FillerItemPriority, ItemCount, MapStationActivationPreset,
-        RandomizerSettings, SpeedBooster, WallJump, get_objective_groups,
+        AreaAssignmentBaseOrder, AreaAssignmentPreset, AreaConstraints, AreaPartition,
+        CrashFixesPreset, DisableETankSetting, DoorLocksSize, ETankRefill, FillerItemPriority,
+        ItemCount, MapStationActivationPreset, RandomizerSettings, SpeedBooster, WallJump,
+        get_objective_groups,
     },
     spoiler_log::SpoilerLog,
     spoiler_map,
@@ -190,6 +191,18 @@ impl SeedHeaderTemplate<'_> {
             if other_settings.area_assignment.mother_brain_in_tourian {
                 game_variations.push("Mother Brain in Tourian");
             }
+            match other_settings.area_assignment.partition {
+                AreaPartition::Map => {}
+                AreaPartition::Size => {
+                    game_variations.push("Areas regrouped by size");
+                }
+                AreaPartition::Items => {
+                    game_variations.push("Areas regrouped by item locations");
+                }
+            }
+            if other_settings.area_assignment.constraints != AreaConstraints::default() {
+                game_variations.push("Area constraints");
+            }
         }
         if other_settings.door_locks_size == DoorLocksSize::Small {
             game_variations.push("Door locks drawn smaller on map");
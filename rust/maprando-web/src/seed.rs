@@ -28,7 +28,10 @@ use maprando::{
         ControllerButton, ControllerConfig, CustomizeSettings, DoorTheme, FlashingSetting,
         MusicSettings, PaletteTheme, ShakingSetting, TileTheme, parse_controller_button,
     },
-    patch::{Rom, make_rom},
+    patch::{
+        bps::create_bps_patch, input_rom::normalize_input_rom, make_rom_with_info,
+        passes::ConflictPolicy, title::load_title_background,
+    },
     randomize::Randomization,
     settings::{RandomizerSettings, try_upgrade_settings},
 };
@@ -127,19 +130,21 @@ async fn get_seed_file(
     let filename = &info.1;
     println!("get_seed_file {filename}");
 
-    let data_result: Result<Vec<u8>> = if filename.starts_with("visualizer/")
-        && app_data.static_visualizer
-    {
-        let path = Path::new(VISUALIZER_PATH).join(filename.strip_prefix("visualizer/").unwrap());
-        std::fs::read(&path)
-            .map_err(anyhow::Error::from)
-            .with_context(|| format!("Error reading static file: {}", path.display()))
-    } else {
-        app_data
-            .seed_repository
-            .get_file(seed_name, &("public/".to_string() + filename))
-            .await
-    };
+    let data_result: Result<Vec<u8>> =
+        if filename.starts_with("visualizer/") && app_data.static_visualizer {
+            let path = app_data
+                .base_path
+                .join(VISUALIZER_PATH)
+                .join(filename.strip_prefix("visualizer/").unwrap());
+            std::fs::read(&path)
+                .map_err(anyhow::Error::from)
+                .with_context(|| format!("Error reading static file: {}", path.display()))
+        } else {
+            app_data
+                .seed_repository
+                .get_file(seed_name, &("public/".to_string() + filename))
+                .await
+        };
 
     match data_result {
         Ok(data) => {
@@ -160,11 +165,15 @@ async fn get_seed_file(
 
 #[derive(Template)]
 #[template(path = "errors/invalid_rom.html")]
-struct InvalidRomTemplate {}
+struct InvalidRomTemplate {
+    reason: String,
+}
 
 #[derive(MultipartForm)]
 struct CustomizeRequest {
     rom: Bytes,
+    output_format: Option<Text<String>>,
+    title_image: Option<Bytes>,
     samus_sprite: Text<String>,
     etank_color: Text<String>,
     map_theme: Text<String>,
@@ -258,7 +267,21 @@ async fn customize_seed(
     app_data: web::Data<AppData>,
 ) -> impl Responder {
     let seed_name = &info.0;
-    let orig_rom = Rom::new(req.rom.data.to_vec());
+    let orig_rom = match normalize_input_rom(&req.rom.data) {
+        Ok(normalized) => {
+            if normalized.header_removed {
+                info!("Removed copier header from input ROM");
+            }
+            normalized.rom
+        }
+        Err(err) => {
+            info!("Invalid input ROM: {err}");
+            let template = InvalidRomTemplate {
+                reason: err.to_string(),
+            };
+            return HttpResponse::BadRequest().body(template.render().unwrap());
+        }
+    };
     let mut rom = orig_rom.clone();
 
     let settings_bytes = app_data
@@ -292,11 +315,21 @@ async fn customize_seed(
         Some(serde_json::from_slice(&randomization_bytes).unwrap())
     };
 
-    let rom_digest = crypto_hash::hex_digest(crypto_hash::Algorithm::SHA256, &rom.data);
-    info!("Rom digest: {rom_digest}");
-    if rom_digest != "12b77c4bc9c1832cee8881244659065ee1d84c70c3d29e6eaf92e6798cc2ca72" {
-        return HttpResponse::BadRequest().body(InvalidRomTemplate {}.render().unwrap());
-    }
+    // An empty file is sent when no title image is selected.
+    let title_background = match req.title_image.as_ref().filter(|x| !x.data.is_empty()) {
+        Some(title_image) => match load_title_background(
+            &title_image.data,
+            &app_data.game_data.title_screen_data.map_station,
+        ) {
+            Ok(img) => Some(img),
+            Err(err) => {
+                info!("Invalid title screen image: {err:#}");
+                return HttpResponse::BadRequest()
+                    .body(format!("Invalid title screen image: {err:#}"));
+            }
+        },
+        None => None,
+    };
 
     let customize_settings = CustomizeSettings {
         samus_sprite: Some(req.samus_sprite.0.clone()),
@@ -394,14 +427,16 @@ async fn customize_seed(
             load_state_buttons: get_load_state_buttons(&req),
             moonwalk: req.moonwalk.0,
         },
+        title_background,
     };
 
+    let mut build_info = None;
     if settings.is_some()
         && let Some(mut randomization) = randomization
     {
         info!("Patching ROM");
         upgrade_randomization(&mut randomization);
-        match make_rom(
+        match make_rom_with_info(
             &rom,
             settings.as_ref().unwrap(),
             &customize_settings,
@@ -409,9 +444,12 @@ async fn customize_seed(
             &app_data.game_data,
             &app_data.samus_sprite_categories,
             &app_data.mosaic_themes,
+            ConflictPolicy::Warn,
         ) {
-            Ok(r) => {
+            Ok((r, new_build_info)) => {
+                new_build_info.write_log.warn_conflicts();
                 rom = r;
+                build_info = Some(new_build_info);
             }
             Err(err) => {
                 return HttpResponse::InternalServerError()
@@ -422,6 +460,37 @@ async fn customize_seed(
         return HttpResponse::InternalServerError()
             .body("Seed incompatible with current customizer");
     }
+    let output_format = req.output_format.as_ref().map(|x| x.0.as_str());
+    if output_format == Some("bps") {
+        info!("Creating BPS patch");
+        let patch = create_bps_patch(&orig_rom.data, &rom.data);
+        return HttpResponse::Ok()
+            .content_type("application/octet-stream")
+            .insert_header(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(
+                    "map-rando-".to_string() + seed_name + ".bps",
+                )],
+            })
+            .body(patch);
+    }
+    if let Some(ext @ ("sym" | "mlb")) = output_format {
+        let symbols = &build_info.unwrap().symbols;
+        let symbols_text = if ext == "sym" {
+            symbols.to_sym()
+        } else {
+            symbols.to_mlb()
+        };
+        return HttpResponse::Ok()
+            .content_type("text/plain")
+            .insert_header(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(
+                    "map-rando-".to_string() + seed_name + "." + ext,
+                )],
+            })
+            .body(symbols_text);
+    }
     HttpResponse::Ok()
         .content_type("application/octet-stream")
         .insert_header(ContentDisposition {
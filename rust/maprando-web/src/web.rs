@@ -7,6 +7,7 @@ use maprando::{
     seed_repository::SeedRepository,
 };
 use maprando_game::GameData;
+use std::path::PathBuf;
 
 pub const VERSION: usize = include!("../../VERSION");
 
@@ -33,4 +34,5 @@ pub struct AppData {
     pub static_visualizer: bool,
     pub etank_colors: Vec<Vec<String>>, // colors in HTML hex format, e.g "#ff0000"
     pub mosaic_themes: Vec<MosaicTheme>,
+    pub base_path: PathBuf, // directory containing `static/` and `maprando-wasm/`
 }
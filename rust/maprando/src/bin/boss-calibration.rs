@@ -0,0 +1,37 @@
+use anyhow::{Result, bail};
+use clap::Parser;
+use maprando::boss_simulation::{Boss, format_calibration_table, get_calibration_table};
+
+/// Print a table comparing simulated boss fights against the closed-form fight models,
+/// for each boss, item loadout, and proficiency level.
+#[derive(Parser)]
+struct Args {
+    /// Boss to simulate (e.g. "Kraid"); by default all bosses are included
+    #[arg(long)]
+    boss: Option<String>,
+
+    /// Number of simulated fights per table row
+    #[arg(long, default_value_t = 1000)]
+    trials: usize,
+
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let bosses: Vec<Boss> = match &args.boss {
+        Some(name) => {
+            let Some(boss) = Boss::ALL.iter().find(|b| b.name() == name) else {
+                bail!("Unrecognized boss: {name}");
+            };
+            vec![*boss]
+        }
+        None => Boss::ALL.to_vec(),
+    };
+    for boss in bosses {
+        let rows = get_calibration_table(boss, args.trials, args.seed);
+        println!("{}", format_calibration_table(&rows));
+    }
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use maprando::map_index::MAP_INDEX_FILENAME;
+use maprando::map_repository::MapRepository;
+use maprando_game::GameData;
+use maprando_game::cache::default_cache_path;
+use maprando_game::data_source::open_data_source;
+use std::path::PathBuf;
+
+/// Build the metadata index of a map repository, used for selecting maps by their properties.
+#[derive(Parser)]
+struct Args {
+    /// Directory containing the randomizer data
+    #[arg(long, default_value = ".")]
+    base_path: PathBuf,
+
+    /// Bundled data archive to load data from, in place of `base_path`
+    #[arg(long)]
+    data_archive: Option<PathBuf>,
+
+    /// Map repository directory, relative to the data root (e.g. "maps/vanilla")
+    repository: String,
+
+    /// File to write (default: the repository's index file under `base_path`)
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .format_timestamp_millis()
+        .init();
+    let args = Args::parse();
+    let data_source = open_data_source(&args.base_path, args.data_archive.as_deref())?;
+    let game_data = GameData::load_from_source(
+        data_source.clone(),
+        Some(&default_cache_path(&args.base_path)),
+    )?;
+
+    let repository = MapRepository::new(&args.repository, data_source, &args.repository)?;
+    let index = repository.build_index(&game_data)?;
+
+    let output = args.output.unwrap_or_else(|| {
+        args.base_path
+            .join(&args.repository)
+            .join(MAP_INDEX_FILENAME)
+    });
+    std::fs::write(&output, serde_json::to_vec_pretty(&index)?)
+        .with_context(|| format!("Unable to write {}", output.display()))?;
+    println!(
+        "Indexed {} map(s) to {}",
+        index.entries.len(),
+        output.display()
+    );
+    Ok(())
+}
@@ -9,6 +9,7 @@ use json::JsonValue;
 use log::{error, info};
 use maprando::customize::Allocator;
 use maprando::patch::bps::BPSEncoder;
+use maprando::patch::compress::compress;
 use maprando::patch::suffix_tree::SuffixTree;
 use maprando::patch::{Rom, get_room_state_ptrs, pc2snes, snes2pc};
 use maprando_game::{
@@ -164,7 +165,7 @@ impl MosaicPatchBuilder {
                     .context("error running compressor")?;
                 output = std::fs::read(output_path)?;
             } else {
-                output = lznint::compress(data);
+                output = compress(data);
                 std::fs::write(&output_path, output.clone())?;
             }
         } else {
@@ -0,0 +1,47 @@
+use anyhow::Result;
+use clap::Parser;
+use log::info;
+use maprando_game::data_source::{MemorySource, default_data_source};
+use std::path::PathBuf;
+
+/// Bundle the data files used by the randomizer into a single archive, which can be passed to
+/// the other tools with `--data-archive`.
+#[derive(Parser)]
+struct Args {
+    /// Directory containing the randomizer data (`data/`), with the other data files
+    /// (sm-json-data, patches, maps, etc.) in its parent directory
+    #[arg(long, default_value = ".")]
+    base_path: PathBuf,
+
+    #[arg(long)]
+    output: PathBuf,
+
+    /// Files or directories to include, as paths in the data layout (e.g. "maps/vanilla")
+    #[arg(long, num_args = 1.., default_values_t = [
+        "sm-json-data".to_string(),
+        "room_geometry.json".to_string(),
+        "TitleScreen/Images".to_string(),
+        "gfx".to_string(),
+        "patches".to_string(),
+        "data".to_string(),
+        "maps/vanilla".to_string(),
+    ])]
+    include: Vec<String>,
+}
+
+fn main() -> Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .format_timestamp_millis()
+        .init();
+
+    let args = Args::parse();
+    let source = default_data_source(&args.base_path);
+    let mut bundle = MemorySource::new();
+    for path in &args.include {
+        info!("Adding {path}");
+        bundle.add_from(source.as_ref(), &[path.as_str()])?;
+    }
+    bundle.save_archive(&args.output)?;
+    info!("Wrote {}", args.output.display());
+    Ok(())
+}
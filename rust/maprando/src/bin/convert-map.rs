@@ -0,0 +1,126 @@
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use maprando::map_areas::{assign_areas, parse_area_partition};
+use maprando::map_format::{map_from_text, map_to_text, read_avro_maps, write_avro_maps};
+use maprando_game::cache::default_cache_path;
+use maprando_game::data_source::open_data_source;
+use maprando_game::{GameData, Map};
+use rand::{SeedableRng, rngs::StdRng};
+use std::path::{Path, PathBuf};
+
+/// Convert maps between Avro batches (.avro), `Map` JSON (.json), and the editable text form
+/// (.txt), with the format chosen by file extension. Avro inputs contribute all of their maps,
+/// or only the one selected by `--index`.
+#[derive(Parser)]
+struct Args {
+    /// Directory containing the randomizer data
+    #[arg(long, default_value = ".")]
+    base_path: PathBuf,
+
+    /// Bundled data archive to load data from, in place of `base_path`
+    #[arg(long)]
+    data_archive: Option<PathBuf>,
+
+    /// Map files to read
+    #[arg(required = true)]
+    input: Vec<PathBuf>,
+
+    /// File to write: an Avro batch can hold any number of maps, while JSON and text output
+    /// require exactly one
+    #[arg(long)]
+    output: PathBuf,
+
+    /// Index of the map to extract from each Avro input (in stored order)
+    #[arg(long)]
+    index: Option<usize>,
+
+    /// Assign areas, subareas, and subsubareas to the maps: "map" keeps each map's areas and
+    /// only assigns subareas, while "size" or "items" regroups the rooms into areas balanced by
+    /// map tiles or by item locations
+    #[arg(long)]
+    assign_areas: Option<String>,
+
+    /// Random seed for area assignment
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+enum MapFormat {
+    Avro,
+    Json,
+    Text,
+}
+
+fn get_format(path: &Path) -> Result<MapFormat> {
+    match path.extension().and_then(|x| x.to_str()) {
+        Some("avro") => Ok(MapFormat::Avro),
+        Some("json") => Ok(MapFormat::Json),
+        Some("txt") => Ok(MapFormat::Text),
+        _ => bail!(
+            "Unknown map format for {}: expected .avro, .json, or .txt",
+            path.display()
+        ),
+    }
+}
+
+fn read_maps(path: &Path, index: Option<usize>, game_data: &GameData) -> Result<Vec<Map>> {
+    let data = std::fs::read(path).with_context(|| format!("Unable to read {}", path.display()))?;
+    let maps = match get_format(path)? {
+        MapFormat::Avro => {
+            let maps = read_avro_maps(&data, game_data)?;
+            match index {
+                Some(i) => match maps.get(i) {
+                    Some(map) => vec![map.clone()],
+                    None => bail!("{} has only {} maps", path.display(), maps.len()),
+                },
+                None => maps,
+            }
+        }
+        MapFormat::Json => vec![serde_json::from_slice(&data)?],
+        MapFormat::Text => vec![map_from_text(std::str::from_utf8(&data)?, game_data)?],
+    };
+    Ok(maps)
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let data_source = open_data_source(&args.base_path, args.data_archive.as_deref())?;
+    let game_data =
+        GameData::load_from_source(data_source, Some(&default_cache_path(&args.base_path)))?;
+
+    let mut maps = vec![];
+    for path in &args.input {
+        let input_maps = read_maps(path, args.index, &game_data)
+            .with_context(|| format!("Unable to load maps from {}", path.display()))?;
+        maps.extend(input_maps);
+    }
+
+    if let Some(partition) = &args.assign_areas {
+        let partition = parse_area_partition(partition)?;
+        let mut rng = StdRng::seed_from_u64(args.seed);
+        for (i, map) in maps.iter_mut().enumerate() {
+            assign_areas(map, &game_data, partition, &mut rng)
+                .with_context(|| format!("Unable to assign areas to map {i}"))?;
+        }
+    }
+
+    let output_data = match get_format(&args.output)? {
+        MapFormat::Avro => write_avro_maps(&maps, &game_data)?,
+        format => {
+            let [map] = maps.as_slice() else {
+                bail!(
+                    "{} map(s) given, but JSON and text output hold a single map (use --index to select one)",
+                    maps.len()
+                );
+            };
+            match format {
+                MapFormat::Json => serde_json::to_vec(map)?,
+                _ => map_to_text(map, &game_data)?.into_bytes(),
+            }
+        }
+    };
+    std::fs::write(&args.output, output_data)
+        .with_context(|| format!("Unable to write {}", args.output.display()))?;
+    println!("Wrote {} map(s) to {}", maps.len(), args.output.display());
+    Ok(())
+}
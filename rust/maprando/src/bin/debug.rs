@@ -168,14 +168,10 @@ fn main() -> Result<()> {
         .format_timestamp_millis()
         .init();
 
-    let tech_path = Path::new("data/tech_data.json");
-    let notable_path = Path::new("data/notable_data.json");
-    let presets_path = Path::new("data/presets");
-
     let mut game_data = GameData::load(Path::new("."))?;
     game_data.make_links_data(&|_, _| (0, 1));
 
-    let preset_data = PresetData::load(tech_path, notable_path, presets_path, &game_data)?;
+    let preset_data = PresetData::load(game_data.data_source().as_ref(), &game_data)?;
     let mut settings = preset_data.default_preset.clone();
     settings.skill_assumption_settings = preset_data.skill_presets.last().unwrap().clone();
     let difficulty = preset_data.difficulty_tiers.last().unwrap();
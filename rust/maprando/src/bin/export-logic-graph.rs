@@ -0,0 +1,145 @@
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use maprando::difficulty::{get_full_global, get_link_difficulty_length};
+use maprando::logic_graph::{
+    LogicGraphFilter, get_logic_graph, logic_graph_to_dot, logic_graph_to_graphml,
+};
+use maprando::map_format::map_from_text;
+use maprando::preset::PresetData;
+use maprando::randomize::{Randomizer, get_difficulty_tiers, get_objectives, randomize_doors};
+use maprando::settings::try_upgrade_settings;
+use maprando_game::cache::default_cache_path;
+use maprando_game::data_source::{DataSource, open_data_source};
+use maprando_game::{GameData, Item, Map};
+use rand::{SeedableRng, rngs::StdRng};
+use std::path::PathBuf;
+
+/// Export the logic graph of a seed (base links plus the seed's door links) as GraphML
+/// (.graphml) or Graphviz DOT (.dot), chosen by the output file extension.
+#[derive(Parser)]
+struct Args {
+    /// Directory containing the randomizer data
+    #[arg(long, default_value = ".")]
+    base_path: PathBuf,
+
+    /// Bundled data archive to load data from, in place of `base_path`
+    #[arg(long)]
+    data_archive: Option<PathBuf>,
+
+    /// Randomizer settings (JSON); defaults to the default preset
+    #[arg(long)]
+    settings: Option<PathBuf>,
+
+    /// Map to use, as `Map` JSON (.json) or text (.txt); defaults to the vanilla map
+    #[arg(long)]
+    map: Option<PathBuf>,
+
+    /// Seed for objectives and door randomization
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Only include links traversable with these items (comma-separated, e.g. "Morph,Bombs")
+    /// using the skill settings' difficulty
+    #[arg(long)]
+    items: Option<String>,
+
+    #[arg(long)]
+    output: PathBuf,
+}
+
+fn main() -> Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .format_timestamp_millis()
+        .init();
+    let args = Args::parse();
+    let data_source = open_data_source(&args.base_path, args.data_archive.as_deref())?;
+    let mut game_data = GameData::load_from_source(
+        data_source.clone(),
+        Some(&default_cache_path(&args.base_path)),
+    )?;
+    let preset_data = PresetData::load(data_source.as_ref(), &game_data)?;
+    let global = get_full_global(&game_data);
+    game_data.make_links_data(&|link, game_data| {
+        get_link_difficulty_length(link, game_data, &preset_data, &global)
+    });
+
+    let settings = match &args.settings {
+        Some(path) => {
+            let settings_str = std::fs::read_to_string(path)
+                .with_context(|| format!("Unable to read {}", path.display()))?;
+            try_upgrade_settings(settings_str, &preset_data, true)?.1
+        }
+        None => preset_data.default_preset.clone(),
+    };
+    let map: Map = match &args.map {
+        Some(path) => {
+            let map_str = std::fs::read_to_string(path)
+                .with_context(|| format!("Unable to read {}", path.display()))?;
+            match path.extension().and_then(|x| x.to_str()) {
+                Some("json") => serde_json::from_str(&map_str)?,
+                Some("txt") => map_from_text(&map_str, &game_data)?,
+                _ => bail!("Unknown map format: expected .json or .txt"),
+            }
+        }
+        None => {
+            serde_json::from_str(&data_source.read_to_string("maps/vanilla/vanilla_map.json")?)?
+        }
+    };
+
+    let difficulty_tiers = get_difficulty_tiers(
+        &settings,
+        &preset_data.difficulty_tiers,
+        &game_data,
+        &preset_data.tech_by_difficulty["Implicit"],
+        &preset_data.notables_by_difficulty["Implicit"],
+    );
+    let mut rng = StdRng::seed_from_u64(args.seed);
+    let objectives = get_objectives(&settings, Some(&map), &game_data, &mut rng);
+    let locked_door_data =
+        randomize_doors(&game_data, &map, &settings, &objectives, args.seed as usize);
+    let randomizer = Randomizer::new(
+        &map,
+        &locked_door_data,
+        objectives,
+        &settings,
+        &difficulty_tiers,
+        &game_data,
+        &game_data.base_links_data,
+        &mut rng,
+    );
+
+    let filter = match &args.items {
+        Some(items_str) => {
+            let mut items: Vec<Item> = vec![];
+            for name in items_str
+                .split(',')
+                .map(|x| x.trim())
+                .filter(|x| !x.is_empty())
+            {
+                let item = Item::try_from(name).with_context(|| format!("Unknown item: {name}"))?;
+                items.push(item);
+            }
+            Some(LogicGraphFilter {
+                items,
+                difficulty: &difficulty_tiers[0],
+            })
+        }
+        None => None,
+    };
+    let graph = get_logic_graph(&randomizer, filter.as_ref());
+
+    let output_data = match args.output.extension().and_then(|x| x.to_str()) {
+        Some("graphml") => logic_graph_to_graphml(&graph),
+        Some("dot") => logic_graph_to_dot(&graph),
+        _ => bail!("Unknown output format: expected .graphml or .dot"),
+    };
+    std::fs::write(&args.output, output_data)
+        .with_context(|| format!("Unable to write {}", args.output.display()))?;
+    println!(
+        "Wrote {} vertices and {} links to {}",
+        graph.vertices.len(),
+        graph.links.len(),
+        args.output.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use maprando::map_areas::parse_area_partition;
+use maprando::map_format::{map_to_text, write_avro_maps};
+use maprando::map_generation::{MapGeneratorSettings, generate_map};
+use maprando_game::GameData;
+use maprando_game::cache::default_cache_path;
+use maprando_game::data_source::open_data_source;
+use rand::{SeedableRng, rngs::StdRng};
+use std::path::PathBuf;
+
+/// Generate map layouts from the room geometry. The output format is chosen by file extension:
+/// an Avro batch (.avro) of `count` maps, or a single map as JSON (.json) or text (.txt).
+#[derive(Parser)]
+struct Args {
+    /// Directory containing the randomizer data
+    #[arg(long, default_value = ".")]
+    base_path: PathBuf,
+
+    /// Bundled data archive to load data from, in place of `base_path`
+    #[arg(long)]
+    data_archive: Option<PathBuf>,
+
+    #[arg(long)]
+    output: PathBuf,
+
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Number of maps to generate (for Avro output)
+    #[arg(long, default_value_t = 1)]
+    count: usize,
+
+    /// Text file listing the names of the rooms to include, one per line (default: all rooms)
+    #[arg(long)]
+    room_list: Option<PathBuf>,
+
+    /// Number of layouts to try for each map before giving up
+    #[arg(long, default_value_t = 20)]
+    max_attempts: usize,
+
+    /// How to group rooms into areas: balanced by "size" (map tiles) or by "items"
+    #[arg(long, default_value = "size")]
+    area_partition: String,
+}
+
+fn main() -> Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .format_timestamp_millis()
+        .init();
+    let args = Args::parse();
+    let data_source = open_data_source(&args.base_path, args.data_archive.as_deref())?;
+    let game_data =
+        GameData::load_from_source(data_source, Some(&default_cache_path(&args.base_path)))?;
+
+    let mut settings = MapGeneratorSettings::new(&game_data);
+    settings.max_attempts = args.max_attempts;
+    settings.area_partition = parse_area_partition(&args.area_partition)?;
+    if let Some(path) = &args.room_list {
+        let room_list = std::fs::read_to_string(path)
+            .with_context(|| format!("Unable to read {}", path.display()))?;
+        settings.room_mask = vec![false; game_data.room_geometry.len()];
+        for name in room_list
+            .lines()
+            .map(|x| x.trim())
+            .filter(|x| !x.is_empty())
+        {
+            let Some(room_idx) = game_data.room_geometry.iter().position(|r| r.name == name) else {
+                bail!("Unknown room in room list: {name}");
+            };
+            settings.room_mask[room_idx] = true;
+        }
+    }
+
+    let extension = args.output.extension().and_then(|x| x.to_str());
+    if args.count != 1 && extension != Some("avro") {
+        bail!("Generating more than one map requires Avro (.avro) output");
+    }
+    let mut rng = StdRng::seed_from_u64(args.seed);
+    let mut maps = vec![];
+    for i in 0..args.count {
+        maps.push(generate_map(&settings, &game_data, &mut rng)?);
+        println!("Generated map {}/{}", i + 1, args.count);
+    }
+
+    let output_data = match extension {
+        Some("avro") => write_avro_maps(&maps, &game_data)?,
+        Some("json") => serde_json::to_vec(&maps[0])?,
+        Some("txt") => map_to_text(&maps[0], &game_data)?.into_bytes(),
+        _ => bail!("Unknown output format: expected .avro, .json, or .txt"),
+    };
+    std::fs::write(&args.output, output_data)
+        .with_context(|| format!("Unable to write {}", args.output.display()))?;
+    Ok(())
+}
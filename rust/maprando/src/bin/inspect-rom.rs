@@ -0,0 +1,118 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use log::info;
+use maprando::patch::Rom;
+use maprando::patch::input_rom::load_input_rom;
+use maprando::patch::inspect::inspect_rom;
+use maprando::preset::PresetData;
+use maprando::settings::MapPreset;
+use maprando::spoiler_map;
+use maprando_game::cache::default_cache_path;
+use maprando_game::data_source::open_data_source;
+use maprando_game::{GameData, Item};
+use std::path::PathBuf;
+use strum::VariantNames;
+
+/// Recover the randomization (items, map, locked doors, objectives, start location, and
+/// autotracker settings) from a Map Rando ROM.
+#[derive(Parser)]
+struct Args {
+    /// Map Rando ROM to inspect
+    #[arg(long)]
+    rom: PathBuf,
+
+    /// Vanilla ROM, used to identify the door connections
+    #[arg(long)]
+    vanilla_rom: PathBuf,
+
+    /// Path at which to save the recovered randomization as JSON
+    #[arg(long)]
+    output_json: Option<PathBuf>,
+
+    #[arg(long)]
+    output_spoiler_map_explored: Option<PathBuf>,
+
+    #[arg(long)]
+    output_spoiler_map_outline: Option<PathBuf>,
+
+    /// Directory containing the randomizer data
+    #[arg(long, default_value = ".")]
+    base_path: PathBuf,
+
+    /// Bundled data archive to load data from, in place of `base_path`
+    #[arg(long)]
+    data_archive: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .format_timestamp_millis()
+        .init();
+    let args = Args::parse();
+    let data_source = open_data_source(&args.base_path, args.data_archive.as_deref())?;
+    let game_data = GameData::load_from_source(
+        data_source.clone(),
+        Some(&default_cache_path(&args.base_path)),
+    )?;
+
+    let rom = Rom::load(&args.rom)?;
+    let vanilla_rom = load_input_rom(&args.vanilla_rom)?;
+    let inspection = inspect_rom(&rom, &vanilla_rom, &game_data)
+        .with_context(|| format!("Unable to inspect ROM {}", args.rom.display()))?;
+    let randomization = &inspection.randomization;
+
+    println!(
+        "Seed: {} ({:08X})",
+        randomization.seed_name, randomization.display_seed
+    );
+    println!("Start location: {}", randomization.start_location.name);
+    println!(
+        "Map: {} rooms, {} door connections",
+        randomization.map.room_mask.iter().filter(|&&x| x).count(),
+        randomization.map.doors.len()
+    );
+    println!("Locked doors: {}", randomization.locked_doors.len());
+    println!("Objectives: {:?}", randomization.objectives);
+    println!("Escape time: {}s", randomization.escape_time_seconds);
+    for (&item, &(room_id, node_id)) in
+        std::iter::zip(&randomization.item_placement, &game_data.item_locations)
+    {
+        if item == Item::Nothing {
+            continue;
+        }
+        let room_name = &game_data.room_json_map[&room_id]["name"];
+        println!(
+            "  {}: {} (node {node_id})",
+            Item::VARIANTS[item as usize],
+            room_name.as_str().unwrap_or("?")
+        );
+    }
+
+    if let Some(path) = &args.output_json {
+        info!("Writing recovered randomization to {}", path.display());
+        std::fs::write(path, serde_json::to_string_pretty(&inspection)?)?;
+    }
+
+    if args.output_spoiler_map_explored.is_some() || args.output_spoiler_map_outline.is_some() {
+        let preset_data = PresetData::load(data_source.as_ref(), &game_data)?;
+        let mut settings = preset_data.default_preset.clone();
+        settings.map_layout = match inspection.autotracker_settings.map_layout {
+            Some(MapPreset::Vanilla) => "Vanilla",
+            Some(MapPreset::Small) => "Small",
+            Some(MapPreset::Wild) => "Wild",
+            Some(MapPreset::Standard) | None => "Standard",
+        }
+        .to_string();
+        let spoiler_maps =
+            spoiler_map::get_spoiler_map(randomization, &game_data, &settings, true)?;
+        if let Some(path) = &args.output_spoiler_map_explored {
+            info!("Writing spoiler map (explored) to {}", path.display());
+            std::fs::write(path, &spoiler_maps.explored)?;
+        }
+        if let Some(path) = &args.output_spoiler_map_outline {
+            info!("Writing spoiler map (outline) to {}", path.display());
+            std::fs::write(path, &spoiler_maps.outline)?;
+        }
+    }
+    Ok(())
+}
@@ -6,15 +6,23 @@ use maprando::customize::{
     ControllerConfig, CustomizeSettings, MusicSettings, StatuesHallwayAudio, StatuesHallwayTiling,
 };
 use maprando::difficulty::{get_full_global, get_link_difficulty_length};
-use maprando::patch::Rom;
-use maprando::patch::make_rom;
+use maprando::map_areas::validate_area_assignment;
+use maprando::patch::bps::create_bps_patch;
+use maprando::patch::input_rom::load_input_rom;
+use maprando::patch::make_rom_with_info;
+use maprando::patch::passes::ConflictPolicy;
+use maprando::patch::title::load_title_background;
 use maprando::preset::PresetData;
+use maprando::randomize::snapshot::{load_snapshot, save_snapshot};
 use maprando::randomize::{
-    Randomization, Randomizer, get_difficulty_tiers, get_objectives, randomize_doors,
+    Randomization, RandomizationCheckpoint, Randomizer, get_difficulty_tiers, get_objectives,
+    make_locked_door_data, randomize_doors,
 };
 use maprando::settings::{RandomizerSettings, StartLocationMode};
 use maprando::spoiler_log::SpoilerLog;
 use maprando::spoiler_map;
+use maprando_game::cache::default_cache_path;
+use maprando_game::data_source::{DataSource, open_data_source};
 use maprando_game::{GameData, Map};
 use rand::{RngCore, SeedableRng};
 use std::path::{Path, PathBuf};
@@ -54,6 +62,23 @@ struct Args {
     #[arg(long)]
     output_rom: Option<PathBuf>,
 
+    /// Path at which to save a BPS patch from the input ROM to the output ROM
+    #[arg(long)]
+    output_bps: Option<PathBuf>,
+
+    /// Path at which to save a report of the bytes written by each patch pass, and any conflicts
+    #[arg(long)]
+    output_patch_report: Option<PathBuf>,
+
+    /// Fail if a patch pass overwrites bytes from another pass without declaring an override
+    #[arg(long)]
+    strict_patch_conflicts: bool,
+
+    /// Path at which to save debugger symbols for the output ROM, as bsnes-plus (.sym) or
+    /// Mesen (.mlb) labels
+    #[arg(long)]
+    output_symbols: Option<PathBuf>,
+
     #[arg(long)]
     output_spoiler_log: Option<PathBuf>,
 
@@ -65,29 +90,54 @@ struct Args {
 
     #[arg(long)]
     area_themed_palette: bool,
+
+    /// Image to use as the title screen background, in place of a randomly assembled one
+    #[arg(long)]
+    title_image: Option<PathBuf>,
+
+    /// Directory in which to save a snapshot of the randomization state at each item placement step
+    #[arg(long)]
+    checkpoint_dir: Option<PathBuf>,
+
+    /// Snapshot file from which to resume a randomization, in place of performing a new one
+    #[arg(long)]
+    resume_checkpoint: Option<PathBuf>,
+
+    /// Directory containing the randomizer data (`data/`), with the other data files
+    /// (sm-json-data, patches, maps, etc.) in its parent directory
+    #[arg(long, default_value = ".")]
+    base_path: PathBuf,
+
+    /// Bundled data archive to load all data files from, in place of `base_path`
+    #[arg(long)]
+    data_archive: Option<PathBuf>,
 }
 
-fn get_settings(args: &Args, preset_data: &PresetData) -> Result<RandomizerSettings> {
+fn get_settings(
+    args: &Args,
+    data_source: &dyn DataSource,
+    preset_data: &PresetData,
+) -> Result<RandomizerSettings> {
     let mut settings = preset_data.default_preset.clone();
 
     if let Some(preset) = &args.preset {
         let path = format!("data/presets/full-settings/{preset}.json");
-        let s = std::fs::read_to_string(path)?;
+        let s = data_source.read_to_string(&path)?;
         settings = serde_json::from_str(&s)?;
     }
     if let Some(skill_preset) = &args.skill_preset {
         let path = format!("data/presets/skill-assumptions/{skill_preset}.json");
-        let s = std::fs::read_to_string(path)?;
+        let s = data_source.read_to_string(&path)?;
         settings.skill_assumption_settings = serde_json::from_str(&s)?;
     }
     if let Some(item_preset) = &args.item_preset {
         let path = format!("data/presets/item-progression/{item_preset}.json");
-        let s = std::fs::read_to_string(path)?;
+        let s = data_source.read_to_string(&path)?;
         settings.item_progression_settings = serde_json::from_str(&s)?;
     }
     if let Some(qol_preset) = &args.qol_preset {
         let path = format!("data/presets/item-quality-of-life/{qol_preset}.json");
-        let s = std::fs::read_to_string(path)?;
+        let s = data_source.read_to_string(&path)?;
         settings.quality_of_life_settings = serde_json::from_str(&s)?;
     }
     settings.other_settings.random_seed = args.random_seed;
@@ -152,6 +202,14 @@ fn get_randomization(
         1
     };
     let max_map_attempts = max_attempts / max_attempts_per_map;
+    if let Some(checkpoint_dir) = &args.checkpoint_dir {
+        std::fs::create_dir_all(checkpoint_dir).with_context(|| {
+            format!(
+                "Unable to create checkpoint directory {}",
+                checkpoint_dir.display()
+            )
+        })?;
+    }
     let mut attempt_num = 0;
     for _ in 0..max_map_attempts {
         let map_seed = (rng.next_u64() & 0xFFFFFFFF) as usize;
@@ -177,7 +235,7 @@ fn get_randomization(
         let randomizer = Randomizer::new(
             &map,
             &locked_door_data,
-            objectives,
+            objectives.clone(),
             settings,
             &difficulty_tiers,
             game_data,
@@ -193,7 +251,30 @@ fn get_randomization(
             info!(
                 "Attempt {attempt_num}/{max_attempts}: Map seed={map_seed}, door randomization seed={door_seed}, item placement seed={item_seed}"
             );
-            match randomizer.randomize(attempt_num, item_seed, 1, true) {
+            let mut on_checkpoint = |checkpoint: &RandomizationCheckpoint| -> Result<()> {
+                if let Some(checkpoint_dir) = &args.checkpoint_dir {
+                    let path = checkpoint_dir.join(format!(
+                        "attempt-{attempt_num}-step-{}.snapshot",
+                        checkpoint.state.step_num
+                    ));
+                    save_snapshot(
+                        &path,
+                        settings,
+                        &map,
+                        &objectives,
+                        &locked_door_data.locked_doors,
+                        checkpoint,
+                    )?;
+                }
+                Ok(())
+            };
+            match randomizer.randomize_with_checkpoints(
+                attempt_num,
+                item_seed,
+                1,
+                true,
+                &mut on_checkpoint,
+            ) {
                 Ok(randomization) => {
                     return Ok(randomization);
                 }
@@ -206,13 +287,56 @@ fn get_randomization(
     bail!("Exhausted randomization attempts");
 }
 
+fn resume_randomization(
+    path: &Path,
+    game_data: &GameData,
+    preset_data: &PresetData,
+) -> Result<(RandomizerSettings, Randomization, SpoilerLog)> {
+    let snapshot = load_snapshot(path)?;
+    let settings = snapshot.settings;
+    let implicit_tech = &preset_data.tech_by_difficulty["Implicit"];
+    let implicit_notables = &preset_data.notables_by_difficulty["Implicit"];
+    let difficulty_tiers = get_difficulty_tiers(
+        &settings,
+        &preset_data.difficulty_tiers,
+        game_data,
+        implicit_tech,
+        implicit_notables,
+    );
+    let locked_door_data = make_locked_door_data(snapshot.locked_doors, game_data);
+    let mut rng = rand::rngs::StdRng::from_seed([0u8; 32]);
+    let randomizer = Randomizer::new(
+        &snapshot.map,
+        &locked_door_data,
+        snapshot.objectives,
+        &settings,
+        &difficulty_tiers,
+        game_data,
+        &game_data.base_links_data,
+        &mut rng,
+    );
+    info!(
+        "Resuming attempt {} at step {} from {}",
+        snapshot.checkpoint.attempt_num_rando,
+        snapshot.checkpoint.state.step_num,
+        path.display()
+    );
+    let (randomization, spoiler_log) =
+        randomizer.resume_from_checkpoint(snapshot.checkpoint, true, &mut |_| Ok(()))?;
+    Ok((settings, randomization, spoiler_log))
+}
+
 fn main() -> Result<()> {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
         .format_timestamp_millis()
         .init();
 
     let args = Args::parse();
-    let mut game_data = GameData::load(Path::new("."))?;
+    let data_source = open_data_source(&args.base_path, args.data_archive.as_deref())?;
+    let mut game_data = GameData::load_from_source(
+        data_source.clone(),
+        Some(&default_cache_path(&args.base_path)),
+    )?;
 
     if let Some(start_location_name) = &args.start_location {
         game_data
@@ -220,22 +344,31 @@ fn main() -> Result<()> {
             .retain(|x| &x.name == start_location_name);
     }
 
-    let tech_path = Path::new("data/tech_data.json");
-    let notable_path = Path::new("data/notable_data.json");
-    let presets_path = Path::new("data/presets");
-    let preset_data = PresetData::load(tech_path, notable_path, presets_path, &game_data)?;
+    let preset_data = PresetData::load(data_source.as_ref(), &game_data)?;
     let global = get_full_global(&game_data);
     game_data.make_links_data(&|link, game_data| {
         get_link_difficulty_length(link, game_data, &preset_data, &global)
     });
-    let settings = get_settings(&args, &preset_data)?;
 
     // Perform randomization (map selection & item placement):
-    let (randomization, spoiler_log) =
-        get_randomization(&args, &settings, &game_data, &preset_data)?;
+    let (settings, randomization, spoiler_log) = match &args.resume_checkpoint {
+        Some(path) => resume_randomization(path, &game_data, &preset_data)?,
+        None => {
+            let settings = get_settings(&args, data_source.as_ref(), &preset_data)?;
+            validate_area_assignment(
+                &settings.other_settings.area_assignment,
+                &settings.map_layout,
+                &game_data,
+            )
+            .context("Invalid area assignment")?;
+            let (randomization, spoiler_log) =
+                get_randomization(&args, &settings, &game_data, &preset_data)?;
+            (settings, randomization, spoiler_log)
+        }
+    };
 
     // Generate the patched ROM:
-    let orig_rom = Rom::load(&args.input_rom)?;
+    let orig_rom = load_input_rom(&args.input_rom)?;
     let mut input_rom = orig_rom.clone();
     input_rom.data.resize(0x400000, 0);
 
@@ -263,9 +396,25 @@ fn main() -> Result<()> {
         shaking: maprando::customize::ShakingSetting::Vanilla,
         flashing: maprando::customize::FlashingSetting::Vanilla,
         controller_config: ControllerConfig::default(),
+        title_background: match &args.title_image {
+            Some(path) => {
+                let data = std::fs::read(path)
+                    .with_context(|| format!("Unable to read title image {}", path.display()))?;
+                Some(load_title_background(
+                    &data,
+                    &game_data.title_screen_data.map_station,
+                )?)
+            }
+            None => None,
+        },
     };
 
-    let output_rom = make_rom(
+    let conflict_policy = if args.strict_patch_conflicts {
+        ConflictPolicy::Strict
+    } else {
+        ConflictPolicy::Warn
+    };
+    let (output_rom, build_info) = make_rom_with_info(
         &input_rom,
         &settings,
         &customize_settings,
@@ -281,7 +430,9 @@ fn main() -> Result<()> {
             }],
         }],
         &[],
+        conflict_policy,
     )?;
+    build_info.write_log.warn_conflicts();
 
     // Save the outputs:
     if let Some(output_rom_path) = &args.output_rom {
@@ -289,6 +440,33 @@ fn main() -> Result<()> {
         output_rom.save(output_rom_path)?;
     }
 
+    if let Some(output_patch_report_path) = &args.output_patch_report {
+        println!(
+            "Writing patch pass report to {}",
+            output_patch_report_path.display()
+        );
+        std::fs::write(output_patch_report_path, build_info.write_log.report())?;
+    }
+
+    if let Some(output_symbols_path) = &args.output_symbols {
+        println!(
+            "Writing debugger symbols to {}",
+            output_symbols_path.display()
+        );
+        let symbols = match output_symbols_path.extension().and_then(|x| x.to_str()) {
+            Some("sym") => build_info.symbols.to_sym(),
+            Some("mlb") => build_info.symbols.to_mlb(),
+            _ => bail!("Unknown symbol file format: expected .sym or .mlb"),
+        };
+        std::fs::write(output_symbols_path, symbols)?;
+    }
+
+    if let Some(output_bps_path) = &args.output_bps {
+        println!("Writing BPS patch to {}", output_bps_path.display());
+        let patch = create_bps_patch(&orig_rom.data, &output_rom.data);
+        std::fs::write(output_bps_path, patch)?;
+    }
+
     if let Some(output_spoiler_log_path) = &args.output_spoiler_log {
         println!(
             "Writing spoiler log to {}",
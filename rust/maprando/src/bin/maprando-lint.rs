@@ -0,0 +1,34 @@
+use anyhow::Result;
+use clap::Parser;
+use maprando_game::GameData;
+use maprando_game::data_source::open_data_source;
+use std::path::PathBuf;
+
+/// Check sm-json-data for problems, reporting all of them instead of stopping at the first.
+/// Exits with a non-zero status if any problems are found.
+#[derive(Parser)]
+struct Args {
+    /// Directory containing the randomizer data (with sm-json-data under `../sm-json-data`)
+    #[arg(long, default_value = ".")]
+    base_path: PathBuf,
+
+    /// Bundled data archive to load sm-json-data from, in place of `base_path`
+    #[arg(long)]
+    data_archive: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let data_source = open_data_source(&args.base_path, args.data_archive.as_deref())?;
+    let issues = GameData::lint(data_source)?;
+    for issue in &issues {
+        println!("{issue}");
+    }
+    if issues.is_empty() {
+        println!("No problems found.");
+        Ok(())
+    } else {
+        println!("{} problem(s) found.", issues.len());
+        std::process::exit(1);
+    }
+}
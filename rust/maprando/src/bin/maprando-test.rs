@@ -8,6 +8,7 @@ use maprando::customize::{
 use maprando::difficulty::{get_full_global, get_link_difficulty_length};
 use maprando::map_repository::MapRepository;
 use maprando::patch::Rom;
+use maprando::patch::input_rom::normalize_input_rom;
 use maprando::patch::make_rom;
 use maprando::preset::PresetData;
 use maprando::randomize::{
@@ -20,6 +21,8 @@ use maprando::settings::{
 };
 use maprando::spoiler_log::SpoilerLog;
 use maprando::spoiler_map;
+use maprando_game::cache::default_cache_path;
+use maprando_game::data_source::open_data_source;
 use maprando_game::{GameData, Map};
 use rand::{RngCore, SeedableRng};
 use std::path::{Path, PathBuf};
@@ -52,6 +55,15 @@ struct Args {
 
     #[arg(long)]
     rebuild_traversals: Option<bool>,
+
+    /// Directory containing the randomizer data (`data/`), with the other data files
+    /// (sm-json-data, patches, maps, etc.) in its parent directory
+    #[arg(long, default_value = ".")]
+    base_path: PathBuf,
+
+    /// Bundled data archive to load all data files from, in place of `base_path`
+    #[arg(long)]
+    data_archive: Option<PathBuf>,
 }
 
 // Reduced version of web::AppData for test tool
@@ -147,7 +159,7 @@ fn get_randomization(
         settings_json,
     )?;
 
-    let mut map_batch: Vec<Map> = vec![];
+    let mut map_batch: Vec<(String, Map)> = vec![];
 
     for _ in 0..max_map_attempts {
         let map_seed = (rng.next_u64() & 0xFFFFFFFF) as usize;
@@ -157,7 +169,8 @@ fn get_randomization(
             map_batch = map_repo.get_map_batch(map_seed, game_data).unwrap();
         }
 
-        let mut map = map_batch.pop().unwrap();
+        let (map_id, mut map) = map_batch.pop().unwrap();
+        info!("Map ID: {map_id}");
         if !assign_map_areas(&mut map, &settings, map_seed, game_data) {
             info!("Area assignment failed for map seed={map_seed}");
             continue;
@@ -238,6 +251,7 @@ fn make_random_customization(app: &TestAppData) -> CustomizeSettings {
             false => maprando::customize::FlashingSetting::Vanilla,
         },
         controller_config: ControllerConfig::default(),
+        title_background: None,
     }
 }
 
@@ -333,23 +347,24 @@ fn perform_test_cycle(app: &TestAppData, cycle_count: usize) -> Result<()> {
 }
 
 fn build_app_data(args: &Args) -> Result<TestAppData> {
-    let etank_colors_path = Path::new("data/etank_colors.json");
-    let vanilla_map_path = Path::new("../maps/vanilla");
-    let small_maps_path = Path::new("../maps/v119-small-avro");
-    let standard_maps_path = Path::new("../maps/v119-standard-avro");
-    let wild_maps_path = Path::new("../maps/v119-wild-avro");
-    let samus_sprites_path = Path::new("../MapRandoSprites/samus_sprites/manifest.json");
-    let mut game_data = GameData::load(Path::new("."))?;
+    let etank_colors_path = "data/etank_colors.json";
+    let vanilla_map_path = "maps/vanilla";
+    let small_maps_path = "maps/v119-small-avro";
+    let standard_maps_path = "maps/v119-standard-avro";
+    let wild_maps_path = "maps/v119-wild-avro";
+    let samus_sprites_path = "MapRandoSprites/samus_sprites/manifest.json";
+    let data_source = open_data_source(&args.base_path, args.data_archive.as_deref())?;
+    let mut game_data = GameData::load_from_source(
+        data_source.clone(),
+        Some(&default_cache_path(&args.base_path)),
+    )?;
 
     if !args.output_seeds.is_dir() {
         bail!("{0} is not a directory", args.output_seeds.display());
     }
 
     info!("Loading logic preset data");
-    let tech_path = Path::new("data/tech_data.json");
-    let notable_path = Path::new("data/notable_data.json");
-    let presets_path = Path::new("data/presets");
-    let preset_data = PresetData::load(tech_path, notable_path, presets_path, &game_data)?;
+    let preset_data = PresetData::load(data_source.as_ref(), &game_data)?;
     let global = get_full_global(&game_data);
     game_data.make_links_data(&|link, game_data| {
         get_link_difficulty_length(link, game_data, &preset_data, &global)
@@ -359,13 +374,15 @@ fn build_app_data(args: &Args) -> Result<TestAppData> {
     base_preset.start_location_settings.mode = StartLocationMode::Random;
 
     if let Some(fixed_preset) = &args.preset {
-        let path = if fixed_preset.ends_with(".json") {
-            fixed_preset.clone()
+        let s = if fixed_preset.ends_with(".json") {
+            std::fs::read_to_string(fixed_preset)
+                .context(format!("Unable to read {}", fixed_preset.as_str()))?
         } else {
-            format!("data/presets/full-settings/{fixed_preset}.json")
+            let path = format!("data/presets/full-settings/{fixed_preset}.json");
+            data_source
+                .read_to_string(&path)
+                .context(format!("Unable to read {}", path.as_str()))?
         };
-        let s =
-            std::fs::read_to_string(&path).context(format!("Unable to read {}", path.as_str()))?;
         base_preset = serde_json::from_str(&s)?;
     }
 
@@ -373,7 +390,8 @@ fn build_app_data(args: &Args) -> Result<TestAppData> {
     // If we are using a locked-in preset, go ahead and remove all the others.
     if let Some(fixed_preset) = &args.skill_preset {
         let path = format!("data/presets/skill-assumptions/{fixed_preset}.json");
-        let s = std::fs::read_to_string(&path)
+        let s = data_source
+            .read_to_string(&path)
             .context(format!("Unable to load skill preset: {path}"))?;
         let p: SkillAssumptionSettings = serde_json::from_str(&s)?;
         skill_presets = vec![p];
@@ -388,7 +406,8 @@ fn build_app_data(args: &Args) -> Result<TestAppData> {
     // If we are using a locked-in preset, go ahead and remove all the others.
     if let Some(fixed_preset) = &args.item_preset {
         let path = format!("data/presets/item-progression/{fixed_preset}.json");
-        let s = std::fs::read_to_string(&path)
+        let s = data_source
+            .read_to_string(&path)
             .context(format!("Unable to load item progression preset: {path}"))?;
         let p: ItemProgressionSettings = serde_json::from_str(&s)?;
         item_presets = vec![p];
@@ -398,14 +417,15 @@ fn build_app_data(args: &Args) -> Result<TestAppData> {
     // If we are using a locked-in preset, go ahead and remove all the others.
     if let Some(fixed_preset) = &args.qol_preset {
         let path = format!("data/presets/quality-of-life/{fixed_preset}.json");
-        let s =
-            std::fs::read_to_string(&path).context(format!("Unable to load QoL preset: {path}"))?;
+        let s = data_source
+            .read_to_string(&path)
+            .context(format!("Unable to load QoL preset: {path}"))?;
         let p: QualityOfLifeSettings = serde_json::from_str(&s)?;
         qol_presets = vec![p];
     }
 
     let etank_color_from_json: Vec<Vec<String>> =
-        serde_json::from_str(&std::fs::read_to_string(etank_colors_path)?)?;
+        serde_json::from_str(&data_source.read_to_string(etank_colors_path)?)?;
     let mut etank_colors_str: Vec<String> = vec![];
     for mut v in etank_color_from_json {
         etank_colors_str.append(&mut v);
@@ -423,7 +443,7 @@ fn build_app_data(args: &Args) -> Result<TestAppData> {
         .collect();
 
     let samus_sprite_categories: Vec<SamusSpriteCategory> =
-        serde_json::from_str(&std::fs::read_to_string(samus_sprites_path)?)?;
+        serde_json::from_str(&data_source.read_to_string(samus_sprites_path)?)?;
 
     let mut samus_sprites: Vec<String> = vec![];
     for cat in &samus_sprite_categories {
@@ -432,10 +452,11 @@ fn build_app_data(args: &Args) -> Result<TestAppData> {
         }
     }
 
+    // A non-vanilla base ROM is allowed here (e.g. for testing against a modified ROM), with a warning.
     let mut input_rom = Rom::load(&args.input_rom)?;
-    let rom_digest = crypto_hash::hex_digest(crypto_hash::Algorithm::SHA256, &input_rom.data);
-    if rom_digest != "12b77c4bc9c1832cee8881244659065ee1d84c70c3d29e6eaf92e6798cc2ca72" {
-        info!("Warning: use of non-vanilla base ROM! Digest = {rom_digest}");
+    match normalize_input_rom(&input_rom.data) {
+        Ok(normalized) => input_rom = normalized.rom,
+        Err(err) => info!("Warning: use of non-vanilla base ROM! {err}"),
     }
     input_rom.data.resize(0x400000, 0);
 
@@ -446,10 +467,10 @@ fn build_app_data(args: &Args) -> Result<TestAppData> {
         game_data,
         preset_data,
         map_repos: vec![
-            MapRepository::new("Vanilla", vanilla_map_path)?,
-            MapRepository::new("Small", small_maps_path)?,
-            MapRepository::new("Standard", standard_maps_path)?,
-            MapRepository::new("Wild", wild_maps_path)?,
+            MapRepository::new("Vanilla", data_source.clone(), vanilla_map_path)?,
+            MapRepository::new("Small", data_source.clone(), small_maps_path)?,
+            MapRepository::new("Standard", data_source.clone(), standard_maps_path)?,
+            MapRepository::new("Wild", data_source.clone(), wild_maps_path)?,
         ],
         base_preset,
         skill_presets,
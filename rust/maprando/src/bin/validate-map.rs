@@ -0,0 +1,103 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use maprando::map_index::get_map_id;
+use maprando::map_repository::MapRepository;
+use maprando::map_validation::get_map_issues;
+use maprando_game::cache::default_cache_path;
+use maprando_game::data_source::open_data_source;
+use maprando_game::{GameData, Map};
+use std::path::{Path, PathBuf};
+
+/// Check maps for problems: overlapping rooms, bad door connections, out-of-range areas,
+/// disconnected rooms, and rooms outside the map bounds. Exits with a non-zero status if any
+/// problems are found.
+#[derive(Parser)]
+struct Args {
+    /// Directory containing the randomizer data
+    #[arg(long, default_value = ".")]
+    base_path: PathBuf,
+
+    /// Bundled data archive to load data from, in place of `base_path`
+    #[arg(long)]
+    data_archive: Option<PathBuf>,
+
+    /// Map JSON files to check, or directories of them
+    map: Vec<PathBuf>,
+
+    /// Map repository in the data source to check all maps of (e.g. "maps/v119-standard-avro")
+    #[arg(long)]
+    repository: Option<String>,
+}
+
+fn get_map_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut out = vec![];
+    for path in paths {
+        if path.is_dir() {
+            let mut dir_files = vec![];
+            for entry in path.read_dir()? {
+                let entry_path = entry?.path();
+                if entry_path.extension().is_some_and(|x| x == "json") {
+                    dir_files.push(entry_path);
+                }
+            }
+            dir_files.sort();
+            out.extend(dir_files);
+        } else {
+            out.push(path.clone());
+        }
+    }
+    Ok(out)
+}
+
+fn load_map_file(path: &Path) -> Result<Map> {
+    let map_str = std::fs::read_to_string(path)
+        .with_context(|| format!("Unable to read {}", path.display()))?;
+    serde_json::from_str(&map_str).with_context(|| format!("Unable to parse {}", path.display()))
+}
+
+// Print the problems with a map, returning the number found.
+fn report(name: &str, map: &Map, game_data: &GameData) -> usize {
+    let issues = get_map_issues(map, game_data);
+    for issue in &issues {
+        println!("{name}: {issue}");
+    }
+    issues.len()
+}
+
+fn main() -> Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .format_timestamp_millis()
+        .init();
+    let args = Args::parse();
+    let data_source = open_data_source(&args.base_path, args.data_archive.as_deref())?;
+    let game_data = GameData::load_from_source(
+        data_source.clone(),
+        Some(&default_cache_path(&args.base_path)),
+    )?;
+
+    let mut num_maps = 0;
+    let mut num_issues = 0;
+    for path in get_map_files(&args.map)? {
+        let map = load_map_file(&path)?;
+        num_issues += report(&path.display().to_string(), &map, &game_data);
+        num_maps += 1;
+    }
+    if let Some(repository_path) = &args.repository {
+        let repository = MapRepository::new(repository_path, data_source.clone(), repository_path)?;
+        for (file_idx, filename) in repository.filenames.iter().enumerate() {
+            let maps = repository.get_stored_maps(file_idx, &game_data)?;
+            for (i, map) in maps.iter().enumerate() {
+                num_issues += report(&get_map_id(filename, i), map, &game_data);
+                num_maps += 1;
+            }
+        }
+    }
+
+    if num_issues == 0 {
+        println!("No problems found in {num_maps} map(s).");
+        Ok(())
+    } else {
+        println!("{num_issues} problem(s) found in {num_maps} map(s).");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,835 @@
+// Monte Carlo simulation of boss fights, used to calibrate and regression-test the closed-form
+// fight models in `maprando_logic::boss_requirements`.
+//
+// Each fight is simulated frame by frame, using a coarse abstraction of the boss's vulnerability
+// windows and attack patterns. Samus fires whichever usable weapon has the highest expected damage
+// rate, with reaction delays and accuracy depending on proficiency, and takes damage from boss
+// attacks that she fails to dodge (subject to invincibility frames after each hit).
+
+use maprando_game::{Capacity, Item};
+use maprando_logic::{
+    Inventory, LocalState,
+    boss_requirements::{
+        apply_botwoon_requirement, apply_crocomire_requirement, apply_draygon_requirement,
+        apply_golden_torizo_requirement, apply_kraid_requirement, apply_mother_brain_2_requirement,
+        apply_phantoon_requirement, apply_ridley_requirement, apply_spore_spawn_requirement,
+    },
+    helpers::{get_charge_damage, get_uncharged_damage, suit_damage_factor},
+};
+use rand::{Rng, SeedableRng};
+use strum::VariantNames;
+
+const FRAMES_PER_SECOND: f32 = 60.0;
+
+// Number of frames that Samus is invincible after taking a hit.
+const INVINCIBILITY_FRAMES: u32 = 60;
+
+// Fights lasting longer than this are considered failures.
+const MAX_FIGHT_FRAMES: u32 = 20 * 60 * 60;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Boss {
+    Phantoon,
+    Draygon,
+    Ridley,
+    Botwoon,
+    MotherBrain2,
+    Kraid,
+    Crocomire,
+    SporeSpawn,
+    GoldenTorizo,
+}
+
+impl Boss {
+    pub const ALL: [Boss; 9] = [
+        Boss::Phantoon,
+        Boss::Draygon,
+        Boss::Ridley,
+        Boss::Botwoon,
+        Boss::MotherBrain2,
+        Boss::Kraid,
+        Boss::Crocomire,
+        Boss::SporeSpawn,
+        Boss::GoldenTorizo,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Boss::Phantoon => "Phantoon",
+            Boss::Draygon => "Draygon",
+            Boss::Ridley => "Ridley",
+            Boss::Botwoon => "Botwoon",
+            Boss::MotherBrain2 => "Mother Brain 2",
+            Boss::Kraid => "Kraid",
+            Boss::Crocomire => "Crocomire",
+            Boss::SporeSpawn => "Spore Spawn",
+            Boss::GoldenTorizo => "Golden Torizo",
+        }
+    }
+}
+
+struct AttackSpec {
+    period_frames: u32,
+    damage: f32,
+    // Chance of being hit by each instance of the attack, at proficiency 0 and without any
+    // of the helpful items:
+    hit_chance: f32,
+    // Fraction of the hit chance that is avoided at proficiency 1:
+    dodge: f32,
+    // Multipliers to the hit chance when Samus has the given item:
+    item_factors: &'static [(Item, f32)],
+}
+
+struct BossSpec {
+    hp: f32,
+    // The boss can only be damaged during the first `open_frames` of every `cycle_frames`:
+    cycle_frames: u32,
+    open_frames: u32,
+    super_damage: f32,
+    missile_damage: f32,
+    power_bomb_damage: f32,
+    beam_vulnerable: bool,
+    // Fraction of Supers that land (e.g. Golden Torizo catches those fired from the front):
+    super_accuracy_factor: f32,
+    attacks: &'static [AttackSpec],
+    // Attacks stop once the boss HP falls to this level (Botwoon's second phase):
+    harmless_below_hp: f32,
+    // Energy per second gained from farming drops, at proficiency 1:
+    farm_rate: f32,
+    // Energy per second lost to heat, without Varia:
+    heat_dps: f32,
+    // Rate at which the boss recovers HP (Crocomire regaining ground), per second:
+    regen_rate: f32,
+    // Unavoidable damage at the end of the fight (Mother Brain's rainbow beam), halved by Varia:
+    fixed_damage: f32,
+}
+
+fn get_boss_spec(boss: Boss) -> BossSpec {
+    match boss {
+        Boss::Phantoon => BossSpec {
+            hp: 2500.0,
+            cycle_frames: 600,
+            open_frames: 180,
+            super_damage: 600.0,
+            missile_damage: 100.0,
+            power_bomb_damage: 0.0,
+            beam_vulnerable: false,
+            super_accuracy_factor: 1.0,
+            attacks: &[
+                AttackSpec {
+                    period_frames: 300,
+                    damage: 40.0,
+                    hit_chance: 0.5,
+                    dodge: 0.9,
+                    item_factors: &[],
+                },
+                AttackSpec {
+                    period_frames: 600,
+                    damage: 40.0,
+                    hit_chance: 0.3,
+                    dodge: 1.0,
+                    item_factors: &[],
+                },
+            ],
+            harmless_below_hp: 0.0,
+            farm_rate: 4.0,
+            heat_dps: 0.0,
+            regen_rate: 0.0,
+            fixed_damage: 0.0,
+        },
+        Boss::Draygon => BossSpec {
+            hp: 6000.0,
+            cycle_frames: 900,
+            open_frames: 450,
+            super_damage: 300.0,
+            missile_damage: 100.0,
+            power_bomb_damage: 0.0,
+            beam_vulnerable: false,
+            super_accuracy_factor: 1.0,
+            attacks: &[
+                // Swoops:
+                AttackSpec {
+                    period_frames: 450,
+                    damage: 160.0,
+                    hit_chance: 0.6,
+                    dodge: 0.95,
+                    item_factors: &[(Item::Morph, 0.3), (Item::Gravity, 0.7)],
+                },
+                // Goop:
+                AttackSpec {
+                    period_frames: 900,
+                    damage: 160.0,
+                    hit_chance: 0.6,
+                    dodge: 0.95,
+                    item_factors: &[(Item::Gravity, 0.3)],
+                },
+            ],
+            harmless_below_hp: 0.0,
+            farm_rate: 2.5,
+            heat_dps: 0.0,
+            regen_rate: 0.0,
+            fixed_damage: 0.0,
+        },
+        Boss::Ridley => BossSpec {
+            hp: 18000.0,
+            cycle_frames: 1,
+            open_frames: 1,
+            super_damage: 600.0,
+            missile_damage: 100.0,
+            power_bomb_damage: 400.0,
+            beam_vulnerable: true,
+            super_accuracy_factor: 1.0,
+            attacks: &[AttackSpec {
+                period_frames: 60,
+                damage: 50.0,
+                hit_chance: 0.8,
+                dodge: 0.8,
+                item_factors: &[(Item::Morph, 0.5), (Item::ScrewAttack, 0.6)],
+            }],
+            harmless_below_hp: 0.0,
+            farm_rate: 0.0,
+            heat_dps: 15.0,
+            regen_rate: 0.0,
+            fixed_damage: 0.0,
+        },
+        Boss::Botwoon => BossSpec {
+            hp: 3000.0,
+            cycle_frames: 480,
+            open_frames: 240,
+            super_damage: 300.0,
+            missile_damage: 100.0,
+            power_bomb_damage: 0.0,
+            beam_vulnerable: true,
+            super_accuracy_factor: 1.0,
+            attacks: &[AttackSpec {
+                period_frames: 300,
+                damage: 96.0,
+                hit_chance: 0.5,
+                dodge: 0.8,
+                item_factors: &[(Item::Morph, 0.7), (Item::Gravity, 0.8)],
+            }],
+            harmless_below_hp: 1500.0,
+            farm_rate: 0.0,
+            heat_dps: 0.0,
+            regen_rate: 0.0,
+            fixed_damage: 0.0,
+        },
+        Boss::MotherBrain2 => BossSpec {
+            hp: 18000.0,
+            cycle_frames: 1,
+            open_frames: 1,
+            // Assumes Supers do double damage:
+            super_damage: 600.0,
+            missile_damage: 100.0,
+            power_bomb_damage: 0.0,
+            beam_vulnerable: false,
+            super_accuracy_factor: 1.0,
+            attacks: &[AttackSpec {
+                period_frames: 120,
+                damage: 40.0,
+                hit_chance: 0.6,
+                dodge: 1.0,
+                item_factors: &[],
+            }],
+            harmless_below_hp: 0.0,
+            farm_rate: 0.0,
+            heat_dps: 0.0,
+            regen_rate: 0.0,
+            fixed_damage: 600.0,
+        },
+        Boss::Kraid => BossSpec {
+            hp: 1000.0,
+            cycle_frames: 360,
+            open_frames: 120,
+            super_damage: 300.0,
+            missile_damage: 100.0,
+            power_bomb_damage: 0.0,
+            beam_vulnerable: true,
+            super_accuracy_factor: 1.0,
+            attacks: &[
+                // Flying claws:
+                AttackSpec {
+                    period_frames: 240,
+                    damage: 20.0,
+                    hit_chance: 0.5,
+                    dodge: 0.9,
+                    item_factors: &[],
+                },
+                // Belly spikes:
+                AttackSpec {
+                    period_frames: 300,
+                    damage: 10.0,
+                    hit_chance: 0.5,
+                    dodge: 0.9,
+                    item_factors: &[],
+                },
+            ],
+            harmless_below_hp: 0.0,
+            farm_rate: 0.0,
+            heat_dps: 0.0,
+            regen_rate: 0.0,
+            fixed_damage: 0.0,
+        },
+        Boss::Crocomire => BossSpec {
+            hp: 3000.0,
+            cycle_frames: 240,
+            open_frames: 120,
+            super_damage: 300.0,
+            missile_damage: 100.0,
+            power_bomb_damage: 0.0,
+            beam_vulnerable: false,
+            super_accuracy_factor: 1.0,
+            attacks: &[AttackSpec {
+                period_frames: 120,
+                damage: 20.0,
+                hit_chance: 0.4,
+                dodge: 0.9,
+                item_factors: &[],
+            }],
+            harmless_below_hp: 0.0,
+            farm_rate: 1.0,
+            heat_dps: 0.0,
+            regen_rate: 8.0,
+            fixed_damage: 0.0,
+        },
+        Boss::SporeSpawn => BossSpec {
+            hp: 960.0,
+            cycle_frames: 720,
+            open_frames: 120,
+            super_damage: 300.0,
+            missile_damage: 100.0,
+            power_bomb_damage: 0.0,
+            beam_vulnerable: true,
+            super_accuracy_factor: 1.0,
+            attacks: &[AttackSpec {
+                period_frames: 90,
+                damage: 12.0,
+                hit_chance: 0.4,
+                dodge: 0.9,
+                item_factors: &[],
+            }],
+            harmless_below_hp: 0.0,
+            farm_rate: 3.0,
+            heat_dps: 0.0,
+            regen_rate: 0.0,
+            fixed_damage: 0.0,
+        },
+        Boss::GoldenTorizo => BossSpec {
+            hp: 8000.0,
+            cycle_frames: 1,
+            open_frames: 1,
+            super_damage: 300.0,
+            missile_damage: 100.0,
+            power_bomb_damage: 0.0,
+            beam_vulnerable: true,
+            super_accuracy_factor: 0.5,
+            attacks: &[
+                // Eye beams:
+                AttackSpec {
+                    period_frames: 180,
+                    damage: 60.0,
+                    hit_chance: 0.5,
+                    dodge: 0.85,
+                    item_factors: &[(Item::SpaceJump, 0.8), (Item::ScrewAttack, 0.7)],
+                },
+                // Eggs:
+                AttackSpec {
+                    period_frames: 240,
+                    damage: 30.0,
+                    hit_chance: 0.5,
+                    dodge: 0.85,
+                    item_factors: &[(Item::ScrewAttack, 0.5)],
+                },
+                // Swipes:
+                AttackSpec {
+                    period_frames: 300,
+                    damage: 60.0,
+                    hit_chance: 0.4,
+                    dodge: 0.9,
+                    item_factors: &[(Item::SpaceJump, 0.7)],
+                },
+            ],
+            harmless_below_hp: 0.0,
+            farm_rate: 0.0,
+            heat_dps: 0.0,
+            regen_rate: 0.0,
+            fixed_damage: 0.0,
+        },
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Weapon {
+    Super,
+    Missile,
+    PowerBomb,
+    Charge,
+    Beam,
+}
+
+struct WeaponSpec {
+    weapon: Weapon,
+    damage: f32,
+    cooldown_frames: u32,
+}
+
+fn get_weapon_specs(spec: &BossSpec, inventory: &Inventory) -> Vec<WeaponSpec> {
+    let mut weapons = vec![];
+    if inventory.max_supers > 0 {
+        weapons.push(WeaponSpec {
+            weapon: Weapon::Super,
+            damage: spec.super_damage * spec.super_accuracy_factor,
+            cooldown_frames: 30,
+        });
+    }
+    if inventory.max_missiles > 0 {
+        weapons.push(WeaponSpec {
+            weapon: Weapon::Missile,
+            damage: spec.missile_damage,
+            cooldown_frames: 20,
+        });
+    }
+    if inventory.max_power_bombs > 0
+        && inventory.items[Item::Morph as usize]
+        && spec.power_bomb_damage > 0.0
+    {
+        weapons.push(WeaponSpec {
+            weapon: Weapon::PowerBomb,
+            damage: spec.power_bomb_damage,
+            cooldown_frames: 180,
+        });
+    }
+    let charge_damage = get_charge_damage(inventory);
+    if charge_damage > 0.0 {
+        weapons.push(WeaponSpec {
+            weapon: Weapon::Charge,
+            damage: charge_damage,
+            cooldown_frames: 60,
+        });
+    }
+    if spec.beam_vulnerable {
+        weapons.push(WeaponSpec {
+            weapon: Weapon::Beam,
+            damage: get_uncharged_damage(inventory),
+            cooldown_frames: 12,
+        });
+    }
+    weapons
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FightOutcome {
+    pub success: bool,
+    pub seconds: f32,
+    pub energy_used: Capacity,
+    pub missiles_used: Capacity,
+    pub supers_used: Capacity,
+    pub power_bombs_used: Capacity,
+}
+
+pub fn simulate_fight<R: Rng>(
+    boss: Boss,
+    inventory: &Inventory,
+    proficiency: f32,
+    rng: &mut R,
+) -> FightOutcome {
+    let spec = get_boss_spec(boss);
+    let weapons = get_weapon_specs(&spec, inventory);
+    let suit_factor = suit_damage_factor(inventory) as f32;
+    let accuracy = 0.6 + 0.4 * proficiency;
+    // Average delay (in frames) between a weapon being ready and firing it:
+    let mean_reaction_frames = 5.0 + 45.0 * (1.0 - proficiency);
+    let farm_rate = spec.farm_rate * (0.25 + 0.75 * proficiency) / FRAMES_PER_SECOND;
+    let heat_rate = if inventory.items[Item::Varia as usize] {
+        0.0
+    } else {
+        spec.heat_dps / FRAMES_PER_SECOND
+    };
+    let hit_chances: Vec<f32> = spec
+        .attacks
+        .iter()
+        .map(|attack| {
+            let mut chance = attack.hit_chance * (1.0 - attack.dodge * proficiency);
+            for &(item, factor) in attack.item_factors {
+                if inventory.items[item as usize] {
+                    chance *= factor;
+                }
+            }
+            chance
+        })
+        .collect();
+    // Randomize the relative timing of the attack patterns:
+    let attack_offsets: Vec<u32> = spec
+        .attacks
+        .iter()
+        .map(|attack| rng.gen_range(0..attack.period_frames))
+        .collect();
+
+    let mut boss_hp = spec.hp;
+    let mut missiles = inventory.max_missiles;
+    let mut supers = inventory.max_supers;
+    let mut power_bombs = inventory.max_power_bombs;
+    let mut energy_deficit: f32 = 0.0;
+    let mut peak_energy_deficit: f32 = 0.0;
+    let mut invincibility: u32 = 0;
+    let mut cooldown: u32 = 0;
+    let mut frame: u32 = 0;
+    let mut success = false;
+
+    while frame < MAX_FIGHT_FRAMES {
+        frame += 1;
+
+        // Boss attacks:
+        if boss_hp > spec.harmless_below_hp {
+            for (i, attack) in spec.attacks.iter().enumerate() {
+                if (frame + attack_offsets[i]) % attack.period_frames == 0
+                    && invincibility == 0
+                    && rng.gen_bool(hit_chances[i] as f64)
+                {
+                    energy_deficit += attack.damage / suit_factor;
+                    invincibility = INVINCIBILITY_FRAMES;
+                }
+            }
+        }
+        invincibility = invincibility.saturating_sub(1);
+        energy_deficit = f32::max(0.0, energy_deficit + heat_rate - farm_rate);
+        peak_energy_deficit = f32::max(peak_energy_deficit, energy_deficit);
+
+        boss_hp = f32::min(spec.hp, boss_hp + spec.regen_rate / FRAMES_PER_SECOND);
+
+        // Samus attacks, using the available weapon with the highest expected damage rate:
+        if cooldown > 0 {
+            cooldown -= 1;
+            continue;
+        }
+        if frame % spec.cycle_frames >= spec.open_frames {
+            continue;
+        }
+        let weapon = weapons
+            .iter()
+            .filter(|w| match w.weapon {
+                Weapon::Super => supers > 0,
+                Weapon::Missile => missiles > 0,
+                Weapon::PowerBomb => power_bombs > 0,
+                Weapon::Charge | Weapon::Beam => true,
+            })
+            .max_by(|a, b| {
+                let rate_a = a.damage / a.cooldown_frames as f32;
+                let rate_b = b.damage / b.cooldown_frames as f32;
+                rate_a.total_cmp(&rate_b)
+            });
+        let Some(weapon) = weapon else {
+            // No way left to damage the boss.
+            break;
+        };
+        match weapon.weapon {
+            Weapon::Super => supers -= 1,
+            Weapon::Missile => missiles -= 1,
+            Weapon::PowerBomb => power_bombs -= 1,
+            Weapon::Charge | Weapon::Beam => {}
+        }
+        if rng.gen_bool(accuracy as f64) {
+            boss_hp -= weapon.damage;
+        }
+        if boss_hp <= 0.0 {
+            success = true;
+            break;
+        }
+        let reaction_frames = rng.gen_range(0.0..2.0 * mean_reaction_frames);
+        cooldown = weapon.cooldown_frames + reaction_frames as u32;
+    }
+
+    let mut fixed_damage = spec.fixed_damage;
+    if inventory.items[Item::Varia as usize] {
+        fixed_damage /= 2.0;
+    }
+    FightOutcome {
+        success,
+        seconds: frame as f32 / FRAMES_PER_SECOND,
+        energy_used: (peak_energy_deficit + fixed_damage).ceil() as Capacity,
+        missiles_used: inventory.max_missiles - missiles,
+        supers_used: inventory.max_supers - supers,
+        power_bombs_used: inventory.max_power_bombs - power_bombs,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Percentiles {
+    pub p10: f32,
+    pub p50: f32,
+    pub p90: f32,
+    pub p99: f32,
+}
+
+impl Percentiles {
+    fn from_samples(mut samples: Vec<f32>) -> Self {
+        if samples.is_empty() {
+            return Percentiles {
+                p10: f32::NAN,
+                p50: f32::NAN,
+                p90: f32::NAN,
+                p99: f32::NAN,
+            };
+        }
+        samples.sort_by(|a, b| a.total_cmp(b));
+        let get = |q: f32| samples[((samples.len() - 1) as f32 * q).round() as usize];
+        Percentiles {
+            p10: get(0.1),
+            p50: get(0.5),
+            p90: get(0.9),
+            p99: get(0.99),
+        }
+    }
+}
+
+// Distribution of the resources needed to win a fight, over the successful trials.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FightDistribution {
+    pub trials: usize,
+    pub success_rate: f32,
+    pub seconds: Percentiles,
+    pub energy_used: Percentiles,
+    pub missiles_used: Percentiles,
+    pub supers_used: Percentiles,
+    pub power_bombs_used: Percentiles,
+}
+
+pub fn simulate_fights(
+    boss: Boss,
+    inventory: &Inventory,
+    proficiency: f32,
+    trials: usize,
+    seed: u64,
+) -> FightDistribution {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    let outcomes: Vec<FightOutcome> = (0..trials)
+        .map(|_| simulate_fight(boss, inventory, proficiency, &mut rng))
+        .filter(|x| x.success)
+        .collect();
+    let get =
+        |f: fn(&FightOutcome) -> f32| Percentiles::from_samples(outcomes.iter().map(f).collect());
+    FightDistribution {
+        trials,
+        success_rate: outcomes.len() as f32 / trials as f32,
+        seconds: get(|x| x.seconds),
+        energy_used: get(|x| x.energy_used as f32),
+        missiles_used: get(|x| x.missiles_used as f32),
+        supers_used: get(|x| x.supers_used as f32),
+        power_bombs_used: get(|x| x.power_bombs_used as f32),
+    }
+}
+
+// Resources used according to the closed-form fight model, assuming all patience tech is
+// available (the simulation reports fight durations instead), or None if the model fails.
+pub fn get_model_outcome(
+    boss: Boss,
+    inventory: &Inventory,
+    proficiency: f32,
+) -> Option<FightOutcome> {
+    let mut local = LocalState::full(false);
+    let success = match boss {
+        Boss::Phantoon => apply_phantoon_requirement(inventory, &mut local, proficiency, false),
+        Boss::Draygon => {
+            apply_draygon_requirement(inventory, &mut local, proficiency, true, true, true, false)
+        }
+        Boss::Ridley => apply_ridley_requirement(
+            inventory,
+            &mut local,
+            proficiency,
+            true,
+            true,
+            true,
+            true,
+            false,
+            maprando_game::RidleyStuck::None,
+            false,
+        ),
+        Boss::Botwoon => {
+            apply_botwoon_requirement(inventory, &mut local, proficiency, false, false)
+                && apply_botwoon_requirement(inventory, &mut local, proficiency, true, false)
+        }
+        Boss::MotherBrain2 => apply_mother_brain_2_requirement(
+            inventory,
+            &mut local,
+            proficiency,
+            true,
+            true,
+            false,
+            false,
+        ),
+        Boss::Kraid => {
+            apply_kraid_requirement(inventory, &mut local, proficiency, true, true, true, false)
+        }
+        Boss::Crocomire => {
+            apply_crocomire_requirement(inventory, &mut local, proficiency, true, true, true, false)
+        }
+        Boss::SporeSpawn => apply_spore_spawn_requirement(
+            inventory,
+            &mut local,
+            proficiency,
+            true,
+            true,
+            true,
+            false,
+        ),
+        Boss::GoldenTorizo => apply_golden_torizo_requirement(
+            inventory,
+            &mut local,
+            proficiency,
+            true,
+            true,
+            true,
+            false,
+        ),
+    };
+    if !success {
+        return None;
+    }
+    Some(FightOutcome {
+        success,
+        seconds: f32::NAN,
+        energy_used: local.energy_missing(inventory, true),
+        missiles_used: inventory.max_missiles - local.missiles_available(inventory, false),
+        supers_used: inventory.max_supers - local.supers_available(inventory, false),
+        power_bombs_used: inventory.max_power_bombs - local.power_bombs_available(inventory, false),
+    })
+}
+
+pub struct Loadout {
+    pub name: &'static str,
+    pub items: &'static [Item],
+    pub missiles: Capacity,
+    pub supers: Capacity,
+    pub power_bombs: Capacity,
+}
+
+pub const CALIBRATION_LOADOUTS: &[Loadout] = &[
+    Loadout {
+        name: "Missiles",
+        items: &[Item::Morph],
+        missiles: 30,
+        supers: 0,
+        power_bombs: 0,
+    },
+    Loadout {
+        name: "Charge",
+        items: &[Item::Morph, Item::Charge],
+        missiles: 10,
+        supers: 0,
+        power_bombs: 0,
+    },
+    Loadout {
+        name: "Early",
+        items: &[Item::Morph, Item::Charge, Item::Varia, Item::Spazer],
+        missiles: 40,
+        supers: 10,
+        power_bombs: 10,
+    },
+    Loadout {
+        name: "Late",
+        items: &[
+            Item::Morph,
+            Item::Charge,
+            Item::Varia,
+            Item::Gravity,
+            Item::Wave,
+            Item::Ice,
+            Item::Plasma,
+            Item::SpaceJump,
+            Item::ScrewAttack,
+        ],
+        missiles: 100,
+        supers: 30,
+        power_bombs: 20,
+    },
+];
+
+pub const CALIBRATION_PROFICIENCIES: &[f32] = &[0.0, 0.3, 0.5, 0.7, 1.0];
+
+pub fn make_inventory(loadout: &Loadout, max_energy: Capacity) -> Inventory {
+    let mut items = vec![false; Item::VARIANTS.len()];
+    for &item in loadout.items {
+        items[item as usize] = true;
+    }
+    Inventory {
+        items,
+        max_energy,
+        max_reserves: 0,
+        max_missiles: loadout.missiles,
+        max_supers: loadout.supers,
+        max_power_bombs: loadout.power_bombs,
+        collectible_missile_packs: 0,
+        collectible_super_packs: 0,
+        collectible_power_bomb_packs: 0,
+        collectible_reserve_tanks: 0,
+    }
+}
+
+pub struct CalibrationRow {
+    pub boss: Boss,
+    pub loadout: &'static str,
+    pub proficiency: f32,
+    pub simulated: FightDistribution,
+    pub model: Option<FightOutcome>,
+}
+
+pub fn get_calibration_table(boss: Boss, trials: usize, seed: u64) -> Vec<CalibrationRow> {
+    let mut rows = vec![];
+    for loadout in CALIBRATION_LOADOUTS {
+        // Energy capacity is made large enough to not be a constraint, so that the energy used
+        // can be compared directly.
+        let inventory = make_inventory(loadout, 9999);
+        for &proficiency in CALIBRATION_PROFICIENCIES {
+            rows.push(CalibrationRow {
+                boss,
+                loadout: loadout.name,
+                proficiency,
+                simulated: simulate_fights(boss, &inventory, proficiency, trials, seed),
+                model: get_model_outcome(boss, &inventory, proficiency),
+            });
+        }
+    }
+    rows
+}
+
+pub fn format_calibration_table(rows: &[CalibrationRow]) -> String {
+    let mut out = format!(
+        "{:<14} {:<9} {:>5} {:>6} {:>7} {:>17} {:>7} {:>11} {:>7} {:>11} {:>7}\n",
+        "boss",
+        "loadout",
+        "prof",
+        "win%",
+        "time",
+        "energy p50/p90",
+        "model",
+        "missiles",
+        "model",
+        "supers",
+        "model"
+    );
+    let fmt_model = |x: Option<Capacity>| match x {
+        Some(x) => x.to_string(),
+        None => "fail".to_string(),
+    };
+    for row in rows {
+        let sim = &row.simulated;
+        out += &format!(
+            "{:<14} {:<9} {:>5.2} {:>5.1}% {:>6.0}s {:>8.0}/{:<8.0} {:>7} {:>5.0}/{:<5.0} {:>7} {:>5.0}/{:<5.0} {:>7}\n",
+            row.boss.name(),
+            row.loadout,
+            row.proficiency,
+            sim.success_rate * 100.0,
+            sim.seconds.p50,
+            sim.energy_used.p50,
+            sim.energy_used.p90,
+            fmt_model(row.model.as_ref().map(|x| x.energy_used)),
+            sim.missiles_used.p50,
+            sim.missiles_used.p90,
+            fmt_model(row.model.as_ref().map(|x| x.missiles_used)),
+            sim.supers_used.p50,
+            sim.supers_used.p90,
+            fmt_model(row.model.as_ref().map(|x| x.supers_used)),
+        );
+    }
+    out
+}
@@ -6,17 +6,34 @@ pub mod vanilla_music;
 
 use anyhow::{Result, bail};
 use log::info;
+use ndarray::Array3;
 use std::cmp::min;
-use std::path::Path;
 
 use crate::patch::glowpatch_writer::write_glowpatch;
-use crate::patch::{Rom, apply_ips_patch, snes2pc, write_credits_big_char};
-use maprando_game::{GameData, Map};
+use crate::patch::passes::RomWriteLog;
+use crate::patch::{
+    Rom, apply_ips_patch, get_ips_patch_overrides, snes2pc, write_credits_big_char,
+};
+use maprando_game::{GameData, Map, data_source::DataSource};
 use mosaic::MosaicTheme;
 use retiling::apply_retiling;
 use room_palettes::apply_area_themed_palettes;
 use samus_sprite::SamusSpriteCategory;
 
+// Apply an IPS file as its own pass, so that its writes are attributed to it rather than to the
+// enclosing customization pass.
+fn apply_ips_patch_pass(
+    rom: &mut Rom,
+    write_log: &mut RomWriteLog,
+    data_source: &dyn DataSource,
+    pass_name: &str,
+    patch_path: &str,
+) -> Result<()> {
+    write_log.begin(rom, pass_name, get_ips_patch_overrides(pass_name))?;
+    apply_ips_patch(rom, data_source, patch_path)?;
+    write_log.end(rom)
+}
+
 struct AllocatorBlock {
     start_addr: usize,
     end_addr: usize,
@@ -191,6 +208,9 @@ pub struct CustomizeSettings {
     pub statues_hallway_tiling: StatuesHallwayTiling,
     pub statues_hallway_audio: StatuesHallwayAudio,
     pub controller_config: ControllerConfig,
+    // Title screen background, as prepared by `patch::title::load_title_background`. If not provided,
+    // a background is assembled randomly (based on the seed) from the bundled title screen images.
+    pub title_background: Option<Array3<u8>>,
 }
 
 impl Default for CustomizeSettings {
@@ -217,6 +237,7 @@ impl Default for CustomizeSettings {
             statues_hallway_tiling: StatuesHallwayTiling::Default,
             statues_hallway_audio: StatuesHallwayAudio::Enabled,
             controller_config: ControllerConfig::default(),
+            title_background: None,
         }
     }
 }
@@ -233,6 +254,8 @@ fn remove_mother_brain_flashing(rom: &mut Rom) -> Result<()> {
 
 fn apply_custom_samus_sprite(
     rom: &mut Rom,
+    write_log: &mut RomWriteLog,
+    data_source: &dyn DataSource,
     settings: &CustomizeSettings,
     samus_sprite_categories: &[SamusSpriteCategory],
 ) -> Result<()> {
@@ -241,8 +264,8 @@ fn apply_custom_samus_sprite(
             .samus_sprite
             .clone()
             .unwrap_or("samus_vanilla".to_string());
-        let patch_path_str = format!("../patches/samus_sprites/{sprite_name}.ips");
-        apply_ips_patch(rom, Path::new(&patch_path_str))?;
+        let patch_path = format!("patches/samus_sprites/{sprite_name}.ips");
+        apply_ips_patch_pass(rom, write_log, data_source, "ips/samus_sprite", &patch_path)?;
 
         if settings.vanilla_screw_attack_animation {
             // Disable spin attack animation, to make it behave like vanilla: Screw attack animation will look like
@@ -353,7 +376,12 @@ fn get_button_list_mask(buttons: &[ControllerButton]) -> isize {
     mask
 }
 
-fn apply_controller_config(rom: &mut Rom, controller_config: &ControllerConfig) -> Result<()> {
+fn apply_controller_config(
+    rom: &mut Rom,
+    write_log: &mut RomWriteLog,
+    data_source: &dyn DataSource,
+    controller_config: &ControllerConfig,
+) -> Result<()> {
     let control_data = vec![
         (0x81B325, controller_config.jump, ControllerButton::A),
         (0x81B32B, controller_config.dash, ControllerButton::B),
@@ -385,7 +413,13 @@ fn apply_controller_config(rom: &mut Rom, controller_config: &ControllerConfig)
     rom.write_u16(snes2pc(0x82FE7E), quick_reload_mask)?;
 
     if controller_config.moonwalk {
-        apply_ips_patch(rom, Path::new("../patches/ips/enable_moonwalk.ips"))?;
+        apply_ips_patch_pass(
+            rom,
+            write_log,
+            data_source,
+            "ips/enable_moonwalk",
+            "patches/ips/enable_moonwalk.ips",
+        )?;
     }
     // $82FE7E
 
@@ -511,6 +545,7 @@ fn disable_music(rom: &mut Rom) -> Result<()> {
 
 pub fn customize_rom(
     rom: &mut Rom,
+    write_log: &mut RomWriteLog,
     orig_rom: &Rom,
     map: &Map,
     settings: &CustomizeSettings,
@@ -518,9 +553,11 @@ pub fn customize_rom(
     samus_sprite_categories: &[SamusSpriteCategory],
     mosaic_themes: &[MosaicTheme],
 ) -> Result<()> {
+    let data_source = game_data.data_source().as_ref();
     remove_mother_brain_flashing(rom)?;
     apply_retiling(
         rom,
+        write_log,
         orig_rom,
         map,
         game_data,
@@ -539,7 +576,13 @@ pub fn customize_rom(
     match settings.door_theme {
         DoorTheme::Vanilla => {}
         DoorTheme::Alternate => {
-            apply_ips_patch(rom, Path::new("../patches/ips/alternate_door_colors.ips"))?;
+            apply_ips_patch_pass(
+                rom,
+                write_log,
+                data_source,
+                "ips/alternate_door_colors",
+                "patches/ips/alternate_door_colors.ips",
+            )?;
         }
     }
 
@@ -559,7 +602,13 @@ pub fn customize_rom(
     // customized HUD colors which would get messed up.
     rom.write_u16(snes2pc(0xA7DC6E), 0x0040)?;
 
-    apply_custom_samus_sprite(rom, settings, samus_sprite_categories)?;
+    apply_custom_samus_sprite(
+        rom,
+        write_log,
+        data_source,
+        settings,
+        samus_sprite_categories,
+    )?;
     if let Some((r, g, b)) = settings.etank_color {
         let color = (r as isize) | ((g as isize) << 5) | ((b as isize) << 10);
         rom.write_u16(snes2pc(0x82FFFE), color)?; // Gameplay ETank color
@@ -567,7 +616,13 @@ pub fn customize_rom(
         rom.write_u16(snes2pc(0xA7CA7B), color)?; // During Phantoon power-on
     }
     if settings.reserve_hud_style {
-        apply_ips_patch(rom, Path::new("../patches/ips/reserve_hud.ips"))?;
+        apply_ips_patch_pass(
+            rom,
+            write_log,
+            data_source,
+            "ips/reserve_hud",
+            "patches/ips/reserve_hud.ips",
+        )?;
     }
     if settings.room_names {
         rom.write_u16(snes2pc(0x82FFFA), 1)?;
@@ -630,13 +685,25 @@ pub fn customize_rom(
     }
     match settings.flashing {
         FlashingSetting::Vanilla => {
-            apply_ips_patch(rom, Path::new("../patches/ips/flashing_placebo.ips"))?;
+            apply_ips_patch_pass(
+                rom,
+                write_log,
+                data_source,
+                "ips/flashing_placebo",
+                "patches/ips/flashing_placebo.ips",
+            )?;
         }
         FlashingSetting::Reduced => {
-            apply_ips_patch(rom, Path::new("../patches/ips/flashing_placebo.ips"))?;
+            apply_ips_patch_pass(
+                rom,
+                write_log,
+                data_source,
+                "ips/flashing_placebo",
+                "patches/ips/flashing_placebo.ips",
+            )?;
             write_glowpatch(rom, &game_data.reduced_flashing_patch)?;
         }
     }
-    apply_controller_config(rom, &settings.controller_config)?;
+    apply_controller_config(rom, write_log, data_source, &settings.controller_config)?;
     Ok(())
 }
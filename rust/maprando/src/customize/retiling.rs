@@ -1,28 +1,44 @@
-use std::path::Path;
-
 use super::mosaic::MosaicTheme;
 use crate::{
     customize::StatuesHallwayTiling,
-    patch::{Rom, apply_ips_patch, bps::BPSPatch, get_room_state_ptrs, snes2pc},
+    patch::{Rom, bps::BPSPatch, get_room_state_ptrs, passes::RomWriteLog, snes2pc},
 };
 use anyhow::{Context, Result};
 use hashbrown::HashMap;
-use maprando_game::{DoorPtr, GameData, Map, RoomPtr, RoomStateIdx};
+use maprando_game::{DoorPtr, GameData, Map, RoomPtr, RoomStateIdx, data_source::DataSource};
 use rand::{Rng, SeedableRng};
 
-use super::TileTheme;
+use super::{TileTheme, apply_ips_patch_pass};
 
-const BPS_PATCH_PATH: &str = "../patches/mosaic";
+const BPS_PATCH_PATH: &str = "patches/mosaic";
 
-fn apply_bps_patch(rom: &mut Rom, orig_rom: &Rom, filename: &str) -> Result<()> {
-    let path = Path::new(BPS_PATCH_PATH).join(filename);
-    let patch_bytes = std::fs::read(path).with_context(|| format!("Loading {filename}"))?;
+fn apply_bps_patch(
+    rom: &mut Rom,
+    orig_rom: &Rom,
+    data_source: &dyn DataSource,
+    filename: &str,
+) -> Result<()> {
+    let path = format!("{BPS_PATCH_PATH}/{filename}");
+    let patch_bytes = data_source
+        .read(&path)
+        .with_context(|| format!("Loading {filename}"))?;
     let patch = BPSPatch::new(patch_bytes)?;
     patch.apply(&orig_rom.data, &mut rom.data);
+    // The patch is applied to the ROM data directly, so write its output ranges again through
+    // `Rom`, for them to be attributed to the current pass:
+    for (start, end) in patch.get_written_ranges() {
+        let data = rom.data[start..end].to_vec();
+        rom.write_n(start, &data)?;
+    }
     Ok(())
 }
 
-fn apply_toilet(rom: &mut Rom, orig_rom: &Rom, theme_name: &str) -> Result<()> {
+fn apply_toilet(
+    rom: &mut Rom,
+    orig_rom: &Rom,
+    data_source: &dyn DataSource,
+    theme_name: &str,
+) -> Result<()> {
     let toilet_intersecting_room_ptr_addr = snes2pc(0xB5FE70);
     let toilet_rel_x_addr = snes2pc(0xB5FE72);
     let toilet_rel_y_addr = snes2pc(0xB5FE73);
@@ -41,7 +57,7 @@ fn apply_toilet(rom: &mut Rom, orig_rom: &Rom, theme_name: &str) -> Result<()> {
         format!("{theme_name}-{room_ptr:X}-Transit-{x}-{y}.bps")
     };
     println!("toilet patch: {patch_filename}");
-    apply_bps_patch(rom, orig_rom, &patch_filename)
+    apply_bps_patch(rom, orig_rom, data_source, &patch_filename)
         .context(format!("Applying Toilet patch: {patch_filename}"))?;
 
     Ok(())
@@ -49,6 +65,7 @@ fn apply_toilet(rom: &mut Rom, orig_rom: &Rom, theme_name: &str) -> Result<()> {
 
 pub fn apply_retiling(
     rom: &mut Rom,
+    write_log: &mut RomWriteLog,
     orig_rom: &Rom,
     map: &Map,
     game_data: &GameData,
@@ -64,6 +81,7 @@ pub fn apply_retiling(
         (StatuesHallwayTiling::Enabled, _) => true,
     };
 
+    let data_source = game_data.data_source().as_ref();
     let tourian_neighbors = game_data.get_tourian_neighbors(map, false);
     let tourian_neighbors_strict = game_data.get_tourian_neighbors(map, true);
     let patch_names = vec![
@@ -77,8 +95,14 @@ pub fn apply_retiling(
         "in_place_level_data",
     ];
     for name in &patch_names {
-        let patch_path_str = format!("../patches/ips/{name}.ips");
-        apply_ips_patch(rom, Path::new(&patch_path_str))?;
+        let patch_path = format!("patches/ips/{name}.ips");
+        apply_ips_patch_pass(
+            rom,
+            write_log,
+            data_source,
+            &format!("ips/{name}"),
+            &patch_path,
+        )?;
     }
 
     let mut fx_door_ptr_map: HashMap<(RoomPtr, RoomStateIdx, DoorPtr), DoorPtr> = HashMap::new();
@@ -102,7 +126,7 @@ pub fn apply_retiling(
 
     let random_seed = u32::from_le_bytes(rom.read_n(snes2pc(0xdfff00), 4)?.try_into()?);
 
-    apply_bps_patch(rom, orig_rom, "tilesets.bps")?;
+    apply_bps_patch(rom, orig_rom, data_source, "tilesets.bps")?;
 
     let mut theme_name_map: HashMap<RoomPtr, String> = HashMap::new();
     for (room_idx, room) in game_data.room_geometry.iter().enumerate() {
@@ -174,7 +198,13 @@ pub fn apply_retiling(
     }
 
     if *theme == TileTheme::AreaThemed {
-        apply_ips_patch(rom, Path::new("../patches/ips/mosaic_fx_fix.ips"))?;
+        apply_ips_patch_pass(
+            rom,
+            write_log,
+            data_source,
+            "ips/mosaic_fx_fix",
+            "patches/ips/mosaic_fx_fix.ips",
+        )?;
     }
 
     if *theme != TileTheme::Vanilla {
@@ -214,7 +244,7 @@ pub fn apply_retiling(
         let state_ptrs = get_room_state_ptrs(rom, room_ptr)?;
         for (state_idx, (_event_ptr, state_ptr)) in state_ptrs.iter().enumerate() {
             let patch_filename = format!("{theme_name}-{room_ptr:X}-{state_idx}.bps");
-            apply_bps_patch(rom, orig_rom, &patch_filename)?;
+            apply_bps_patch(rom, orig_rom, data_source, &patch_filename)?;
 
             let fx_ptr = rom.read_u16(state_ptr + 6)? as usize;
             for i in 0..4 {
@@ -230,7 +260,12 @@ pub fn apply_retiling(
     }
 
     if map.room_mask[game_data.toilet_room_idx] {
-        apply_toilet(rom, orig_rom, &theme_name_map[&toilet_room_ptr])?;
+        apply_toilet(
+            rom,
+            orig_rom,
+            data_source,
+            &theme_name_map[&toilet_room_ptr],
+        )?;
     }
 
     Ok(())
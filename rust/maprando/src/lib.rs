@@ -3,10 +3,17 @@
 // TODO: consider removing this later. It's not a bad lint but I don't want to deal with it now.
 #![allow(clippy::too_many_arguments)]
 
+pub mod boss_simulation;
 pub mod customize;
 pub mod difficulty;
 pub mod helpers;
+pub mod logic_graph;
+pub mod map_areas;
+pub mod map_format;
+pub mod map_generation;
+pub mod map_index;
 pub mod map_repository;
+pub mod map_validation;
 pub mod patch;
 pub mod preset;
 pub mod randomize;
@@ -0,0 +1,301 @@
+// Export of a seed's logic graph (the vertices and links that the randomizer traverses), for
+// analysis in external graph tools, as GraphML or Graphviz DOT.
+
+use std::fmt::Write;
+
+use hashbrown::HashMap;
+use maprando_game::{Item, Link, VertexAction, VertexId};
+use maprando_logic::{GlobalState, Inventory, LocalState};
+
+use crate::randomize::{DifficultyConfig, Randomizer};
+use crate::spoiler_log::get_spoiler_game_data;
+use crate::traverse::{apply_requirement, simple_cost_config};
+
+pub struct LogicGraphVertex {
+    pub vertex_id: VertexId,
+    pub room: String,
+    pub node: String,
+    pub obstacle_mask: usize,
+    pub actions: Vec<String>,
+}
+
+pub struct LogicGraphLink {
+    pub from_vertex_id: VertexId,
+    pub to_vertex_id: VertexId,
+    pub strat_name: String,
+    pub requirement: String,
+    pub difficulty: u8,
+    // Whether the link is specific to the seed (i.e. a door link), rather than a base link.
+    pub seed_link: bool,
+}
+
+pub struct LogicGraph {
+    pub vertices: Vec<LogicGraphVertex>,
+    pub links: Vec<LogicGraphLink>,
+}
+
+// Restricts the graph to links which can be traversed with the given items and skills (from
+// a full energy/ammo state), along with the vertices they touch.
+pub struct LogicGraphFilter<'a> {
+    pub items: Vec<Item>,
+    pub difficulty: &'a DifficultyConfig,
+}
+
+fn action_name(action: &VertexAction) -> &'static str {
+    match action {
+        VertexAction::Nothing => "Nothing",
+        VertexAction::MaybeExit(..) => "MaybeExit",
+        VertexAction::Exit(_) => "Exit",
+        VertexAction::Enter(_) => "Enter",
+        VertexAction::DoorUnlock(..) => "DoorUnlock",
+        VertexAction::ItemCollect(_) => "ItemCollect",
+        VertexAction::FlagSet(_) => "FlagSet",
+    }
+}
+
+fn get_filter_global(randomizer: &Randomizer, filter: &LogicGraphFilter) -> GlobalState {
+    let game_data = randomizer.game_data;
+    let items = vec![false; game_data.item_isv.keys.len()];
+    let weapon_mask = game_data.get_weapon_mask(&items, &filter.difficulty.tech);
+    let inventory = Inventory {
+        items,
+        max_energy: 99,
+        max_reserves: 0,
+        max_missiles: 0,
+        max_supers: 0,
+        max_power_bombs: 0,
+        collectible_missile_packs: 0,
+        collectible_super_packs: 0,
+        collectible_power_bomb_packs: 0,
+        collectible_reserve_tanks: 0,
+    };
+    let mut global = GlobalState {
+        pool_inventory: inventory.clone(),
+        inventory,
+        // Game flags and locked doors are treated as already set/unlocked, so that only the
+        // link requirements themselves are checked:
+        flags: vec![true; game_data.flag_isv.keys.len()],
+        doors_unlocked: vec![true; randomizer.locked_door_data.locked_doors.len()],
+        weapon_mask,
+    };
+    for &item in &filter.items {
+        global.collect(item, game_data, 1.0, &filter.difficulty.tech);
+    }
+    global.pool_inventory = global.inventory.clone();
+    global
+}
+
+fn is_traversable(
+    link: &Link,
+    randomizer: &Randomizer,
+    global: &GlobalState,
+    difficulty: &DifficultyConfig,
+) -> bool {
+    apply_requirement(
+        &link.requirement,
+        global,
+        LocalState::full(false),
+        false,
+        randomizer.settings,
+        difficulty,
+        randomizer.game_data,
+        &randomizer.door_map,
+        randomizer.locked_door_data,
+        &randomizer.objectives,
+        &simple_cost_config(),
+    )
+    .is_some()
+}
+
+// Get the graph of base links and the seed's door links, optionally filtered.
+pub fn get_logic_graph(randomizer: &Randomizer, filter: Option<&LogicGraphFilter>) -> LogicGraph {
+    let spoiler_game_data = get_spoiler_game_data(randomizer);
+    let room_names: HashMap<usize, &str> = spoiler_game_data
+        .rooms
+        .iter()
+        .map(|x| (x.room_id, x.name.as_str()))
+        .collect();
+    let node_names: HashMap<(usize, usize), &str> = spoiler_game_data
+        .nodes
+        .iter()
+        .map(|x| ((x.room_id, x.node_id), x.name.as_str()))
+        .collect();
+
+    let filter_global = filter.map(|f| get_filter_global(randomizer, f));
+    let num_base_links = randomizer.base_links_data.links.len();
+    let mut used_vertices = vec![filter.is_none(); spoiler_game_data.vertices.len()];
+    let mut links = vec![];
+    for (idx, spoiler_link) in spoiler_game_data.links.iter().enumerate() {
+        let link = randomizer.get_link(idx);
+        let traversable = match (filter, &filter_global) {
+            (Some(f), Some(global)) => is_traversable(link, randomizer, global, f.difficulty),
+            _ => true,
+        };
+        if !traversable {
+            continue;
+        }
+        used_vertices[link.from_vertex_id] = true;
+        used_vertices[link.to_vertex_id] = true;
+        links.push(LogicGraphLink {
+            from_vertex_id: spoiler_link.from_vertex_id,
+            to_vertex_id: spoiler_link.to_vertex_id,
+            strat_name: spoiler_link.strat_name.clone(),
+            requirement: link.requirement.to_compact_string(randomizer.game_data),
+            difficulty: link.difficulty,
+            seed_link: idx >= num_base_links,
+        });
+    }
+
+    let mut vertices = vec![];
+    for (vertex_id, key) in spoiler_game_data.vertices.iter().enumerate() {
+        if !used_vertices[vertex_id] {
+            continue;
+        }
+        vertices.push(LogicGraphVertex {
+            vertex_id,
+            room: room_names
+                .get(&key.room_id)
+                .map(|x| x.to_string())
+                .unwrap_or_else(|| format!("Room {}", key.room_id)),
+            node: node_names
+                .get(&(key.room_id, key.node_id))
+                .map(|x| x.to_string())
+                .unwrap_or_else(|| format!("Node {}", key.node_id)),
+            obstacle_mask: key.obstacle_mask,
+            actions: key
+                .actions
+                .iter()
+                .map(|x| action_name(x).to_string())
+                .collect(),
+        });
+    }
+    LogicGraph { vertices, links }
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+pub fn logic_graph_to_graphml(graph: &LogicGraph) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    for (id, element, name, ty) in [
+        ("room", "node", "room", "string"),
+        ("node", "node", "node", "string"),
+        ("obstacle_mask", "node", "obstacle_mask", "int"),
+        ("actions", "node", "actions", "string"),
+        ("strat", "edge", "strat", "string"),
+        ("requirement", "edge", "requirement", "string"),
+        ("difficulty", "edge", "difficulty", "int"),
+        ("seed_link", "edge", "seed_link", "boolean"),
+    ] {
+        writeln!(
+            out,
+            "  <key id=\"{id}\" for=\"{element}\" attr.name=\"{name}\" attr.type=\"{ty}\"/>"
+        )
+        .unwrap();
+    }
+    out.push_str("  <graph id=\"logic\" edgedefault=\"directed\">\n");
+    for v in &graph.vertices {
+        writeln!(out, "    <node id=\"v{}\">", v.vertex_id).unwrap();
+        writeln!(
+            out,
+            "      <data key=\"room\">{}</data>",
+            escape_xml(&v.room)
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "      <data key=\"node\">{}</data>",
+            escape_xml(&v.node)
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "      <data key=\"obstacle_mask\">{}</data>",
+            v.obstacle_mask
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "      <data key=\"actions\">{}</data>",
+            escape_xml(&v.actions.join(","))
+        )
+        .unwrap();
+        out.push_str("    </node>\n");
+    }
+    for l in &graph.links {
+        writeln!(
+            out,
+            "    <edge source=\"v{}\" target=\"v{}\">",
+            l.from_vertex_id, l.to_vertex_id
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "      <data key=\"strat\">{}</data>",
+            escape_xml(&l.strat_name)
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "      <data key=\"requirement\">{}</data>",
+            escape_xml(&l.requirement)
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "      <data key=\"difficulty\">{}</data>",
+            l.difficulty
+        )
+        .unwrap();
+        writeln!(out, "      <data key=\"seed_link\">{}</data>", l.seed_link).unwrap();
+        out.push_str("    </edge>\n");
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+pub fn logic_graph_to_dot(graph: &LogicGraph) -> String {
+    let mut out = String::new();
+    out.push_str("digraph logic {\n");
+    for v in &graph.vertices {
+        let mut label = format!("{}: {}", v.room, v.node);
+        if v.obstacle_mask != 0 {
+            write!(label, " [obstacles {:#x}]", v.obstacle_mask).unwrap();
+        }
+        if !v.actions.is_empty() {
+            write!(label, " ({})", v.actions.join(", ")).unwrap();
+        }
+        writeln!(
+            out,
+            "  v{} [label=\"{}\"];",
+            v.vertex_id,
+            escape_dot(&label)
+        )
+        .unwrap();
+    }
+    for l in &graph.links {
+        writeln!(
+            out,
+            "  v{} -> v{} [label=\"{}\", requirement=\"{}\", difficulty={}{}];",
+            l.from_vertex_id,
+            l.to_vertex_id,
+            escape_dot(&l.strat_name),
+            escape_dot(&l.requirement),
+            l.difficulty,
+            if l.seed_link { ", style=dashed" } else { "" }
+        )
+        .unwrap();
+    }
+    out.push_str("}\n");
+    out
+}
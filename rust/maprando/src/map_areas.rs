@@ -0,0 +1,484 @@
+// Partitioning of a map's rooms into areas, subareas, and subsubareas, and the choice of which
+// area each of the map's areas becomes.
+//
+// Each level is split by growing connected regions of rooms outward from starting rooms which
+// are spread apart, always extending the region that is smallest so far (by map tiles, or by
+// item locations), so every area, subarea, and subsubarea is contiguous. The Toilet is kept
+// together with the room(s) that it passes through, which must share its area, subarea, and
+// subsubarea.
+
+use anyhow::{Context, Result, bail};
+use hashbrown::{HashMap, HashSet};
+use rand::{Rng, seq::SliceRandom};
+use std::collections::VecDeque;
+
+use crate::patch::NUM_AREAS;
+use crate::randomize::Randomizer;
+use crate::settings::{AreaAssignment, AreaConstraints, AreaPartition};
+use maprando_game::{AreaIdx, GameData, Map, RoomGeometryRoomIdx};
+
+const NUM_SUBAREAS: usize = 2;
+const NUM_SUBSUBAREAS: usize = 2;
+const MIN_SUBAREA_ROOMS: usize = 5;
+const MIN_SUBSUBAREA_ROOMS: usize = 2;
+const SPLIT_ATTEMPTS: usize = 100;
+
+fn add_weight(a: (usize, usize), b: (usize, usize)) -> (usize, usize) {
+    (a.0 + b.0, a.1 + b.1)
+}
+
+// Rooms of a map, with the Toilet merged into the room(s) that it passes through. Each merged
+// group is represented by one of its rooms, which stands in for the group when partitioning.
+struct RoomGraph {
+    rep: Vec<RoomGeometryRoomIdx>,
+    neighbors: HashMap<RoomGeometryRoomIdx, Vec<RoomGeometryRoomIdx>>,
+    // Primary and secondary size of each group (e.g. item locations, then map tiles).
+    weights: HashMap<RoomGeometryRoomIdx, (usize, usize)>,
+}
+
+impl RoomGraph {
+    fn new(map: &Map, game_data: &GameData, partition: AreaPartition) -> Result<Self> {
+        let mut rep: Vec<RoomGeometryRoomIdx> = (0..map.room_mask.len()).collect();
+        let toilet_intersections = Randomizer::get_toilet_intersections(map, game_data);
+        if let Some(&first) = toilet_intersections.first() {
+            rep[game_data.toilet_room_idx] = first;
+            for &r in &toilet_intersections {
+                rep[r] = first;
+            }
+        }
+
+        let mut neighbors: HashMap<RoomGeometryRoomIdx, Vec<RoomGeometryRoomIdx>> = HashMap::new();
+        for (src, dst, _) in &map.doors {
+            let (src_room, _) = *game_data
+                .room_and_door_idxs_by_door_ptr_pair
+                .get(src)
+                .context("Unknown door in map")?;
+            let (dst_room, _) = *game_data
+                .room_and_door_idxs_by_door_ptr_pair
+                .get(dst)
+                .context("Unknown door in map")?;
+            let (a, b) = (rep[src_room], rep[dst_room]);
+            if a != b {
+                neighbors.entry(a).or_default().push(b);
+                neighbors.entry(b).or_default().push(a);
+            }
+        }
+
+        let mut item_counts = vec![0; map.room_mask.len()];
+        for &(room_id, _) in &game_data.item_locations {
+            let room_ptr = game_data.room_ptr_by_id[&room_id];
+            item_counts[game_data.room_idx_by_ptr[&room_ptr]] += 1;
+        }
+        let mut weights: HashMap<RoomGeometryRoomIdx, (usize, usize)> = HashMap::new();
+        for (room_idx, room) in game_data.room_geometry.iter().enumerate() {
+            if !map.room_mask[room_idx] {
+                continue;
+            }
+            let tiles = room.map.iter().flatten().filter(|&&x| x == 1).count();
+            let w = weights.entry(rep[room_idx]).or_default();
+            match partition {
+                AreaPartition::Items => {
+                    w.0 += item_counts[room_idx];
+                    w.1 += tiles;
+                }
+                AreaPartition::Map | AreaPartition::Size => {
+                    w.0 += tiles;
+                }
+            }
+        }
+
+        Ok(RoomGraph {
+            rep,
+            neighbors,
+            weights,
+        })
+    }
+
+    // Representative rooms of the groups whose rooms are selected by `filter`.
+    fn groups(&self, map: &Map, filter: impl Fn(RoomGeometryRoomIdx) -> bool) -> Vec<usize> {
+        (0..map.room_mask.len())
+            .filter(|&r| map.room_mask[r] && self.rep[r] == r && filter(r))
+            .collect()
+    }
+
+    fn is_connected(&self, rooms: &[RoomGeometryRoomIdx]) -> bool {
+        let room_set: HashSet<RoomGeometryRoomIdx> = rooms.iter().copied().collect();
+        let Some(&first) = rooms.first() else {
+            return true;
+        };
+        let mut visited: HashSet<RoomGeometryRoomIdx> = HashSet::new();
+        let mut stack = vec![first];
+        visited.insert(first);
+        while let Some(r) = stack.pop() {
+            for &n in self.neighbors.get(&r).into_iter().flatten() {
+                if room_set.contains(&n) && visited.insert(n) {
+                    stack.push(n);
+                }
+            }
+        }
+        visited.len() == rooms.len()
+    }
+
+    // Split the given (connected) groups into (at most) `num_parts` connected parts of similar
+    // size, by growing the parts outward from starting groups which are spread apart.
+    fn partition<R: Rng>(
+        &self,
+        rooms: &[RoomGeometryRoomIdx],
+        num_parts: usize,
+        rng: &mut R,
+    ) -> HashMap<RoomGeometryRoomIdx, usize> {
+        let room_set: HashSet<RoomGeometryRoomIdx> = rooms.iter().copied().collect();
+        let get_neighbors = |room_idx: RoomGeometryRoomIdx| {
+            self.neighbors
+                .get(&room_idx)
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(|r| room_set.contains(r))
+        };
+        let mut assignment: HashMap<RoomGeometryRoomIdx, usize> = HashMap::new();
+        let Some(&first) = rooms.choose(rng) else {
+            return assignment;
+        };
+
+        // Choose each starting room to be as far as possible from the previous ones:
+        let mut seeds = vec![first];
+        while seeds.len() < num_parts.min(rooms.len()) {
+            let mut dist: HashMap<RoomGeometryRoomIdx, usize> = HashMap::new();
+            let mut queue: VecDeque<RoomGeometryRoomIdx> = VecDeque::new();
+            for &s in &seeds {
+                dist.insert(s, 0);
+                queue.push_back(s);
+            }
+            while let Some(r) = queue.pop_front() {
+                let d = dist[&r];
+                for n in get_neighbors(r) {
+                    if !dist.contains_key(&n) {
+                        dist.insert(n, d + 1);
+                        queue.push_back(n);
+                    }
+                }
+            }
+            let farthest = rooms
+                .iter()
+                .copied()
+                .filter(|r| !seeds.contains(r))
+                .max_by_key(|r| dist.get(r).copied().unwrap_or(usize::MAX))
+                .unwrap();
+            seeds.push(farthest);
+        }
+
+        let mut sizes = vec![(0, 0); seeds.len()];
+        for (part, &s) in seeds.iter().enumerate() {
+            assignment.insert(s, part);
+            sizes[part] = add_weight(sizes[part], self.weights[&s]);
+        }
+        // Repeatedly grow the smallest part that can still grow:
+        loop {
+            let mut frontier: Vec<Vec<RoomGeometryRoomIdx>> = vec![vec![]; seeds.len()];
+            for &r in rooms {
+                if let Some(&part) = assignment.get(&r) {
+                    for n in get_neighbors(r) {
+                        if !assignment.contains_key(&n) {
+                            frontier[part].push(n);
+                        }
+                    }
+                }
+            }
+            let Some(part) = (0..seeds.len())
+                .filter(|&p| !frontier[p].is_empty())
+                .min_by_key(|&p| sizes[p])
+            else {
+                break;
+            };
+            let &r = frontier[part].choose(rng).unwrap();
+            assignment.insert(r, part);
+            sizes[part] = add_weight(sizes[part], self.weights[&r]);
+        }
+        assignment
+    }
+
+    // Split connected groups into exactly `num_parts` parts, each with at least `min_rooms`
+    // groups (or as many as the number of groups allows).
+    fn split<R: Rng>(
+        &self,
+        rooms: &[RoomGeometryRoomIdx],
+        num_parts: usize,
+        min_rooms: usize,
+        rng: &mut R,
+    ) -> Result<HashMap<RoomGeometryRoomIdx, usize>> {
+        if rooms.len() < num_parts {
+            bail!(
+                "{} room(s) can't be split into {num_parts} parts",
+                rooms.len()
+            );
+        }
+        if !self.is_connected(rooms) {
+            bail!("Rooms to be split are not connected");
+        }
+        let min_rooms = min_rooms.min(rooms.len() / num_parts);
+        for _ in 0..SPLIT_ATTEMPTS {
+            let assignment = self.partition(rooms, num_parts, rng);
+            let mut counts = vec![0; num_parts];
+            for &part in assignment.values() {
+                counts[part] += 1;
+            }
+            if counts.iter().all(|&c| c >= min_rooms) {
+                return Ok(assignment);
+            }
+        }
+        bail!(
+            "Unable to split {} room(s) into {num_parts} parts",
+            rooms.len()
+        );
+    }
+}
+
+// Give every room in a merged group the area, subarea, and subsubarea of its representative.
+fn spread_to_groups(map: &mut Map, graph: &RoomGraph) {
+    for r in 0..map.room_mask.len() {
+        let rep = graph.rep[r];
+        map.area[r] = map.area[rep];
+        map.subarea[r] = map.subarea[rep];
+        map.subsubarea[r] = map.subsubarea[rep];
+    }
+}
+
+fn assign_subareas_with_graph<R: Rng>(
+    map: &mut Map,
+    game_data: &GameData,
+    graph: &RoomGraph,
+    rng: &mut R,
+) -> Result<()> {
+    let ship_rep = graph.rep[game_data.ship_room_idx];
+    for area in 0..NUM_AREAS {
+        let area_rooms = graph.groups(map, |r| map.area[r] == area);
+        if area_rooms.is_empty() {
+            continue;
+        }
+        if !graph.is_connected(&area_rooms) {
+            bail!("Area {area} is not contiguous");
+        }
+        let mut subareas = graph.split(&area_rooms, NUM_SUBAREAS, MIN_SUBAREA_ROOMS, rng)?;
+        if let Some(&ship_subarea) = subareas.get(&ship_rep) {
+            // Keep the ship in subarea 0:
+            for s in subareas.values_mut() {
+                *s = (*s + NUM_SUBAREAS - ship_subarea) % NUM_SUBAREAS;
+            }
+        }
+        for subarea in 0..NUM_SUBAREAS {
+            let subarea_rooms: Vec<_> = area_rooms
+                .iter()
+                .copied()
+                .filter(|r| subareas[r] == subarea)
+                .collect();
+            let mut subsubareas =
+                graph.split(&subarea_rooms, NUM_SUBSUBAREAS, MIN_SUBSUBAREA_ROOMS, rng)?;
+            if let Some(&ship_subsubarea) = subsubareas.get(&ship_rep) {
+                for s in subsubareas.values_mut() {
+                    *s = (*s + NUM_SUBSUBAREAS - ship_subsubarea) % NUM_SUBSUBAREAS;
+                }
+            }
+            for &r in &subarea_rooms {
+                map.subarea[r] = subarea;
+                map.subsubarea[r] = subsubareas[&r];
+            }
+        }
+    }
+    spread_to_groups(map, graph);
+    Ok(())
+}
+
+// Parse an area partition name, as given on the command line.
+pub fn parse_area_partition(s: &str) -> Result<AreaPartition> {
+    match s.to_lowercase().as_str() {
+        "map" => Ok(AreaPartition::Map),
+        "size" => Ok(AreaPartition::Size),
+        "items" => Ok(AreaPartition::Items),
+        _ => bail!("Unknown area partition: {s} (expected map, size, or items)"),
+    }
+}
+
+// Assign subareas and subsubareas within the map's existing areas, which must be contiguous.
+pub fn assign_subareas<R: Rng>(map: &mut Map, game_data: &GameData, rng: &mut R) -> Result<()> {
+    let graph = RoomGraph::new(map, game_data, AreaPartition::Size)?;
+    // The Toilet and the room(s) it passes through must already agree on their area:
+    for r in 0..map.room_mask.len() {
+        if map.room_mask[r] && map.area[r] != map.area[graph.rep[r]] {
+            bail!(
+                "{} is not in the same area as the room that the Toilet passes through",
+                game_data.room_geometry[r].name
+            );
+        }
+    }
+    assign_subareas_with_graph(map, game_data, &graph, rng)
+}
+
+// Assign areas (unless keeping the map's own areas), subareas, and subsubareas to the rooms
+// of a map.
+pub fn assign_areas<R: Rng>(
+    map: &mut Map,
+    game_data: &GameData,
+    partition: AreaPartition,
+    rng: &mut R,
+) -> Result<()> {
+    if partition == AreaPartition::Map {
+        return assign_subareas(map, game_data, rng);
+    }
+    let graph = RoomGraph::new(map, game_data, partition)?;
+    let rooms = graph.groups(map, |_| true);
+    let areas = graph.split(&rooms, NUM_AREAS, 1, rng)?;
+    for &r in &rooms {
+        map.area[r] = areas[&r];
+    }
+    spread_to_groups(map, &graph);
+    assign_subareas_with_graph(map, game_data, &graph, rng)
+}
+
+fn get_area_idx(game_data: &GameData, name: &str) -> Result<AreaIdx> {
+    game_data
+        .area_names
+        .iter()
+        .position(|x| x == name)
+        .with_context(|| format!("Unknown area: {name}"))
+}
+
+fn get_room_idx(game_data: &GameData, name: &str) -> Result<RoomGeometryRoomIdx> {
+    game_data
+        .room_geometry
+        .iter()
+        .position(|x| x.name == name)
+        .with_context(|| format!("Unknown room: {name}"))
+}
+
+// Check that the constraints only refer to known rooms and areas.
+pub fn validate_area_constraints(
+    constraints: &AreaConstraints,
+    game_data: &GameData,
+) -> Result<()> {
+    for pin in &constraints.pinned_rooms {
+        get_room_idx(game_data, &pin.room)?;
+        get_area_idx(game_data, &pin.area)?;
+    }
+    for sep in &constraints.separated_rooms {
+        get_room_idx(game_data, &sep.room)?;
+        get_area_idx(game_data, &sep.area)?;
+    }
+    for limit in &constraints.room_limits {
+        get_area_idx(game_data, &limit.area)?;
+    }
+    Ok(())
+}
+
+// Check that the area assignment can be used with the map layout, and that its constraints only
+// refer to known rooms and areas.
+pub fn validate_area_assignment(
+    area_assignment: &AreaAssignment,
+    map_layout: &str,
+    game_data: &GameData,
+) -> Result<()> {
+    // The vanilla areas are laid out separately on the pause map, so rooms regrouped into
+    // other areas would almost never fit together:
+    if map_layout == "Vanilla" && area_assignment.partition != AreaPartition::Map {
+        bail!("Areas can't be regrouped on the Vanilla map layout");
+    }
+    validate_area_constraints(&area_assignment.constraints, game_data)
+}
+
+fn get_permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![vec![]];
+    }
+    let mut out = vec![];
+    for p in get_permutations(n - 1) {
+        for i in 0..n {
+            let mut q = p.clone();
+            q.insert(i, n - 1);
+            out.push(q);
+        }
+    }
+    out
+}
+
+// Choose which area each of the map's areas becomes (as a mapping from the map's area to the
+// new area), satisfying the constraints along with the `fixed` room pins. Among the mappings
+// which do, the one which agrees with `preferred` on the most areas is chosen.
+pub fn solve_area_mapping(
+    map: &Map,
+    game_data: &GameData,
+    constraints: &AreaConstraints,
+    fixed: &[(RoomGeometryRoomIdx, AreaIdx)],
+    preferred: &[AreaIdx],
+) -> Result<Vec<AreaIdx>> {
+    // Constraints expressed in terms of the map's areas:
+    let mut pins: Vec<(AreaIdx, AreaIdx)> = vec![];
+    for &(room_idx, area) in fixed {
+        if map.room_mask[room_idx] {
+            pins.push((map.area[room_idx], area));
+        }
+    }
+    for pin in &constraints.pinned_rooms {
+        let room_idx = get_room_idx(game_data, &pin.room)?;
+        if map.room_mask[room_idx] {
+            pins.push((map.area[room_idx], get_area_idx(game_data, &pin.area)?));
+        }
+    }
+    let mut separations: Vec<(AreaIdx, AreaIdx)> = vec![];
+    for sep in &constraints.separated_rooms {
+        let room_idx = get_room_idx(game_data, &sep.room)?;
+        if map.room_mask[room_idx] {
+            separations.push((map.area[room_idx], get_area_idx(game_data, &sep.area)?));
+        }
+    }
+    let mut limits: Vec<(AreaIdx, usize, usize)> = vec![];
+    for limit in &constraints.room_limits {
+        limits.push((
+            get_area_idx(game_data, &limit.area)?,
+            limit.min_rooms.unwrap_or(0),
+            limit.max_rooms.unwrap_or(usize::MAX),
+        ));
+    }
+
+    let mut room_counts = [0; NUM_AREAS];
+    for room_idx in 0..map.room_mask.len() {
+        if map.room_mask[room_idx] {
+            room_counts[map.area[room_idx]] += 1;
+        }
+    }
+    let mut adjacent = [[false; NUM_AREAS]; NUM_AREAS];
+    for (src, dst, _) in &map.doors {
+        let (src_room, _) = *game_data
+            .room_and_door_idxs_by_door_ptr_pair
+            .get(src)
+            .context("Unknown door in map")?;
+        let (dst_room, _) = *game_data
+            .room_and_door_idxs_by_door_ptr_pair
+            .get(dst)
+            .context("Unknown door in map")?;
+        adjacent[map.area[src_room]][map.area[dst_room]] = true;
+        adjacent[map.area[dst_room]][map.area[src_room]] = true;
+    }
+
+    let is_valid = |mapping: &[AreaIdx]| {
+        let mut reverse_mapping = [0; NUM_AREAS];
+        for (i, &a) in mapping.iter().enumerate() {
+            reverse_mapping[a] = i;
+        }
+        pins.iter().all(|&(from, to)| mapping[from] == to)
+            && separations
+                .iter()
+                .all(|&(from, to)| mapping[from] != to && !adjacent[from][reverse_mapping[to]])
+            && limits.iter().all(|&(to, min, max)| {
+                let cnt = room_counts[reverse_mapping[to]];
+                cnt >= min && cnt <= max
+            })
+    };
+    if is_valid(preferred) {
+        return Ok(preferred.to_vec());
+    }
+    get_permutations(NUM_AREAS)
+        .into_iter()
+        .filter(|m| is_valid(m.as_slice()))
+        .min_by_key(|m| (0..NUM_AREAS).filter(|&i| m[i] != preferred[i]).count())
+        .context("No area assignment satisfies the constraints")
+}
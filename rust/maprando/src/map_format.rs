@@ -0,0 +1,438 @@
+// Conversion between the formats that maps are stored in:
+//
+// - Avro batches of `StoredMap` records, as used by map repositories. Rooms and doors are
+//   identified by sm-json-data room ID and room geometry door index.
+// - `Map` JSON, as taken by the CLI. Rooms are identified by room geometry index and doors by
+//   their door pointers.
+// - A text form for editing by hand, where rooms and doors are identified by room and node
+//   names from sm-json-data, e.g.:
+//
+//     room "Landing Site" x=23 y=0 area=0 subarea=0 subsubarea=0
+//     door "Landing Site" "Top Left Door" <-> "Gauntlet Entrance" "Right Door"
+//
+//   One-way connections use "->" in place of "<->". Lines starting with '#' are comments.
+
+use anyhow::{Context, Result, bail};
+use apache_avro::types::Value;
+use hashbrown::HashMap;
+use serde::Deserialize;
+
+use maprando_game::{
+    DoorPtrPair, GameData, Map, NodeId, RoomGeometryDoorIdx, RoomGeometryRoomIdx, RoomId,
+};
+
+const STORED_MAP_SCHEMA: &str = r#"{
+    "type": "record",
+    "name": "com.maprando.sm.map",
+    "fields": [
+        {"name": "room_id", "type": {"type": "array", "items": "int"}},
+        {"name": "room_x", "type": {"type": "array", "items": "int"}},
+        {"name": "room_y", "type": {"type": "array", "items": "int"}},
+        {"name": "room_area", "type": {"type": "array", "items": "int"}},
+        {"name": "room_subarea", "type": {"type": "array", "items": "int"}},
+        {"name": "room_subsubarea", "type": {"type": "array", "items": "int"}},
+        {"name": "conn_from_room_id", "type": {"type": "array", "items": "int"}},
+        {"name": "conn_from_door_id", "type": {"type": "array", "items": "int"}},
+        {"name": "conn_to_room_id", "type": {"type": "array", "items": "int"}},
+        {"name": "conn_to_door_id", "type": {"type": "array", "items": "int"}},
+        {"name": "conn_bidirectional", "type": {"type": "array", "items": "boolean"}}
+    ]
+}"#;
+
+#[derive(Deserialize, Default, Clone, Debug)]
+pub struct StoredMap {
+    pub room_id: Vec<RoomId>,
+    pub room_x: Vec<usize>,
+    pub room_y: Vec<usize>,
+    pub room_area: Vec<usize>,
+    pub room_subarea: Vec<usize>,
+    pub room_subsubarea: Vec<usize>,
+    pub conn_from_room_id: Vec<usize>,
+    pub conn_from_door_id: Vec<usize>,
+    pub conn_to_room_id: Vec<usize>,
+    pub conn_to_door_id: Vec<usize>,
+    pub conn_bidirectional: Vec<bool>,
+}
+
+impl StoredMap {
+    fn to_avro_value(&self) -> Value {
+        let int_array =
+            |v: &[usize]| Value::Array(v.iter().map(|&x| Value::Int(x as i32)).collect());
+        Value::Record(vec![
+            ("room_id".to_string(), int_array(&self.room_id)),
+            ("room_x".to_string(), int_array(&self.room_x)),
+            ("room_y".to_string(), int_array(&self.room_y)),
+            ("room_area".to_string(), int_array(&self.room_area)),
+            ("room_subarea".to_string(), int_array(&self.room_subarea)),
+            (
+                "room_subsubarea".to_string(),
+                int_array(&self.room_subsubarea),
+            ),
+            (
+                "conn_from_room_id".to_string(),
+                int_array(&self.conn_from_room_id),
+            ),
+            (
+                "conn_from_door_id".to_string(),
+                int_array(&self.conn_from_door_id),
+            ),
+            (
+                "conn_to_room_id".to_string(),
+                int_array(&self.conn_to_room_id),
+            ),
+            (
+                "conn_to_door_id".to_string(),
+                int_array(&self.conn_to_door_id),
+            ),
+            (
+                "conn_bidirectional".to_string(),
+                Value::Array(
+                    self.conn_bidirectional
+                        .iter()
+                        .map(|&x| Value::Boolean(x))
+                        .collect(),
+                ),
+            ),
+        ])
+    }
+}
+
+fn get_room_idx(game_data: &GameData, room_id: RoomId) -> Result<RoomGeometryRoomIdx> {
+    let room_ptr = game_data
+        .room_ptr_by_id
+        .get(&room_id)
+        .with_context(|| format!("Unknown room ID {room_id}"))?;
+    game_data
+        .room_idx_by_ptr
+        .get(room_ptr)
+        .copied()
+        .with_context(|| format!("No room geometry for room ID {room_id}"))
+}
+
+fn get_room_id(game_data: &GameData, room_idx: RoomGeometryRoomIdx) -> Result<RoomId> {
+    let room = &game_data.room_geometry[room_idx];
+    game_data
+        .room_id_by_ptr
+        .get(&room.rom_address)
+        .copied()
+        .with_context(|| format!("No room ID for {}", room.name))
+}
+
+fn get_door_ptr_pair(
+    game_data: &GameData,
+    room_id: RoomId,
+    door_idx: RoomGeometryDoorIdx,
+) -> Result<DoorPtrPair> {
+    let room_idx = get_room_idx(game_data, room_id)?;
+    let room = &game_data.room_geometry[room_idx];
+    let door = room
+        .doors
+        .get(door_idx)
+        .with_context(|| format!("{} has no door {door_idx}", room.name))?;
+    Ok((door.exit_ptr, door.entrance_ptr))
+}
+
+fn get_door_location(
+    game_data: &GameData,
+    door_ptr_pair: DoorPtrPair,
+) -> Result<(RoomId, RoomGeometryDoorIdx)> {
+    for (room_idx, room) in game_data.room_geometry.iter().enumerate() {
+        for (door_idx, door) in room.doors.iter().enumerate() {
+            if (door.exit_ptr, door.entrance_ptr) == door_ptr_pair {
+                return Ok((get_room_id(game_data, room_idx)?, door_idx));
+            }
+        }
+    }
+    bail!("Unknown door {door_ptr_pair:?}");
+}
+
+pub fn stored_map_to_map(stored_map: &StoredMap, game_data: &GameData) -> Result<Map> {
+    let num_rooms = game_data.room_geometry.len();
+    let mut map = Map {
+        room_mask: vec![false; num_rooms],
+        rooms: vec![(0, 0); num_rooms],
+        doors: vec![],
+        area: vec![0; num_rooms],
+        subarea: vec![0; num_rooms],
+        subsubarea: vec![0; num_rooms],
+    };
+    for i in 0..stored_map.room_id.len() {
+        let room_idx = get_room_idx(game_data, stored_map.room_id[i])?;
+        map.room_mask[room_idx] = true;
+        map.rooms[room_idx] = (stored_map.room_x[i], stored_map.room_y[i]);
+        map.area[room_idx] = stored_map.room_area[i];
+        map.subarea[room_idx] = stored_map.room_subarea[i];
+        map.subsubarea[room_idx] = stored_map.room_subsubarea[i];
+    }
+    for i in 0..stored_map.conn_from_door_id.len() {
+        let src = get_door_ptr_pair(
+            game_data,
+            stored_map.conn_from_room_id[i],
+            stored_map.conn_from_door_id[i],
+        )?;
+        let dst = get_door_ptr_pair(
+            game_data,
+            stored_map.conn_to_room_id[i],
+            stored_map.conn_to_door_id[i],
+        )?;
+        map.doors.push((src, dst, stored_map.conn_bidirectional[i]));
+    }
+    Ok(map)
+}
+
+pub fn map_to_stored_map(map: &Map, game_data: &GameData) -> Result<StoredMap> {
+    let mut stored_map = StoredMap::default();
+    for room_idx in 0..game_data.room_geometry.len() {
+        if !map.room_mask[room_idx] {
+            continue;
+        }
+        stored_map.room_id.push(get_room_id(game_data, room_idx)?);
+        stored_map.room_x.push(map.rooms[room_idx].0);
+        stored_map.room_y.push(map.rooms[room_idx].1);
+        stored_map.room_area.push(map.area[room_idx]);
+        stored_map.room_subarea.push(map.subarea[room_idx]);
+        stored_map
+            .room_subsubarea
+            .push(map.subsubarea.get(room_idx).copied().unwrap_or(0));
+    }
+    for &(src, dst, bidirectional) in &map.doors {
+        let (src_room_id, src_door_idx) = get_door_location(game_data, src)?;
+        let (dst_room_id, dst_door_idx) = get_door_location(game_data, dst)?;
+        stored_map.conn_from_room_id.push(src_room_id);
+        stored_map.conn_from_door_id.push(src_door_idx);
+        stored_map.conn_to_room_id.push(dst_room_id);
+        stored_map.conn_to_door_id.push(dst_door_idx);
+        stored_map.conn_bidirectional.push(bidirectional);
+    }
+    Ok(stored_map)
+}
+
+// Read all maps in an Avro batch, in the order they are stored.
+pub fn read_avro_maps(data: &[u8], game_data: &GameData) -> Result<Vec<Map>> {
+    let avro_reader = apache_avro::Reader::new(data)?;
+    let mut out = vec![];
+    for value in avro_reader {
+        let stored_map: StoredMap = apache_avro::from_value(&value?)?;
+        out.push(stored_map_to_map(&stored_map, game_data)?);
+    }
+    Ok(out)
+}
+
+pub fn write_avro_maps(maps: &[Map], game_data: &GameData) -> Result<Vec<u8>> {
+    let schema = apache_avro::Schema::parse_str(STORED_MAP_SCHEMA)?;
+    let mut writer = apache_avro::Writer::new(&schema, Vec::new());
+    for map in maps {
+        writer.append(map_to_stored_map(map, game_data)?.to_avro_value())?;
+    }
+    Ok(writer.into_inner()?)
+}
+
+fn get_room_name(game_data: &GameData, room_id: RoomId) -> Result<&str> {
+    game_data
+        .room_json_map
+        .get(&room_id)
+        .and_then(|x| x["name"].as_str())
+        .with_context(|| format!("Unknown room ID {room_id}"))
+}
+
+fn get_node_name(game_data: &GameData, room_id: RoomId, node_id: NodeId) -> Result<&str> {
+    game_data
+        .node_json_map
+        .get(&(room_id, node_id))
+        .and_then(|x| x["name"].as_str())
+        .with_context(|| format!("Unknown node {node_id} in room ID {room_id}"))
+}
+
+pub fn map_to_text(map: &Map, game_data: &GameData) -> Result<String> {
+    let mut out = String::new();
+    out.push_str("# Rooms: name, position of top-left corner, area, subarea, subsubarea\n");
+    for (room_idx, room) in game_data.room_geometry.iter().enumerate() {
+        if !map.room_mask[room_idx] {
+            continue;
+        }
+        let (x, y) = map.rooms[room_idx];
+        out.push_str(&format!(
+            "room \"{}\" x={x} y={y} area={} subarea={} subsubarea={}\n",
+            room.name,
+            map.area[room_idx],
+            map.subarea[room_idx],
+            map.subsubarea.get(room_idx).copied().unwrap_or(0)
+        ));
+    }
+    out.push_str("\n# Doors: room and node names, \"<->\" for two-way or \"->\" for one-way\n");
+    for &(src, dst, bidirectional) in &map.doors {
+        let mut names = vec![];
+        for ptr_pair in [src, dst] {
+            let &(room_id, node_id) = game_data
+                .door_ptr_pair_map
+                .get(&ptr_pair)
+                .with_context(|| format!("Unknown door {ptr_pair:?}"))?;
+            names.push((
+                get_room_name(game_data, room_id)?,
+                get_node_name(game_data, room_id, node_id)?,
+            ));
+        }
+        let arrow = if bidirectional { "<->" } else { "->" };
+        out.push_str(&format!(
+            "door \"{}\" \"{}\" {arrow} \"{}\" \"{}\"\n",
+            names[0].0, names[0].1, names[1].0, names[1].1
+        ));
+    }
+    Ok(out)
+}
+
+// Split a line into whitespace-separated tokens, where double-quoted strings are single tokens.
+fn tokenize(line: &str) -> Result<Vec<String>> {
+    let mut out = vec![];
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut token = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(c) => token.push(c),
+                    None => bail!("Unterminated quoted string"),
+                }
+            }
+            out.push(token);
+        } else {
+            let mut token = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                token.push(c);
+                chars.next();
+            }
+            out.push(token);
+        }
+    }
+    Ok(out)
+}
+
+struct TextParser<'a> {
+    game_data: &'a GameData,
+    room_idx_by_name: HashMap<&'a str, RoomGeometryRoomIdx>,
+    room_id_by_name: HashMap<&'a str, RoomId>,
+    node_id_by_name: HashMap<(RoomId, &'a str), NodeId>,
+}
+
+impl<'a> TextParser<'a> {
+    fn new(game_data: &'a GameData) -> Self {
+        let mut room_id_by_name = HashMap::new();
+        for (&room_id, room_json) in &game_data.room_json_map {
+            if let Some(name) = room_json["name"].as_str() {
+                room_id_by_name.insert(name, room_id);
+            }
+        }
+        let mut node_id_by_name = HashMap::new();
+        for (&(room_id, node_id), node_json) in &game_data.node_json_map {
+            if let Some(name) = node_json["name"].as_str() {
+                node_id_by_name.insert((room_id, name), node_id);
+            }
+        }
+        TextParser {
+            game_data,
+            room_idx_by_name: game_data
+                .room_geometry
+                .iter()
+                .enumerate()
+                .map(|(i, room)| (room.name.as_str(), i))
+                .collect(),
+            room_id_by_name,
+            node_id_by_name,
+        }
+    }
+
+    fn parse_room(&self, tokens: &[String], map: &mut Map) -> Result<()> {
+        let Some(name) = tokens.first() else {
+            bail!("Missing room name");
+        };
+        let &room_idx = self
+            .room_idx_by_name
+            .get(name.as_str())
+            .with_context(|| format!("Unknown room \"{name}\""))?;
+        if map.room_mask[room_idx] {
+            bail!("Room \"{name}\" is listed more than once");
+        }
+        map.room_mask[room_idx] = true;
+        for token in &tokens[1..] {
+            let Some((key, value)) = token.split_once('=') else {
+                bail!("Expected key=value, found \"{token}\"");
+            };
+            let value: usize = value
+                .parse()
+                .with_context(|| format!("Invalid value for {key}: \"{value}\""))?;
+            match key {
+                "x" => map.rooms[room_idx].0 = value,
+                "y" => map.rooms[room_idx].1 = value,
+                "area" => map.area[room_idx] = value,
+                "subarea" => map.subarea[room_idx] = value,
+                "subsubarea" => map.subsubarea[room_idx] = value,
+                _ => bail!("Unknown room property \"{key}\""),
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_door_ptr_pair(&self, room_name: &str, node_name: &str) -> Result<DoorPtrPair> {
+        let &room_id = self
+            .room_id_by_name
+            .get(room_name)
+            .with_context(|| format!("Unknown room \"{room_name}\""))?;
+        let &node_id = self
+            .node_id_by_name
+            .get(&(room_id, node_name))
+            .with_context(|| format!("Unknown node \"{node_name}\" in \"{room_name}\""))?;
+        self.game_data
+            .reverse_door_ptr_pair_map
+            .get(&(room_id, node_id))
+            .copied()
+            .with_context(|| format!("\"{node_name}\" in \"{room_name}\" is not a door"))
+    }
+
+    fn parse_door(&self, tokens: &[String], map: &mut Map) -> Result<()> {
+        let [src_room, src_node, arrow, dst_room, dst_node] = tokens else {
+            bail!("Expected: door \"<room>\" \"<node>\" <-> \"<room>\" \"<node>\"");
+        };
+        let bidirectional = match arrow.as_str() {
+            "<->" => true,
+            "->" => false,
+            _ => bail!("Expected \"<->\" or \"->\", found \"{arrow}\""),
+        };
+        let src = self.parse_door_ptr_pair(src_room, src_node)?;
+        let dst = self.parse_door_ptr_pair(dst_room, dst_node)?;
+        map.doors.push((src, dst, bidirectional));
+        Ok(())
+    }
+}
+
+pub fn map_from_text(text: &str, game_data: &GameData) -> Result<Map> {
+    let parser = TextParser::new(game_data);
+    let num_rooms = game_data.room_geometry.len();
+    let mut map = Map {
+        room_mask: vec![false; num_rooms],
+        rooms: vec![(0, 0); num_rooms],
+        doors: vec![],
+        area: vec![0; num_rooms],
+        subarea: vec![0; num_rooms],
+        subsubarea: vec![0; num_rooms],
+    };
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let result = tokenize(line).and_then(|tokens| match tokens[0].as_str() {
+            "room" => parser.parse_room(&tokens[1..], &mut map),
+            "door" => parser.parse_door(&tokens[1..], &mut map),
+            x => bail!("Unknown line type \"{x}\""),
+        });
+        result.with_context(|| format!("Line {}: {line}", i + 1))?;
+    }
+    Ok(map)
+}
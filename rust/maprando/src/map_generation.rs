@@ -0,0 +1,756 @@
+// Generation of map layouts directly from the room geometry, as an alternative to the
+// pre-generated map repositories.
+//
+// The layout is grown one room at a time: an open door is chosen (the one with the fewest ways
+// to connect it, so that hard-to-match doors are dealt with first), and a room is placed so that
+// one of its doors connects to it. A placement is only allowed if the room doesn't overlap
+// anything, and every door on its boundary lines up with a matching door (same direction type
+// and subtype: normal, elevator, or sand) or with empty space. When no room fits an open door,
+// the most recent placements are undone and the search continues. Rooms joined by sand are kept
+// in their vanilla arrangement, since their sand doors only fit each other.
+//
+// Once every door is connected, areas, subareas, and subsubareas are assigned (see `map_areas`),
+// and the result is checked with the map validator.
+
+use anyhow::{Result, bail};
+use hashbrown::{HashMap, HashSet};
+use log::info;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::{Rng, seq::SliceRandom};
+
+use crate::map_areas::assign_areas;
+use crate::map_validation::{MAP_GRID_SIZE, get_map_issues};
+use crate::settings::AreaPartition;
+use maprando_game::{DoorPtrPair, GameData, Map, RoomGeometryDoorIdx, RoomGeometryRoomIdx, RoomId};
+
+// Rooms (by room ID) which are allowed to intersect the Toilet, with the columns (relative to the
+// room) where they may do so. The Toilet must pass through exactly one of these rooms, which must
+// be within its middle six tiles.
+const TOILET_ROOMS: &[(RoomId, &[i32])] = &[
+    (136, &[0, 1, 2, 3]),         // Amphitheatre
+    (183, &[2, 4, 5]),            // Aqueduct
+    (70, &[1]),                   // Below Spazer
+    (77, &[0]),                   // Beta Power Bomb Room
+    (161, &[2, 3, 4]),            // Bowling Alley
+    (97, &[1]),                   // Bubble Mountain
+    (100, &[0]),                  // Bat Cave
+    (90, &[1, 2]),                // Cathedral Entrance
+    (91, &[0, 1, 2]),             // Cathedral
+    (39, &[0]),                   // Construction Zone
+    (201, &[0]),                  // Crab Hole
+    (121, &[1]),                  // Crocomire Escape
+    (111, &[0]),                  // Crumble Shaft
+    (104, &[0, 1, 2, 3]),         // Double Chamber
+    (7, &[0, 1]),                 // The Moat
+    (192, &[0]),                  // The Precious Room
+    (45, &[0, 1, 2]),             // Early Supers Room
+    (34, &[0, 1, 2, 3, 4, 5, 6]), // East Ocean
+    (166, &[0]),                  // Electric Death Room
+    (51, &[0]),                   // Etecoon Energy Tank Room
+    (133, &[0]),                  // Fast Pillars Setup Room
+    (173, &[1]),                  // Fish Tank
+    (128, &[0]),                  // Grapple Beam Room
+    (130, &[0]),                  // Grapple Tutorial Room 2
+    (108, &[0]),                  // Ice Beam Snake Room
+    (217, &[0]),                  // Kassiuz Room
+    (83, &[0]),                   // Kraid Eye Door Room
+    (113, &[1]),                  // Kronic Boost Room
+    (8, &[3, 5]),                 // Landing Site
+    (115, &[1]),                  // Lava Dive Room
+    (146, &[1]),                  // Lower Norfair Spring Ball Maze Room
+    (178, &[1, 2]),               // Mama Turtle Room
+    (227, &[0]),                  // Metroid Room 2
+    (144, &[3]),                  // Mickey Mouse Room
+    (174, &[3, 5]),               // Mt. Everest
+    (180, &[0]),                  // Northwest Maridia Bug Room
+    (220, &[0, 1]),               // Pants Room
+    (10, &[3]),                   // Parlor And Alcatraz
+    (61, &[0, 1]),                // Pink Brinstar Hopper Room
+    (60, &[0]),                   // Pink Brinstar Power Bomb Room
+    (219, &[0, 1]),               // Plasma Room
+    (197, &[1, 2]),               // Plasma Spark Room
+    (123, &[1]),                  // Post Crocomire Farming Room
+    (127, &[0, 1, 2, 3]),         // Post Crocomire Jump Room
+    (179, &[1, 2]),               // Pseudo Plasma Spark Room
+    (137, &[0]),                  // Red Kihunter Shaft
+    (236, &[0]),                  // Rinka Shaft
+    (151, &[0]),                  // Screw Attack Room
+    (233, &[0]),                  // Seaweed Room
+    (103, &[0]),                  // Single Chamber
+    (135, &[0]),                  // The Worst Room In The Game
+    (148, &[1]),                  // Three Musketeers' Room
+    (241, &[0, 1, 2, 3, 4, 5]),   // Tourian Escape Room 3
+    (242, &[0, 1, 2]),            // Tourian Escape Room 4
+    (116, &[2]),                  // Volcano Room
+    (138, &[1]),                  // Wasteland
+    (78, &[1]),                   // Warehouse Entrance
+    (181, &[0]),                  // Watering Hole
+    (195, &[0]),                  // West Cactus Alley Room
+    (32, &[0, 1, 2, 5]),          // West Ocean
+    (167, &[1, 2]),               // Wrecked Ship Energy Tank Room
+];
+
+// Rows of the Toilet which other rooms may pass through.
+const TOILET_OPEN_ROWS: std::ops::Range<i32> = 2..8;
+
+// Number of area assignments to try for each layout before giving up on it.
+const AREA_ATTEMPTS: usize = 20;
+
+pub struct MapGeneratorSettings {
+    // Rooms to include, indexed by room geometry index. Rooms joined by sand are included or
+    // excluded together.
+    pub room_mask: Vec<bool>,
+    // Number of room placements (including undone ones) to try for each layout.
+    pub max_steps: usize,
+    // Number of layouts to try before giving up.
+    pub max_attempts: usize,
+    // How to group the rooms into areas (`Map` is treated like `Size`, as layouts have no
+    // areas of their own).
+    pub area_partition: AreaPartition,
+}
+
+impl MapGeneratorSettings {
+    pub fn new(game_data: &GameData) -> Self {
+        MapGeneratorSettings {
+            room_mask: vec![true; game_data.room_geometry.len()],
+            max_steps: 100000,
+            max_attempts: 20,
+            area_partition: AreaPartition::Size,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    fn parse(s: &str) -> Result<Self> {
+        Ok(match s {
+            "left" => Direction::Left,
+            "right" => Direction::Right,
+            "up" => Direction::Up,
+            "down" => Direction::Down,
+            _ => bail!("Unrecognized door direction: {s}"),
+        })
+    }
+
+    fn offset(self) -> (i32, i32) {
+        match self {
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+        }
+    }
+}
+
+type Tile = (i32, i32);
+
+fn step(tile: Tile, direction: Direction) -> Tile {
+    let (dx, dy) = direction.offset();
+    (tile.0 + dx, tile.1 + dy)
+}
+
+struct UnitDoor {
+    x: i32,
+    y: i32,
+    direction: Direction,
+    subtype: String,
+    room_idx: RoomGeometryRoomIdx,
+    door_idx: RoomGeometryDoorIdx,
+}
+
+// A group of rooms which are placed together: usually a single room, or rooms joined by sand.
+struct Unit {
+    // Rooms in the unit, with their position relative to the unit.
+    rooms: Vec<(RoomGeometryRoomIdx, i32, i32)>,
+    // Tiles which other rooms can't overlap.
+    tiles: Vec<Tile>,
+    // Tiles which one other room may pass through (the middle of the Toilet).
+    open_tiles: Vec<Tile>,
+    width: i32,
+    height: i32,
+    // Doors which connect outside the unit.
+    doors: Vec<UnitDoor>,
+    // Connections between doors within the unit, kept as in the vanilla map.
+    internal_connections: Vec<(DoorPtrPair, DoorPtrPair, bool)>,
+}
+
+impl Unit {
+    fn door_at(&self, tile: Tile, direction: Direction) -> Option<&UnitDoor> {
+        self.doors
+            .iter()
+            .find(|d| (d.x, d.y) == tile && d.direction == direction)
+    }
+
+    fn room_at(&self, tile: Tile, game_data: &GameData) -> Option<(RoomGeometryRoomIdx, Tile)> {
+        for &(room_idx, rx, ry) in &self.rooms {
+            let room_map = &game_data.room_geometry[room_idx].map;
+            let (x, y) = (tile.0 - rx, tile.1 - ry);
+            if y >= 0
+                && (y as usize) < room_map.len()
+                && x >= 0
+                && (x as usize) < room_map[0].len()
+                && room_map[y as usize][x as usize] == 1
+            {
+                return Some((room_idx, (x, y)));
+            }
+        }
+        None
+    }
+}
+
+fn get_units(game_data: &GameData, room_mask: &[bool]) -> Result<Vec<Unit>> {
+    let vanilla_map: Map = serde_json::from_slice(
+        &game_data
+            .data_source()
+            .read("maps/vanilla/vanilla_map.json")?,
+    )?;
+    let mut door_map: HashMap<DoorPtrPair, RoomGeometryRoomIdx> = HashMap::new();
+    for (room_idx, room) in game_data.room_geometry.iter().enumerate() {
+        for door in &room.doors {
+            door_map.insert((door.exit_ptr, door.entrance_ptr), room_idx);
+        }
+    }
+
+    // Group rooms which are joined by sand in the vanilla map:
+    let num_rooms = game_data.room_geometry.len();
+    let mut group: Vec<usize> = (0..num_rooms).collect();
+    let mut sand_connections: Vec<(DoorPtrPair, DoorPtrPair, bool)> = vec![];
+    for &(src, dst, bidirectional) in &vanilla_map.doors {
+        let src_room_idx = door_map[&src];
+        let dst_room_idx = door_map[&dst];
+        let is_sand = game_data.room_geometry[src_room_idx]
+            .doors
+            .iter()
+            .any(|d| (d.exit_ptr, d.entrance_ptr) == src && d.subtype == "sand");
+        if is_sand {
+            let (a, b) = (group[src_room_idx], group[dst_room_idx]);
+            for g in group.iter_mut() {
+                if *g == b {
+                    *g = a;
+                }
+            }
+            sand_connections.push((src, dst, bidirectional));
+        }
+    }
+
+    let mut units = vec![];
+    for group_idx in 0..num_rooms {
+        let members: Vec<RoomGeometryRoomIdx> =
+            (0..num_rooms).filter(|&i| group[i] == group_idx).collect();
+        if members.is_empty() || !members.iter().any(|&i| room_mask[i]) {
+            continue;
+        }
+        let min_x = members
+            .iter()
+            .map(|&i| vanilla_map.rooms[i].0)
+            .min()
+            .unwrap();
+        let min_y = members
+            .iter()
+            .map(|&i| vanilla_map.rooms[i].1)
+            .min()
+            .unwrap();
+        let mut unit = Unit {
+            rooms: vec![],
+            tiles: vec![],
+            open_tiles: vec![],
+            width: 0,
+            height: 0,
+            doors: vec![],
+            internal_connections: vec![],
+        };
+        for &room_idx in &members {
+            let room = &game_data.room_geometry[room_idx];
+            let rx = (vanilla_map.rooms[room_idx].0 - min_x) as i32;
+            let ry = (vanilla_map.rooms[room_idx].1 - min_y) as i32;
+            unit.rooms.push((room_idx, rx, ry));
+            for (y, row) in room.map.iter().enumerate() {
+                for (x, &t) in row.iter().enumerate() {
+                    let tile = (rx + x as i32, ry + y as i32);
+                    if t != 1 {
+                        continue;
+                    }
+                    if room_idx == game_data.toilet_room_idx
+                        && TOILET_OPEN_ROWS.contains(&(y as i32))
+                    {
+                        unit.open_tiles.push(tile);
+                    } else {
+                        unit.tiles.push(tile);
+                    }
+                    unit.width = unit.width.max(tile.0 + 1);
+                    unit.height = unit.height.max(tile.1 + 1);
+                }
+            }
+            for (door_idx, door) in room.doors.iter().enumerate() {
+                if door.subtype == "sand" {
+                    continue;
+                }
+                unit.doors.push(UnitDoor {
+                    x: rx + door.x as i32,
+                    y: ry + door.y as i32,
+                    direction: Direction::parse(&door.direction)?,
+                    subtype: door.subtype.clone(),
+                    room_idx,
+                    door_idx,
+                });
+            }
+        }
+        unit.internal_connections = sand_connections
+            .iter()
+            .filter(|(src, _, _)| members.contains(&door_map[src]))
+            .copied()
+            .collect();
+        units.push(unit);
+    }
+    Ok(units)
+}
+
+struct OpenDoor {
+    subtype: String,
+}
+
+// Key for an open door: the tile that it leads into, and its direction.
+type OpenDoorKey = (Tile, Direction);
+
+enum DoorChange {
+    Opened(OpenDoorKey),
+    Closed(OpenDoorKey, OpenDoor),
+}
+
+struct Placement {
+    unit_idx: usize,
+    door_changes: Vec<DoorChange>,
+}
+
+struct Layout<'a> {
+    game_data: &'a GameData,
+    units: &'a [Unit],
+    toilet_unit_idx: Option<usize>,
+    toilet_columns: HashMap<RoomGeometryRoomIdx, &'static [i32]>,
+    positions: Vec<Option<Tile>>,
+    occupied: HashMap<Tile, usize>,
+    open_doors: HashMap<OpenDoorKey, OpenDoor>,
+    history: Vec<Placement>,
+}
+
+impl<'a> Layout<'a> {
+    fn new(game_data: &'a GameData, units: &'a [Unit]) -> Self {
+        let mut toilet_columns = HashMap::new();
+        for &(room_id, columns) in TOILET_ROOMS {
+            if let Some(&room_idx) = game_data.room_idx_by_id.get(&room_id) {
+                toilet_columns.insert(room_idx, columns);
+            }
+        }
+        Layout {
+            game_data,
+            units,
+            toilet_unit_idx: units.iter().position(|u| !u.open_tiles.is_empty()),
+            toilet_columns,
+            positions: vec![None; units.len()],
+            occupied: HashMap::new(),
+            open_doors: HashMap::new(),
+            history: vec![],
+        }
+    }
+
+    fn toilet_open_tiles(&self) -> Vec<Tile> {
+        let Some(toilet_idx) = self.toilet_unit_idx else {
+            return vec![];
+        };
+        let Some((px, py)) = self.positions[toilet_idx] else {
+            return vec![];
+        };
+        self.units[toilet_idx]
+            .open_tiles
+            .iter()
+            .map(|&(x, y)| (px + x, py + y))
+            .collect()
+    }
+
+    // Check whether the unit at the given position may pass through the Toilet at the given
+    // (absolute) tile.
+    fn can_intersect_toilet(&self, unit_idx: usize, pos: Tile, tile: Tile) -> bool {
+        let unit = &self.units[unit_idx];
+        let rel_tile = (tile.0 - pos.0, tile.1 - pos.1);
+        match unit.room_at(rel_tile, self.game_data) {
+            Some((room_idx, (x, _))) => self
+                .toilet_columns
+                .get(&room_idx)
+                .is_some_and(|columns| columns.contains(&x)),
+            None => true,
+        }
+    }
+
+    // Check whether a unit can be placed at a given position, returning the number of doors
+    // it would connect.
+    fn check_placement(&self, unit_idx: usize, pos: Tile) -> Option<usize> {
+        let unit = &self.units[unit_idx];
+        let grid_size = MAP_GRID_SIZE as i32;
+        if pos.0 < 0
+            || pos.1 < 0
+            || pos.0 + unit.width > grid_size
+            || pos.1 + unit.height > grid_size
+        {
+            return None;
+        }
+        let tiles: HashSet<Tile> = unit
+            .tiles
+            .iter()
+            .map(|&(x, y)| (pos.0 + x, pos.1 + y))
+            .collect();
+        if tiles.iter().any(|t| self.occupied.contains_key(t)) {
+            return None;
+        }
+
+        // Passing through the Toilet:
+        if Some(unit_idx) == self.toilet_unit_idx {
+            let mut intersecting: Option<usize> = None;
+            for &(x, y) in &unit.open_tiles {
+                let tile = (pos.0 + x, pos.1 + y);
+                if let Some(&other_idx) = self.occupied.get(&tile) {
+                    if intersecting.is_some_and(|i| i != other_idx)
+                        || !self.can_intersect_toilet(
+                            other_idx,
+                            self.positions[other_idx].unwrap(),
+                            tile,
+                        )
+                    {
+                        return None;
+                    }
+                    intersecting = Some(other_idx);
+                }
+            }
+            // The Toilet is only placed through a room, rather than waiting for a room to be
+            // placed through it later:
+            intersecting?;
+        } else {
+            // Once the Toilet is placed, it already passes through a room, so nothing else can:
+            if self
+                .toilet_open_tiles()
+                .iter()
+                .any(|tile| tiles.contains(tile))
+            {
+                return None;
+            }
+        }
+
+        // Doors of the unit must lead to empty space or a matching open door:
+        let mut num_connections = 0;
+        for door in &unit.doors {
+            let tile = (pos.0 + door.x, pos.1 + door.y);
+            let neighbor = step(tile, door.direction);
+            let matching = self.open_doors.get(&(tile, door.direction.opposite()));
+            if let Some(open_door) = matching {
+                if open_door.subtype != door.subtype {
+                    return None;
+                }
+                num_connections += 1;
+            } else if self.occupied.contains_key(&neighbor) {
+                return None;
+            }
+        }
+
+        // Open doors leading into the unit must match a door of the unit:
+        for &tile in &tiles {
+            for direction in [
+                Direction::Left,
+                Direction::Right,
+                Direction::Up,
+                Direction::Down,
+            ] {
+                if let Some(open_door) = self.open_doors.get(&(tile, direction)) {
+                    let rel_tile = (tile.0 - pos.0, tile.1 - pos.1);
+                    match unit.door_at(rel_tile, direction.opposite()) {
+                        Some(door) if door.subtype == open_door.subtype => {}
+                        _ => return None,
+                    }
+                }
+            }
+        }
+        Some(num_connections)
+    }
+
+    fn place(&mut self, unit_idx: usize, pos: Tile) {
+        let unit = &self.units[unit_idx];
+        self.positions[unit_idx] = Some(pos);
+        for &(x, y) in &unit.tiles {
+            self.occupied.insert((pos.0 + x, pos.1 + y), unit_idx);
+        }
+        let mut door_changes = vec![];
+        for door in &unit.doors {
+            let tile = (pos.0 + door.x, pos.1 + door.y);
+            let key = (tile, door.direction.opposite());
+            if let Some(open_door) = self.open_doors.remove(&key) {
+                door_changes.push(DoorChange::Closed(key, open_door));
+            } else {
+                let key = (step(tile, door.direction), door.direction);
+                self.open_doors.insert(
+                    key,
+                    OpenDoor {
+                        subtype: door.subtype.clone(),
+                    },
+                );
+                door_changes.push(DoorChange::Opened(key));
+            }
+        }
+
+        self.history.push(Placement {
+            unit_idx,
+            door_changes,
+        });
+    }
+
+    fn undo(&mut self) {
+        let placement = self.history.pop().unwrap();
+        let unit = &self.units[placement.unit_idx];
+        let pos = self.positions[placement.unit_idx].take().unwrap();
+        for &(x, y) in &unit.tiles {
+            self.occupied.remove(&(pos.0 + x, pos.1 + y));
+        }
+        for change in placement.door_changes.into_iter().rev() {
+            match change {
+                DoorChange::Opened(key) => {
+                    self.open_doors.remove(&key);
+                }
+                DoorChange::Closed(key, open_door) => {
+                    self.open_doors.insert(key, open_door);
+                }
+            }
+        }
+    }
+
+    // Possible placements connecting to an open door, as (unit index, position, number of
+    // doors connected).
+    fn get_candidates(
+        &self,
+        key: OpenDoorKey,
+        doors_by_type: &HashMap<(Direction, &str), Vec<(usize, usize)>>,
+    ) -> Vec<(usize, Tile, usize)> {
+        let (tile, direction) = key;
+        let subtype = self.open_doors[&key].subtype.as_str();
+        let mut out = vec![];
+        for &(unit_idx, door_idx) in doors_by_type
+            .get(&(direction.opposite(), subtype))
+            .into_iter()
+            .flatten()
+        {
+            if self.positions[unit_idx].is_some() {
+                continue;
+            }
+            let door = &self.units[unit_idx].doors[door_idx];
+            let pos = (tile.0 - door.x, tile.1 - door.y);
+            if let Some(num_connections) = self.check_placement(unit_idx, pos) {
+                out.push((unit_idx, pos, num_connections));
+            }
+        }
+        out
+    }
+
+    fn candidate_weight(&self, unit_idx: usize, pos: Tile, num_connections: usize) -> f64 {
+        let unit = &self.units[unit_idx];
+        let num_doors = unit.doors.len();
+        // Place large rooms with many doors early, keeping small dead-end rooms for filling
+        // in the remaining open doors at the end:
+        let size = num_doors as f64 + unit.tiles.len() as f64 / 4.0;
+        // Keep the map compact:
+        let center = MAP_GRID_SIZE as f64 / 2.0;
+        let dist = (pos.0 as f64 + unit.width as f64 / 2.0 - center).abs()
+            + (pos.1 as f64 + unit.height as f64 / 2.0 - center).abs();
+        // Prefer closing loops, and avoid adding open doors when there are already many:
+        let mut weight =
+            8.0f64.powi(num_connections as i32 - 1) * size * size / (1.0 + dist).powi(2);
+        if self.open_doors.len() > 12 {
+            let added = num_doors.saturating_sub(2 * num_connections);
+            weight *= 0.3f64.powi(added as i32);
+        }
+        // The Toilet rarely fits, so take the chance when it does:
+        if Some(unit_idx) == self.toilet_unit_idx {
+            weight *= 1000.0;
+        }
+        weight
+    }
+
+    // Search for a layout with every unit placed and every door connected.
+    fn search<R: Rng>(&mut self, max_steps: usize, rng: &mut R) -> bool {
+        let units = self.units;
+        let mut doors_by_type: HashMap<(Direction, &str), Vec<(usize, usize)>> = HashMap::new();
+        for (unit_idx, unit) in units.iter().enumerate() {
+            for (door_idx, door) in unit.doors.iter().enumerate() {
+                doors_by_type
+                    .entry((door.direction, door.subtype.as_str()))
+                    .or_default()
+                    .push((unit_idx, door_idx));
+            }
+        }
+
+        let start_candidates: Vec<usize> = (0..units.len())
+            .filter(|&i| Some(i) != self.toilet_unit_idx)
+            .collect();
+        let Some(&start_idx) = start_candidates.choose(rng) else {
+            return false;
+        };
+        let start_unit = &units[start_idx];
+        let center = MAP_GRID_SIZE as i32 / 2;
+        self.place(
+            start_idx,
+            (
+                center - start_unit.width / 2,
+                center - start_unit.height / 2,
+            ),
+        );
+
+        let mut num_failures = 0;
+        for _ in 0..max_steps {
+            if self.open_doors.is_empty() {
+                if self.history.len() == units.len() {
+                    return true;
+                }
+                // The layout closed up without using every room, so back up:
+                let n = rng.gen_range(1..=10).min(self.history.len() - 1);
+                for _ in 0..n {
+                    self.undo();
+                }
+                continue;
+            }
+
+            // Find the open door with the fewest candidates:
+            let mut keys: Vec<OpenDoorKey> = self.open_doors.keys().copied().collect();
+            keys.sort_by_key(|&((x, y), d)| (x, y, d as u8));
+            keys.shuffle(rng);
+            let mut best: Option<Vec<(usize, Tile, usize)>> = None;
+            for key in keys {
+                let candidates = self.get_candidates(key, &doors_by_type);
+                let done = candidates.is_empty();
+                if best.as_ref().is_none_or(|b| candidates.len() < b.len()) {
+                    best = Some(candidates);
+                }
+                if done {
+                    break;
+                }
+            }
+            let candidates = best.unwrap();
+
+            if candidates.is_empty() {
+                num_failures += 1;
+                let n = rng
+                    .gen_range(1..=1 + num_failures / 50)
+                    .min(self.history.len() - 1);
+                for _ in 0..n {
+                    self.undo();
+                }
+                continue;
+            }
+            let weights: Vec<f64> = candidates
+                .iter()
+                .map(|&(unit_idx, pos, n)| self.candidate_weight(unit_idx, pos, n))
+                .collect();
+            let (unit_idx, pos, _) = match WeightedIndex::new(&weights) {
+                Ok(dist) => candidates[dist.sample(rng)],
+                Err(_) => *candidates.choose(rng).unwrap(),
+            };
+            self.place(unit_idx, pos);
+        }
+        false
+    }
+
+    fn get_map(&self) -> Map {
+        let num_rooms = self.game_data.room_geometry.len();
+        let mut map = Map {
+            room_mask: vec![false; num_rooms],
+            rooms: vec![(0, 0); num_rooms],
+            doors: vec![],
+            area: vec![0; num_rooms],
+            subarea: vec![0; num_rooms],
+            subsubarea: vec![0; num_rooms],
+        };
+        let mut doors_by_tile: HashMap<(Tile, Direction), &UnitDoor> = HashMap::new();
+        for (unit_idx, unit) in self.units.iter().enumerate() {
+            let pos = self.positions[unit_idx].unwrap();
+            for &(room_idx, rx, ry) in &unit.rooms {
+                map.room_mask[room_idx] = true;
+                map.rooms[room_idx] = ((pos.0 + rx) as usize, (pos.1 + ry) as usize);
+            }
+            for door in &unit.doors {
+                doors_by_tile.insert(((pos.0 + door.x, pos.1 + door.y), door.direction), door);
+            }
+            map.doors.extend(unit.internal_connections.iter().copied());
+        }
+        let ptr_pair = |door: &UnitDoor| -> DoorPtrPair {
+            let d = &self.game_data.room_geometry[door.room_idx].doors[door.door_idx];
+            (d.exit_ptr, d.entrance_ptr)
+        };
+        let mut keys: Vec<&(Tile, Direction)> = doors_by_tile.keys().collect();
+        keys.sort_by_key(|&&((x, y), d)| (x, y, d as u8));
+        for &(tile, direction) in keys {
+            // Connections go from right to left and from down to up:
+            if direction != Direction::Right && direction != Direction::Down {
+                continue;
+            }
+            let src = doors_by_tile[&(tile, direction)];
+            let dst = doors_by_tile[&(step(tile, direction), direction.opposite())];
+            map.doors.push((ptr_pair(src), ptr_pair(dst), true));
+        }
+        map
+    }
+}
+
+pub fn generate_map<R: Rng>(
+    settings: &MapGeneratorSettings,
+    game_data: &GameData,
+    rng: &mut R,
+) -> Result<Map> {
+    if settings.room_mask.len() != game_data.room_geometry.len() {
+        bail!(
+            "Room mask has length {}, expected {}",
+            settings.room_mask.len(),
+            game_data.room_geometry.len()
+        );
+    }
+    let units = get_units(game_data, &settings.room_mask)?;
+    for attempt in 0..settings.max_attempts {
+        let mut layout = Layout::new(game_data, &units);
+        if !layout.search(settings.max_steps, rng) {
+            info!("Map generation attempt {attempt}: no layout found");
+            continue;
+        }
+        let mut map = layout.get_map();
+        for _ in 0..AREA_ATTEMPTS {
+            let partition = match settings.area_partition {
+                AreaPartition::Map => AreaPartition::Size,
+                p => p,
+            };
+            if let Err(e) = assign_areas(&mut map, game_data, partition, rng) {
+                info!("Map generation attempt {attempt}: area assignment failed ({e})");
+                continue;
+            }
+            let issues = get_map_issues(&map, game_data);
+            if issues.is_empty() {
+                return Ok(map);
+            }
+            info!(
+                "Map generation attempt {attempt}: rejected layout ({})",
+                issues[0]
+            );
+        }
+    }
+    bail!(
+        "Unable to generate a map in {} attempts",
+        settings.max_attempts
+    );
+}
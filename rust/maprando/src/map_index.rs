@@ -0,0 +1,190 @@
+// Metadata about the maps in a map repository, computed offline (see the `build-map-index` tool)
+// so that maps can be selected by their properties without loading every map file.
+
+use std::collections::VecDeque;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::patch::NUM_AREAS;
+use crate::randomize::Randomizer;
+use crate::settings::AreaPartition;
+use maprando_game::{GameData, Map, RoomGeometryRoomIdx};
+
+// Name of the index file within a map repository directory.
+pub const MAP_INDEX_FILENAME: &str = "map_index.json";
+
+// A one-way connection counts as a long one-way path if returning from its destination
+// to its source takes more than this many door transitions (or is impossible).
+pub const LONG_ONE_WAY_MIN_DOORS: usize = 6;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MapProperties {
+    pub num_rooms: usize,
+    pub toilet_intersections: usize,
+    // Number of map tiles in the largest area divided by the number in the smallest area.
+    // This is for the areas stored with the map, which area assignment only permutes unless the
+    // rooms are regrouped (see `AreaPartition`), so queries on it are rejected in that case.
+    pub area_size_ratio: f32,
+    // Largest number of door transitions needed to reach a room from the ship.
+    pub hub_distance: usize,
+    pub long_one_way_paths: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MapIndexEntry {
+    pub id: String,
+    pub file: String,
+    pub index: usize,
+    pub properties: MapProperties,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MapIndex {
+    pub entries: Vec<MapIndexEntry>,
+}
+
+// Constraints for selecting a map from a repository. Unset fields are unconstrained.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct MapQuery {
+    pub map_id: Option<String>,
+    pub max_toilet_intersections: Option<usize>,
+    pub max_area_size_ratio: Option<f32>,
+    pub max_hub_distance: Option<usize>,
+    pub max_long_one_way_paths: Option<usize>,
+}
+
+impl MapQuery {
+    // Maps whose areas are of similar size.
+    pub fn balanced_areas() -> Self {
+        MapQuery {
+            max_area_size_ratio: Some(2.0),
+            ..MapQuery::default()
+        }
+    }
+
+    // Check that the query can be used with the area partition: the area properties of the
+    // index don't apply to rooms regrouped into new areas.
+    pub fn validate(&self, partition: AreaPartition) -> Result<()> {
+        if self.max_area_size_ratio.is_some() && partition != AreaPartition::Map {
+            bail!("max_area_size_ratio can't be used when the rooms are regrouped into new areas");
+        }
+        Ok(())
+    }
+
+    pub fn matches(&self, entry: &MapIndexEntry) -> bool {
+        let p = &entry.properties;
+        self.map_id.as_ref().is_none_or(|x| x == &entry.id)
+            && self
+                .max_toilet_intersections
+                .is_none_or(|x| p.toilet_intersections <= x)
+            && self
+                .max_area_size_ratio
+                .is_none_or(|x| p.area_size_ratio <= x)
+            && self.max_hub_distance.is_none_or(|x| p.hub_distance <= x)
+            && self
+                .max_long_one_way_paths
+                .is_none_or(|x| p.long_one_way_paths <= x)
+    }
+}
+
+// Stable ID of a map, given by its file in the repository and its position in stored order.
+pub fn get_map_id(file: &str, index: usize) -> String {
+    format!("{file}:{index}")
+}
+
+pub fn parse_map_id(id: &str) -> Result<(&str, usize)> {
+    let (file, index) = id
+        .rsplit_once(':')
+        .with_context(|| format!("Invalid map ID: {id}"))?;
+    let index = index
+        .parse()
+        .with_context(|| format!("Invalid map ID: {id}"))?;
+    Ok((file, index))
+}
+
+// Room-level connections: for each room, the rooms that its doors lead to.
+fn get_room_graph(map: &Map, game_data: &GameData) -> Vec<Vec<RoomGeometryRoomIdx>> {
+    let mut graph = vec![vec![]; game_data.room_geometry.len()];
+    for (src, dst, bidirectional) in &map.doors {
+        let (src_room_idx, _) = game_data.room_and_door_idxs_by_door_ptr_pair[src];
+        let (dst_room_idx, _) = game_data.room_and_door_idxs_by_door_ptr_pair[dst];
+        graph[src_room_idx].push(dst_room_idx);
+        if *bidirectional {
+            graph[dst_room_idx].push(src_room_idx);
+        }
+    }
+    graph
+}
+
+// Number of door transitions from `start` to each room, if reachable.
+fn get_distances(
+    graph: &[Vec<RoomGeometryRoomIdx>],
+    start: RoomGeometryRoomIdx,
+) -> Vec<Option<usize>> {
+    let mut dist = vec![None; graph.len()];
+    let mut queue = VecDeque::new();
+    dist[start] = Some(0);
+    queue.push_back(start);
+    while let Some(room_idx) = queue.pop_front() {
+        let d = dist[room_idx].unwrap();
+        for &next in &graph[room_idx] {
+            if dist[next].is_none() {
+                dist[next] = Some(d + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    dist
+}
+
+pub fn get_map_properties(map: &Map, game_data: &GameData) -> MapProperties {
+    let num_rooms = map.room_mask.iter().filter(|&&x| x).count();
+    let toilet_intersections = Randomizer::get_toilet_intersections(map, game_data).len();
+
+    let mut area_tiles = [0usize; NUM_AREAS];
+    for (room_idx, room) in game_data.room_geometry.iter().enumerate() {
+        if !map.room_mask[room_idx] {
+            continue;
+        }
+        area_tiles[map.area[room_idx]] += room.map.iter().flatten().filter(|&&x| x == 1).count();
+    }
+    let used_area_tiles: Vec<usize> = area_tiles.into_iter().filter(|&x| x > 0).collect();
+    let area_size_ratio = match (used_area_tiles.iter().max(), used_area_tiles.iter().min()) {
+        (Some(&max), Some(&min)) => max as f32 / min as f32,
+        _ => 1.0,
+    };
+
+    let graph = get_room_graph(map, game_data);
+    let hub_distance = if map.room_mask[game_data.ship_room_idx] {
+        get_distances(&graph, game_data.ship_room_idx)
+            .into_iter()
+            .flatten()
+            .max()
+            .unwrap_or(0)
+    } else {
+        0
+    };
+
+    let mut long_one_way_paths = 0;
+    for (src, dst, bidirectional) in &map.doors {
+        if *bidirectional {
+            continue;
+        }
+        let (src_room_idx, _) = game_data.room_and_door_idxs_by_door_ptr_pair[src];
+        let (dst_room_idx, _) = game_data.room_and_door_idxs_by_door_ptr_pair[dst];
+        match get_distances(&graph, dst_room_idx)[src_room_idx] {
+            Some(d) if d <= LONG_ONE_WAY_MIN_DOORS => {}
+            _ => long_one_way_paths += 1,
+        }
+    }
+
+    MapProperties {
+        num_rooms,
+        toilet_intersections,
+        area_size_ratio,
+        hub_distance,
+        long_one_way_paths,
+    }
+}
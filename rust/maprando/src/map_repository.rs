@@ -1,34 +1,22 @@
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
 use log::info;
 use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
 use serde::Deserialize;
-use std::{
-    fs::File,
-    io::BufReader,
-    path::{Path, PathBuf},
-};
+use std::sync::Arc;
 
+use crate::map_format::read_avro_maps;
+use crate::map_index::{
+    MAP_INDEX_FILENAME, MapIndex, MapIndexEntry, MapQuery, get_map_id, get_map_properties,
+    parse_map_id,
+};
 use crate::randomize::Randomizer;
-use maprando_game::{GameData, Map, RoomId};
+use maprando_game::{GameData, Map, data_source::DataSource};
 
 pub struct MapRepository {
-    pub base_path: PathBuf,
+    pub data_source: Arc<dyn DataSource>,
+    pub base_path: String,
     pub filenames: Vec<String>,
-}
-
-#[derive(Deserialize)]
-struct StoredMap {
-    pub room_id: Vec<RoomId>,
-    pub room_x: Vec<usize>,
-    pub room_y: Vec<usize>,
-    pub room_area: Vec<usize>,
-    pub room_subarea: Vec<usize>,
-    pub room_subsubarea: Vec<usize>,
-    pub conn_from_room_id: Vec<usize>,
-    pub conn_from_door_id: Vec<usize>,
-    pub conn_to_room_id: Vec<usize>,
-    pub conn_to_door_id: Vec<usize>,
-    pub conn_bidirectional: Vec<bool>,
+    pub index: Option<MapIndex>,
 }
 
 #[derive(Deserialize)]
@@ -45,95 +33,126 @@ struct MapManifest {
 }
 
 impl MapRepository {
-    pub fn new(name: &str, base_path: &Path) -> Result<Self> {
-        let manifest_bytes = std::fs::read(base_path.join("manifest.json"))?;
+    // Open the map repository in directory `base_path` of the data source (e.g. "maps/vanilla").
+    pub fn new(name: &str, data_source: Arc<dyn DataSource>, base_path: &str) -> Result<Self> {
+        let manifest_bytes = data_source.read(&format!("{base_path}/manifest.json"))?;
         let manifest: MapManifest = serde_json::from_slice(&manifest_bytes)?;
 
         let num_maps = match manifest.maps_per_file {
             MapsPerFile::Fixed(n) => manifest.files.len() * n,
             MapsPerFile::Variable(v) => v.iter().sum(),
         };
-        info!(
-            "{}: {} maps available ({})",
-            name,
-            num_maps,
-            base_path.display()
-        );
+        info!("{}: {} maps available ({})", name, num_maps, base_path);
+
+        // The metadata index is optional, but is required for selecting maps by property.
+        let index_path = format!("{base_path}/{MAP_INDEX_FILENAME}");
+        let index = if data_source.list(base_path)?.contains(&index_path) {
+            let index_bytes = data_source.read(&index_path)?;
+            let index: MapIndex = serde_json::from_slice(&index_bytes)
+                .with_context(|| format!("Unable to parse {index_path}"))?;
+            info!("{}: {} maps indexed", name, index.entries.len());
+            Some(index)
+        } else {
+            None
+        };
+
         Ok(MapRepository {
-            base_path: base_path.to_owned(),
+            data_source,
+            base_path: base_path.to_string(),
             filenames: manifest.files,
+            index,
         })
     }
 
-    pub fn get_map_batch(&self, seed: usize, game_data: &GameData) -> Result<Vec<Map>> {
-        let idx = seed % self.filenames.len();
-        let path = self.base_path.join(&self.filenames[idx]);
-        info!("Map batch file: {}", path.display());
-
-        let file = File::open(path)?;
-        let buf_reader = BufReader::new(file);
-        let avro_reader = apache_avro::Reader::new(buf_reader)?;
-        let mut map_vec: Vec<Map> = vec![];
-        let room_geometry = &game_data.room_geometry;
-
-        for value in avro_reader {
-            let stored_map: StoredMap = apache_avro::from_value(&value?)?;
-            let num_rooms = stored_map.room_id.len();
-            let num_conns = stored_map.conn_from_door_id.len();
-
-            let mut room_mask: Vec<bool> = vec![false; room_geometry.len()];
-            let mut rooms: Vec<(usize, usize)> = vec![(0, 0); room_geometry.len()];
-            let mut areas: Vec<usize> = vec![0; room_geometry.len()];
-            let mut subareas: Vec<usize> = vec![0; room_geometry.len()];
-            let mut subsubareas: Vec<usize> = vec![0; room_geometry.len()];
-            for i in 0..num_rooms {
-                let room_id = stored_map.room_id[i];
-                let room_ptr = game_data.room_ptr_by_id[&room_id];
-                let room_idx = game_data.room_idx_by_ptr[&room_ptr];
-                room_mask[room_idx] = true;
-                rooms[room_idx] = (stored_map.room_x[i], stored_map.room_y[i]);
-                areas[room_idx] = stored_map.room_area[i];
-                subareas[room_idx] = stored_map.room_subarea[i];
-                subsubareas[room_idx] = stored_map.room_subsubarea[i];
-            }
+    // Get a batch of maps from a file selected by `seed`, in shuffled order, each with its
+    // stable map ID.
+    pub fn get_map_batch(&self, seed: usize, game_data: &GameData) -> Result<Vec<(String, Map)>> {
+        let file_idx = seed % self.filenames.len();
+        let mut map_vec: Vec<(String, Map)> = self
+            .get_stored_maps(file_idx, game_data)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, map)| (get_map_id(&self.filenames[file_idx], i), map))
+            .collect();
 
-            let mut doors = vec![];
-            for i in 0..num_conns {
-                let from_room_id = stored_map.conn_from_room_id[i];
-                let from_room_ptr = game_data.room_ptr_by_id[&from_room_id];
-                let from_room_idx = game_data.room_idx_by_ptr[&from_room_ptr];
-                let from_door_id = stored_map.conn_from_door_id[i];
-                let from_exit_ptr =
-                    game_data.room_geometry[from_room_idx].doors[from_door_id].exit_ptr;
-                let from_entrance_ptr =
-                    game_data.room_geometry[from_room_idx].doors[from_door_id].entrance_ptr;
-                let to_room_id = stored_map.conn_to_room_id[i];
-                let to_room_ptr = game_data.room_ptr_by_id[&to_room_id];
-                let to_room_idx = game_data.room_idx_by_ptr[&to_room_ptr];
-                let to_door_id = stored_map.conn_to_door_id[i];
-                let to_exit_ptr = game_data.room_geometry[to_room_idx].doors[to_door_id].exit_ptr;
-                let to_entrance_ptr =
-                    game_data.room_geometry[to_room_idx].doors[to_door_id].entrance_ptr;
-                let bidirectional = stored_map.conn_bidirectional[i];
-                doors.push((
-                    (from_exit_ptr, from_entrance_ptr),
-                    (to_exit_ptr, to_entrance_ptr),
-                    bidirectional,
-                ));
+        let mut rng_seed = [0u8; 32];
+        rng_seed[..8].copy_from_slice(&seed.to_le_bytes());
+        let mut rng = StdRng::from_seed(rng_seed);
+        map_vec.shuffle(&mut rng);
+
+        Ok(map_vec)
+    }
+
+    pub fn get_map_by_id(&self, id: &str, game_data: &GameData) -> Result<Map> {
+        let (file, index) = parse_map_id(id)?;
+        let Some(file_idx) = self.filenames.iter().position(|x| x == file) else {
+            bail!("Map file {file} not found in repository {}", self.base_path);
+        };
+        let mut maps = self.get_stored_maps(file_idx, game_data)?;
+        if index >= maps.len() {
+            bail!("Map {id} not found: {file} has only {} maps", maps.len());
+        }
+        Ok(maps.swap_remove(index))
+    }
+
+    // Get the indexed maps satisfying the query.
+    pub fn find_maps(&self, query: &MapQuery) -> Result<Vec<&MapIndexEntry>> {
+        let Some(index) = &self.index else {
+            bail!(
+                "Map repository {} has no {MAP_INDEX_FILENAME}, so maps can't be selected by property",
+                self.base_path
+            );
+        };
+        Ok(index.entries.iter().filter(|x| query.matches(x)).collect())
+    }
+
+    // Select a map satisfying the query, using `seed` to choose among the matches.
+    pub fn select_map(
+        &self,
+        seed: usize,
+        query: &MapQuery,
+        game_data: &GameData,
+    ) -> Result<(String, Map)> {
+        if let Some(id) = &query.map_id {
+            return Ok((id.clone(), self.get_map_by_id(id, game_data)?));
+        }
+        let matches = self.find_maps(query)?;
+        if matches.is_empty() {
+            bail!("No maps in {} match the query", self.base_path);
+        }
+        let id = matches[seed % matches.len()].id.clone();
+        let map = self.get_map_by_id(&id, game_data)?;
+        Ok((id, map))
+    }
+
+    // Compute the metadata index over all maps in the repository.
+    pub fn build_index(&self, game_data: &GameData) -> Result<MapIndex> {
+        let mut entries = vec![];
+        for (file_idx, file) in self.filenames.iter().enumerate() {
+            let maps = self.get_stored_maps(file_idx, game_data)?;
+            for (index, map) in maps.iter().enumerate() {
+                entries.push(MapIndexEntry {
+                    id: get_map_id(file, index),
+                    file: file.clone(),
+                    index,
+                    properties: get_map_properties(map, game_data),
+                });
             }
+        }
+        Ok(MapIndex { entries })
+    }
 
-            let mut map = Map {
-                room_mask,
-                rooms,
-                doors,
-                area: areas,
-                subarea: subareas,
-                subsubarea: subsubareas,
-            };
+    // Get the maps in a file, in stored order.
+    pub fn get_stored_maps(&self, file_idx: usize, game_data: &GameData) -> Result<Vec<Map>> {
+        let path = format!("{}/{}", self.base_path, self.filenames[file_idx]);
+        info!("Map batch file: {path}");
 
+        let data = self.data_source.read(&path)?;
+        let mut map_vec = read_avro_maps(&data, game_data)?;
+        for map in &mut map_vec {
             // Make Toilet area/subarea/subsubarea align with its intersecting room(s):
             // TODO: Push this upstream into the map generation
-            let toilet_intersections = Randomizer::get_toilet_intersections(&map, game_data);
+            let toilet_intersections = Randomizer::get_toilet_intersections(map, game_data);
             if !toilet_intersections.is_empty() {
                 let area = map.area[toilet_intersections[0]];
                 let subarea = map.subarea[toilet_intersections[0]];
@@ -178,15 +197,7 @@ impl MapRepository {
                         && x.1 != toilet_bottom
                 });
             }
-
-            map_vec.push(map);
         }
-
-        let mut rng_seed = [0u8; 32];
-        rng_seed[..8].copy_from_slice(&seed.to_le_bytes());
-        let mut rng = StdRng::from_seed(rng_seed);
-        map_vec.shuffle(&mut rng);
-
         Ok(map_vec)
     }
 }
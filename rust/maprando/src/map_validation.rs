@@ -0,0 +1,417 @@
+// Validation of `Map` layouts against the room geometry, so that a bad map file is reported
+// clearly instead of showing up as index panics or odd behavior in the patched ROM.
+
+use std::fmt;
+
+use anyhow::{Result, bail};
+use hashbrown::{HashMap, HashSet};
+use maprando_game::{
+    DoorPtrPair, GameData, Map, RoomGeometryDoorIdx, RoomGeometryPartIdx, RoomGeometryRoomIdx,
+};
+
+use crate::patch::NUM_AREAS;
+
+// Size of the grid that the whole map is laid out on (as used for the spoiler map).
+pub const MAP_GRID_SIZE: usize = 72;
+// Size of the in-game pause map for each area, which every area must fit within.
+pub const AREA_MAP_WIDTH: usize = 64;
+pub const AREA_MAP_HEIGHT: usize = 32;
+const NUM_SUBAREAS: usize = 2;
+const NUM_SUBSUBAREAS: usize = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapIssueKind {
+    // Per-room vectors with the wrong length.
+    Shape,
+    // Area, subarea, or subsubarea out of range.
+    AreaRange,
+    // Rooms outside the map grid, or areas too large for the pause map.
+    Bounds,
+    Overlap,
+    // Doors that are unknown, connected the wrong number of times, or with incompatible directions.
+    Door,
+    Connectivity,
+}
+
+#[derive(Clone, Debug)]
+pub struct MapIssue {
+    pub kind: MapIssueKind,
+    pub message: String,
+}
+
+impl fmt::Display for MapIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+// A door connection between two known doors: (source, destination, bidirectional).
+type DoorConnection = (
+    (RoomGeometryRoomIdx, RoomGeometryDoorIdx),
+    (RoomGeometryRoomIdx, RoomGeometryDoorIdx),
+    bool,
+);
+
+struct Validator<'a> {
+    map: &'a Map,
+    game_data: &'a GameData,
+    issues: Vec<MapIssue>,
+}
+
+fn opposite_direction(direction: &str) -> &'static str {
+    match direction {
+        "left" => "right",
+        "right" => "left",
+        "up" => "down",
+        "down" => "up",
+        _ => "",
+    }
+}
+
+impl Validator<'_> {
+    fn add(&mut self, kind: MapIssueKind, message: String) {
+        self.issues.push(MapIssue { kind, message });
+    }
+
+    fn room_name(&self, room_idx: RoomGeometryRoomIdx) -> &str {
+        &self.game_data.room_geometry[room_idx].name
+    }
+
+    fn included_rooms(&self) -> Vec<RoomGeometryRoomIdx> {
+        (0..self.game_data.room_geometry.len())
+            .filter(|&i| self.map.room_mask[i])
+            .collect()
+    }
+
+    fn check_shape(&mut self) -> bool {
+        let num_rooms = self.game_data.room_geometry.len();
+        let mut lengths = vec![
+            ("room_mask", self.map.room_mask.len()),
+            ("rooms", self.map.rooms.len()),
+            ("area", self.map.area.len()),
+            ("subarea", self.map.subarea.len()),
+        ];
+        // Old maps don't have subsubareas:
+        if !self.map.subsubarea.is_empty() {
+            lengths.push(("subsubarea", self.map.subsubarea.len()));
+        }
+        let mut ok = true;
+        for (field, len) in lengths {
+            if len != num_rooms {
+                self.add(
+                    MapIssueKind::Shape,
+                    format!("{field} has length {len}, expected {num_rooms} (one per room)"),
+                );
+                ok = false;
+            }
+        }
+        ok
+    }
+
+    fn check_areas(&mut self) {
+        for room_idx in self.included_rooms() {
+            let mut values = vec![
+                ("area", self.map.area[room_idx], NUM_AREAS),
+                ("subarea", self.map.subarea[room_idx], NUM_SUBAREAS),
+            ];
+            if !self.map.subsubarea.is_empty() {
+                values.push(("subsubarea", self.map.subsubarea[room_idx], NUM_SUBSUBAREAS));
+            }
+            for (field, value, limit) in values {
+                if value >= limit {
+                    self.add(
+                        MapIssueKind::AreaRange,
+                        format!(
+                            "{} has {field} {value}, expected less than {limit}",
+                            self.room_name(room_idx)
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    // Map tiles occupied by a room, in map coordinates.
+    fn room_tiles(&self, room_idx: RoomGeometryRoomIdx) -> Vec<(usize, usize)> {
+        let (x0, y0) = self.map.rooms[room_idx];
+        let mut out = vec![];
+        for (y, row) in self.game_data.room_geometry[room_idx]
+            .map
+            .iter()
+            .enumerate()
+        {
+            for (x, &tile) in row.iter().enumerate() {
+                if tile == 1 {
+                    out.push((x0 + x, y0 + y));
+                }
+            }
+        }
+        out
+    }
+
+    fn check_bounds_and_overlaps(&mut self) {
+        let mut occupied: HashMap<(usize, usize, usize), RoomGeometryRoomIdx> = HashMap::new();
+        let mut area_bounds: HashMap<usize, (usize, usize, usize, usize)> = HashMap::new();
+        let mut reported_pairs: HashSet<(RoomGeometryRoomIdx, RoomGeometryRoomIdx)> =
+            HashSet::new();
+        for room_idx in self.included_rooms() {
+            let room = &self.game_data.room_geometry[room_idx];
+            let (x0, y0) = self.map.rooms[room_idx];
+            let height = room.map.len();
+            let width = room.map.first().map(|row| row.len()).unwrap_or(0);
+            if x0 + width > MAP_GRID_SIZE || y0 + height > MAP_GRID_SIZE {
+                self.add(
+                    MapIssueKind::Bounds,
+                    format!(
+                        "{} at ({x0}, {y0}) extends outside the {MAP_GRID_SIZE}x{MAP_GRID_SIZE} map grid",
+                        room.name
+                    ),
+                );
+            }
+            let area = self.map.area[room_idx];
+            for (x, y) in self.room_tiles(room_idx) {
+                let bounds = area_bounds.entry(area).or_insert((x, x, y, y));
+                bounds.0 = bounds.0.min(x);
+                bounds.1 = bounds.1.max(x);
+                bounds.2 = bounds.2.min(y);
+                bounds.3 = bounds.3.max(y);
+
+                // The Toilet is drawn on top of the room it passes through, and each area has its
+                // own pause map, so only overlaps within an area (not involving the Toilet) count.
+                if room_idx == self.game_data.toilet_room_idx {
+                    continue;
+                }
+                if let Some(&other_idx) = occupied.get(&(area, x, y)) {
+                    if reported_pairs.insert((other_idx, room_idx)) {
+                        self.add(
+                            MapIssueKind::Overlap,
+                            format!(
+                                "{} overlaps {} at ({x}, {y})",
+                                self.room_name(room_idx),
+                                self.room_name(other_idx)
+                            ),
+                        );
+                    }
+                } else {
+                    occupied.insert((area, x, y), room_idx);
+                }
+            }
+        }
+
+        let mut areas: Vec<usize> = area_bounds.keys().copied().collect();
+        areas.sort();
+        for area in areas {
+            let (min_x, max_x, min_y, max_y) = area_bounds[&area];
+            let width = max_x - min_x + 1;
+            let height = max_y - min_y + 1;
+            if width > AREA_MAP_WIDTH || height > AREA_MAP_HEIGHT {
+                self.add(
+                    MapIssueKind::Bounds,
+                    format!(
+                        "area {area} is {width}x{height} tiles, which does not fit in the {AREA_MAP_WIDTH}x{AREA_MAP_HEIGHT} pause map"
+                    ),
+                );
+            }
+        }
+    }
+
+    fn get_door_map(&self) -> HashMap<DoorPtrPair, (RoomGeometryRoomIdx, RoomGeometryDoorIdx)> {
+        let mut out = HashMap::new();
+        for (room_idx, room) in self.game_data.room_geometry.iter().enumerate() {
+            for (door_idx, door) in room.doors.iter().enumerate() {
+                out.insert((door.exit_ptr, door.entrance_ptr), (room_idx, door_idx));
+            }
+        }
+        out
+    }
+
+    fn door_name(
+        &self,
+        (room_idx, door_idx): (RoomGeometryRoomIdx, RoomGeometryDoorIdx),
+    ) -> String {
+        let door = &self.game_data.room_geometry[room_idx].doors[door_idx];
+        format!(
+            "{} {} door {}",
+            self.room_name(room_idx),
+            door.direction,
+            door_idx
+        )
+    }
+
+    // Check doors, returning the connections between known doors.
+    fn check_doors(&mut self) -> Vec<DoorConnection> {
+        let door_map = self.get_door_map();
+        let mut connection_count: HashMap<(RoomGeometryRoomIdx, RoomGeometryDoorIdx), usize> =
+            HashMap::new();
+        let mut connections = vec![];
+        for &(src_ptr_pair, dst_ptr_pair, bidirectional) in &self.map.doors {
+            let mut known = true;
+            for ptr_pair in [src_ptr_pair, dst_ptr_pair] {
+                if !door_map.contains_key(&ptr_pair) {
+                    self.add(
+                        MapIssueKind::Door,
+                        format!("unknown door {ptr_pair:?} in door connection"),
+                    );
+                    known = false;
+                }
+            }
+            if !known {
+                continue;
+            }
+            let src = door_map[&src_ptr_pair];
+            let dst = door_map[&dst_ptr_pair];
+            for door in [src, dst] {
+                *connection_count.entry(door).or_default() += 1;
+                if !self.map.room_mask[door.0] {
+                    self.add(
+                        MapIssueKind::Door,
+                        format!(
+                            "{} is connected, but its room is not included in room_mask",
+                            self.door_name(door)
+                        ),
+                    );
+                }
+            }
+            let src_direction = &self.game_data.room_geometry[src.0].doors[src.1].direction;
+            let dst_direction = &self.game_data.room_geometry[dst.0].doors[dst.1].direction;
+            if opposite_direction(src_direction) != dst_direction {
+                self.add(
+                    MapIssueKind::Door,
+                    format!(
+                        "{} is connected to {}, which has an incompatible direction",
+                        self.door_name(src),
+                        self.door_name(dst)
+                    ),
+                );
+            }
+            connections.push((src, dst, bidirectional));
+        }
+
+        for room_idx in self.included_rooms() {
+            for door_idx in 0..self.game_data.room_geometry[room_idx].doors.len() {
+                let count = connection_count
+                    .get(&(room_idx, door_idx))
+                    .copied()
+                    .unwrap_or(0);
+                if count != 1 {
+                    self.add(
+                        MapIssueKind::Door,
+                        format!(
+                            "{} is connected {count} times, expected exactly once",
+                            self.door_name((room_idx, door_idx))
+                        ),
+                    );
+                }
+            }
+        }
+        connections
+    }
+
+    // Check that every room part can be reached from every other, when all items are available.
+    // Within a room, each part is the set of doors mutually reachable with all items. Durable
+    // part connections (e.g. opened by defeating a boss) work in both directions once made,
+    // while transient ones (e.g. falling through sand) are one-way.
+    fn check_connectivity(&mut self, connections: &[DoorConnection]) {
+        type Node = (RoomGeometryRoomIdx, RoomGeometryPartIdx);
+        let mut nodes: Vec<Node> = vec![];
+        let mut forward: HashMap<Node, Vec<Node>> = HashMap::new();
+        let mut backward: HashMap<Node, Vec<Node>> = HashMap::new();
+        let mut add_edge = |src: Node, dst: Node| {
+            forward.entry(src).or_default().push(dst);
+            backward.entry(dst).or_default().push(src);
+        };
+        for room_idx in self.included_rooms() {
+            let room = &self.game_data.room_geometry[room_idx];
+            for part_idx in 0..room.parts.len() {
+                nodes.push((room_idx, part_idx));
+            }
+            for &(a, b) in &room.durable_part_connections {
+                add_edge((room_idx, a), (room_idx, b));
+                add_edge((room_idx, b), (room_idx, a));
+            }
+            for &(a, b) in &room.transient_part_connections {
+                add_edge((room_idx, a), (room_idx, b));
+            }
+        }
+        let part_of = |(room_idx, door_idx): (RoomGeometryRoomIdx, RoomGeometryDoorIdx)| {
+            self.game_data.room_geometry[room_idx]
+                .parts
+                .iter()
+                .position(|part| part.contains(&door_idx))
+                .map(|part_idx| (room_idx, part_idx))
+        };
+        for &(src, dst, bidirectional) in connections {
+            if let (Some(src_node), Some(dst_node)) = (part_of(src), part_of(dst)) {
+                add_edge(src_node, dst_node);
+                if bidirectional {
+                    add_edge(dst_node, src_node);
+                }
+            }
+        }
+
+        let Some(&start) = nodes.first() else {
+            self.add(MapIssueKind::Connectivity, "map has no rooms".to_string());
+            return;
+        };
+        let reachable = |edges: &HashMap<Node, Vec<Node>>| -> HashSet<Node> {
+            let mut seen: HashSet<Node> = HashSet::new();
+            seen.insert(start);
+            let mut stack = vec![start];
+            while let Some(node) = stack.pop() {
+                for &next in edges.get(&node).into_iter().flatten() {
+                    if seen.insert(next) {
+                        stack.push(next);
+                    }
+                }
+            }
+            seen
+        };
+        let start_name = self.room_name(start.0).to_string();
+        for (edges, description) in [
+            (&forward, "cannot be reached from"),
+            (&backward, "cannot reach"),
+        ] {
+            let seen = reachable(edges);
+            let mut room_names: Vec<&str> = vec![];
+            for node in &nodes {
+                if !seen.contains(node) && !room_names.contains(&self.room_name(node.0)) {
+                    room_names.push(self.room_name(node.0));
+                }
+            }
+            if !room_names.is_empty() {
+                let message = format!(
+                    "map is not strongly connected: {} room(s) {description} {start_name}: {}",
+                    room_names.len(),
+                    room_names.join(", ")
+                );
+                self.add(MapIssueKind::Connectivity, message);
+            }
+        }
+    }
+}
+
+// Check a map against the room geometry, returning all problems found.
+pub fn get_map_issues(map: &Map, game_data: &GameData) -> Vec<MapIssue> {
+    let mut validator = Validator {
+        map,
+        game_data,
+        issues: vec![],
+    };
+    // The remaining checks index the per-room vectors, so they require the right lengths.
+    if validator.check_shape() {
+        validator.check_areas();
+        validator.check_bounds_and_overlaps();
+        let connections = validator.check_doors();
+        validator.check_connectivity(&connections);
+    }
+    validator.issues
+}
+
+pub fn validate_map(map: &Map, game_data: &GameData) -> Result<()> {
+    let issues = get_map_issues(map, game_data);
+    if !issues.is_empty() {
+        let messages: Vec<String> = issues.iter().map(|x| x.to_string()).collect();
+        bail!("Invalid map:\n{}", messages.join("\n"));
+    }
+    Ok(())
+}
@@ -3,9 +3,15 @@ pub mod bps;
 pub mod compress;
 pub mod decompress;
 pub mod glowpatch_writer;
+pub mod input_rom;
+pub mod inspect;
 pub mod ips_write;
+pub mod item_graphics;
+pub mod journal;
 pub mod map_tiles;
+pub mod passes;
 pub mod suffix_tree;
+pub mod symbols;
 pub mod title;
 
 use std::path::Path;
@@ -30,16 +36,31 @@ use ips;
 use log::info;
 use maprando_game::{
     DoorPtr, DoorPtrPair, DoorType, GameData, Item, Map, NodePtr, RoomGeometryDoor, RoomPtr,
-    util::sorted_hashmap_iter,
+    StartLocation, data_source::DataSource, util::sorted_hashmap_iter,
 };
 use ndarray::Array3;
 use rand::{Rng, SeedableRng};
 use std::iter;
 use strum::VariantNames;
 
+use self::item_graphics::load_item_graphics;
+use self::journal::{RomJournal, RomWrite};
 use self::map_tiles::write_tile_4bpp;
+use self::passes::{ConflictPolicy, PassRegistry, PassTarget, RomWriteLog};
+use self::symbols::SymbolTable;
 
 pub const NUM_AREAS: usize = 6;
+// PLM arguments used for gray door states (we reserve all of them even though not all are used)
+const GRAY_DOOR_STATE_INDICES: [usize; 70] = [
+    0x2, 0x3, 0x4, 0x6, 0x7, 0x8, 0x9, 0xa, 0xb, 0x11, 0x12, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19,
+    0x1a, 0x1b, 0x1c, 0x24, 0x25, 0x2c, 0x2d, 0x2e, 0x2f, 0x31, 0x36, 0x37, 0x3e, 0x40, 0x41, 0x42,
+    0x43, 0x46, 0x47, 0x48, 0x4f, 0x50, 0x59, 0x5a, 0x5b, 0x5d, 0x60, 0x80, 0x81, 0x82, 0x83, 0x86,
+    0x87, 0x88, 0x89, 0x8a, 0x91, 0x93, 0x97, 0x9c, 0x9d, 0x9e, 0x9f, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4,
+    0xa5, 0xa6, 0xaa, 0xab, 0xac,
+];
+// Number of door unlock bits available, including those reserved for gray doors:
+pub const MAX_DOOR_STATES: usize = 512;
+pub const MAX_LOCKED_DOORS: usize = MAX_DOOR_STATES - GRAY_DOOR_STATE_INDICES.len();
 
 type PcAddr = usize; // PC pointer to ROM data
 type AsmPtr = usize; // 16-bit SNES pointer to ASM code in bank 0x8F
@@ -57,6 +78,9 @@ pub struct Rom {
     pub data: Vec<u8>,
     pub track_touched: bool,
     pub touched: HashSet<usize>,
+    // If enabled, a record of each write (see `journal`), attributed to `journal_pass`:
+    pub journal: Option<Vec<RomWrite>>,
+    pub journal_pass: String,
 }
 
 impl Rom {
@@ -65,6 +89,8 @@ impl Rom {
             data,
             track_touched: false,
             touched: HashSet::new(),
+            journal: None,
+            journal_pass: String::new(),
         }
     }
 
@@ -73,6 +99,10 @@ impl Rom {
         self.touched.clear();
     }
 
+    pub fn enable_journal(&mut self) {
+        self.journal = Some(vec![]);
+    }
+
     pub fn load(path: &Path) -> Result<Self> {
         let data = std::fs::read(path)
             .with_context(|| format!("Unable to load ROM at path {}", path.display()))?;
@@ -116,13 +146,25 @@ impl Rom {
         Ok(&self.data[addr..(addr + n)])
     }
 
+    // Common part of the write methods, after bounds checks.
+    fn write_bytes(&mut self, addr: usize, x: &[u8]) {
+        self.data[addr..(addr + x.len())].copy_from_slice(x);
+        if self.track_touched {
+            self.touched.extend(addr..(addr + x.len()));
+        }
+        if let Some(journal) = &mut self.journal {
+            journal.push(RomWrite {
+                pass: self.journal_pass.clone(),
+                addr,
+                data: x.to_vec(),
+            });
+        }
+    }
+
     pub fn write_u8(&mut self, addr: usize, x: isize) -> Result<()> {
         ensure!(addr < self.data.len(), "write_u8 address out of bounds");
         ensure!((0..=0xFF).contains(&x), "write_u8 data does not fit");
-        self.data[addr] = x as u8;
-        if self.track_touched {
-            self.touched.insert(addr);
-        }
+        self.write_bytes(addr, &[x as u8]);
         Ok(())
     }
 
@@ -132,12 +174,7 @@ impl Rom {
             "write_u16 address out of bounds"
         );
         ensure!((0..=0xFFFF).contains(&x), "write_u16 data does not fit");
-        self.write_u8(addr, x & 0xFF)?;
-        self.write_u8(addr + 1, x >> 8)?;
-        if self.track_touched {
-            self.touched.insert(addr);
-            self.touched.insert(addr + 1);
-        }
+        self.write_bytes(addr, &(x as u16).to_le_bytes());
         Ok(())
     }
 
@@ -147,14 +184,7 @@ impl Rom {
             "write_u24 address out of bounds"
         );
         ensure!((0..=0xFFFFFF).contains(&x), "write_u24 data does not fit");
-        self.write_u8(addr, x & 0xFF)?;
-        self.write_u8(addr + 1, (x >> 8) & 0xFF)?;
-        self.write_u8(addr + 2, x >> 16)?;
-        if self.track_touched {
-            self.touched.insert(addr);
-            self.touched.insert(addr + 1);
-            self.touched.insert(addr + 2);
-        }
+        self.write_bytes(addr, &(x as u32).to_le_bytes()[0..3]);
         Ok(())
     }
 
@@ -163,14 +193,7 @@ impl Rom {
             addr + x.len() <= self.data.len(),
             "write_n address out of bounds"
         );
-        for (i, &b) in x.iter().enumerate() {
-            self.write_u8(addr + i, b as isize)?;
-        }
-        if self.track_touched {
-            for i in 0..x.len() {
-                self.touched.insert(addr + i);
-            }
-        }
+        self.write_bytes(addr, x);
         Ok(())
     }
 
@@ -233,6 +256,47 @@ pub struct Patcher<'a> {
     // per-area vec of (addr, bitmask) of cross-area tiles to reveal when map is activated:
     pub map_reveal_bitmasks: Vec<Vec<(u16, u16)>>,
     pub extra_room_data: HashMap<RoomPtr, ExtraRoomData>,
+    // (locked door index, room pointer, offset into the room's extra setup ASM) of each door spawn:
+    pub locked_door_setup_asm: Vec<(usize, RoomPtr, usize)>,
+    pub write_log: RomWriteLog,
+    pub symbols: SymbolTable,
+}
+
+impl PassTarget for Patcher<'_> {
+    fn rom_and_write_log(&mut self) -> (&mut Rom, &mut RomWriteLog) {
+        (&mut *self.rom, &mut self.write_log)
+    }
+}
+
+// Earlier IPS patches whose bytes the given IPS patch pass intentionally overwrites.
+pub(crate) fn get_ips_patch_overrides(pass_name: &str) -> &'static [&'static str] {
+    match pass_name {
+        "orig_ips/pause_menu_objectives" => &["orig_ips/hud_expansion_opaque"],
+        "ips/max_ammo_display_fast" => &["orig_ips/hud_expansion_opaque"],
+        "ips/map_area" => &[
+            "orig_ips/hud_expansion_opaque",
+            "orig_ips/pause_menu_objectives",
+        ],
+        "ips/map_progress_maintain" => &["ips/map_area"],
+        "ips/item_dots_disappear" => &["ips/map_area", "ips/map_progress_maintain"],
+        "ips/fast_pause_menu" => &["orig_ips/pause_menu_objectives"],
+        "ips/missile_refill_all" => &["orig_ips/hud_expansion_opaque"],
+        "ips/split_speed" => &["orig_ips/hud_expansion_opaque"],
+        "ips/reserve_backward_fill" => &["orig_ips/hud_expansion_opaque"],
+        "ips/savestate" => &["ips/map_progress_maintain"],
+        "ips/vanilla_fanfare_stop_sounds" => &["ips/fast_saves"],
+        "ips/itemsounds" => &["ips/fast_saves"],
+        // Applied during customization:
+        "ips/Area Palettes" => &["orig_ips/hud_expansion_opaque"],
+        "ips/samus_sprite" => &["orig_ips/hud_expansion_opaque"],
+        "ips/pause_menu_objectives" => &[
+            "orig_ips/hud_expansion_opaque",
+            "orig_ips/pause_menu_objectives",
+            "ips/map_area",
+            "ips/fast_pause_menu",
+        ],
+        _ => &[],
+    }
 }
 
 pub fn xy_to_map_offset(x: isize, y: isize) -> isize {
@@ -350,6 +414,115 @@ fn item_to_plm_type(item: Item, orig_plm_type: isize) -> isize {
     plm_table[item_container as usize][item_id as usize]
 }
 
+// RAM address and bitmask of the flag for completing an objective.
+pub fn get_objective_flag_bit(obj: Objective) -> (isize, isize) {
+    use Objective::*;
+    match obj {
+        Kraid => (0xD829, 1),
+        Ridley => (0xD82A, 1),
+        Phantoon => (0xD82B, 1),
+        Draygon => (0xD82C, 1),
+        SporeSpawn => (0xD829, 2),
+        Crocomire => (0xD82A, 2),
+        Botwoon => (0xD82C, 2),
+        GoldenTorizo => (0xD82A, 4),
+        MetroidRoom1 => (0xD822, 1),
+        MetroidRoom2 => (0xD822, 2),
+        MetroidRoom3 => (0xD822, 4),
+        MetroidRoom4 => (0xD822, 8),
+        BombTorizo => (0xD828, 4),
+        BowlingStatue => (0xD823, 1),
+        AcidChozoStatue => (0xD821, 0x10),
+        PitRoom => (0xD823, 2),
+        BabyKraidRoom => (0xD823, 4),
+        PlasmaRoom => (0xD823, 8),
+        MetalPiratesRoom => (0xD823, 0x10),
+    }
+}
+
+// Offsets of the words of the start location load station entry that are set by
+// `get_start_load_station_data`: room pointer, door pointer, screen X/Y, and Samus Y/X.
+pub const START_LOAD_STATION_OFFSETS: [usize; 6] = [0, 2, 6, 8, 10, 12];
+
+pub fn get_start_load_station_data(game_data: &GameData, loc: &StartLocation) -> [isize; 6] {
+    let room_addr = game_data.room_ptr_by_id[&loc.room_id];
+    let door_node_id = loc.door_load_node_id.unwrap_or(loc.node_id);
+    let (_, entrance_ptr) = game_data.reverse_door_ptr_pair_map[&(loc.room_id, door_node_id)];
+    let x_pixels = (loc.x * 16.0) as isize;
+    let y_pixels = (loc.y * 16.0) as isize - 24;
+    let mut screen_x = x_pixels & 0xFF00;
+    let mut screen_y = y_pixels & 0xFF00;
+    screen_x += (loc.camera_offset_x.unwrap_or(0.0) * 16.0) as isize;
+    screen_y += (loc.camera_offset_y.unwrap_or(0.0) * 16.0) as isize;
+    let samus_x = x_pixels - (screen_x + 0x80);
+    let samus_y = y_pixels - screen_y;
+    [
+        (room_addr & 0xFFFF) as isize,
+        (entrance_ptr.unwrap() & 0xFFFF) as isize,
+        screen_x,
+        screen_y,
+        ((samus_y as i16) as u16) as isize,
+        ((samus_x as i16) as u16) as isize,
+    ]
+}
+
+// Position (in 16x16 tiles, relative to the room) and PLM type of the PLM that spawns a locked door.
+pub fn get_locked_door_plm(door: &RoomGeometryDoor, door_type: DoorType) -> (usize, usize, usize) {
+    let (mut x, mut y) = match door.direction.as_str() {
+        "right" => (door.x * 16 + 14 - door.offset.unwrap_or(0), door.y * 16 + 6),
+        "left" => (door.x * 16 + 1 + door.offset.unwrap_or(0), door.y * 16 + 6),
+        "up" => (door.x * 16 + 6, door.y * 16 + 1 + door.offset.unwrap_or(0)),
+        "down" => (door.x * 16 + 6, door.y * 16 + 14 - door.offset.unwrap_or(0)),
+        _ => panic!("Unexpected door direction: {}", door.direction),
+    };
+    let plm_id = match (door_type, door.direction.as_str()) {
+        (DoorType::Yellow, "right") => 0xC85A,
+        (DoorType::Yellow, "left") => 0xC860,
+        (DoorType::Yellow, "down") => 0xC866,
+        (DoorType::Yellow, "up") => 0xC86C,
+        (DoorType::Green, "right") => 0xC872,
+        (DoorType::Green, "left") => 0xC878,
+        (DoorType::Green, "down") => 0xC87E,
+        (DoorType::Green, "up") => 0xC884,
+        (DoorType::Red, "right") => 0xC88A,
+        (DoorType::Red, "left") => 0xC890,
+        (DoorType::Red, "down") => 0xC896,
+        (DoorType::Red, "up") => 0xC89C,
+        (DoorType::Beam(_), "right") => 0xFCC0,
+        (DoorType::Beam(_), "left") => 0xFCC6,
+        (DoorType::Beam(_), "down") => 0xFCCC,
+        (DoorType::Beam(_), "up") => 0xFCD2,
+        (DoorType::Wall, "right") => 0xF5C0,
+        (DoorType::Wall, "left") => {
+            x -= 1;
+            0xF5C0
+        }
+        (DoorType::Wall, "down") => match door.offset {
+            Some(0) => 0xF5C4,
+            Some(1) => 0xF5C8,
+            Some(2) => 0xF5CC,
+            _ => panic!("unexpected door offset: {:?}", door.offset),
+        },
+        (DoorType::Wall, "up") => match door.offset {
+            Some(0) => {
+                y -= 1;
+                0xF5C4
+            }
+            Some(1) => {
+                y -= 2;
+                0xF5C8
+            }
+            Some(2) => {
+                y -= 3;
+                0xF5CC
+            }
+            _ => panic!("unexpected door offset: {:?}", door.offset),
+        },
+        (a, b) => panic!("Unexpected door type: {a:?} {b}"),
+    };
+    (x, y, plm_id)
+}
+
 fn write_credits_big_letter(rom: &mut Rom, letter: char, addr: usize) -> Result<()> {
     if letter <= 'P' {
         rom.write_u16(addr, letter as isize - 'A' as isize + 0x0020)?;
@@ -403,19 +576,44 @@ pub fn get_room_state_ptrs(rom: &Rom, room_ptr: usize) -> Result<Vec<(usize, usi
     }
 }
 
-pub fn apply_ips_patch(rom: &mut Rom, patch_path: &Path) -> Result<()> {
-    let patch_data = std::fs::read(patch_path)
-        .with_context(|| format!("Unable to read patch {}", patch_path.display()))?;
+pub fn apply_ips_patch(
+    rom: &mut Rom,
+    data_source: &dyn DataSource,
+    patch_path: &str,
+) -> Result<()> {
+    let patch_data = data_source
+        .read(patch_path)
+        .with_context(|| format!("Unable to read patch {patch_path}"))?;
     let patch = ips::Patch::parse(&patch_data)
-        .with_context(|| format!("Unable to parse patch {}", patch_path.display()))?;
+        .with_context(|| format!("Unable to parse patch {patch_path}"))?;
     for hunk in patch.hunks() {
         rom.write_n(hunk.offset(), hunk.payload())?;
     }
     Ok(())
 }
 
-fn apply_orig_ips_patches(rom: &mut Rom, settings: &RandomizerSettings) -> Result<()> {
-    let patches_dir = Path::new("../patches/ips/");
+// Add the labels of an IPS patch to the symbol table, if the patch was built with symbols.
+fn load_ips_patch_symbols(
+    symbols: &mut SymbolTable,
+    data_source: &dyn DataSource,
+    patch_name: &str,
+) -> Result<()> {
+    let sym_path = format!("patches/ips/{patch_name}.sym");
+    if let Ok(sym_data) = data_source.read(&sym_path) {
+        symbols
+            .load_wla(&String::from_utf8_lossy(&sym_data), patch_name)
+            .with_context(|| format!("Unable to parse symbols {sym_path}"))?;
+    }
+    Ok(())
+}
+
+fn apply_orig_ips_patches(
+    rom: &mut Rom,
+    data_source: &dyn DataSource,
+    settings: &RandomizerSettings,
+    write_log: &mut RomWriteLog,
+    symbols: &mut SymbolTable,
+) -> Result<()> {
     let mut patches: Vec<&'static str> = vec!["mb_barrier_clear", "mb_left_entrance", "gray_doors"];
     patches.push("hud_expansion_opaque");
 
@@ -428,8 +626,12 @@ fn apply_orig_ips_patches(rom: &mut Rom, settings: &RandomizerSettings) -> Resul
     }
 
     for patch_name in patches {
-        let patch_path = patches_dir.join(patch_name.to_string() + ".ips");
-        apply_ips_patch(rom, &patch_path)?;
+        let patch_path = format!("patches/ips/{patch_name}.ips");
+        let pass_name = format!("orig_ips/{patch_name}");
+        write_log.begin(rom, &pass_name, get_ips_patch_overrides(&pass_name))?;
+        apply_ips_patch(rom, data_source, &patch_path)?;
+        write_log.end(rom)?;
+        load_ips_patch_symbols(symbols, data_source, patch_name)?;
     }
 
     Ok(())
@@ -438,7 +640,6 @@ fn apply_orig_ips_patches(rom: &mut Rom, settings: &RandomizerSettings) -> Resul
 impl Patcher<'_> {
     fn apply_ips_patches(&mut self) -> Result<()> {
         self.rom.data.resize(0x400000, 0);
-        let patches_dir = Path::new("../patches/ips/");
         let mut patches = vec![
             "complementary_suits",
             "disable_map_icons",
@@ -774,8 +975,17 @@ impl Patcher<'_> {
         }
 
         for patch_name in patches {
-            let patch_path = patches_dir.join(patch_name.to_string() + ".ips");
-            apply_ips_patch(self.rom, &patch_path)?;
+            let patch_path = format!("patches/ips/{patch_name}.ips");
+            let pass_name = format!("ips/{patch_name}");
+            self.write_log
+                .begin(self.rom, &pass_name, get_ips_patch_overrides(&pass_name))?;
+            apply_ips_patch(self.rom, self.game_data.data_source().as_ref(), &patch_path)?;
+            self.write_log.end(self.rom)?;
+            load_ips_patch_symbols(
+                &mut self.symbols,
+                self.game_data.data_source().as_ref(),
+                patch_name,
+            )?;
         }
 
         // Write settings flags, e.g. for use by auto-tracking tools:
@@ -1156,6 +1366,11 @@ impl Patcher<'_> {
             );
             self.rom
                 .write_n(snes2pc(0x8F8000 | door_asm_free_space), asm)?;
+            self.symbols.add(
+                &format!("extra_door_asm_{door_ptr:05X}"),
+                snes2pc(0x8F8000 | door_asm_free_space),
+                asm.len() + 3,
+            );
             // Reserve 3 bytes for the JMP instruction to the original ASM (if applicable, or RTS otherwise):
             door_asm_free_space += asm.len() + 3;
         }
@@ -1200,9 +1415,16 @@ impl Patcher<'_> {
         // This is used to mark area transition arrows/letters in the other area's map.
         let table_ptr = 0x90FA00;
         let mut ptr = 0x90FA10;
+        self.symbols
+            .add("map_reveal_table", snes2pc(table_ptr), NUM_AREAS * 2);
         for area in 0..NUM_AREAS {
             self.rom
                 .write_u16(snes2pc(table_ptr + area * 2), ptr & 0xFFFF)?;
+            self.symbols.add(
+                &format!("map_reveal_tiles_area_{area}"),
+                snes2pc(ptr as usize),
+                self.map_reveal_bitmasks[area].len() * 4 + 4,
+            );
             for &(offset, bitmask) in &self.map_reveal_bitmasks[area] {
                 self.rom.write_u16(snes2pc(ptr as usize), offset as isize)?;
                 self.rom
@@ -1225,10 +1447,17 @@ impl Patcher<'_> {
         let gfx_bytes: Vec<u8> = font.gfx.iter().copied().flatten().collect();
         assert!(gfx_bytes.len() <= 0x300);
         self.rom.write_n(snes2pc(font_addr), &gfx_bytes)?;
+        self.symbols
+            .add("room_name_font_gfx", snes2pc(font_addr), gfx_bytes.len());
 
         let font_width_addr = 0xE3C300;
         assert!(font.widths.len() <= 0x60);
         self.rom.write_n(snes2pc(font_width_addr), &font.widths)?;
+        self.symbols.add(
+            "room_name_font_widths",
+            snes2pc(font_width_addr),
+            font.widths.len(),
+        );
 
         Ok(())
     }
@@ -1257,6 +1486,11 @@ impl Patcher<'_> {
             let pixel_offset = 128 - total_width / 2;
             self.rom.write_u8(snes2pc(addr), pixel_offset as isize)?;
             self.rom.write_n(snes2pc(addr + 1), &name_data)?;
+            self.symbols.add(
+                &format!("room_name_{room_ptr:05X}"),
+                snes2pc(addr),
+                name_data.len() + 1,
+            );
             let data = self.extra_room_data.get_mut(room_ptr).unwrap();
             data.room_name = (snes2pc(addr) & 0xFFFF) as u16;
             addr += name_data.len() + 1;
@@ -1372,6 +1606,9 @@ impl Patcher<'_> {
         map_patcher.apply_patches()?;
 
         let mut next_addr = 0xE48000;
+        // Dynamic tiles (for collected items and unlocked doors) are stored separately, after the
+        // per-area lists, to leave room for many locked doors:
+        let mut next_dynamic_addr = map_patcher.next_dynamic_tile_addr;
         for &room_ptr in &self.game_data.room_ptrs {
             let room_idx = self.game_data.room_idx_by_ptr[&room_ptr];
             if !self.map.room_mask[room_idx] {
@@ -1383,6 +1620,11 @@ impl Patcher<'_> {
             if map_patcher.room_map_gfx[&room_ptr].len() > 96 {
                 bail!("Too many map tiles in room {:x}", room_ptr);
             }
+            self.symbols.add(
+                &format!("map_tiles_{room_ptr:05X}"),
+                snes2pc(next_addr),
+                map_patcher.room_map_gfx[&room_ptr].len() * 2 + 2,
+            );
             for &x in &map_patcher.room_map_gfx[&room_ptr] {
                 map_patcher.rom.write_u16(snes2pc(next_addr), x as isize)?;
                 next_addr += 2;
@@ -1394,6 +1636,11 @@ impl Patcher<'_> {
 
             self.extra_room_data.get_mut(&room_ptr).unwrap().map_tilemap =
                 (next_addr & 0xFFFF) as u16;
+            self.symbols.add(
+                &format!("map_tilemap_{room_ptr:05X}"),
+                snes2pc(next_addr),
+                map_patcher.room_map_tilemap[&room_ptr].len() * 2,
+            );
             for &x in &map_patcher.room_map_tilemap[&room_ptr] {
                 map_patcher.rom.write_u16(snes2pc(next_addr), x as isize)?;
                 next_addr += 2;
@@ -1402,29 +1649,38 @@ impl Patcher<'_> {
             self.extra_room_data
                 .get_mut(&room_ptr)
                 .unwrap()
-                .dynamic_tiles = (next_addr & 0xFFFF) as u16;
+                .dynamic_tiles = (next_dynamic_addr & 0xFFFF) as u16;
+            self.symbols.add(
+                &format!("map_dynamic_tiles_{room_ptr:05X}"),
+                snes2pc(next_dynamic_addr),
+                map_patcher.room_map_dynamic_tiles[&room_ptr].len() * 6 + 2,
+            );
             map_patcher.rom.write_u16(
-                snes2pc(next_addr),
+                snes2pc(next_dynamic_addr),
                 map_patcher.room_map_dynamic_tiles[&room_ptr].len() as isize,
             )?;
-            next_addr += 2;
+            next_dynamic_addr += 2;
             for &(item_idx, offset, word) in &map_patcher.room_map_dynamic_tiles[&room_ptr] {
                 map_patcher
                     .rom
-                    .write_u8(snes2pc(next_addr), (item_idx as isize) >> 3)?; // item byte index
+                    .write_u8(snes2pc(next_dynamic_addr), (item_idx as isize) >> 3)?; // item byte index
+                map_patcher.rom.write_u8(
+                    snes2pc(next_dynamic_addr + 1),
+                    1 << ((item_idx as isize) & 7),
+                )?; // item bitmask
                 map_patcher
                     .rom
-                    .write_u8(snes2pc(next_addr + 1), 1 << ((item_idx as isize) & 7))?; // item bitmask
+                    .write_u16(snes2pc(next_dynamic_addr + 2), offset as isize)?; // tilemap offset
                 map_patcher
                     .rom
-                    .write_u16(snes2pc(next_addr + 2), offset as isize)?; // tilemap offset
-                map_patcher
-                    .rom
-                    .write_u16(snes2pc(next_addr + 4), word as isize)?; // tilemap word to write, once item bit is set
-                next_addr += 6;
+                    .write_u16(snes2pc(next_dynamic_addr + 4), word as isize)?; // tilemap word to write, once item bit is set
+                next_dynamic_addr += 6;
             }
         }
         assert!(next_addr <= 0xE50000);
+        if next_dynamic_addr > map_tiles::DYNAMIC_TILE_DATA_END {
+            bail!("Dynamic tile data overflow: {next_dynamic_addr:x}");
+        }
 
         Ok(())
     }
@@ -1895,58 +2151,57 @@ impl Patcher<'_> {
         Ok(())
     }
 
+    fn make_random_title_background(&self, rng: &mut rand::rngs::StdRng) -> Array3<u8> {
+        let mut img = Array3::<u8>::zeros((224, 256, 3));
+        let top_left_idx = rng.gen_range(0..self.game_data.title_screen_data.top_left.len());
+        let top_right_idx = rng.gen_range(0..self.game_data.title_screen_data.top_right.len());
+        let bottom_left_idx = rng.gen_range(0..self.game_data.title_screen_data.bottom_left.len());
+        let bottom_right_idx =
+            rng.gen_range(0..self.game_data.title_screen_data.bottom_right.len());
+
+        let top_left_slice = self.game_data.title_screen_data.top_left[top_left_idx]
+            .slice(ndarray::s![32..144, 0..128, ..]);
+        let top_right_slice = self.game_data.title_screen_data.top_right[top_right_idx]
+            .slice(ndarray::s![32..144, 128..256, ..]);
+        let bottom_left_slice = self.game_data.title_screen_data.bottom_left[bottom_left_idx]
+            .slice(ndarray::s![112..224, 0..128, ..]);
+        let bottom_right_slice = self.game_data.title_screen_data.bottom_right[bottom_right_idx]
+            .slice(ndarray::s![112..224, 128..256, ..]);
+
+        img.slice_mut(ndarray::s![0..112, 0..128, ..])
+            .assign(&top_left_slice);
+        img.slice_mut(ndarray::s![0..112, 128..256, ..])
+            .assign(&top_right_slice);
+        img.slice_mut(ndarray::s![112..224, 0..128, ..])
+            .assign(&bottom_left_slice);
+        img.slice_mut(ndarray::s![112..224, 128..256, ..])
+            .assign(&bottom_right_slice);
+
+        title::draw_map_station(&mut img, &self.game_data.title_screen_data.map_station);
+        img
+    }
+
     fn apply_title_screen_patches(&mut self) -> Result<()> {
         let mut rng_seed = [0u8; 32];
         rng_seed[..8].copy_from_slice(&self.randomization.seed.to_le_bytes());
         let mut rng = rand::rngs::StdRng::from_seed(rng_seed);
 
-        let mut img = Array3::<u8>::zeros((224, 256, 3));
         loop {
-            let top_left_idx = rng.gen_range(0..self.game_data.title_screen_data.top_left.len());
-            let top_right_idx = rng.gen_range(0..self.game_data.title_screen_data.top_right.len());
-            let bottom_left_idx =
-                rng.gen_range(0..self.game_data.title_screen_data.bottom_left.len());
-            let bottom_right_idx =
-                rng.gen_range(0..self.game_data.title_screen_data.bottom_right.len());
-
-            let top_left_slice = self.game_data.title_screen_data.top_left[top_left_idx]
-                .slice(ndarray::s![32..144, 0..128, ..]);
-            let top_right_slice = self.game_data.title_screen_data.top_right[top_right_idx]
-                .slice(ndarray::s![32..144, 128..256, ..]);
-            let bottom_left_slice = self.game_data.title_screen_data.bottom_left[bottom_left_idx]
-                .slice(ndarray::s![112..224, 0..128, ..]);
-            let bottom_right_slice = self.game_data.title_screen_data.bottom_right
-                [bottom_right_idx]
-                .slice(ndarray::s![112..224, 128..256, ..]);
-
-            img.slice_mut(ndarray::s![0..112, 0..128, ..])
-                .assign(&top_left_slice);
-            img.slice_mut(ndarray::s![0..112, 128..256, ..])
-                .assign(&top_right_slice);
-            img.slice_mut(ndarray::s![112..224, 0..128, ..])
-                .assign(&bottom_left_slice);
-            img.slice_mut(ndarray::s![112..224, 128..256, ..])
-                .assign(&bottom_right_slice);
-
-            let map = &self.game_data.title_screen_data.map_station;
-            for y in 0..224 {
-                for x in 0..256 {
-                    if map[(y, x, 0)] == 0 && map[(y, x, 1)] == 0 && map[(y, x, 2)] == 0 {
-                        continue;
-                    }
-                    img[(y, x, 0)] = map[(y, x, 0)];
-                    img[(y, x, 1)] = map[(y, x, 1)];
-                    img[(y, x, 2)] = map[(y, x, 2)];
-                }
-            }
-
+            // A custom image was already checked to be encodable, when it was loaded.
+            let img = match &self.customize_settings.title_background {
+                Some(img) => img.clone(),
+                None => self.make_random_title_background(&mut rng),
+            };
             let mut title_patcher = title::TitlePatcher::new(self.rom);
             let bg_result = title_patcher.patch_title_background(&img);
             if let Err(e) = bg_result {
+                if self.customize_settings.title_background.is_some() {
+                    return Err(e.context("Unable to use custom title screen image"));
+                }
                 info!("Failed title screen randomization: {}", e);
                 continue;
             }
-            title_patcher.patch_title_foreground()?;
+            title_patcher.patch_title_foreground(self.game_data.data_source().as_ref())?;
             title_patcher.patch_title_gradient()?;
             title_patcher.patch_title_blue_light()?;
             println!(
@@ -2381,30 +2636,12 @@ impl Patcher<'_> {
         self.rom.write_n(initial_boss_bits, &[0; 6])?;
 
         // Set starting room and Samus and camera starting location:
-        let loc = self.randomization.start_location.clone();
-        let room_addr = self.game_data.room_ptr_by_id[&loc.room_id];
-        let door_node_id = loc.door_load_node_id.unwrap_or(loc.node_id);
-        let (_, entrance_ptr) =
-            self.game_data.reverse_door_ptr_pair_map[&(loc.room_id, door_node_id)];
-        let x_pixels = (loc.x * 16.0) as isize;
-        let y_pixels = (loc.y * 16.0) as isize - 24;
-        let mut screen_x = x_pixels & 0xFF00;
-        let mut screen_y = y_pixels & 0xFF00;
-        screen_x += (loc.camera_offset_x.unwrap_or(0.0) * 16.0) as isize;
-        screen_y += (loc.camera_offset_y.unwrap_or(0.0) * 16.0) as isize;
-        let samus_x = x_pixels - (screen_x + 0x80);
-        let samus_y = y_pixels - screen_y;
         let station_addr = snes2pc(0x80C4E1);
-        self.rom
-            .write_u16(station_addr, (room_addr & 0xFFFF) as isize)?;
-        self.rom
-            .write_u16(station_addr + 2, (entrance_ptr.unwrap() & 0xFFFF) as isize)?;
-        self.rom.write_u16(station_addr + 6, screen_x)?;
-        self.rom.write_u16(station_addr + 8, screen_y)?;
-        self.rom
-            .write_u16(station_addr + 10, ((samus_y as i16) as u16) as isize)?;
-        self.rom
-            .write_u16(station_addr + 12, ((samus_x as i16) as u16) as isize)?;
+        let station_data =
+            get_start_load_station_data(self.game_data, &self.randomization.start_location);
+        for (offset, value) in iter::zip(START_LOAD_STATION_OFFSETS, station_data) {
+            self.rom.write_u16(station_addr + offset, value)?;
+        }
         Ok(())
     }
 
@@ -2558,81 +2795,35 @@ impl Patcher<'_> {
         Ok(())
     }
 
-    fn apply_single_locked_door(&mut self, locked_door: LockedDoor, state_index: u8) -> Result<()> {
+    fn apply_single_locked_door(
+        &mut self,
+        locked_door: LockedDoor,
+        locked_door_idx: usize,
+    ) -> Result<()> {
+        let state_index = self.locked_door_state_indices[locked_door_idx];
         let (room_idx, door_idx) =
             self.game_data.room_and_door_idxs_by_door_ptr_pair[&locked_door.src_ptr_pair];
         let room = &self.game_data.room_geometry[room_idx];
         let door = &room.doors[door_idx];
-        let (mut x, mut y) = match door.direction.as_str() {
-            "right" => (door.x * 16 + 14 - door.offset.unwrap_or(0), door.y * 16 + 6),
-            "left" => (door.x * 16 + 1 + door.offset.unwrap_or(0), door.y * 16 + 6),
-            "up" => (door.x * 16 + 6, door.y * 16 + 1 + door.offset.unwrap_or(0)),
-            "down" => (door.x * 16 + 6, door.y * 16 + 14 - door.offset.unwrap_or(0)),
-            _ => panic!("Unexpected door direction: {}", door.direction),
-        };
-        let plm_id = match (locked_door.door_type, door.direction.as_str()) {
-            (DoorType::Yellow, "right") => 0xC85A,
-            (DoorType::Yellow, "left") => 0xC860,
-            (DoorType::Yellow, "down") => 0xC866,
-            (DoorType::Yellow, "up") => 0xC86C,
-            (DoorType::Green, "right") => 0xC872,
-            (DoorType::Green, "left") => 0xC878,
-            (DoorType::Green, "down") => 0xC87E,
-            (DoorType::Green, "up") => 0xC884,
-            (DoorType::Red, "right") => 0xC88A,
-            (DoorType::Red, "left") => 0xC890,
-            (DoorType::Red, "down") => 0xC896,
-            (DoorType::Red, "up") => 0xC89C,
-            (DoorType::Beam(_), "right") => 0xFCC0,
-            (DoorType::Beam(_), "left") => 0xFCC6,
-            (DoorType::Beam(_), "down") => 0xFCCC,
-            (DoorType::Beam(_), "up") => 0xFCD2,
-            (DoorType::Wall, "right") => 0xF5C0,
-            (DoorType::Wall, "left") => {
-                x -= 1;
-                0xF5C0
-            }
-            (DoorType::Wall, "down") => match door.offset {
-                Some(0) => 0xF5C4,
-                Some(1) => 0xF5C8,
-                Some(2) => 0xF5CC,
-                _ => panic!("unexpected door offset: {:?}", door.offset),
-            },
-            (DoorType::Wall, "up") => match door.offset {
-                Some(0) => {
-                    y -= 1;
-                    0xF5C4
-                }
-                Some(1) => {
-                    y -= 2;
-                    0xF5C8
-                }
-                Some(2) => {
-                    y -= 3;
-                    0xF5CC
-                }
-                _ => panic!("unexpected door offset: {:?}", door.offset),
-            },
-            (a, b) => panic!("Unexpected door type: {a:?} {b}"),
-        };
+        let (x, y, plm_id) = get_locked_door_plm(door, locked_door.door_type);
         // TODO: Instead of using extra setup ASM to spawn the doors, it might be better to just rewrite
         // the room PLM list, to add the new door PLMs.
         let mut write_asm = |room_ptr: usize, x: usize, y: usize| {
-            self.extra_setup_asm
-                .entry(room_ptr)
-                .or_default()
-                .extend(vec![
-                    0x22,
-                    0x80,
-                    0xF3,
-                    0x84, // JSL $84F380  (Spawn hard-coded PLM with room argument)
-                    x as u8,
-                    y as u8, // X and Y coordinates in 16x16 tiles
-                    (plm_id & 0x00FF) as u8,
-                    (plm_id >> 8) as u8,
-                    state_index,
-                    0x00, // PLM argument (index for door unlock state)
-                ]);
+            let setup_asm = self.extra_setup_asm.entry(room_ptr).or_default();
+            self.locked_door_setup_asm
+                .push((locked_door_idx, room_ptr, setup_asm.len()));
+            setup_asm.extend(vec![
+                0x22,
+                0x80,
+                0xF3,
+                0x84, // JSL $84F380  (Spawn hard-coded PLM with room argument)
+                x as u8,
+                y as u8, // X and Y coordinates in 16x16 tiles
+                (plm_id & 0x00FF) as u8,
+                (plm_id >> 8) as u8,
+                (state_index & 0xFF) as u8,
+                (state_index >> 8) as u8, // PLM argument (index for door unlock state)
+            ]);
             if let DoorType::Beam(beam) = locked_door.door_type {
                 let gfx_base_addr = if door.direction == "right" || door.direction == "left" {
                     (2 * (beam as usize)) * 0x498
@@ -2683,17 +2874,8 @@ impl Patcher<'_> {
         Ok(())
     }
 
-    fn assign_locked_door_states(&mut self) {
-        // PLM arguments used for gray door states (we reserve all of them even though not all are used)
-        let reserved_state_indexes: HashSet<usize> = [
-            0x2, 0x3, 0x4, 0x6, 0x7, 0x8, 0x9, 0xa, 0xb, 0x11, 0x12, 0x14, 0x15, 0x16, 0x17, 0x18,
-            0x19, 0x1a, 0x1b, 0x1c, 0x24, 0x25, 0x2c, 0x2d, 0x2e, 0x2f, 0x31, 0x36, 0x37, 0x3e,
-            0x40, 0x41, 0x42, 0x43, 0x46, 0x47, 0x48, 0x4f, 0x50, 0x59, 0x5a, 0x5b, 0x5d, 0x60,
-            0x80, 0x81, 0x82, 0x83, 0x86, 0x87, 0x88, 0x89, 0x8a, 0x91, 0x93, 0x97, 0x9c, 0x9d,
-            0x9e, 0x9f, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xaa, 0xab, 0xac,
-        ]
-        .into_iter()
-        .collect();
+    fn assign_locked_door_states(&mut self) -> Result<()> {
+        let reserved_state_indexes: HashSet<usize> = GRAY_DOOR_STATE_INDICES.into_iter().collect();
         let mut next_state_index: usize = 0;
         let mut state_idxs: Vec<usize> = vec![];
 
@@ -2704,11 +2886,19 @@ impl Patcher<'_> {
             state_idxs.push(next_state_index);
             next_state_index += 1;
         }
+        // Door unlock bits are stored at $7ED8B0 through $7ED8EF:
+        if next_state_index > MAX_DOOR_STATES {
+            bail!(
+                "Too many locked doors: {} (at most {MAX_LOCKED_DOORS} are supported)",
+                self.randomization.locked_doors.len()
+            );
+        }
         self.locked_door_state_indices = state_idxs;
+        Ok(())
     }
 
     fn apply_locked_doors(&mut self) -> Result<()> {
-        self.assign_locked_door_states();
+        self.assign_locked_door_states()?;
         for (i, door) in self.randomization.locked_doors.iter().enumerate() {
             let mut door = *door;
             if door.door_type == DoorType::Gray {
@@ -2716,10 +2906,10 @@ impl Patcher<'_> {
                 // TODO: simplify things by just handling it here?
                 continue;
             }
-            self.apply_single_locked_door(door, self.locked_door_state_indices[i] as u8)?;
+            self.apply_single_locked_door(door, i)?;
             if door.bidirectional {
                 std::mem::swap(&mut door.src_ptr_pair, &mut door.dst_ptr_pair);
-                self.apply_single_locked_door(door, self.locked_door_state_indices[i] as u8)?;
+                self.apply_single_locked_door(door, i)?;
             }
         }
         Ok(())
@@ -2746,6 +2936,25 @@ impl Patcher<'_> {
             let mut asm = asm.clone();
             asm.push(0x60); // RTS
             self.rom.write_n(next_addr, &asm)?;
+            self.symbols.add(
+                &format!("extra_setup_asm_{room_ptr:05X}"),
+                next_addr,
+                asm.len(),
+            );
+            for &(door_idx, _, offset) in self
+                .locked_door_setup_asm
+                .iter()
+                .filter(|x| x.1 == room_ptr)
+            {
+                self.symbols.add(
+                    &format!(
+                        "locked_door_{door_idx}_state_{:02X}_{room_ptr:05X}",
+                        self.locked_door_state_indices[door_idx]
+                    ),
+                    next_addr + offset,
+                    0,
+                );
+            }
             self.extra_room_data
                 .get_mut(&room_ptr)
                 .unwrap()
@@ -2758,185 +2967,22 @@ impl Patcher<'_> {
         Ok(())
     }
 
-    fn write_walljump_item_graphics(&mut self) -> Result<()> {
-        let b = 0x8;
-        let w = 0xc;
-        let frame_1: [[u8; 16]; 16] = [
-            [0, 0, 0, b, b, b, b, b, b, b, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, b, 4, 4, 5, 6, 6, b, b, b, b, 0, 0, 0],
-            [0, 0, 0, b, 4, 5, 6, 6, b, b, 6, 4, b, 0, 0, 0],
-            [0, 0, 0, b, 5, 6, 6, b, b, 6, 4, 5, b, 0, 0, 0],
-            [0, 0, 0, b, 5, 6, b, b, 6, 4, 5, b, b, 0, 0, 0],
-            [0, 0, 0, b, 6, 6, b, 6, 4, 5, 6, b, 0, 0, 0, 0],
-            [0, 0, b, b, b, b, 6, b, 6, 5, b, 0, 0, 0, 0, 0],
-            [0, 0, b, 5, 6, 6, b, 6, b, 6, b, 0, 0, 0, 0, 0],
-            [0, 0, b, 4, 5, 6, b, 5, 6, b, b, 0, 0, 0, 0, 0],
-            [0, 0, b, b, 6, 5, b, 6, b, b, 0, 0, 0, 0, 0, 0],
-            [0, b, 6, b, b, 5, b, b, b, 6, b, 0, 0, 0, 0, 0],
-            [b, b, 6, 6, b, b, b, 6, 4, 5, b, b, 0, 0, 0, 0],
-            [b, 6, 6, 4, 6, b, 6, 6, 6, 6, 6, 6, b, b, 0, 0],
-            [b, 6, 4, 4, 6, b, 5, 5, 5, 4, 4, 4, 5, 5, b, b],
-            [b, 6, 5, 5, 6, b, b, b, 6, 6, 6, 6, 6, 6, 6, b],
-            [b, b, b, b, b, 0, 0, 0, b, b, b, b, b, b, b, b],
-        ];
-        let frame_2 = frame_1.map(|row| {
-            row.map(|x| match x {
-                4 => w,
-                5 => 4,
-                6 => 5,
-                7 => 6,
-                x if x == b => 7,
-                x => x,
-            })
-        });
-        let frames: [[[u8; 16]; 16]; 2] = [frame_1, frame_2];
-        let mut addr = snes2pc(0x899100);
-        for f in &frames {
-            for tile_y in 0..2 {
-                for tile_x in 0..2 {
-                    let mut tile: [[u8; 8]; 8] = [[0; 8]; 8];
-                    for y in 0..8 {
-                        for x in 0..8 {
-                            tile[y][x] = f[tile_y * 8 + y][tile_x * 8 + x];
-                        }
-                    }
-                    write_tile_4bpp(self.rom, addr, tile)?;
-                    addr += 32;
-                }
-            }
-        }
-        Ok(())
-    }
-
-    fn write_spark_booster_item_graphics(&mut self) -> Result<()> {
-        let w = 0xc;
-        let frame_1: [[u8; 16]; 16] = [
-            [0, 7, 6, 5, 5, 4, 4, w, 4, 4, 5, 5, 6, 7, 0, 0],
-            [0, 7, 7, 7, 6, 6, 5, 5, 5, 6, 6, 7, 7, 7, 0, 0],
-            [0, 0, 7, 6, 5, 5, 4, 4, 4, 5, 5, 6, 7, 0, 0, 0],
-            [0, 7, 6, 5, 5, 4, 4, 3, 4, 4, 5, 5, 6, 7, 0, 0],
-            [0, 7, 6, 5, 5, 4, 3, 1, 3, 4, 5, 5, 6, 7, 0, 0],
-            [0, 7, 6, 5, 5, 4, 3, 1, 3, 3, 5, 5, 6, 7, 0, 0],
-            [0, 7, 6, 5, 5, 3, 1, w, 1, 3, 5, 5, 6, 7, 0, 0],
-            [0, 7, 6, 5, 5, 3, 1, 1, w, 3, 3, 5, 6, 7, 0, 0],
-            [0, 7, 6, 5, 3, 1, 1, 1, 1, 1, 3, 5, 6, 7, 0, 0],
-            [0, 7, 6, 5, 3, 1, 3, 3, 3, 1, 3, 3, 6, 7, 0, 0],
-            [0, 7, 6, 3, 1, 3, 3, w, 3, 3, 1, 3, 6, 7, 0, 0],
-            [0, 7, 6, 3, 3, 3, 4, w, 4, 3, 3, 3, 6, 7, 0, 0],
-            [0, 7, 6, 5, 5, 4, 4, w, 4, 4, 5, 5, 6, 7, 0, 0],
-            [0, 0, 7, 7, 6, 6, 5, 5, 5, 6, 6, 7, 7, 0, 0, 0],
-            [0, 7, 5, 6, 5, 5, 4, w, 4, 5, 5, 6, 5, 7, 0, 0],
-            [0, 7, 6, 7, 7, 7, 5, 4, 5, 7, 7, 7, 6, 7, 0, 0],
-        ];
-        let frame_2: [[u8; 16]; 16] = [
-            [0, 7, 6, 5, 5, 4, 4, w, 4, 4, 5, 5, 6, 7, 0, 0],
-            [0, 7, 7, 7, 6, 6, 5, 5, 5, 6, 6, 7, 7, 7, 0, 0],
-            [0, 0, 7, 6, 5, 5, 4, 4, 4, 5, 5, 6, 7, 0, 0, 0],
-            [0, 7, 6, 5, 5, 4, 4, 3, 4, 4, 5, 5, 6, 7, 0, 0],
-            [0, 7, 6, 5, 5, 4, 3, w, 3, 4, 5, 5, 6, 7, 0, 0],
-            [0, 7, 6, 5, 5, 4, 3, 1, 3, 3, 5, 5, 6, 7, 0, 0],
-            [0, 7, 6, 5, 5, 3, 1, w, w, 3, 5, 5, 6, 7, 0, 0],
-            [0, 7, 6, 5, 5, 3, w, 1, w, 3, 3, 5, 6, 7, 0, 0],
-            [0, 7, 6, 5, 3, w, 1, w, 1, w, 3, 5, 6, 7, 0, 0],
-            [0, 7, 6, 5, 3, w, 3, 3, 3, w, 3, 3, 6, 7, 0, 0],
-            [0, 7, 6, 3, w, 3, 3, w, 3, 3, w, 3, 6, 7, 0, 0],
-            [0, 7, 6, 3, 3, 3, 4, w, 4, 3, 3, 3, 6, 7, 0, 0],
-            [0, 7, 6, 5, 5, 4, 4, w, 4, 4, 5, 5, 6, 7, 0, 0],
-            [0, 0, 7, 7, 6, 6, 5, 5, 5, 6, 6, 7, 7, 0, 0, 0],
-            [0, 7, 5, 6, 5, 5, 4, w, 4, 5, 5, 6, 5, 7, 0, 0],
-            [0, 7, 6, 7, 7, 7, 5, 4, 5, 7, 7, 7, 6, 7, 0, 0],
-        ];
-        let frames: [[[u8; 16]; 16]; 2] = [frame_1, frame_2];
-        let mut addr = snes2pc(0x899600);
-        for f in &frames {
-            for tile_y in 0..2 {
-                for tile_x in 0..2 {
-                    let mut tile: [[u8; 8]; 8] = [[0; 8]; 8];
-                    for y in 0..8 {
-                        for x in 0..8 {
-                            tile[y][x] = f[tile_y * 8 + y][tile_x * 8 + x];
-                        }
-                    }
-                    write_tile_4bpp(self.rom, addr, tile)?;
-                    addr += 32;
-                }
-            }
-        }
-        Ok(())
-    }
-
-    fn write_bluebooster_item_graphics(&mut self) -> Result<()> {
-        let x = 0xc; // white for palette 0
-        let w = 0xe; // white for palette 3
-        let frame_1: [[u8; 16]; 16] = [
-            [0, 0, 0, 0, 0, 0, 3, 3, 3, 2, 2, 2, 1, 1, 1, 0],
-            [0, 0, 0, 0, 3, 3, 3, 2, 3, 2, 1, 2, 1, w, 1, 0],
-            [0, 0, 3, 3, 3, 2, 2, 3, 3, 1, 2, 2, w, 1, 1, 0],
-            [0, 3, 3, 2, 1, 2, 3, 3, 1, 2, 2, w, 1, 1, 0, 0],
-            [3, 2, 2, 1, 2, 2, 3, 1, 1, 2, w, w, 1, 0, 0, 0],
-            [0, 3, 3, 2, 2, 2, 3, 3, 1, 2, 2, w, 1, 1, 0, 0],
-            [0, 0, 0, 3, 3, 2, 2, 3, 3, 1, 2, 2, w, 1, 1, 0],
-            [0, 0, 0, 0, 0, 3, 3, 2, 3, 2, 1, 2, 1, w, 1, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 7, 7, 6, 6, 6, 6, 6, 6, 7, 7, 0, 0, 0],
-            [0, 0, 0, 0, 6, 5, 4, x, x, 4, 5, 6, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 7, 7, 6, 6, 7, 7, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 6, 5, 4, x, x, 4, 5, 6, 0, 0, 0, 0],
-            [0, 0, 0, 7, 7, 6, 6, 6, 6, 6, 6, 7, 7, 0, 0, 0],
-            [0, 7, 4, 5, 6, 5, 4, x, x, 4, 5, 6, 5, 4, 7, 0],
-            [0, 7, 5, 7, 7, 7, 7, 4, 4, 7, 7, 7, 7, 5, 7, 0],
-        ];
-        let frame_2: [[u8; 16]; 16] = [
-            [0, 0, 0, 0, 0, 0, 3, 3, 3, 2, 2, 2, 1, 1, 1, 0],
-            [0, 0, 0, 0, 3, 3, 3, 1, 3, 2, 1, 2, 1, 1, 1, 0],
-            [0, 0, 3, 3, 3, 1, 1, 3, 3, w, 2, 2, w, 1, 1, 0],
-            [0, 3, 3, 1, 1, 2, 3, 3, 1, 2, 2, 1, 1, 1, 0, 0],
-            [3, 1, 2, 1, 2, 1, 3, 1, w, 2, 1, w, 1, 0, 0, 0],
-            [0, 3, 3, 2, 1, 2, 3, 3, 1, 2, 2, w, 1, 1, 0, 0],
-            [0, 0, 0, 3, 3, 1, 1, 3, 3, 1, 2, 2, 1, 1, 1, 0],
-            [0, 0, 0, 0, 0, 3, 3, 1, 3, 2, w, 2, 1, w, 1, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 7, 7, 6, 6, 6, 6, 6, 6, 7, 7, 0, 0, 0],
-            [0, 0, 0, 0, 6, 5, 4, x, x, 4, 5, 6, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 7, 7, 6, 6, 7, 7, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 6, 5, 4, x, x, 4, 5, 6, 0, 0, 0, 0],
-            [0, 0, 0, 7, 7, 6, 6, 6, 6, 6, 6, 7, 7, 0, 0, 0],
-            [0, 7, 4, 5, 6, 5, 4, x, x, 4, 5, 6, 5, 4, 7, 0],
-            [0, 7, 5, 7, 7, 7, 7, 4, 4, 7, 7, 7, 7, 5, 7, 0],
-        ];
-        let frames: [[[u8; 16]; 16]; 2] = [frame_1, frame_2];
-        let mut addr = snes2pc(0x899700);
-        for f in &frames {
-            for tile_y in 0..2 {
-                for tile_x in 0..2 {
-                    let mut tile: [[u8; 8]; 8] = [[0; 8]; 8];
-                    for y in 0..8 {
-                        for x in 0..8 {
-                            tile[y][x] = f[tile_y * 8 + y][tile_x * 8 + x];
-                        }
-                    }
-                    write_tile_4bpp(self.rom, addr, tile)?;
-                    addr += 32;
-                }
+    fn write_custom_item_graphics(&mut self) -> Result<()> {
+        let item_graphics = load_item_graphics(self.game_data.data_source().as_ref())?;
+        for gfx in item_graphics {
+            let addr = snes2pc(gfx.snes_addr);
+            self.symbols.add(
+                &format!("item_gfx_{}", gfx.name),
+                addr,
+                gfx.tiles.len() * 32,
+            );
+            for (i, &tile) in gfx.tiles.iter().enumerate() {
+                write_tile_4bpp(self.rom, addr + i * 32, tile)?;
             }
         }
         Ok(())
     }
 
-    fn write_nothing_item_graphics(&mut self) -> Result<()> {
-        // Used in Bomb Torizo Room.
-        self.rom.write_n(snes2pc(0x89B100), &[0; 0x100])?;
-        Ok(())
-    }
-
-    fn write_custom_item_graphics(&mut self) -> Result<()> {
-        self.write_walljump_item_graphics()?;
-        self.write_spark_booster_item_graphics()?;
-        self.write_bluebooster_item_graphics()?;
-        self.write_nothing_item_graphics()?;
-        Ok(())
-    }
-
     fn apply_room_outline(&mut self, room_idx: usize, room_ptr: usize) -> Result<()> {
         let room = &self.game_data.room_geometry[room_idx];
         let room_x = self.rom.read_u8(room.rom_address + 2)?;
@@ -3069,6 +3115,11 @@ impl Patcher<'_> {
         ];
         let free_space_addr = snes2pc(0xEA8000);
         let gfx_size = 0x498; // Size of graphics + tilemaps per combination of beam type and orientation
+        // The dynamic tile data region follows the beam door data in the same bank:
+        assert!(
+            free_space_addr + beam_palettes.len() * 2 * gfx_size
+                <= snes2pc(map_tiles::DYNAMIC_TILE_TABLE_ADDR)
+        );
 
         // Tilemap (16x16 tiles) indexed by orientation (0=horizontal, 1=vertical), then beam (0..5):
         // each address points to data for four 16x16 tiles, half of which is standard door frame and half of which is beam stuff
@@ -3231,6 +3282,8 @@ impl Patcher<'_> {
         for (&room_ptr, data) in sorted_hashmap_iter(&self.extra_room_data) {
             let addr = next_addr;
             next_addr += 11;
+            self.symbols
+                .add(&format!("extra_room_data_{room_ptr:05X}"), addr, 11);
             // Write "extra room data", which is basically an extension of the room header:
             self.rom.write_u8(addr, data.map_area as isize)?;
             self.rom
@@ -3500,6 +3553,11 @@ impl Patcher<'_> {
         }
 
         assert!(addr < snes2pc(0xB6F660));
+        self.symbols.add(
+            "objective_screen_tilemap",
+            snes2pc(0xB6F200),
+            addr - snes2pc(0xB6F200),
+        );
 
         if self.settings.quality_of_life_settings.fast_pause_menu {
             self.rom.write_u16(
@@ -3520,30 +3578,16 @@ impl Patcher<'_> {
         obj_coords_vec.sort();
         for (obj_i, coords) in obj_coords_vec.iter().enumerate() {
             let obj = obj_coords[coords];
-            let (addr, mask) = match obj {
-                Kraid => (0xD829, 1),
-                Ridley => (0xD82A, 1),
-                Phantoon => (0xD82B, 1),
-                Draygon => (0xD82C, 1),
-                SporeSpawn => (0xD829, 2),
-                Crocomire => (0xD82A, 2),
-                Botwoon => (0xD82C, 2),
-                GoldenTorizo => (0xD82A, 4),
-                MetroidRoom1 => (0xD822, 1),
-                MetroidRoom2 => (0xD822, 2),
-                MetroidRoom3 => (0xD822, 4),
-                MetroidRoom4 => (0xD822, 8),
-                BombTorizo => (0xD828, 4),
-                BowlingStatue => (0xD823, 1),
-                AcidChozoStatue => (0xD821, 0x10),
-                PitRoom => (0xD823, 2),
-                BabyKraidRoom => (0xD823, 4),
-                PlasmaRoom => (0xD823, 8),
-                MetalPiratesRoom => (0xD823, 0x10),
-            };
+            let (addr, mask) = get_objective_flag_bit(obj);
             self.rom.write_u16(snes2pc(0x8FEBC0) + obj_i * 2, addr)?;
             self.rom.write_u16(snes2pc(0x8FEBE8) + obj_i * 2, mask)?;
         }
+        let num_objectives = self.randomization.objectives.len();
+        self.symbols.add("objective_count", snes2pc(0x82FFFC), 2);
+        self.symbols
+            .add("objective_addrs", snes2pc(0x8FEBC0), num_objectives * 2);
+        self.symbols
+            .add("objective_masks", snes2pc(0x8FEBE8), num_objectives * 2);
 
         Ok(())
     }
@@ -3656,10 +3700,123 @@ pub fn make_rom(
     samus_sprite_categories: &[SamusSpriteCategory],
     mosaic_themes: &[MosaicTheme],
 ) -> Result<Rom> {
+    let (rom, build_info) = make_rom_with_info(
+        base_rom,
+        randomizer_settings,
+        customize_settings,
+        randomization,
+        game_data,
+        samus_sprite_categories,
+        mosaic_themes,
+        ConflictPolicy::Warn,
+    )?;
+    build_info.write_log.warn_conflicts();
+    Ok(rom)
+}
+
+// Information about how a ROM was generated, beyond the ROM itself.
+pub struct RomBuildInfo {
+    // Which patch pass wrote each part of the ROM (unless generated with `ConflictPolicy::Ignore`):
+    pub write_log: RomWriteLog,
+    // Debugger symbols for the generated data and the IPS patches:
+    pub symbols: SymbolTable,
+}
+
+// Generate the ROM, also returning the information about how it was built.
+#[allow(clippy::too_many_arguments)]
+pub fn make_rom_with_info(
+    base_rom: &Rom,
+    randomizer_settings: &RandomizerSettings,
+    customize_settings: &CustomizeSettings,
+    randomization: &Randomization,
+    game_data: &GameData,
+    samus_sprite_categories: &[SamusSpriteCategory],
+    mosaic_themes: &[MosaicTheme],
+    conflict_policy: ConflictPolicy,
+) -> Result<(Rom, RomBuildInfo)> {
+    let (rom, build_info, _) = generate_rom(
+        base_rom,
+        randomizer_settings,
+        customize_settings,
+        randomization,
+        game_data,
+        samus_sprite_categories,
+        mosaic_themes,
+        conflict_policy,
+        false,
+    )?;
+    Ok((rom, build_info))
+}
+
+// A zero-filled stand-in for the vanilla ROM, with only the item PLMs filled in (as plain energy
+// tanks), since item placement depends on the original PLM types.
+pub fn make_stand_in_rom(game_data: &GameData) -> Result<Rom> {
+    let mut rom = Rom::new(vec![0; 0x300000]);
+    for loc in &game_data.item_locations {
+        rom.write_u16(game_data.node_ptr_map[loc], 0xEED7)?;
+    }
+    Ok(rom)
+}
+
+// Generate a ROM starting from the stand-in for the vanilla ROM, returning the journal
+// of writes made. Passes that fail without the vanilla data (or, with `ConflictPolicy::Strict`,
+// on a conflict) are skipped and listed in the journal.
+pub fn make_rom_journal(
+    randomizer_settings: &RandomizerSettings,
+    customize_settings: &CustomizeSettings,
+    randomization: &Randomization,
+    game_data: &GameData,
+    conflict_policy: ConflictPolicy,
+) -> Result<RomJournal> {
+    let mut base_rom = make_stand_in_rom(game_data)?;
+    base_rom.enable_journal();
+    let (rom, _, failures) = generate_rom(
+        &base_rom,
+        randomizer_settings,
+        customize_settings,
+        randomization,
+        game_data,
+        &[],
+        &[],
+        conflict_policy,
+        true,
+    )?;
+    Ok(RomJournal {
+        writes: rom.journal.unwrap_or_default(),
+        failed_passes: failures
+            .into_iter()
+            .map(|(name, err)| (name.to_string(), format!("{err:#}")))
+            .collect(),
+    })
+}
+
+type PassFailures = Vec<(&'static str, anyhow::Error)>;
+
+#[allow(clippy::too_many_arguments)]
+fn generate_rom(
+    base_rom: &Rom,
+    randomizer_settings: &RandomizerSettings,
+    customize_settings: &CustomizeSettings,
+    randomization: &Randomization,
+    game_data: &GameData,
+    samus_sprite_categories: &[SamusSpriteCategory],
+    mosaic_themes: &[MosaicTheme],
+    conflict_policy: ConflictPolicy,
+    keep_going: bool,
+) -> Result<(Rom, RomBuildInfo, PassFailures)> {
+    let mut write_log = RomWriteLog::new(conflict_policy);
+    let mut symbols = SymbolTable::default();
     let mut orig_rom = base_rom.clone();
     // clear_free_space(&mut orig_rom)?;
-    apply_orig_ips_patches(&mut orig_rom, randomizer_settings)?;
+    apply_orig_ips_patches(
+        &mut orig_rom,
+        game_data.data_source().as_ref(),
+        randomizer_settings,
+        &mut write_log,
+        &mut symbols,
+    )?;
 
+    write_log.begin(&mut orig_rom, "orig_rom_fixes", &[])?;
     // Remove solid wall that spawns in Tourian Escape Room 1 while coming through right door.
     // Note that this wall spawns in two ways: 1) as a normal PLM which spawns when entering through either door
     // (and which we remove in `remove_non_blue_doors`), and 2) as a door cap closing when coming in from the right
@@ -3670,6 +3827,7 @@ pub fn make_rom(
     // Even though there is no door cap closing animation, we need to move the door cap X out of the way to the left,
     // otherwise corrupts the hazard marker PLM somehow:
     orig_rom.write_u8(snes2pc(0x83AA90), 0x1E)?; // Door cap X = 0x1E
+    write_log.end(&mut orig_rom)?;
 
     let mut rom = orig_rom.clone();
     let starting_items = get_starting_items(randomizer_settings);
@@ -3689,77 +3847,227 @@ pub fn make_rom(
         nothing_item_bitmask: [0; 0x40],
         map_reveal_bitmasks: vec![vec![]; NUM_AREAS],
         extra_room_data: HashMap::new(),
+        locked_door_setup_asm: vec![],
+        write_log,
+        symbols,
+    };
+
+    // Passes run in the order added here, except as needed to satisfy declared dependencies.
+    let qol = &randomizer_settings.quality_of_life_settings;
+    let mut passes: PassRegistry<Patcher> = PassRegistry::default();
+    passes
+        .add("apply_ips_patches", |p| p.apply_ips_patches())
+        .overrides(&["ips/disableable_etanks", "ips/escape_items"]);
+    passes
+        .add("init_extra_room_data", |p| p.init_extra_room_data())
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("place_items", |p| p.place_items())
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("set_start_location", |p| p.set_start_location())
+        .overrides(&["ips/new_game"])
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("set_starting_items", |p| p.set_starting_items())
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("fix_save_stations", |p| p.fix_save_stations())
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("write_map_areas", |p| p.write_map_areas())
+        .after(&["init_extra_room_data"]);
+    passes
+        .add("make_map_revealed", |p| p.make_map_revealed())
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("write_beam_door_tiles", |p| p.write_beam_door_tiles())
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("apply_locked_doors", |p| p.apply_locked_doors())
+        .after(&["write_beam_door_tiles"]);
+    passes
+        .add("apply_map_tile_patches", |p| p.apply_map_tile_patches())
+        .overrides(&[
+            "orig_ips/hud_expansion_opaque",
+            "orig_ips/pause_menu_objectives",
+            "ips/pause_menu_objectives",
+            "ips/map_area",
+            "ips/max_ammo_display_fast",
+            "ips/missile_refill_all",
+            "ips/reserve_backward_fill",
+            "ips/split_speed",
+        ])
+        .after(&["write_map_areas", "apply_locked_doors"]);
+    passes
+        .add("prepare_extra_door_asm", |p| p.prepare_extra_door_asm())
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("write_door_data", |p| p.write_door_data())
+        .overrides(&["orig_rom_fixes", "orig_ips/mb_left_entrance"])
+        .after(&["prepare_extra_door_asm", "apply_locked_doors"]);
+    passes
+        .add("write_map_reveal_tiles", |p| p.write_map_reveal_tiles())
+        .after(&["apply_map_tile_patches"]);
+    passes
+        .add("write_room_name_font", |p| p.write_room_name_font())
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("write_room_name_data", |p| p.write_room_name_data())
+        .after(&["init_extra_room_data"]);
+    passes
+        .add("remove_non_blue_doors", |p| p.remove_non_blue_doors())
+        .after(&["apply_locked_doors"]);
+    passes
+        .add("write_crash_handler", |p| {
+            let settings = p.settings;
+            p.write_crash_handler(&settings.quality_of_life_settings.crash_fixes)
+        })
+        .overrides(&["ips/crash_handle_base"])
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("override_music", |p| override_music(p.rom))
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("use_area_based_music", |p| p.use_area_based_music())
+        .after(&["override_music"])
+        .enabled(
+            randomizer_settings.map_layout != "Vanilla"
+                || randomizer_settings.other_settings.area_assignment.preset
+                    != Some(AreaAssignmentPreset::Standard),
+        );
+    passes
+        .add("setup_door_specific_fx", |p| p.setup_door_specific_fx())
+        .after(&["write_door_data"]);
+    //nn note - the cre wasn't applied in ultralow qol, is this also camera fixes or some other reason - check this too!
+    passes
+        .add("setup_reload_cre", |p| p.setup_reload_cre())
+        .after(&["write_door_data"])
+        .enabled(qol.camera_fixes);
+    passes
+        .add("apply_title_screen_patches", |p| {
+            p.apply_title_screen_patches()
+        })
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("customize_escape_timer", |p| p.customize_escape_timer())
+        .overrides(&["ips/escape"])
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("apply_miscellaneous_patches", |p| {
+            p.apply_miscellaneous_patches()
+        })
+        .overrides(&["ips/escape", "ips/no source/no_explosions_before_escape"])
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("apply_mother_brain_fight_patches", |p| {
+            p.apply_mother_brain_fight_patches()
+        })
+        .overrides(&["ips/fast_mother_brain_cutscene"])
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("write_custom_item_graphics", |p| {
+            p.write_custom_item_graphics()
+        })
+        .after(&["place_items"]);
+    passes
+        .add("write_objective_data", |p| p.write_objective_data())
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("write_autotracker_skill_setting", |p| {
+            p.write_autotracker_skill_setting()
+        })
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("write_autotracker_item_progression", |p| {
+            p.write_autotracker_item_progression()
+        })
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("write_autotracker_qol_setting", |p| {
+            p.write_autotracker_qol_setting()
+        })
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("write_autotracker_objective_setting", |p| {
+            p.write_autotracker_objective_setting()
+        })
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("write_autotracker_map_layout", |p| {
+            p.write_autotracker_map_layout()
+        })
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("apply_seed_identifiers", |p| p.apply_seed_identifiers())
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("apply_credits", |p| p.apply_credits())
+        .overrides(&["ips/credits"])
+        .after(&["place_items"]);
+    passes
+        .add("apply_hazard_markers", |p| p.apply_hazard_markers())
+        .after(&["write_door_data"])
+        .enabled(qol.hazard_markers);
+    passes
+        .add("apply_all_room_outlines", |p| p.apply_all_room_outlines())
+        .after(&["write_map_reveal_tiles"])
+        .enabled(qol.room_outline_revealed);
+    passes
+        .add("apply_toilet_data", |p| p.apply_toilet_data())
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("apply_mother_brain_setup_asm", |p| {
+            p.apply_mother_brain_setup_asm()
+        })
+        .after(&["apply_ips_patches"]);
+    passes
+        .add("apply_extra_setup_asm", |p| p.apply_extra_setup_asm())
+        .after(&["apply_mother_brain_setup_asm", "apply_toilet_data"]);
+    passes
+        .add("write_extra_room_data", |p| p.write_extra_room_data())
+        .after(&[
+            "write_map_areas",
+            "write_room_name_data",
+            "apply_map_tile_patches",
+            "apply_extra_setup_asm",
+        ]);
+    passes
+        .add("customize_rom", |p| {
+            info!("CustomizeSettings: {customize_settings:?}");
+            let (rom, write_log) = p.rom_and_write_log();
+            customize_rom(
+                rom,
+                write_log,
+                base_rom,
+                &randomization.map,
+                customize_settings,
+                game_data,
+                samus_sprite_categories,
+                mosaic_themes,
+            )
+        })
+        .overrides(&[
+            "ips/fast_reload",
+            "ips/map_area",
+            "ips/credits",
+            "ips/samus_sprite",
+            "ips/flashing_placebo",
+        ])
+        .after(&["write_extra_room_data"]);
+    let failures = if keep_going {
+        passes.run_keep_going(&mut patcher)?
+    } else {
+        passes.run(&mut patcher)?;
+        vec![]
     };
-    patcher.apply_ips_patches()?;
-    patcher.init_extra_room_data()?;
-    patcher.place_items()?;
-    patcher.set_start_location()?;
-    patcher.set_starting_items()?;
-    patcher.fix_save_stations()?;
-    patcher.write_map_areas()?;
-    patcher.make_map_revealed()?;
-    patcher.write_beam_door_tiles()?;
-    patcher.apply_locked_doors()?;
-    patcher.apply_map_tile_patches()?;
-    patcher.prepare_extra_door_asm()?;
-    patcher.write_door_data()?;
-    patcher.write_map_reveal_tiles()?;
-    patcher.write_room_name_font()?;
-    patcher.write_room_name_data()?;
-    patcher.remove_non_blue_doors()?;
-    patcher.write_crash_handler(&patcher.settings.quality_of_life_settings.crash_fixes)?;
-    override_music(patcher.rom)?;
-    if randomizer_settings.map_layout != "Vanilla"
-        || randomizer_settings.other_settings.area_assignment.preset
-            != Some(AreaAssignmentPreset::Standard)
-    {
-        patcher.use_area_based_music()?;
-    }
-    patcher.setup_door_specific_fx()?;
-    if randomizer_settings.quality_of_life_settings.camera_fixes {
-        //nn note - the cre wasn't applied in ultralow qol, is this also camera fixes or some other reason - check this too!
-        patcher.setup_reload_cre()?;
-    }
-    patcher.apply_title_screen_patches()?;
-    patcher.customize_escape_timer()?;
-    patcher.apply_miscellaneous_patches()?;
-    patcher.apply_mother_brain_fight_patches()?;
-    patcher.write_custom_item_graphics()?;
-    patcher.write_objective_data()?;
-    patcher.write_autotracker_skill_setting()?;
-    patcher.write_autotracker_item_progression()?;
-    patcher.write_autotracker_qol_setting()?;
-    patcher.write_autotracker_objective_setting()?;
-    patcher.write_autotracker_map_layout()?;
-    patcher.apply_seed_identifiers()?;
-    patcher.apply_credits()?;
-    if randomizer_settings.quality_of_life_settings.hazard_markers {
-        patcher.apply_hazard_markers()?;
-    }
-    if randomizer_settings
-        .quality_of_life_settings
-        .room_outline_revealed
-    {
-        patcher.apply_all_room_outlines()?;
-    }
-    patcher.apply_toilet_data()?;
-    patcher.apply_mother_brain_setup_asm()?;
-    patcher.apply_extra_setup_asm()?;
-    patcher.write_extra_room_data()?;
-
-    info!("CustomizeSettings: {customize_settings:?}");
-    customize_rom(
-        patcher.rom,
-        base_rom,
-        &randomization.map,
-        customize_settings,
-        game_data,
-        samus_sprite_categories,
-        mosaic_themes,
-    )?;
 
     // ROM Checksum: Do not modify the ROM contents after this point
     fix_snes_checksum(patcher.rom);
 
-    Ok(rom)
+    let build_info = RomBuildInfo {
+        write_log: patcher.write_log,
+        symbols: patcher.symbols,
+    };
+    Ok((rom, build_info, failures))
 }
@@ -1,4 +1,4 @@
-use anyhow::{Result, bail};
+use anyhow::{Result, bail, ensure};
 
 use super::suffix_tree::SuffixTree;
 
@@ -27,6 +27,10 @@ enum BPSBlock {
 #[derive(Debug)]
 pub struct BPSPatch {
     blocks: Vec<BPSBlock>,
+    pub src_size: usize,
+    pub dst_size: usize,
+    pub src_crc32: u32,
+    pub dst_crc32: u32,
 }
 
 impl BPSPatch {
@@ -35,9 +39,43 @@ impl BPSPatch {
         decoder.decode()?;
         Ok(BPSPatch {
             blocks: decoder.blocks,
+            src_size: decoder.src_size,
+            dst_size: decoder.dst_size,
+            src_crc32: decoder.src_crc32,
+            dst_crc32: decoder.dst_crc32,
         })
     }
 
+    // Apply the patch to a complete source file, producing the target file. Unlike `apply`, this
+    // checks the source and target against the sizes and CRC32 checksums recorded in the patch,
+    // so that applying it to the wrong base file fails rather than producing a corrupt output.
+    pub fn apply_checked(&self, source: &[u8]) -> Result<Vec<u8>> {
+        ensure!(
+            source.len() == self.src_size,
+            "BPS source size mismatch: expected {} bytes, got {}",
+            self.src_size,
+            source.len()
+        );
+        let src_crc32 = compute_crc32(source);
+        ensure!(
+            src_crc32 == self.src_crc32,
+            "BPS source checksum mismatch: expected {:08X}, got {:08X}",
+            self.src_crc32,
+            src_crc32
+        );
+        let mut output = source.to_vec();
+        output.resize(self.dst_size, 0);
+        self.apply(source, &mut output);
+        let dst_crc32 = compute_crc32(&output);
+        ensure!(
+            dst_crc32 == self.dst_crc32,
+            "BPS target checksum mismatch: expected {:08X}, got {:08X}",
+            self.dst_crc32,
+            dst_crc32
+        );
+        Ok(output)
+    }
+
     pub fn apply(&self, source: &[u8], output: &mut [u8]) {
         for block in &self.blocks {
             match block {
@@ -69,6 +107,29 @@ impl BPSPatch {
         }
     }
 
+    // Ranges of the output written by `apply`. Unlike `get_modified_ranges`, this includes source
+    // copies to the same position, since these overwrite the output with the source data.
+    pub fn get_written_ranges(&self) -> Vec<(usize, usize)> {
+        let mut out: Vec<(usize, usize)> = vec![];
+        for block in &self.blocks {
+            match block {
+                &BPSBlock::Unchanged => {}
+                &BPSBlock::SourceCopy {
+                    dst_start, length, ..
+                }
+                | &BPSBlock::TargetCopy {
+                    dst_start, length, ..
+                } => {
+                    out.push((dst_start, dst_start + length));
+                }
+                BPSBlock::Data { dst_start, data } => {
+                    out.push((*dst_start, *dst_start + data.len()));
+                }
+            }
+        }
+        out
+    }
+
     // This function isn't used now, but could be useful later, so keeping it around.
     pub fn get_modified_ranges(&self) -> Vec<(usize, usize)> {
         let mut out: Vec<(usize, usize)> = vec![];
@@ -105,6 +166,10 @@ struct BPSDecoder {
     src_pos: usize,
     dst_pos: usize,
     blocks: Vec<BPSBlock>,
+    src_size: usize,
+    dst_size: usize,
+    src_crc32: u32,
+    dst_crc32: u32,
 }
 
 impl BPSDecoder {
@@ -116,13 +181,19 @@ impl BPSDecoder {
             src_pos: 0,
             dst_pos: 0,
             blocks: vec![],
+            src_size: 0,
+            dst_size: 0,
+            src_crc32: 0,
+            dst_crc32: 0,
         }
     }
 
     fn decode(&mut self) -> Result<()> {
-        assert!(self.read_n(4)? == "BPS1".as_bytes());
-        let _src_size = self.decode_number()?;
-        let _dst_size = self.decode_number()?;
+        if self.patch_bytes.len() < 16 || self.read_n(4)? != "BPS1".as_bytes() {
+            bail!("invalid BPS header");
+        }
+        self.src_size = self.decode_number()?;
+        self.dst_size = self.decode_number()?;
         let metadata_size = self.decode_number()?;
         self.patch_pos += metadata_size;
         while self.patch_pos < self.patch_bytes.len() - 12 {
@@ -133,8 +204,16 @@ impl BPSDecoder {
                 self.blocks.push(block);
             }
         }
-        assert!(self.patch_pos == self.patch_bytes.len() - 12);
-        // Ignore the checksums at the end of the file.
+        if self.patch_pos != self.patch_bytes.len() - 12 {
+            bail!("invalid BPS block data");
+        }
+        self.src_crc32 = u32::from_le_bytes(self.read_n(4)?.try_into().unwrap());
+        self.dst_crc32 = u32::from_le_bytes(self.read_n(4)?.try_into().unwrap());
+        let patch_crc32 = u32::from_le_bytes(self.read_n(4)?.try_into().unwrap());
+        let patch_len = self.patch_bytes.len();
+        if compute_crc32(&self.patch_bytes[..patch_len - 4]) != patch_crc32 {
+            bail!("BPS patch checksum mismatch");
+        }
         Ok(())
     }
 
@@ -225,7 +304,9 @@ impl BPSDecoder {
 }
 
 pub struct BPSEncoder<'a> {
-    source_suffix_tree: &'a SuffixTree,
+    source: &'a [u8],
+    // If absent, modified ranges are always encoded as literal data.
+    source_suffix_tree: Option<&'a SuffixTree>,
     target: &'a [u8],
     modified_ranges: &'a [(usize, usize)],
     pub patch_bytes: Vec<u8>,
@@ -256,7 +337,28 @@ impl<'a> BPSEncoder<'a> {
         modified_ranges: &'a [(usize, usize)],
     ) -> Self {
         Self {
-            source_suffix_tree,
+            source: &source_suffix_tree.data,
+            source_suffix_tree: Some(source_suffix_tree),
+            target,
+            modified_ranges,
+            patch_bytes: vec![],
+            src_pos: 0,
+            input_pos: 0,
+            count_copy_bytes: 0,
+            count_data_bytes: 0,
+        }
+    }
+
+    // Encoder that does not search the source for matching data. This avoids the cost of building
+    // a suffix tree of the source, at the expense of a larger patch.
+    pub fn new_literal(
+        source: &'a [u8],
+        target: &'a [u8],
+        modified_ranges: &'a [(usize, usize)],
+    ) -> Self {
+        Self {
+            source,
+            source_suffix_tree: None,
             target,
             modified_ranges,
             patch_bytes: vec![],
@@ -269,16 +371,16 @@ impl<'a> BPSEncoder<'a> {
 
     pub fn encode(&mut self) {
         self.write_n("BPS1".as_bytes());
-        self.encode_number(self.source_suffix_tree.data.len());
+        self.encode_number(self.source.len());
         self.encode_number(self.target.len());
         self.encode_number(0); // metadata size
         for r in self.modified_ranges {
             self.encode_range(r.0, r.1);
         }
-        if self.input_pos < self.source_suffix_tree.data.len() {
-            self.encode_unchanged(self.source_suffix_tree.data.len() - self.input_pos);
+        if self.input_pos < self.source.len() {
+            self.encode_unchanged(self.source.len() - self.input_pos);
         }
-        self.write_n(&compute_crc32(&self.source_suffix_tree.data).to_le_bytes());
+        self.write_n(&compute_crc32(self.source).to_le_bytes());
         self.write_n(&compute_crc32(self.target).to_le_bytes());
         self.write_n(&compute_crc32(&self.patch_bytes).to_le_bytes());
         self.check_encoding();
@@ -294,9 +396,10 @@ impl<'a> BPSEncoder<'a> {
 
         // Data and source copy blocks:
         while start_addr < end_addr {
-            let (source_start, match_length) = self
-                .source_suffix_tree
-                .find_longest_prefix(&self.target[start_addr..end_addr]);
+            let (source_start, match_length) = match self.source_suffix_tree {
+                Some(tree) => tree.find_longest_prefix(&self.target[start_addr..end_addr]),
+                None => (0, 0),
+            };
             if match_length as usize >= SOURCE_MATCH_THRESHOLD {
                 if start_addr > self.input_pos {
                     self.encode_data(&self.target[self.input_pos..start_addr]);
@@ -365,8 +468,41 @@ impl<'a> BPSEncoder<'a> {
     fn check_encoding(&self) {
         // Check decoding using our decoder:
         let patch = BPSPatch::new(self.patch_bytes.clone()).unwrap();
-        let mut output = self.source_suffix_tree.data.clone();
-        patch.apply(&self.source_suffix_tree.data, &mut output);
+        let output = patch.apply_checked(self.source).unwrap();
         assert!(self.target == output);
     }
 }
+
+// Ranges of bytes in `new_rom` which differ from `old_rom`, including any bytes past its end.
+pub fn get_changed_ranges(old_rom: &[u8], new_rom: &[u8]) -> Vec<(usize, usize)> {
+    let mut out: Vec<(usize, usize)> = vec![];
+    let mut start: Option<usize> = None;
+    for i in 0..old_rom.len().min(new_rom.len()) {
+        if old_rom[i] != new_rom[i] {
+            start.get_or_insert(i);
+        } else if let Some(s) = start.take() {
+            out.push((s, i));
+        }
+    }
+    let common_len = old_rom.len().min(new_rom.len());
+    if let Some(s) = start {
+        out.push((s, common_len));
+    }
+    if new_rom.len() > common_len {
+        match out.last_mut() {
+            Some(last) if last.1 == common_len => last.1 = new_rom.len(),
+            _ => out.push((common_len, new_rom.len())),
+        }
+    }
+    out
+}
+
+// Create a standalone BPS patch from `old_rom` to `new_rom`, e.g. to distribute a seed. The patch
+// records CRC32 checksums of both ROMs, so patching the wrong base ROM fails.
+pub fn create_bps_patch(old_rom: &[u8], new_rom: &[u8]) -> Vec<u8> {
+    assert!(new_rom.len() >= old_rom.len());
+    let modified_ranges = get_changed_ranges(old_rom, new_rom);
+    let mut encoder = BPSEncoder::new_literal(old_rom, new_rom, &modified_ranges);
+    encoder.encode();
+    encoder.patch_bytes
+}
@@ -1,69 +1,241 @@
+use super::suffix_tree::SuffixTree;
 use std::cmp::min;
 
 const BLOCK_TYPE_RAW: u8 = 0;
 const BLOCK_TYPE_BYTE_RLE: u8 = 1;
+const BLOCK_TYPE_WORD_RLE: u8 = 2;
+const BLOCK_TYPE_INCREMENT: u8 = 3;
+const BLOCK_TYPE_COPY: u8 = 4;
+const BLOCK_TYPE_XOR_COPY: u8 = 5;
+const BLOCK_TYPE_REL_COPY: u8 = 6;
+const BLOCK_TYPE_XOR_REL_COPY: u8 = 7;
+
+const MAX_BLOCK_SIZE: usize = 1024;
+// A relative XOR copy always has a two-byte header (since a one-byte header starting with 0b111
+// indicates a two-byte header), and its size can't reach 0x301, as the header would then begin with
+// 0xFF, the end-of-data marker.
+const MAX_XOR_REL_COPY_SIZE: usize = 0x300;
+const MAX_REL_OFFSET: usize = 255;
+const MAX_ABS_OFFSET: usize = 0xFFFF;
+
+#[derive(Clone, Copy, Debug)]
+enum Block {
+    Raw,
+    ByteRle,
+    WordRle,
+    Increment,
+    Copy(usize),
+    XorCopy(usize),
+    RelCopy(usize),
+    XorRelCopy(usize),
+}
+
+fn header_len(size: usize, block_type: u8) -> usize {
+    if size <= 32 && block_type != BLOCK_TYPE_XOR_REL_COPY {
+        1
+    } else {
+        2
+    }
+}
 
 fn encode_block_header(size: usize, block_type: u8, out: &mut Vec<u8>) {
     assert!(size >= 1);
-    assert!(size <= 1024);
+    assert!(size <= MAX_BLOCK_SIZE);
     let size1 = size - 1;
-    if size1 <= 31 {
+    if header_len(size, block_type) == 1 {
         out.push(size1 as u8 | (block_type << 5));
     } else {
+        assert!(block_type != BLOCK_TYPE_XOR_REL_COPY || size <= MAX_XOR_REL_COPY_SIZE);
         out.push(0xE0 | (block_type << 2) | ((size1 >> 8) as u8));
         out.push((size1 & 0xFF) as u8);
     }
 }
 
-fn encode_raw_block(data: &[u8], out: &mut Vec<u8>) {
-    for i in 0..data.len().div_ceil(1024) {
-        let block = &data[(i * 1024)..min((i + 1) * 1024, data.len())];
-        encode_block_header(block.len(), BLOCK_TYPE_RAW, out);
-        out.extend(block);
-    }
-}
-
-fn encode_rle_block(value: u8, count: usize, out: &mut Vec<u8>) {
-    for i in 0..count.div_ceil(1024) {
-        let end = min(i * 1024 + 1024, count);
-        let size = end - i * 1024;
-        encode_block_header(size, BLOCK_TYPE_BYTE_RLE, out);
-        out.push(value)
+fn encode_block(data: &[u8], pos: usize, size: usize, block: Block, out: &mut Vec<u8>) {
+    match block {
+        Block::Raw => {
+            encode_block_header(size, BLOCK_TYPE_RAW, out);
+            out.extend(&data[pos..(pos + size)]);
+        }
+        Block::ByteRle => {
+            encode_block_header(size, BLOCK_TYPE_BYTE_RLE, out);
+            out.push(data[pos]);
+        }
+        Block::WordRle => {
+            encode_block_header(size, BLOCK_TYPE_WORD_RLE, out);
+            out.extend(&data[pos..(pos + 2)]);
+        }
+        Block::Increment => {
+            encode_block_header(size, BLOCK_TYPE_INCREMENT, out);
+            out.push(data[pos]);
+        }
+        Block::Copy(src) => {
+            encode_block_header(size, BLOCK_TYPE_COPY, out);
+            out.extend((src as u16).to_le_bytes());
+        }
+        Block::XorCopy(src) => {
+            encode_block_header(size, BLOCK_TYPE_XOR_COPY, out);
+            out.extend((src as u16).to_le_bytes());
+        }
+        Block::RelCopy(rel) => {
+            encode_block_header(size, BLOCK_TYPE_REL_COPY, out);
+            out.push(rel as u8);
+        }
+        Block::XorRelCopy(rel) => {
+            encode_block_header(size, BLOCK_TYPE_XOR_REL_COPY, out);
+            out.push(rel as u8);
+        }
     }
 }
 
-fn encode_block(data: &[u8], value: u8, rle_count: usize, out: &mut Vec<u8>) {
-    if !data.is_empty() {
-        encode_raw_block(data, out);
+// Longest matches of each position against earlier data (not overlapping the position itself), as
+// (source index, length), found using a suffix tree of the data preceding the position.
+fn get_abs_matches(data: &[u8]) -> (Vec<(usize, usize)>, Vec<(usize, usize)>) {
+    let xor_data: Vec<u8> = data.iter().map(|&x| x ^ 0xFF).collect();
+    let mut tree = SuffixTree::new(&[]);
+    let mut matches = Vec::with_capacity(data.len());
+    let mut xor_matches = Vec::with_capacity(data.len());
+    let get_match = |tree: &SuffixTree, query: &[u8]| {
+        let (src, len) = tree.find_longest_prefix(query);
+        if len == 0 || src as usize > MAX_ABS_OFFSET {
+            (0, 0)
+        } else {
+            (src as usize, len as usize)
+        }
+    };
+    for i in 0..data.len() {
+        if i > 0 {
+            tree.push_byte(data[i - 1]);
+        }
+        let end = min(i + MAX_BLOCK_SIZE, data.len());
+        matches.push(get_match(&tree, &data[i..end]));
+        xor_matches.push(get_match(&tree, &xor_data[i..end]));
     }
-    encode_rle_block(value, rle_count, out);
+    (matches, xor_matches)
 }
 
 // Compress data into format used by Super Metroid for graphics, tilemaps, etc.
-// This is a simple encoder that only uses two block types: raw blocks and byte-level RLE (run length encoding).
-// Supporting other block types could allow for a smaller compressed output.
+// This finds an optimal encoding using all block types, given the longest matches available at each
+// position: going backward through the data, the cheapest encoding of each suffix is found by trying
+// every block type and size starting at that position.
 pub fn compress(data: &[u8]) -> Vec<u8> {
-    let mut out: Vec<u8> = Vec::new();
-    let mut block_data: Vec<u8> = Vec::new();
-    let mut prev: isize = -1; // Previous byte value (-1 used just as a dummy value at the beginning)
-    let mut rle_count: usize = 0;
-    for &x in data {
-        if x as isize == prev {
-            rle_count += 1;
+    let n = data.len();
+    let (abs_matches, abs_xor_matches) = get_abs_matches(data);
+
+    // cost[i]: size of the cheapest encoding of data[i..], with the first block of it in choice[i].
+    let mut cost: Vec<usize> = vec![0; n + 1];
+    let mut choice: Vec<(Block, usize)> = vec![(Block::Raw, 0); n];
+    // Length of the run of matching bytes at the current position, for each relative offset:
+    let mut rel_run: Vec<usize> = vec![0; MAX_REL_OFFSET + 1];
+    let mut xor_rel_run: Vec<usize> = vec![0; MAX_REL_OFFSET + 1];
+    let mut byte_run = 0;
+    let mut word_run = 0;
+    let mut inc_run = 0;
+    for i in (0..n).rev() {
+        let max_size = min(MAX_BLOCK_SIZE, n - i);
+        byte_run = if i + 1 < n && data[i + 1] == data[i] {
+            byte_run + 1
+        } else {
+            1
+        };
+        word_run = if i + 2 < n && data[i + 2] == data[i] {
+            word_run + 1
+        } else {
+            min(2, n - i)
+        };
+        inc_run = if i + 1 < n && data[i] != 0xFF && data[i + 1] == data[i] + 1 {
+            inc_run + 1
         } else {
-            if rle_count >= 3 {
-                encode_block(&block_data, prev as u8, rle_count, &mut out);
-                block_data.clear();
+            1
+        };
+
+        let mut best_rel = (0, 0);
+        let mut best_xor_rel = (0, 0);
+        for rel in 1..=min(MAX_REL_OFFSET, i) {
+            let src = data[i - rel];
+            rel_run[rel] = if src == data[i] { rel_run[rel] + 1 } else { 0 };
+            xor_rel_run[rel] = if src ^ 0xFF == data[i] {
+                xor_rel_run[rel] + 1
             } else {
-                for _ in 0..rle_count {
-                    block_data.push(prev as u8);
+                0
+            };
+            if rel_run[rel] > best_rel.1 {
+                best_rel = (rel, rel_run[rel]);
+            }
+            if xor_rel_run[rel] > best_xor_rel.1 {
+                best_xor_rel = (rel, xor_rel_run[rel]);
+            }
+        }
+        let (abs_src, abs_len) = abs_matches[i];
+        let (abs_xor_src, abs_xor_len) = abs_xor_matches[i];
+
+        let mut best = (usize::MAX, Block::Raw, 0);
+        for size in 1..=max_size {
+            let mut consider = |block_cost: usize, block: Block| {
+                let total = block_cost + cost[i + size];
+                if total < best.0 {
+                    best = (total, block, size);
                 }
+            };
+            if size <= byte_run {
+                consider(header_len(size, BLOCK_TYPE_BYTE_RLE) + 1, Block::ByteRle);
+            }
+            if size <= inc_run {
+                consider(header_len(size, BLOCK_TYPE_INCREMENT) + 1, Block::Increment);
+            }
+            if size <= best_rel.1 {
+                consider(
+                    header_len(size, BLOCK_TYPE_REL_COPY) + 1,
+                    Block::RelCopy(best_rel.0),
+                );
+            }
+            if size <= best_xor_rel.1 && size <= MAX_XOR_REL_COPY_SIZE {
+                consider(
+                    header_len(size, BLOCK_TYPE_XOR_REL_COPY) + 1,
+                    Block::XorRelCopy(best_xor_rel.0),
+                );
             }
-            rle_count = 1;
-            prev = x as isize;
+            if size >= 3 && size <= word_run {
+                consider(header_len(size, BLOCK_TYPE_WORD_RLE) + 2, Block::WordRle);
+            }
+            if size <= abs_len {
+                consider(header_len(size, BLOCK_TYPE_COPY) + 2, Block::Copy(abs_src));
+            }
+            if size <= abs_xor_len {
+                consider(
+                    header_len(size, BLOCK_TYPE_XOR_COPY) + 2,
+                    Block::XorCopy(abs_xor_src),
+                );
+            }
+            consider(header_len(size, BLOCK_TYPE_RAW) + size, Block::Raw);
+        }
+        cost[i] = best.0;
+        choice[i] = (best.1, best.2);
+    }
+
+    let mut out: Vec<u8> = Vec::with_capacity(cost[0] + 1);
+    let mut i = 0;
+    let mut raw_start = 0;
+    while i < n {
+        let (block, size) = choice[i];
+        if let Block::Raw = block {
+            // Merge consecutive raw blocks, as long as the size limit allows:
+            if i + size - raw_start > MAX_BLOCK_SIZE {
+                encode_block(data, raw_start, i - raw_start, Block::Raw, &mut out);
+                raw_start = i;
+            }
+        } else {
+            if raw_start < i {
+                encode_block(data, raw_start, i - raw_start, Block::Raw, &mut out);
+            }
+            encode_block(data, i, size, block, &mut out);
+            raw_start = i + size;
         }
+        i += size;
+    }
+    if raw_start < n {
+        encode_block(data, raw_start, n - raw_start, Block::Raw, &mut out);
     }
-    encode_block(&block_data, prev as u8, rle_count, &mut out);
     out.push(0xFF);
     out
 }
@@ -0,0 +1,142 @@
+// Checking and normalization of the user-supplied vanilla ROM: a 512-byte copier header is
+// stripped, and common wrong inputs are identified so that a specific reason can be shown.
+
+use super::Rom;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+pub const VANILLA_ROM_SIZE: usize = 0x300000;
+pub const VANILLA_ROM_SHA256: &str =
+    "12b77c4bc9c1832cee8881244659065ee1d84c70c3d29e6eaf92e6798cc2ca72";
+pub const VANILLA_ROM_CRC32: u32 = 0xD63ED5F8;
+
+const COPIER_HEADER_SIZE: usize = 0x200;
+// Location of the internal ROM header (cartridge title, region, etc.) in a LoROM image:
+const INTERNAL_HEADER_ADDR: usize = 0x7FC0;
+const TITLE: &[u8] = b"Super Metroid";
+const MAP_RANDO_TITLE: &[u8] = b"SUPERMETROID MAPRANDO";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvalidRom {
+    // The ROM is not any version of Super Metroid.
+    NotSuperMetroid { size: usize },
+    // Each pair of bytes is swapped (e.g. from a 16-bit dump read with the wrong byte order).
+    ByteSwapped,
+    // The 32 KB banks are out of order, as in an interleaved copier format.
+    Interleaved,
+    // The European (PAL) version.
+    Pal,
+    // A ROM that was already patched by Map Rando.
+    AlreadyRandomized,
+    // Super Metroid, but modified (e.g. a hack or another randomizer) or a bad dump.
+    Modified { crc32: u32 },
+}
+
+impl std::fmt::Display for InvalidRom {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidRom::NotSuperMetroid { size } => write!(
+                f,
+                "The ROM is not Super Metroid (size {size} bytes; expected {VANILLA_ROM_SIZE})."
+            ),
+            InvalidRom::ByteSwapped => write!(
+                f,
+                "The ROM is byte-swapped (every pair of bytes is reversed). Please provide a ROM \
+                 dumped in the standard byte order."
+            ),
+            InvalidRom::Interleaved => write!(
+                f,
+                "The ROM is in an interleaved format. Please convert it to a standard \
+                 (non-interleaved) ROM."
+            ),
+            InvalidRom::Pal => write!(
+                f,
+                "The ROM is the European (PAL) version of Super Metroid. Please provide the NTSC \
+                 (USA/JU) version."
+            ),
+            InvalidRom::AlreadyRandomized => write!(
+                f,
+                "The ROM has already been randomized. Please provide an original Super Metroid ROM."
+            ),
+            InvalidRom::Modified { crc32 } => write!(
+                f,
+                "The ROM is Super Metroid but does not match the original (CRC32 {crc32:08X}; \
+                 expected {VANILLA_ROM_CRC32:08X}). It may be modified or a bad dump."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InvalidRom {}
+
+pub struct NormalizedRom {
+    pub rom: Rom,
+    pub header_removed: bool,
+}
+
+fn get_title(data: &[u8], addr: usize) -> Option<&[u8]> {
+    data.get(addr..addr + 21)
+}
+
+fn has_title(data: &[u8], addr: usize) -> bool {
+    get_title(data, addr).is_some_and(|x| x.starts_with(TITLE))
+}
+
+fn byte_swap(data: &[u8]) -> Vec<u8> {
+    data.chunks(2)
+        .flat_map(|x| x.iter().rev().copied())
+        .collect()
+}
+
+// Strip a copier header if present, and check that the ROM is the vanilla NTSC Super Metroid.
+pub fn normalize_input_rom(data: &[u8]) -> std::result::Result<NormalizedRom, InvalidRom> {
+    let header_removed = data.len() % 0x8000 == COPIER_HEADER_SIZE;
+    let data = if header_removed {
+        &data[COPIER_HEADER_SIZE..]
+    } else {
+        data
+    };
+
+    if get_title(data, INTERNAL_HEADER_ADDR).is_some_and(|x| x == MAP_RANDO_TITLE) {
+        return Err(InvalidRom::AlreadyRandomized);
+    }
+    if !has_title(data, INTERNAL_HEADER_ADDR) {
+        if has_title(&byte_swap(data), INTERNAL_HEADER_ADDR) {
+            return Err(InvalidRom::ByteSwapped);
+        }
+        if (0..data.len() / 0x8000).any(|bank| has_title(data, bank * 0x8000 + 0x7FC0)) {
+            return Err(InvalidRom::Interleaved);
+        }
+        return Err(InvalidRom::NotSuperMetroid { size: data.len() });
+    }
+    // Destination code in the internal header: 0 = Japan, 1 = North America, 2 and up are PAL
+    // regions (with a few exceptions that Super Metroid was not released in).
+    let region = data.get(INTERNAL_HEADER_ADDR + 0x19).copied().unwrap_or(0);
+    if (0x02..=0x0C).contains(&region) {
+        return Err(InvalidRom::Pal);
+    }
+
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(data);
+    let crc32 = hasher.finalize();
+    let sha256 = crypto_hash::hex_digest(crypto_hash::Algorithm::SHA256, data);
+    if crc32 != VANILLA_ROM_CRC32 || sha256 != VANILLA_ROM_SHA256 {
+        return Err(InvalidRom::Modified { crc32 });
+    }
+    Ok(NormalizedRom {
+        rom: Rom::new(data.to_vec()),
+        header_removed,
+    })
+}
+
+// Load the vanilla ROM from a file, normalizing it with `normalize_input_rom`.
+pub fn load_input_rom(path: &Path) -> Result<Rom> {
+    let data = std::fs::read(path)
+        .with_context(|| format!("Unable to load ROM at path {}", path.display()))?;
+    let normalized = normalize_input_rom(&data)
+        .with_context(|| format!("Invalid input ROM {}", path.display()))?;
+    if normalized.header_removed {
+        log::info!("Removed copier header from input ROM {}", path.display());
+    }
+    Ok(normalized.rom)
+}
@@ -0,0 +1,546 @@
+// Recovery of a `Randomization` from a generated ROM, by reading back the data written by the
+// patcher: item PLMs, door data, room positions and map areas, locked door PLMs, objectives, the
+// start location, and the autotracker settings bytes.
+//
+// Some of the randomization is not stored in the ROM and is left at default values: the RNG seed,
+// the essential spoiler data (credits), subareas, and gray doors (which use the vanilla PLMs).
+
+use super::{
+    Rom, START_LOAD_STATION_OFFSETS, get_locked_door_plm, get_objective_flag_bit,
+    get_room_state_ptrs, get_start_load_station_data, item_to_plm_type, snes2pc,
+};
+use crate::{
+    randomize::{
+        EssentialSpoilerData, LockedDoor, Randomization, Randomizer, get_ship_start_location,
+    },
+    settings::{
+        MapPreset, ObjPreset, Objective, ProgressionPreset, QolPreset, SaveAnimals, SkillPreset,
+    },
+};
+use anyhow::{Context, Result, bail};
+use hashbrown::{HashMap, HashSet};
+use log::warn;
+use maprando_game::{BeamType, DoorPtrPair, DoorType, GameData, Item, Map, StartLocation};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::iter;
+
+const NUM_AREAS: usize = 6;
+
+// Settings presets stored for use by autotrackers (at $DF:FF09 through $DF:FF0D), or `None` if
+// the setting was not from a preset.
+#[derive(Serialize, Deserialize, Default)]
+pub struct AutotrackerSettings {
+    pub skill_preset: Option<SkillPreset>,
+    pub item_progression_preset: Option<ProgressionPreset>,
+    pub quality_of_life_preset: Option<QolPreset>,
+    pub objective_preset: Option<ObjPreset>,
+    pub map_layout: Option<MapPreset>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RomInspection {
+    pub randomization: Randomization,
+    pub autotracker_settings: AutotrackerSettings,
+}
+
+pub fn is_map_rando_rom(rom: &Rom) -> bool {
+    rom.read_n(0x7FC0, 21)
+        .is_ok_and(|x| x == "SUPERMETROID MAPRANDO".as_bytes())
+}
+
+pub fn read_autotracker_settings(rom: &Rom) -> Result<AutotrackerSettings> {
+    let read_byte = |addr: usize| -> Result<u8> { Ok(rom.read_u8(snes2pc(addr))? as u8) };
+    Ok(AutotrackerSettings {
+        skill_preset: SkillPreset::from_byte(read_byte(0xDFFF09)?),
+        item_progression_preset: ProgressionPreset::from_byte(read_byte(0xDFFF0A)?),
+        quality_of_life_preset: QolPreset::from_byte(read_byte(0xDFFF0B)?),
+        objective_preset: ObjPreset::from_byte(read_byte(0xDFFF0C)?),
+        map_layout: MapPreset::from_byte(read_byte(0xDFFF0D)?),
+    })
+}
+
+// Returns the seed name and display seed.
+pub fn read_seed_identifiers(rom: &Rom) -> Result<(String, usize)> {
+    let name_bytes: Vec<u8> = rom
+        .read_n(snes2pc(0xDFFEF0), 16)?
+        .iter()
+        .copied()
+        .take_while(|&b| b != 0)
+        .collect();
+    let seed_name = String::from_utf8(name_bytes).context("Invalid seed name")?;
+    let display_seed =
+        u32::from_le_bytes(rom.read_n(snes2pc(0xDFFF00), 4)?.try_into().unwrap()) as usize;
+    Ok((seed_name, display_seed))
+}
+
+pub fn read_item_placement(rom: &Rom, game_data: &GameData) -> Result<Vec<Item>> {
+    // Invert the PLM type table, for each item container. The "Nothing" item uses the same PLM
+    // types as missiles, so it is recognized by its bit being set in the initial collected items:
+    let mut item_by_plm_type: HashMap<isize, Item> = HashMap::new();
+    for container in 0..3 {
+        for item_id in 0..25usize {
+            let item = Item::try_from(item_id).unwrap();
+            if item == Item::Nothing {
+                continue;
+            }
+            item_by_plm_type.insert(item_to_plm_type(item, 0xEED7 + container * 84), item);
+        }
+    }
+    let nothing_item_bitmask = rom.read_n(snes2pc(0xB5FE12), 0x40)?;
+
+    let mut item_placement = vec![];
+    for &loc in &game_data.item_locations {
+        let item_plm_ptr = game_data.node_ptr_map[&loc];
+        let plm_type = rom.read_u16(item_plm_ptr)?;
+        let item = if plm_type == 0xF700 {
+            // Invisible fake item, used in place of a "Nothing" item in the Bomb Torizo Room.
+            Item::Nothing
+        } else {
+            let Some(&item) = item_by_plm_type.get(&plm_type) else {
+                bail!("Unrecognized item PLM type {plm_type:04X} at item location {loc:?}");
+            };
+            let idx = rom.read_u16(item_plm_ptr + 4)? as usize;
+            if item == Item::Missile && nothing_item_bitmask[idx >> 3] & (1 << (idx & 7)) != 0 {
+                Item::Nothing
+            } else {
+                item
+            }
+        };
+        item_placement.push(item);
+    }
+    Ok(item_placement)
+}
+
+// PC address of the extra room data (see `ExtraRoomData`) that the room header points to.
+fn get_extra_room_data_addr(rom: &Rom, room_ptr: usize) -> Result<usize> {
+    let (_, state_ptr) = get_room_state_ptrs(rom, room_ptr)?[0];
+    Ok(snes2pc(0xB80000 | rom.read_u16(state_ptr + 16)? as usize))
+}
+
+// Find the door connections, by matching the door data at each door exit with the original door
+// data of the door entrances. The door data is copied from the original entrance aside from the
+// area transition flag and ASM pointer, but a few fields may also be modified by other patches,
+// so the closest match among entrances into the same room is used.
+pub fn read_door_connections(
+    rom: &Rom,
+    orig_rom: &Rom,
+    game_data: &GameData,
+) -> Result<Vec<(DoorPtrPair, DoorPtrPair)>> {
+    let mut entrances_by_room: HashMap<isize, Vec<(usize, DoorPtrPair)>> = HashMap::new();
+    for room in &game_data.room_geometry {
+        for door in &room.doors {
+            if let Some(entrance_ptr) = door.entrance_ptr {
+                let room_ptr = orig_rom.read_u16(entrance_ptr)?;
+                entrances_by_room
+                    .entry(room_ptr)
+                    .or_default()
+                    .push((entrance_ptr, (door.exit_ptr, door.entrance_ptr)));
+            }
+        }
+    }
+
+    // (source, destination) door pair for each door exit:
+    let mut connections = vec![];
+    for room in &game_data.room_geometry {
+        for door in &room.doors {
+            let Some(exit_ptr) = door.exit_ptr else {
+                continue;
+            };
+            let door_data = rom.read_n(exit_ptr, 12)?;
+            let room_ptr = door_data[0] as isize | (door_data[1] as isize) << 8;
+            let mut best: Option<(usize, DoorPtrPair)> = None;
+            for &(entrance_ptr, ptr_pair) in entrances_by_room.get(&room_ptr).into_iter().flatten()
+            {
+                let orig_data = orig_rom.read_n(entrance_ptr, 12)?;
+                let score = (3..10).filter(|&i| orig_data[i] == door_data[i]).count();
+                if best.is_none_or(|(s, _)| score > s) {
+                    best = Some((score, ptr_pair));
+                }
+            }
+            let Some((_, dst_ptr_pair)) = best else {
+                bail!(
+                    "No door entrance found for door exit {exit_ptr:X} in {}",
+                    room.name
+                );
+            };
+            connections.push(((door.exit_ptr, door.entrance_ptr), dst_ptr_pair));
+        }
+    }
+    Ok(connections)
+}
+
+fn get_door_offset(direction: &str) -> (isize, isize) {
+    match direction {
+        "left" => (-1, 0),
+        "right" => (1, 0),
+        "up" => (0, -1),
+        "down" => (0, 1),
+        _ => panic!("Unexpected door direction: {direction}"),
+    }
+}
+
+// Reconstruct the map. Room positions in the room headers are relative to the area maps, so the
+// positions of the areas relative to each other are found from the cross-area door connections.
+pub fn read_map(rom: &Rom, orig_rom: &Rom, game_data: &GameData) -> Result<Map> {
+    let num_rooms = game_data.room_geometry.len();
+    let mut room_mask = vec![false; num_rooms];
+    let mut area = vec![0; num_rooms];
+    let mut local_pos: Vec<(isize, isize)> = vec![(0, 0); num_rooms];
+    for (room_idx, room) in game_data.room_geometry.iter().enumerate() {
+        let map_area = rom.read_u8(get_extra_room_data_addr(rom, room.rom_address)?)? as usize;
+        if map_area == 0xFF {
+            // Room is omitted from the map.
+            continue;
+        }
+        if map_area >= NUM_AREAS {
+            bail!("Invalid map area {map_area} for {}", room.name);
+        }
+        room_mask[room_idx] = true;
+        area[room_idx] = map_area;
+        local_pos[room_idx] = (
+            rom.read_u8(room.rom_address + 2)?,
+            rom.read_u8(room.rom_address + 3)?,
+        );
+    }
+
+    let connections = read_door_connections(rom, orig_rom, game_data)?;
+
+    // Offset of each area map within the full map, from a breadth-first search over the
+    // cross-area connections:
+    let mut area_edges: Vec<Vec<(usize, (isize, isize))>> = vec![vec![]; NUM_AREAS];
+    for &(src_ptr_pair, dst_ptr_pair) in &connections {
+        let (src_room_idx, src_door_idx) =
+            game_data.room_and_door_idxs_by_door_ptr_pair[&src_ptr_pair];
+        let (dst_room_idx, dst_door_idx) =
+            game_data.room_and_door_idxs_by_door_ptr_pair[&dst_ptr_pair];
+        if !room_mask[src_room_idx] || !room_mask[dst_room_idx] {
+            continue;
+        }
+        let (src_area, dst_area) = (area[src_room_idx], area[dst_room_idx]);
+        if src_area == dst_area {
+            continue;
+        }
+        let src_door = &game_data.room_geometry[src_room_idx].doors[src_door_idx];
+        let dst_door = &game_data.room_geometry[dst_room_idx].doors[dst_door_idx];
+        let (dx, dy) = get_door_offset(&src_door.direction);
+        let src_pos = local_pos[src_room_idx];
+        let dst_pos = local_pos[dst_room_idx];
+        let delta = (
+            src_pos.0 + src_door.x as isize + dx - dst_pos.0 - dst_door.x as isize,
+            src_pos.1 + src_door.y as isize + dy - dst_pos.1 - dst_door.y as isize,
+        );
+        area_edges[src_area].push((dst_area, delta));
+        area_edges[dst_area].push((src_area, (-delta.0, -delta.1)));
+    }
+    let mut area_offset: Vec<Option<(isize, isize)>> = vec![None; NUM_AREAS];
+    for start_area in 0..NUM_AREAS {
+        if area_offset[start_area].is_some() {
+            continue;
+        }
+        let area_used = (0..num_rooms).any(|i| room_mask[i] && area[i] == start_area);
+        if start_area > 0 && area_used {
+            warn!("Map area {start_area} is not connected to the other areas");
+        }
+        area_offset[start_area] = Some((0, 0));
+        let mut queue = VecDeque::from([start_area]);
+        while let Some(a) = queue.pop_front() {
+            let (x, y) = area_offset[a].unwrap();
+            for &(b, (dx, dy)) in &area_edges[a] {
+                if area_offset[b].is_none() {
+                    area_offset[b] = Some((x + dx, y + dy));
+                    queue.push_back(b);
+                }
+            }
+        }
+    }
+
+    let abs_pos: Vec<(isize, isize)> = (0..num_rooms)
+        .map(|i| {
+            let (x, y) = area_offset[area[i]].unwrap();
+            (local_pos[i].0 + x, local_pos[i].1 + y)
+        })
+        .collect();
+    let masked_pos: Vec<(isize, isize)> = (0..num_rooms)
+        .filter(|&i| room_mask[i])
+        .map(|i| abs_pos[i])
+        .collect();
+    let min_x = masked_pos.iter().map(|p| p.0).min().unwrap_or(0);
+    let min_y = masked_pos.iter().map(|p| p.1).min().unwrap_or(0);
+    let rooms = (0..num_rooms)
+        .map(|i| {
+            if room_mask[i] {
+                (
+                    (abs_pos[i].0 - min_x) as usize,
+                    (abs_pos[i].1 - min_y) as usize,
+                )
+            } else {
+                (0, 0)
+            }
+        })
+        .collect();
+
+    // Each bidirectional connection appears once in each direction. Doors of rooms omitted from
+    // the map keep their vanilla connections, so are skipped:
+    let dst_by_src: HashMap<DoorPtrPair, DoorPtrPair> = connections.iter().copied().collect();
+    let mut doors = vec![];
+    let mut done: HashSet<DoorPtrPair> = HashSet::new();
+    for &(src, dst) in &connections {
+        let (src_room_idx, _) = game_data.room_and_door_idxs_by_door_ptr_pair[&src];
+        let (dst_room_idx, _) = game_data.room_and_door_idxs_by_door_ptr_pair[&dst];
+        if done.contains(&src) || !room_mask[src_room_idx] || !room_mask[dst_room_idx] {
+            continue;
+        }
+        let bidirectional = dst_by_src.get(&dst) == Some(&src);
+        if bidirectional {
+            done.insert(dst);
+        }
+        done.insert(src);
+        doors.push((src, dst, bidirectional));
+    }
+
+    Ok(Map {
+        room_mask,
+        rooms,
+        doors,
+        area,
+        subarea: vec![0; num_rooms],
+        subsubarea: vec![0; num_rooms],
+    })
+}
+
+fn get_locked_door_type(plm_type: usize, next_asm: &[u8]) -> Option<DoorType> {
+    match plm_type {
+        0xC85A..=0xC86C => Some(DoorType::Yellow),
+        0xC872..=0xC884 => Some(DoorType::Green),
+        0xC88A..=0xC89C => Some(DoorType::Red),
+        0xF5C0 | 0xF5C4 | 0xF5C8 | 0xF5CC => Some(DoorType::Wall),
+        0xFCC0..=0xFCD2 => {
+            // LDA #beam_type, followed by LDX #gfx_base_addr and JSL $84FCD8:
+            if next_asm.len() < 10
+                || next_asm[0] != 0xA9
+                || next_asm[6..10] != [0x22, 0xD8, 0xFC, 0x84]
+            {
+                return None;
+            }
+            let beam = match next_asm[1] {
+                0 => BeamType::Charge,
+                1 => BeamType::Ice,
+                2 => BeamType::Wave,
+                3 => BeamType::Spazer,
+                4 => BeamType::Plasma,
+                _ => return None,
+            };
+            Some(DoorType::Beam(beam))
+        }
+        _ => None,
+    }
+}
+
+// Find the locked doors from the PLMs spawned by each room's extra setup ASM (see
+// `apply_single_locked_door`). Both sides of a bidirectional locked door share the same state index.
+// Gray doors are not included.
+pub fn read_locked_doors(rom: &Rom, game_data: &GameData, map: &Map) -> Result<Vec<LockedDoor>> {
+    // Start address of each room's extra setup ASM, including twin rooms (which are not
+    // searched, but bound the ASM of the preceding room):
+    let mut setup_asm_addrs: Vec<(usize, Option<usize>)> = vec![];
+    for (room_idx, room) in game_data.room_geometry.iter().enumerate() {
+        let room_ptrs = iter::once((room.rom_address, Some(room_idx)))
+            .chain(room.twin_rom_address.map(|ptr| (ptr, None)));
+        for (room_ptr, room_idx) in room_ptrs {
+            let asm_ptr = rom.read_u16(get_extra_room_data_addr(rom, room_ptr)? + 1)? as usize;
+            if asm_ptr != 0 {
+                setup_asm_addrs.push((snes2pc(0xB80000 | asm_ptr), room_idx));
+            }
+        }
+    }
+    setup_asm_addrs.sort();
+
+    let mut doors_by_state: BTreeMap<usize, Vec<(DoorPtrPair, DoorType)>> = BTreeMap::new();
+    for (i, &(start, room_idx)) in setup_asm_addrs.iter().enumerate() {
+        let Some(room_idx) = room_idx else {
+            continue;
+        };
+        let end = setup_asm_addrs
+            .get(i + 1)
+            .map(|x| x.0)
+            .unwrap_or(snes2pc(0xB8E000));
+        let asm = rom.read_n(start, end - start)?;
+        let room = &game_data.room_geometry[room_idx];
+        for pos in 0..asm.len().saturating_sub(9) {
+            // JSL $84F380, X, Y, PLM type, PLM argument (door state index):
+            if asm[pos..pos + 4] != [0x22, 0x80, 0xF3, 0x84] {
+                continue;
+            }
+            let x = asm[pos + 4] as usize;
+            let y = asm[pos + 5] as usize;
+            let plm_type = asm[pos + 6] as usize | (asm[pos + 7] as usize) << 8;
+            let state_index = asm[pos + 8] as usize | (asm[pos + 9] as usize) << 8;
+            let Some(door_type) = get_locked_door_type(plm_type, &asm[pos + 10..]) else {
+                bail!(
+                    "Unrecognized locked door PLM {plm_type:04X} in {}",
+                    room.name
+                );
+            };
+            for door in &room.doors {
+                let valid_wall_offset = matches!(door.offset, Some(0..=2));
+                if door_type == DoorType::Wall
+                    && ["up", "down"].contains(&door.direction.as_str())
+                    && !valid_wall_offset
+                {
+                    continue;
+                }
+                if get_locked_door_plm(door, door_type) == (x, y, plm_type) {
+                    let ptr_pair = (door.exit_ptr, door.entrance_ptr);
+                    let doors = doors_by_state.entry(state_index).or_default();
+                    if !doors.iter().any(|d| d.0 == ptr_pair) {
+                        doors.push((ptr_pair, door_type));
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    let other_door: HashMap<DoorPtrPair, DoorPtrPair> = map
+        .doors
+        .iter()
+        .flat_map(|&(src, dst, _)| [(src, dst), (dst, src)])
+        .collect();
+    let mut locked_doors = vec![];
+    for (state_index, doors) in doors_by_state {
+        let (src_ptr_pair, door_type) = doors[0];
+        let dst_ptr_pair = if doors.len() > 1 {
+            doors[1].0
+        } else {
+            let Some(&dst_ptr_pair) = other_door.get(&src_ptr_pair) else {
+                bail!("Locked door with state index {state_index} is not connected");
+            };
+            dst_ptr_pair
+        };
+        locked_doors.push(LockedDoor {
+            src_ptr_pair,
+            dst_ptr_pair,
+            door_type,
+            bidirectional: doors.len() > 1,
+        });
+    }
+    Ok(locked_doors)
+}
+
+// Objectives, in the order of their checkboxes on the objective screen.
+pub fn read_objectives(rom: &Rom) -> Result<Vec<Objective>> {
+    use Objective::*;
+    let all_objectives = [
+        Kraid,
+        Phantoon,
+        Draygon,
+        Ridley,
+        SporeSpawn,
+        Crocomire,
+        Botwoon,
+        GoldenTorizo,
+        MetroidRoom1,
+        MetroidRoom2,
+        MetroidRoom3,
+        MetroidRoom4,
+        BombTorizo,
+        BowlingStatue,
+        AcidChozoStatue,
+        PitRoom,
+        BabyKraidRoom,
+        PlasmaRoom,
+        MetalPiratesRoom,
+    ];
+    let num_objectives = (rom.read_u16(snes2pc(0x82FFFC))? & 0x7FFF) as usize;
+    let mut objectives = vec![];
+    for i in 0..num_objectives {
+        let addr = rom.read_u16(snes2pc(0x8FEBC0) + i * 2)?;
+        let mask = rom.read_u16(snes2pc(0x8FEBE8) + i * 2)?;
+        let Some(&obj) = all_objectives
+            .iter()
+            .find(|&&obj| get_objective_flag_bit(obj) == (addr, mask))
+        else {
+            bail!("Unrecognized objective flag {addr:04X}/{mask:04X}");
+        };
+        objectives.push(obj);
+    }
+    Ok(objectives)
+}
+
+pub fn read_start_location(rom: &Rom, game_data: &GameData) -> Result<StartLocation> {
+    if rom.read_u16(snes2pc(0xB5FE00))? == 5 {
+        // Escape start, which begins at the Tourian escape load station:
+        return Ok(StartLocation::default());
+    }
+    let station_addr = snes2pc(0x80C4E1);
+    let mut station_data = [0; 6];
+    for (value, offset) in station_data.iter_mut().zip(START_LOAD_STATION_OFFSETS) {
+        *value = rom.read_u16(station_addr + offset)?;
+    }
+    let ship_start = get_ship_start_location();
+    for loc in game_data.start_locations.iter().chain([&ship_start]) {
+        if get_start_load_station_data(game_data, loc) == station_data {
+            return Ok(loc.clone());
+        }
+    }
+    bail!(
+        "Unrecognized start location (room {:04X}, door {:04X})",
+        station_data[0],
+        station_data[1]
+    );
+}
+
+fn read_save_animals(rom: &Rom) -> Result<SaveAnimals> {
+    // Addresses must match `apply_miscellaneous_patches`:
+    Ok(if rom.read_u16(snes2pc(0xA1F000))? == 0xFFFF {
+        SaveAnimals::Yes
+    } else if rom.read_u16(snes2pc(0xA6C250))? == 0xFEBC {
+        // Escape typewriter text pointing to "THINK OF THE ANIMALS!"
+        SaveAnimals::Optional
+    } else {
+        SaveAnimals::No
+    })
+}
+
+fn read_escape_time_seconds(rom: &Rom) -> Result<f32> {
+    let from_bcd = |x: isize| (x >> 4) * 10 + (x & 0xF);
+    let seconds = from_bcd(rom.read_u8(snes2pc(0x809E21))?);
+    let minutes = from_bcd(rom.read_u8(snes2pc(0x809E22))?);
+    Ok((minutes * 60 + seconds) as f32)
+}
+
+// Recover the randomization from a Map Rando ROM, using the original (vanilla) ROM to identify
+// the door connections.
+pub fn inspect_rom(rom: &Rom, orig_rom: &Rom, game_data: &GameData) -> Result<RomInspection> {
+    if !is_map_rando_rom(rom) {
+        bail!("ROM is not a Map Rando ROM");
+    }
+    let map = read_map(rom, orig_rom, game_data).context("Reading map")?;
+    let locked_doors = read_locked_doors(rom, game_data, &map).context("Reading locked doors")?;
+    let item_placement = read_item_placement(rom, game_data).context("Reading items")?;
+    let objectives = read_objectives(rom).context("Reading objectives")?;
+    let start_location = read_start_location(rom, game_data).context("Reading start location")?;
+    let (seed_name, display_seed) = read_seed_identifiers(rom)?;
+    let randomization = Randomization {
+        objectives,
+        save_animals: read_save_animals(rom)?,
+        toilet_intersections: Randomizer::get_toilet_intersections(&map, game_data),
+        map,
+        locked_doors,
+        item_placement,
+        start_location,
+        escape_time_seconds: read_escape_time_seconds(rom)?,
+        essential_spoiler_data: EssentialSpoilerData {
+            item_spoiler_info: vec![],
+        },
+        // The RNG seed is not stored in the ROM:
+        seed: 0,
+        display_seed,
+        seed_name,
+    };
+    Ok(RomInspection {
+        randomization,
+        autotracker_settings: read_autotracker_settings(rom)?,
+    })
+}
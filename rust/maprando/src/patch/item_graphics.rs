@@ -0,0 +1,156 @@
+// Item PLM graphics for non-vanilla items, loaded from PNG sprite sheets listed in
+// `gfx/items/items.json`. Each sheet is 32x16 with the two 16x16 animation frames side by side.
+// A frame is made of four 8x8 tiles (top-left, top-right, bottom-left, bottom-right), and the
+// item's PLM selects a palette row for each tile (the 8 palette bytes following the "load item
+// PLM GFX" instruction). Opaque pixels are quantized to the nearest color of the tile's row of
+// the item PLM palette, `gfx/items/palette.png`; transparent pixels become color 0.
+
+use anyhow::{Context, Result, bail, ensure};
+use image::RgbaImage;
+use maprando_game::data_source::DataSource;
+use serde::Deserialize;
+
+pub const ITEM_GRAPHICS_DIR: &str = "gfx/items";
+// Palette rows available to item PLMs. Rows 4 to 7 differ too much between tilesets to be usable.
+pub const ITEM_PALETTE_ROWS: usize = 4;
+pub const ITEM_GRAPHICS_TILES: usize = 8;
+
+pub type ItemTile = [[u8; 8]; 8];
+
+#[derive(Deserialize)]
+struct ItemGraphicsEntry {
+    name: String,
+    image: String,
+    // SNES address of the graphics referenced by the item's PLM, e.g. "$899100".
+    addr: String,
+    palettes: [u8; ITEM_GRAPHICS_TILES],
+}
+
+pub struct ItemGraphics {
+    pub name: String,
+    pub snes_addr: usize,
+    pub tiles: Vec<ItemTile>,
+}
+
+// Colors of the palette rows used by item PLMs, as they normally appear in-game. Entries that
+// are `None` (transparent in the palette image) are not used by item graphics, for example
+// colors that repeat another entry of the same row.
+pub struct ItemPalette {
+    pub rows: [[Option<[u8; 3]>; 16]; ITEM_PALETTE_ROWS],
+}
+
+fn decode_rgba_image(data: &[u8], name: &str) -> Result<RgbaImage> {
+    Ok(image::load_from_memory(data)
+        .with_context(|| format!("Unable to decode image: {name}"))?
+        .to_rgba8())
+}
+
+fn is_transparent(pixel: &image::Rgba<u8>) -> bool {
+    pixel[3] < 128
+}
+
+impl ItemPalette {
+    pub fn from_image(img: &RgbaImage) -> Result<Self> {
+        ensure!(
+            img.width() == 16 && img.height() == ITEM_PALETTE_ROWS as u32,
+            "Item palette image must be 16x{ITEM_PALETTE_ROWS}, not {}x{}",
+            img.width(),
+            img.height()
+        );
+        let mut rows = [[None; 16]; ITEM_PALETTE_ROWS];
+        for (row_idx, row) in rows.iter_mut().enumerate() {
+            // Color 0 is always transparent.
+            for (i, color) in row.iter_mut().enumerate().skip(1) {
+                let pixel = img.get_pixel(i as u32, row_idx as u32);
+                if !is_transparent(pixel) {
+                    *color = Some([pixel[0], pixel[1], pixel[2]]);
+                }
+            }
+        }
+        Ok(ItemPalette { rows })
+    }
+
+    // Index of the color in the given row nearest to `rgb` (the lowest index, in case of a tie).
+    pub fn nearest(&self, row: usize, rgb: [u8; 3]) -> Result<u8> {
+        let Some(colors) = self.rows.get(row) else {
+            bail!("Item palette row {row} must be less than {ITEM_PALETTE_ROWS}");
+        };
+        let dist =
+            |c: [u8; 3]| -> i32 { (0..3).map(|i| (c[i] as i32 - rgb[i] as i32).pow(2)).sum() };
+        colors
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| c.map(|c| (dist(c), i as u8)))
+            .min()
+            .map(|(_, i)| i)
+            .with_context(|| format!("Item palette row {row} has no colors"))
+    }
+}
+
+// Convert an item sprite sheet to the 8 tiles of its PLM graphics (frame 1 followed by frame 2),
+// using the palette row given by `palettes` for each tile.
+pub fn encode_item_graphics(
+    img: &RgbaImage,
+    palette: &ItemPalette,
+    palettes: &[u8; ITEM_GRAPHICS_TILES],
+) -> Result<Vec<ItemTile>> {
+    ensure!(
+        img.width() == 32 && img.height() == 16,
+        "Item sprite sheet must be 32x16, not {}x{}",
+        img.width(),
+        img.height()
+    );
+    let mut tiles: Vec<ItemTile> = vec![];
+    for frame in 0..2 {
+        for tile_y in 0..2 {
+            for tile_x in 0..2 {
+                let row = palettes[tiles.len()] as usize;
+                let mut tile: ItemTile = [[0; 8]; 8];
+                for y in 0..8 {
+                    for x in 0..8 {
+                        let pixel = img.get_pixel(
+                            (frame * 16 + tile_x * 8 + x) as u32,
+                            (tile_y * 8 + y) as u32,
+                        );
+                        if !is_transparent(pixel) {
+                            tile[y][x] = palette.nearest(row, [pixel[0], pixel[1], pixel[2]])?;
+                        }
+                    }
+                }
+                tiles.push(tile);
+            }
+        }
+    }
+    Ok(tiles)
+}
+
+fn parse_snes_addr(addr: &str) -> Result<usize> {
+    usize::from_str_radix(addr.trim_start_matches('$'), 16)
+        .with_context(|| format!("Invalid SNES address: {addr}"))
+}
+
+pub fn load_item_graphics(data_source: &dyn DataSource) -> Result<Vec<ItemGraphics>> {
+    let palette_path = format!("{ITEM_GRAPHICS_DIR}/palette.png");
+    let palette = ItemPalette::from_image(&decode_rgba_image(
+        &data_source.read(&palette_path)?,
+        &palette_path,
+    )?)?;
+
+    let list_path = format!("{ITEM_GRAPHICS_DIR}/items.json");
+    let entries: Vec<ItemGraphicsEntry> =
+        serde_json::from_str(&data_source.read_to_string(&list_path)?)
+            .with_context(|| format!("Unable to parse {list_path}"))?;
+    let mut out: Vec<ItemGraphics> = vec![];
+    for entry in entries {
+        let image_path = format!("{ITEM_GRAPHICS_DIR}/{}", entry.image);
+        let img = decode_rgba_image(&data_source.read(&image_path)?, &image_path)?;
+        let tiles = encode_item_graphics(&img, &palette, &entry.palettes)
+            .with_context(|| format!("Invalid item graphics for {}", entry.name))?;
+        out.push(ItemGraphics {
+            name: entry.name,
+            snes_addr: parse_snes_addr(&entry.addr)?,
+            tiles,
+        });
+    }
+    Ok(out)
+}
@@ -0,0 +1,56 @@
+// Write journal of ROM generation, for testing the patcher without the vanilla ROM.
+//
+// With the journal enabled (`Rom::enable_journal`), every write through the `Rom` write methods
+// (including IPS patches) is recorded along with the patch pass that made it. Generating a ROM
+// from a zero-filled stand-in (`make_rom_journal`) gives a journal that depends only on the
+// settings, randomization and data files, which golden tests can compare against a snapshot.
+
+use std::fmt::Write;
+
+// Writes at least this long are summarized by their checksum rather than listed in full:
+const MAX_LISTED_WRITE_LEN: usize = 32;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RomWrite {
+    pub pass: String,
+    pub addr: usize,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct RomJournal {
+    pub writes: Vec<RomWrite>,
+    // Passes that failed on the stand-in ROM, with their errors:
+    pub failed_passes: Vec<(String, String)>,
+}
+
+impl RomJournal {
+    // Text form of the journal, one line per write, optionally restricted to the given passes.
+    pub fn to_text(&self, passes: Option<&[&str]>) -> String {
+        let included = |pass: &str| passes.is_none_or(|p| p.contains(&pass));
+        let mut out = String::new();
+        for w in &self.writes {
+            if !included(&w.pass) {
+                continue;
+            }
+            write!(out, "{} {:06X} {}", w.pass, w.addr, w.data.len()).unwrap();
+            if w.data.len() < MAX_LISTED_WRITE_LEN {
+                out.push(' ');
+                for b in &w.data {
+                    write!(out, "{b:02X}").unwrap();
+                }
+            } else {
+                let mut hasher = crc32fast::Hasher::new();
+                hasher.update(&w.data);
+                write!(out, " crc32:{:08X}", hasher.finalize()).unwrap();
+            }
+            out.push('\n');
+        }
+        for (pass, err) in &self.failed_passes {
+            if included(pass) {
+                writeln!(out, "{pass} failed: {err}").unwrap();
+            }
+        }
+        out
+    }
+}
@@ -54,12 +54,18 @@ pub struct MapPatcher<'a> {
     pub room_map_gfx: HashMap<RoomPtr, Vec<TilemapWord>>,
     pub room_map_tilemap: HashMap<RoomPtr, Vec<TilemapWord>>,
     pub room_map_dynamic_tiles: HashMap<RoomPtr, Vec<(ItemIdx, TilemapOffset, TilemapWord)>>,
+    // Next free address in the dynamic tile region, after the per-area lists:
+    pub next_dynamic_tile_addr: usize,
 }
 
 pub const VANILLA_ELEVATOR_TILE: TilemapWord = 0xCE; // Index of elevator tile in vanilla game
 pub const ELEVATOR_TILE: TilemapWord = 0x12; // Index of elevator tile with TR's map patch
 pub const TILE_GFX_ADDR_4BPP: usize = 0xE28000; // Where to store area-specific tile graphics (must agree with map_area.asm)
 pub const TILE_GFX_ADDR_2BPP: usize = 0xE38000; // Where to store area-specific tile graphics (must agree with map_area.asm)
+// Region for dynamic tile data (must agree with item_dots_disappear.asm): a table of per-area list
+// pointers and sizes, followed by the per-area lists and then the per-room lists.
+pub const DYNAMIC_TILE_TABLE_ADDR: usize = 0xEAAE00;
+pub const DYNAMIC_TILE_DATA_END: usize = 0xEB0000;
 
 const FLIP_X: TilemapWord = 0x4000;
 const FLIP_Y: TilemapWord = 0x8000;
@@ -1798,6 +1804,7 @@ impl<'a> MapPatcher<'a> {
             room_map_gfx: HashMap::new(),
             room_map_tilemap: HashMap::new(),
             room_map_dynamic_tiles: HashMap::new(),
+            next_dynamic_tile_addr: DYNAMIC_TILE_TABLE_ADDR + 24,
         }
     }
 
@@ -2634,7 +2641,7 @@ impl<'a> MapPatcher<'a> {
         area_data: &[Vec<(ItemIdx, RoomId, MapTile)>],
     ) -> Result<()> {
         // Write per-area item listings, to be used by the patch `item_dots_disappear.asm`.
-        let base_ptr = 0x83E000;
+        let base_ptr = DYNAMIC_TILE_TABLE_ADDR;
         let mut data_ptr = base_ptr + 24;
         for (area_idx, data) in area_data.iter().enumerate() {
             self.rom.write_u16(
@@ -2660,7 +2667,10 @@ impl<'a> MapPatcher<'a> {
             }
             assert_eq!(data_ptr, data_start + 6 * data.len());
         }
-        assert!(data_ptr <= 0x83F000);
+        if data_ptr > DYNAMIC_TILE_DATA_END {
+            bail!("Dynamic tile data overflow: {data_ptr:x}");
+        }
+        self.next_dynamic_tile_addr = data_ptr;
         Ok(())
     }
 
@@ -0,0 +1,416 @@
+// Tracking of which patch pass wrote each part of the ROM, and running passes in dependency order.
+//
+// Each step of ROM generation (a `Patcher` method, an IPS file, etc.) runs as a named pass. While a
+// pass is active, bytes written through the `Rom` write methods are attributed to it (direct
+// writes to `Rom::data` are not tracked). A pass overwriting bytes owned by an earlier pass is a
+// conflict, unless the earlier pass is listed in its declared overrides.
+
+use std::fmt::Write;
+
+use anyhow::{Result, bail};
+use hashbrown::HashMap;
+use log::warn;
+
+use super::Rom;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    // Don't track writes at all, so that neither conflicts nor written byte counts are recorded.
+    // This avoids the tracking overhead when no report or conflict check is wanted.
+    Ignore,
+    // Record conflicts, to be reported afterward (see `RomWriteLog::warn_conflicts`).
+    Warn,
+    // Fail as soon as a conflict occurs.
+    Strict,
+}
+
+#[derive(Clone, Debug)]
+pub struct PassStats {
+    pub name: String,
+    // Total bytes written by the pass (counting repeated writes to the same byte once per flush).
+    pub bytes_written: usize,
+    // Bytes for which this pass was the last writer.
+    pub bytes_owned: usize,
+}
+
+// Undeclared overlapping writes by `pass` to bytes owned by `previous_pass`.
+#[derive(Clone, Debug)]
+pub struct WriteConflict {
+    pub pass: String,
+    pub previous_pass: String,
+    pub ranges: Vec<(usize, usize)>,
+}
+
+impl WriteConflict {
+    pub fn num_bytes(&self) -> usize {
+        self.ranges.iter().map(|(a, b)| b - a).sum()
+    }
+}
+
+struct PassInfo {
+    name: String,
+    bytes_written: usize,
+}
+
+pub struct RomWriteLog {
+    policy: ConflictPolicy,
+    passes: Vec<PassInfo>,
+    pass_idx_by_name: HashMap<String, usize>,
+    // Active passes (index and declared overrides), innermost last:
+    stack: Vec<(usize, Vec<String>)>,
+    // For each ROM byte, 1 + the index of the pass that last wrote it (or 0 if none):
+    owner: Vec<u16>,
+    conflicts: Vec<WriteConflict>,
+}
+
+fn push_range(ranges: &mut Vec<(usize, usize)>, addr: usize) {
+    match ranges.last_mut() {
+        Some(last) if last.1 == addr => last.1 += 1,
+        _ => ranges.push((addr, addr + 1)),
+    }
+}
+
+impl RomWriteLog {
+    pub fn new(policy: ConflictPolicy) -> Self {
+        RomWriteLog {
+            policy,
+            passes: vec![],
+            pass_idx_by_name: HashMap::new(),
+            stack: vec![],
+            owner: vec![],
+            conflicts: vec![],
+        }
+    }
+
+    // Start attributing writes to the given pass. Passes can be nested (e.g. each IPS file within
+    // the pass that applies them), in which case writes go to the innermost pass. A pass that was
+    // already run can be started again, accumulating into the same entry.
+    pub fn begin(&mut self, rom: &mut Rom, name: &str, overrides: &[&str]) -> Result<()> {
+        if !self.stack.is_empty() {
+            self.flush(rom)?;
+        } else if self.policy != ConflictPolicy::Ignore {
+            rom.enable_tracking();
+        }
+        let pass_idx = match self.pass_idx_by_name.get(name) {
+            Some(&idx) => idx,
+            None => {
+                let idx = self.passes.len();
+                self.passes.push(PassInfo {
+                    name: name.to_string(),
+                    bytes_written: 0,
+                });
+                self.pass_idx_by_name.insert(name.to_string(), idx);
+                idx
+            }
+        };
+        let overrides = overrides.iter().map(|x| x.to_string()).collect();
+        self.stack.push((pass_idx, overrides));
+        rom.journal_pass = name.to_string();
+        Ok(())
+    }
+
+    pub fn end(&mut self, rom: &mut Rom) -> Result<()> {
+        self.flush(rom)?;
+        self.stack.pop();
+        match self.stack.last() {
+            Some(&(pass_idx, _)) => {
+                rom.journal_pass = self.passes[pass_idx].name.clone();
+            }
+            None => {
+                rom.track_touched = false;
+                rom.journal_pass.clear();
+            }
+        }
+        Ok(())
+    }
+
+    // Number of active passes.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    // Attribute the bytes written since the last flush to the innermost active pass.
+    fn flush(&mut self, rom: &mut Rom) -> Result<()> {
+        let Some((pass_idx, overrides)) = self.stack.last() else {
+            rom.touched.clear();
+            return Ok(());
+        };
+        let pass_idx = *pass_idx;
+        let ranges = rom.get_modified_ranges();
+        rom.touched.clear();
+        if let Some(&(_, end)) = ranges.last()
+            && end > self.owner.len()
+        {
+            self.owner.resize(end, 0);
+        }
+
+        let mut conflict_ranges: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+        for &(start, end) in &ranges {
+            self.passes[pass_idx].bytes_written += end - start;
+            for addr in start..end {
+                let prev = self.owner[addr] as usize;
+                if prev != 0
+                    && prev - 1 != pass_idx
+                    && !overrides.contains(&self.passes[prev - 1].name)
+                {
+                    push_range(conflict_ranges.entry(prev - 1).or_default(), addr);
+                }
+                self.owner[addr] = (pass_idx + 1) as u16;
+            }
+        }
+
+        let mut prev_idxs: Vec<usize> = conflict_ranges.keys().copied().collect();
+        prev_idxs.sort();
+        for prev_idx in prev_idxs {
+            let pass = self.passes[pass_idx].name.clone();
+            let previous_pass = self.passes[prev_idx].name.clone();
+            let new_ranges = conflict_ranges.remove(&prev_idx).unwrap();
+            if self.policy == ConflictPolicy::Strict {
+                bail!(
+                    "Patch pass '{pass}' overwrites {} bytes from '{previous_pass}' (first at {:#x})",
+                    new_ranges.iter().map(|(a, b)| b - a).sum::<usize>(),
+                    new_ranges[0].0
+                );
+            }
+            match self
+                .conflicts
+                .iter_mut()
+                .find(|c| c.pass == pass && c.previous_pass == previous_pass)
+            {
+                Some(c) => c.ranges.extend(new_ranges),
+                None => self.conflicts.push(WriteConflict {
+                    pass,
+                    previous_pass,
+                    ranges: new_ranges,
+                }),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn conflicts(&self) -> &[WriteConflict] {
+        &self.conflicts
+    }
+
+    // Name of the pass which last wrote the given ROM address, if any.
+    pub fn get_owner(&self, addr: usize) -> Option<&str> {
+        match self.owner.get(addr) {
+            Some(&x) if x > 0 => Some(self.passes[x as usize - 1].name.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn get_stats(&self) -> Vec<PassStats> {
+        let mut bytes_owned = vec![0; self.passes.len()];
+        for &x in &self.owner {
+            if x > 0 {
+                bytes_owned[x as usize - 1] += 1;
+            }
+        }
+        self.passes
+            .iter()
+            .zip(bytes_owned)
+            .map(|(p, bytes_owned)| PassStats {
+                name: p.name.clone(),
+                bytes_written: p.bytes_written,
+                bytes_owned,
+            })
+            .collect()
+    }
+
+    pub fn warn_conflicts(&self) {
+        for c in &self.conflicts {
+            warn!(
+                "Patch pass '{}' overwrites {} bytes from '{}' (first at {:#x})",
+                c.pass,
+                c.num_bytes(),
+                c.previous_pass,
+                c.ranges[0].0
+            );
+        }
+    }
+
+    // Human-readable listing of bytes per pass, followed by any conflicts.
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "{:<48} {:>10} {:>10}", "Pass", "Written", "Owned").unwrap();
+        for s in self.get_stats() {
+            writeln!(
+                out,
+                "{:<48} {:>10} {:>10}",
+                s.name, s.bytes_written, s.bytes_owned
+            )
+            .unwrap();
+        }
+        if !self.conflicts.is_empty() {
+            writeln!(out, "\nConflicts:").unwrap();
+            for c in &self.conflicts {
+                let ranges: Vec<String> = c
+                    .ranges
+                    .iter()
+                    .map(|(a, b)| format!("{a:#x}-{b:#x}"))
+                    .collect();
+                writeln!(
+                    out,
+                    "{} overwrites {} bytes from {}: {}",
+                    c.pass,
+                    c.num_bytes(),
+                    c.previous_pass,
+                    ranges.join(", ")
+                )
+                .unwrap();
+            }
+        }
+        out
+    }
+}
+
+// Anything that passes can run against: gives access to the ROM being patched and its write log.
+pub trait PassTarget {
+    fn rom_and_write_log(&mut self) -> (&mut Rom, &mut RomWriteLog);
+}
+
+type PassFn<'r, T> = Box<dyn FnOnce(&mut T) -> Result<()> + 'r>;
+
+pub struct PatchPass<'r, T> {
+    name: &'static str,
+    after: Vec<&'static str>,
+    overrides: Vec<&'static str>,
+    enabled: bool,
+    run: PassFn<'r, T>,
+}
+
+impl<T> PatchPass<'_, T> {
+    // Passes that must run before this one.
+    pub fn after(&mut self, names: &[&'static str]) -> &mut Self {
+        self.after.extend(names);
+        self
+    }
+
+    // Passes whose bytes this one may intentionally overwrite.
+    pub fn overrides(&mut self, names: &[&'static str]) -> &mut Self {
+        self.overrides.extend(names);
+        self
+    }
+
+    // Disabled passes are skipped, but still count for ordering of the passes that depend on them.
+    pub fn enabled(&mut self, enabled: bool) -> &mut Self {
+        self.enabled = enabled;
+        self
+    }
+}
+
+pub struct PassRegistry<'r, T> {
+    passes: Vec<PatchPass<'r, T>>,
+}
+
+impl<T> Default for PassRegistry<'_, T> {
+    fn default() -> Self {
+        PassRegistry { passes: vec![] }
+    }
+}
+
+impl<'r, T: PassTarget> PassRegistry<'r, T> {
+    pub fn add(
+        &mut self,
+        name: &'static str,
+        run: impl FnOnce(&mut T) -> Result<()> + 'r,
+    ) -> &mut PatchPass<'r, T> {
+        self.passes.push(PatchPass {
+            name,
+            after: vec![],
+            overrides: vec![],
+            enabled: true,
+            run: Box::new(run),
+        });
+        self.passes.last_mut().unwrap()
+    }
+
+    // Order the passes so that each runs after its dependencies, otherwise keeping the order in
+    // which they were added.
+    pub fn get_order(&self) -> Result<Vec<&'static str>> {
+        let mut idx_by_name: HashMap<&str, usize> = HashMap::new();
+        for (i, p) in self.passes.iter().enumerate() {
+            if idx_by_name.insert(p.name, i).is_some() {
+                bail!("Duplicate patch pass '{}'", p.name);
+            }
+        }
+        let mut deps: Vec<Vec<usize>> = vec![];
+        for p in &self.passes {
+            let mut d = vec![];
+            for name in &p.after {
+                match idx_by_name.get(name) {
+                    Some(&i) => d.push(i),
+                    None => bail!("Patch pass '{}' depends on unknown pass '{name}'", p.name),
+                }
+            }
+            deps.push(d);
+        }
+        let mut done = vec![false; self.passes.len()];
+        let mut order = vec![];
+        while order.len() < self.passes.len() {
+            let Some(next) =
+                (0..self.passes.len()).find(|&i| !done[i] && deps[i].iter().all(|&j| done[j]))
+            else {
+                let remaining: Vec<&str> = (0..self.passes.len())
+                    .filter(|&i| !done[i])
+                    .map(|i| self.passes[i].name)
+                    .collect();
+                bail!(
+                    "Cyclic patch pass dependencies among: {}",
+                    remaining.join(", ")
+                );
+            };
+            done[next] = true;
+            order.push(next);
+        }
+        Ok(order.into_iter().map(|i| self.passes[i].name).collect())
+    }
+
+    pub fn run(self, target: &mut T) -> Result<()> {
+        let failures = self.run_passes(target, false)?;
+        assert!(failures.is_empty());
+        Ok(())
+    }
+
+    // Run all passes, continuing past any that fail, and return the names and errors of those.
+    // This is for running on a stand-in ROM (see `journal`), where passes that depend on data
+    // from the vanilla ROM may fail without affecting the others.
+    pub fn run_keep_going(self, target: &mut T) -> Result<Vec<(&'static str, anyhow::Error)>> {
+        self.run_passes(target, true)
+    }
+
+    fn run_passes(
+        self,
+        target: &mut T,
+        keep_going: bool,
+    ) -> Result<Vec<(&'static str, anyhow::Error)>> {
+        let order = self.get_order()?;
+        let mut passes: HashMap<&'static str, PatchPass<'r, T>> =
+            self.passes.into_iter().map(|p| (p.name, p)).collect();
+        let mut failures = vec![];
+        for name in order {
+            let pass = passes.remove(name).unwrap();
+            if !pass.enabled {
+                continue;
+            }
+            let (rom, write_log) = target.rom_and_write_log();
+            let depth = write_log.depth();
+            write_log.begin(rom, pass.name, &pass.overrides)?;
+            let result = (pass.run)(target);
+            let (rom, write_log) = target.rom_and_write_log();
+            match result {
+                Ok(()) => write_log.end(rom)?,
+                Err(e) if keep_going => {
+                    // Close any passes left open by the failure, including this one:
+                    while write_log.depth() > depth {
+                        write_log.end(rom)?;
+                    }
+                    failures.push((name, e));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(failures)
+    }
+}
@@ -0,0 +1,125 @@
+// Debugger symbols for the data the patcher generates, so that injected ASM and tables can be
+// found in bsnes-plus (WLA `.sym`) or Mesen (`.mlb`).
+//
+// Symbol addresses are PC (file) offsets, the same as the `Rom` write methods use.
+
+use super::{pc2snes, snes2pc};
+use anyhow::{Context, Result, bail};
+use std::fmt::Write;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RomSymbol {
+    pub name: String,
+    pub addr: usize,
+    // Size of the labeled block in bytes, or 0 if unknown:
+    pub size: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SymbolTable {
+    pub symbols: Vec<RomSymbol>,
+}
+
+// Keep only characters that every debugger's label syntax accepts.
+fn sanitize_label(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+impl SymbolTable {
+    pub fn add(&mut self, name: &str, addr: usize, size: usize) {
+        self.symbols.push(RomSymbol {
+            name: sanitize_label(name),
+            addr,
+            size,
+        });
+    }
+
+    // Add the labels from a WLA symbol file (as written by asar `--symbols=wla`), each prefixed
+    // by `prefix`. Anonymous labels and labels outside of ROM are skipped.
+    pub fn load_wla(&mut self, text: &str, prefix: &str) -> Result<()> {
+        let mut in_labels = false;
+        for line in text.lines() {
+            let line = line.split(';').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') {
+                in_labels = line == "[labels]";
+                continue;
+            }
+            if !in_labels {
+                continue;
+            }
+            let Some((addr_str, name)) = line.split_once(char::is_whitespace) else {
+                bail!("Invalid symbol line: {line}");
+            };
+            let name = name.trim();
+            if name.starts_with(':') {
+                continue;
+            }
+            let Some((bank_str, offset_str)) = addr_str.split_once(':') else {
+                bail!("Invalid symbol address: {addr_str}");
+            };
+            let bank = usize::from_str_radix(bank_str, 16)
+                .with_context(|| format!("Invalid symbol address: {addr_str}"))?;
+            let offset = usize::from_str_radix(offset_str, 16)
+                .with_context(|| format!("Invalid symbol address: {addr_str}"))?;
+            if (bank & 0x7F) >= 0x7E || offset < 0x8000 {
+                // RAM, or the lower half of a bank which isn't mapped to ROM in LoROM:
+                continue;
+            }
+            self.add(&format!("{prefix}_{name}"), snes2pc(bank << 16 | offset), 0);
+        }
+        Ok(())
+    }
+
+    fn sorted(&self) -> Vec<&RomSymbol> {
+        let mut symbols: Vec<&RomSymbol> = self.symbols.iter().collect();
+        symbols.sort_by(|a, b| a.addr.cmp(&b.addr).then_with(|| a.name.cmp(&b.name)));
+        symbols
+    }
+
+    // WLA symbol file, as read by bsnes-plus.
+    pub fn to_sym(&self) -> String {
+        let mut out = String::from("; generated by Map Rando\n\n[labels]\n");
+        for s in self.sorted() {
+            let snes_addr = pc2snes(s.addr);
+            writeln!(
+                out,
+                "{:02x}:{:04x} {}",
+                snes_addr >> 16,
+                snes_addr & 0xFFFF,
+                s.name
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    // Mesen label file, with ranges for symbols of known size.
+    pub fn to_mlb(&self) -> String {
+        let mut out = String::new();
+        for s in self.sorted() {
+            if s.size > 1 {
+                writeln!(
+                    out,
+                    "SnesPrgRom:{:X}-{:X}:{}",
+                    s.addr,
+                    s.addr + s.size - 1,
+                    s.name
+                )
+                .unwrap();
+            } else {
+                writeln!(out, "SnesPrgRom:{:X}:{}", s.addr, s.name).unwrap();
+            }
+        }
+        out
+    }
+}
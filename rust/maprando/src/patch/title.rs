@@ -1,11 +1,10 @@
-use std::path::Path;
-
 use crate::patch::compress::compress;
-use maprando_game::{IndexedVec, read_image};
+use maprando_game::{IndexedVec, data_source::DataSource, decode_image};
 
 use super::{PcAddr, Rom, decompress::decompress, pc2snes, snes2pc};
-use anyhow::{Result, bail, ensure};
+use anyhow::{Context, Result, bail, ensure};
 use hashbrown::HashMap;
+use image::imageops::FilterType;
 use ndarray::{Array2, Array3, Axis, concatenate};
 use slice_of_array::prelude::*;
 
@@ -15,6 +14,30 @@ pub struct TitlePatcher<'a> {
     pub end_free_space_pc: usize,
 }
 
+// Size of the title screen background, in pixels (height, width):
+pub const TITLE_BACKGROUND_DIM: (usize, usize) = (224, 256);
+// The background shares CGRAM with the sprites, which use the upper 128 colors.
+const MAX_BACKGROUND_COLORS: usize = 128;
+const MAX_BACKGROUND_TILES: usize = 256;
+// Positions (tile_y, tile_x) of the map station tiles, followed by a blank tile, which must be the
+// first tiles of the background.
+const FIXED_BACKGROUND_TILES: [(usize, usize); 5] =
+    [(20, 15), (20, 16), (21, 15), (21, 16), (16, 15)];
+
+type BackgroundTile = [[[u8; 3]; 8]; 8];
+
+fn get_background_tile(image: &Array3<u8>, tile_y: usize, tile_x: usize) -> BackgroundTile {
+    let mut tile: BackgroundTile = [[[0; 3]; 8]; 8];
+    for y in 0..8 {
+        for x in 0..8 {
+            for c in 0..3 {
+                tile[y][x][c] = image[[tile_y * 8 + y, tile_x * 8 + x, c]];
+            }
+        }
+    }
+    tile
+}
+
 fn rgb_to_u16(rgb: (u8, u8, u8)) -> u16 {
     let (r, g, b) = rgb;
     (r as u16) | (g as u16) << 5 | (b as u16) << 10
@@ -30,33 +53,28 @@ fn encode_mode7_graphics(image: &Array3<u8>) -> Result<Graphics> {
     let (height, width, _) = image.dim();
 
     let mut tilemap: Array2<u8> = Array2::zeros([height / 8, width / 8]);
-    let mut tile_isv: IndexedVec<[[[u8; 3]; 8]; 8]> = IndexedVec::default();
+    let mut tile_isv: IndexedVec<BackgroundTile> = IndexedVec::default();
 
     let mut process_tile = |tile_y, tile_x| {
-        let mut tile: [[[u8; 3]; 8]; 8] = [[[0; 3]; 8]; 8];
-        for y in 0..8 {
-            for x in 0..8 {
-                for c in 0..3 {
-                    tile[y][x][c] = image[[tile_y * 8 + y, tile_x * 8 + x, c]];
-                }
-            }
-        }
+        let tile = get_background_tile(image, tile_y, tile_x);
         let tile_idx = tile_isv.add(&tile) as u8;
         tilemap[[tile_y, tile_x]] = tile_idx;
     };
 
     // Process the map station tiles first (required for the animation patch)
-    process_tile(20, 15);
-    process_tile(20, 16);
-    process_tile(21, 15);
-    process_tile(21, 16);
-    process_tile(16, 15); // blank tile
+    for (tile_y, tile_x) in FIXED_BACKGROUND_TILES {
+        process_tile(tile_y, tile_x);
+    }
     for tile_y in 0..(height / 8) {
         for tile_x in 0..(width / 8) {
             process_tile(tile_y, tile_x);
         }
     }
-    ensure!(tile_isv.keys.len() <= 256);
+    ensure!(
+        tile_isv.keys.len() <= MAX_BACKGROUND_TILES,
+        "Title background has {} distinct 8x8 tiles, but at most {MAX_BACKGROUND_TILES} are supported",
+        tile_isv.keys.len()
+    );
 
     let mut new_tiles: Vec<[[u8; 8]; 8]> = Vec::new();
     let mut color_isv: IndexedVec<(u8, u8, u8)> = IndexedVec::default();
@@ -76,6 +94,12 @@ fn encode_mode7_graphics(image: &Array3<u8>) -> Result<Graphics> {
         new_tiles.push(new_tile);
     }
 
+    ensure!(
+        color_isv.keys.len() <= MAX_BACKGROUND_COLORS,
+        "Title background has {} distinct colors, but at most {MAX_BACKGROUND_COLORS} are supported",
+        color_isv.keys.len()
+    );
+
     Ok(Graphics {
         palette: color_isv.keys,
         tiles: new_tiles,
@@ -83,6 +107,212 @@ fn encode_mode7_graphics(image: &Array3<u8>) -> Result<Graphics> {
     })
 }
 
+// Reduce the image to at most `max_colors` distinct colors (at the SNES color depth of 5 bits
+// per channel), using median cut.
+fn reduce_colors(img: &mut Array3<u8>, max_colors: usize) {
+    let (height, width, _) = img.dim();
+    let mut color_counts: HashMap<[u8; 3], usize> = HashMap::new();
+    for y in 0..height {
+        for x in 0..width {
+            let c = [0, 1, 2].map(|i| img[[y, x, i]] / 8);
+            *color_counts.entry(c).or_default() += 1;
+        }
+    }
+    if color_counts.len() <= max_colors {
+        return;
+    }
+    let mut colors: Vec<([u8; 3], usize)> = color_counts.into_iter().collect();
+    colors.sort();
+
+    let mut boxes: Vec<Vec<([u8; 3], usize)>> = vec![colors];
+    while boxes.len() < max_colors {
+        // Split the box having the widest range in any channel, at the median pixel:
+        let (box_idx, channel, range) = boxes
+            .iter()
+            .enumerate()
+            .flat_map(|(i, b)| {
+                (0..3).map(move |c| {
+                    let min = b.iter().map(|x| x.0[c]).min().unwrap();
+                    let max = b.iter().map(|x| x.0[c]).max().unwrap();
+                    (i, c, max - min)
+                })
+            })
+            .max_by_key(|x| x.2)
+            .unwrap();
+        if range == 0 {
+            break;
+        }
+        let mut b = boxes.swap_remove(box_idx);
+        b.sort_by_key(|x| x.0[channel]);
+        let total: usize = b.iter().map(|x| x.1).sum();
+        let mut count = 0;
+        let mut split = b.len() - 1;
+        for (i, x) in b.iter().enumerate() {
+            count += x.1;
+            if count * 2 >= total {
+                split = i + 1;
+                break;
+            }
+        }
+        let rest = b.split_off(split.clamp(1, b.len() - 1));
+        boxes.push(b);
+        boxes.push(rest);
+    }
+
+    // Replace each color with the average color of its box:
+    let mut color_map: HashMap<[u8; 3], [u8; 3]> = HashMap::new();
+    for b in &boxes {
+        let total: usize = b.iter().map(|x| x.1).sum();
+        let avg = [0, 1, 2].map(|c| {
+            let sum: usize = b.iter().map(|x| x.0[c] as usize * x.1).sum();
+            ((sum + total / 2) / total) as u8
+        });
+        for x in b {
+            color_map.insert(x.0, avg);
+        }
+    }
+    for y in 0..height {
+        for x in 0..width {
+            let c = [0, 1, 2].map(|i| img[[y, x, i]] / 8);
+            let new_c = color_map[&c];
+            for i in 0..3 {
+                img[[y, x, i]] = new_c[i] * 8;
+            }
+        }
+    }
+}
+
+// Reduce the image to at most `max_tiles` distinct 8x8 tiles, by repeatedly replacing every
+// occurrence of a tile with the nearest remaining tile, choosing the replacement that changes the
+// image the least. Replacements are existing tiles, so no new colors are introduced. The tiles in
+// `FIXED_BACKGROUND_TILES` are never replaced.
+fn reduce_tiles(img: &mut Array3<u8>, max_tiles: usize) {
+    let (height, width, _) = img.dim();
+    let mut tile_isv: IndexedVec<BackgroundTile> = IndexedVec::default();
+    let mut tilemap: Array2<usize> = Array2::zeros([height / 8, width / 8]);
+    for tile_y in 0..(height / 8) {
+        for tile_x in 0..(width / 8) {
+            tilemap[[tile_y, tile_x]] = tile_isv.add(&get_background_tile(img, tile_y, tile_x));
+        }
+    }
+    let tiles = &tile_isv.keys;
+    let n = tiles.len();
+    if n <= max_tiles {
+        return;
+    }
+
+    let mut counts = vec![0; n];
+    for &i in &tilemap {
+        counts[i] += 1;
+    }
+    let mut fixed = vec![false; n];
+    for (tile_y, tile_x) in FIXED_BACKGROUND_TILES {
+        fixed[tilemap[[tile_y, tile_x]]] = true;
+    }
+    let mut dist: Array2<u64> = Array2::zeros([n, n]);
+    for i in 0..n {
+        for j in 0..i {
+            let mut d = 0;
+            for y in 0..8 {
+                for x in 0..8 {
+                    for c in 0..3 {
+                        d += (tiles[i][y][x][c] as i64 - tiles[j][y][x][c] as i64).pow(2) as u64;
+                    }
+                }
+            }
+            dist[[i, j]] = d;
+            dist[[j, i]] = d;
+        }
+    }
+
+    // The tile that each tile is replaced by (itself, if it is still in use):
+    let mut replacement: Vec<usize> = (0..n).collect();
+    let nearest = |i: usize, replacement: &[usize]| -> usize {
+        (0..n)
+            .filter(|&j| j != i && replacement[j] == j)
+            .min_by_key(|&j| dist[[i, j]])
+            .unwrap()
+    };
+    let mut nearest_tile: Vec<usize> = (0..n).map(|i| nearest(i, &replacement)).collect();
+    for _ in max_tiles..n {
+        let Some(i) = (0..n)
+            .filter(|&i| replacement[i] == i && !fixed[i])
+            .min_by_key(|&i| dist[[i, nearest_tile[i]]] * counts[i] as u64)
+        else {
+            break;
+        };
+        let j = nearest_tile[i];
+        for k in 0..n {
+            if replacement[k] == i {
+                replacement[k] = j;
+            }
+        }
+        counts[j] += counts[i];
+        for k in 0..n {
+            if replacement[k] == k && nearest_tile[k] == i {
+                nearest_tile[k] = nearest(k, &replacement);
+            }
+        }
+    }
+
+    for tile_y in 0..(height / 8) {
+        for tile_x in 0..(width / 8) {
+            let tile = &tiles[replacement[tilemap[[tile_y, tile_x]]]];
+            for y in 0..8 {
+                for x in 0..8 {
+                    for c in 0..3 {
+                        img[[tile_y * 8 + y, tile_x * 8 + x, c]] = tile[y][x][c];
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Draw the map station over the title background, skipping its black (transparent) pixels.
+pub fn draw_map_station(img: &mut Array3<u8>, map_station: &Array3<u8>) {
+    let (height, width) = TITLE_BACKGROUND_DIM;
+    for y in 0..height {
+        for x in 0..width {
+            if map_station[(y, x, 0)] == 0
+                && map_station[(y, x, 1)] == 0
+                && map_station[(y, x, 2)] == 0
+            {
+                continue;
+            }
+            img[(y, x, 0)] = map_station[(y, x, 0)];
+            img[(y, x, 1)] = map_station[(y, x, 1)];
+            img[(y, x, 2)] = map_station[(y, x, 2)];
+        }
+    }
+}
+
+// Prepare a user-supplied image (in any format supported by the `image` crate) for use as the
+// title screen background: it is scaled and cropped to fill the screen, the map station is drawn
+// over it, and its colors and tiles are reduced to fit in the palette and tile graphics. An error
+// is returned if the result still can't be encoded.
+pub fn load_title_background(data: &[u8], map_station: &Array3<u8>) -> Result<Array3<u8>> {
+    let (height, width) = TITLE_BACKGROUND_DIM;
+    let mut decoded =
+        image::load_from_memory(data).context("Unable to decode title screen image")?;
+    if decoded.width() != width as u32 || decoded.height() != height as u32 {
+        decoded = decoded.resize_to_fill(width as u32, height as u32, FilterType::Lanczos3);
+    }
+    let rgb = decoded.to_rgb8();
+    let mut img: Array3<u8> = Array3::zeros([height, width, 3]);
+    for (x, y, pixel) in rgb.enumerate_pixels() {
+        for c in 0..3 {
+            img[[y as usize, x as usize, c]] = pixel[c];
+        }
+    }
+    draw_map_station(&mut img, map_station);
+    // One color is reserved for black, which is always included in the palette.
+    reduce_colors(&mut img, MAX_BACKGROUND_COLORS - 1);
+    reduce_tiles(&mut img, MAX_BACKGROUND_TILES);
+    encode_mode7_graphics(&img).context("Unable to use title screen image")?;
+    Ok(img)
+}
+
 fn decode_tile_4bpp(tile: &[u8; 32]) -> [[u8; 8]; 8] {
     let mut out: [[u8; 8]; 8] = [[0; 8]; 8];
     for y in 0..8 {
@@ -239,7 +469,7 @@ impl<'a> TitlePatcher<'a> {
     }
 
     pub fn patch_title_background(&mut self, img: &Array3<u8>) -> Result<()> {
-        assert!(img.dim() == (224, 256, 3));
+        assert!(img.dim() == (TITLE_BACKGROUND_DIM.0, TITLE_BACKGROUND_DIM.1, 3));
 
         // Compute title background palette, tile GFX, and tilemap:
         let graphics = encode_mode7_graphics(img)?;
@@ -337,15 +567,15 @@ impl<'a> TitlePatcher<'a> {
         Ok(())
     }
 
-    pub fn patch_title_foreground(&mut self) -> Result<()> {
+    pub fn patch_title_foreground(&mut self, data_source: &dyn DataSource) -> Result<()> {
         // Start by loading the vanilla tiles & spritemap, for "Super Metroid" title:
         let mut tiles = self.read_compressed_tiles(snes2pc(0x9580D8))?;
         let mut spritemap = self.read_spritemap(snes2pc(0x8C879D))?;
 
         // Now we will patch the tiles & spritemap by adding "Map Rando" to the same sprite.
         // First load the image:
-        let image_path = Path::new("../gfx/title/maprando.png");
-        let img = read_image(image_path)?;
+        let image_path = "gfx/title/maprando.png";
+        let img = decode_image(&data_source.read(image_path)?, image_path)?;
         assert!(img.dim() == (224, 256, 3));
 
         // We don't modify the palette, just reuse colors from the existing palette.
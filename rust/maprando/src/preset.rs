@@ -1,9 +1,7 @@
-use std::path::Path;
-
 use anyhow::{Context, Result};
 use hashbrown::HashMap;
 use log::warn;
-use maprando_game::{GameData, IndexedVec, NotableId, RoomId, TechId};
+use maprando_game::{GameData, IndexedVec, NotableId, RoomId, TechId, data_source::DataSource};
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -80,14 +78,13 @@ fn get_notables_by_difficulty(
 }
 
 impl PresetData {
-    pub fn load(
-        tech_path: &Path,
-        notable_path: &Path,
-        presets_path: &Path,
-        game_data: &GameData,
-    ) -> Result<Self> {
-        let tech_data_str = std::fs::read_to_string(tech_path)
-            .context(format!("reading from {}", tech_path.display()))?;
+    pub fn load(data_source: &dyn DataSource, game_data: &GameData) -> Result<Self> {
+        let tech_path = "data/tech_data.json";
+        let notable_path = "data/notable_data.json";
+        let presets_path = "data/presets";
+        let tech_data_str = data_source
+            .read_to_string(tech_path)
+            .context(format!("reading from {tech_path}"))?;
         let mut tech_data: Vec<TechData> = serde_json::from_str(&tech_data_str)?;
         for d in &mut tech_data {
             if d.difficulty == "Uncategorized" {
@@ -101,8 +98,9 @@ impl PresetData {
             .map(|x| (x.tech_id, x))
             .collect();
 
-        let notable_data_str = std::fs::read_to_string(notable_path)
-            .context(format!("reading from {}", notable_path.display()))?;
+        let notable_data_str = data_source
+            .read_to_string(notable_path)
+            .context(format!("reading from {notable_path}"))?;
         let mut notable_data: Vec<NotableData> = serde_json::from_str(&notable_data_str)?;
         for d in &mut notable_data {
             if d.difficulty == "Uncategorized" {
@@ -147,15 +145,16 @@ impl PresetData {
 
         let skill_preset_names =
             difficulty_levels.keys[..difficulty_levels.keys.len() - 1].to_vec();
-        let skill_preset_path = presets_path.join("skill-assumptions");
+        let skill_preset_path = format!("{presets_path}/skill-assumptions");
         let mut skill_presets: Vec<SkillAssumptionSettings> = vec![];
         let mut difficulty_tiers: Vec<DifficultyConfig> = vec![];
         for name in &skill_preset_names {
-            let path = skill_preset_path.join(format!("{name}.json"));
-            let preset_str = std::fs::read_to_string(path.clone())
-                .context(format!("reading from {}", path.display()))?;
+            let path = format!("{skill_preset_path}/{name}.json");
+            let preset_str = data_source
+                .read_to_string(&path)
+                .context(format!("reading from {path}"))?;
             let preset: SkillAssumptionSettings =
-                serde_json::from_str(&preset_str).context(format!("parsing {}", path.display()))?;
+                serde_json::from_str(&preset_str).context(format!("parsing {path}"))?;
             let difficulty =
                 DifficultyConfig::new(&preset, game_data, implicit_tech, implicit_notables);
             assert!(preset.preset == Some(name.to_string()));
@@ -168,27 +167,29 @@ impl PresetData {
 
         let item_progression_preset_names =
             ["Normal", "Tricky", "Technical", "Challenge", "Desolate"];
-        let item_progression_preset_path = presets_path.join("item-progression");
+        let item_progression_preset_path = format!("{presets_path}/item-progression");
         let mut item_progression_presets: Vec<ItemProgressionSettings> = vec![];
         for name in item_progression_preset_names {
-            let path = item_progression_preset_path.join(format!("{name}.json"));
-            let preset_str = std::fs::read_to_string(path.clone())
-                .context(format!("reading from {}", path.display()))?;
+            let path = format!("{item_progression_preset_path}/{name}.json");
+            let preset_str = data_source
+                .read_to_string(&path)
+                .context(format!("reading from {path}"))?;
             let preset: ItemProgressionSettings =
-                serde_json::from_str(&preset_str).context(format!("parsing {}", path.display()))?;
+                serde_json::from_str(&preset_str).context(format!("parsing {path}"))?;
             assert!(preset.preset == Some(name.to_string()));
             item_progression_presets.push(preset);
         }
 
         let qol_preset_names = ["Off", "Low", "Default", "High", "Max"];
-        let qol_preset_path = presets_path.join("quality-of-life");
+        let qol_preset_path = format!("{presets_path}/quality-of-life");
         let mut quality_of_life_presets: Vec<QualityOfLifeSettings> = vec![];
         for name in qol_preset_names {
-            let path = qol_preset_path.join(format!("{name}.json"));
-            let preset_str = std::fs::read_to_string(path.clone())
-                .context(format!("reading from {}", path.display()))?;
+            let path = format!("{qol_preset_path}/{name}.json");
+            let preset_str = data_source
+                .read_to_string(&path)
+                .context(format!("reading from {path}"))?;
             let preset: QualityOfLifeSettings =
-                serde_json::from_str(&preset_str).context(format!("parsing {}", path.display()))?;
+                serde_json::from_str(&preset_str).context(format!("parsing {path}"))?;
             assert!(preset.preset == Some(name.to_string()));
             quality_of_life_presets.push(preset);
         }
@@ -202,27 +203,29 @@ impl PresetData {
             "Metroids",
             "Random",
         ];
-        let objective_preset_path = presets_path.join("objectives");
+        let objective_preset_path = format!("{presets_path}/objectives");
         let mut objective_presets: Vec<ObjectiveSettings> = vec![];
         for name in objective_preset_names {
-            let path = objective_preset_path.join(format!("{name}.json"));
-            let preset_str = std::fs::read_to_string(path.clone())
-                .context(format!("reading from {}", path.display()))?;
+            let path = format!("{objective_preset_path}/{name}.json");
+            let preset_str = data_source
+                .read_to_string(&path)
+                .context(format!("reading from {path}"))?;
             let preset: ObjectiveSettings =
-                serde_json::from_str(&preset_str).context(format!("parsing {}", path.display()))?;
+                serde_json::from_str(&preset_str).context(format!("parsing {path}"))?;
             assert!(preset.preset == Some(name.to_string()));
             objective_presets.push(preset);
         }
 
         let doors_preset_names = ["Blue", "Ammo", "Beam"];
-        let doors_preset_path = presets_path.join("doors");
+        let doors_preset_path = format!("{presets_path}/doors");
         let mut doors_presets: Vec<DoorsSettings> = vec![];
         for name in doors_preset_names {
-            let path = doors_preset_path.join(format!("{name}.json"));
-            let preset_str = std::fs::read_to_string(path.clone())
-                .context(format!("reading from {}", path.display()))?;
+            let path = format!("{doors_preset_path}/{name}.json");
+            let preset_str = data_source
+                .read_to_string(&path)
+                .context(format!("reading from {path}"))?;
             let preset: DoorsSettings =
-                serde_json::from_str(&preset_str).context(format!("parsing {}", path.display()))?;
+                serde_json::from_str(&preset_str).context(format!("parsing {path}"))?;
             assert!(preset.preset == Some(name.to_string()));
             doors_presets.push(preset);
         }
@@ -233,14 +236,15 @@ impl PresetData {
             "Mentor Tournament",
             "Summer Series Expert Challenge",
         ];
-        let full_preset_path = presets_path.join("full-settings");
+        let full_preset_path = format!("{presets_path}/full-settings");
         let mut full_presets: Vec<RandomizerSettings> = vec![];
         for name in full_preset_names {
-            let path = full_preset_path.join(format!("{name}.json"));
-            let preset_str = std::fs::read_to_string(path.clone())
-                .context(format!("reading from {}", path.display()))?;
+            let path = format!("{full_preset_path}/{name}.json");
+            let preset_str = data_source
+                .read_to_string(&path)
+                .context(format!("reading from {path}"))?;
             let preset: RandomizerSettings =
-                serde_json::from_str(&preset_str).context(format!("parsing {}", path.display()))?;
+                serde_json::from_str(&preset_str).context(format!("parsing {path}"))?;
             full_presets.push(preset);
         }
 
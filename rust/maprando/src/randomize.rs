@@ -1,14 +1,17 @@
 pub mod escape_timer;
 mod run_speed;
+pub mod snapshot;
 
 use crate::helpers::get_item_priorities;
-use crate::patch::NUM_AREAS;
+use crate::map_areas::{assign_areas, solve_area_mapping};
+use crate::map_validation::{MapIssueKind, get_map_issues};
 use crate::patch::map_tiles::get_objective_tiles;
+use crate::patch::{MAX_LOCKED_DOORS, NUM_AREAS};
 use crate::settings::{
-    AreaAssignmentBaseOrder, FillerItemPriority, ItemCount, ItemPlacementStyle,
-    ItemPriorityStrength, KeyItemPriority, MotherBrainFight, Objective, ObjectiveSetting,
-    ProgressionRate, RandomizerSettings, SaveAnimals, SkillAssumptionSettings, SpeedBooster,
-    StartLocationMode, WallJump,
+    AreaAssignmentBaseOrder, AreaConstraints, AreaPartition, FillerItemPriority, ItemCount,
+    ItemPlacementStyle, ItemPriorityStrength, KeyItemPriority, MotherBrainFight, Objective,
+    ObjectiveSetting, ProgressionRate, RandomizerSettings, SaveAnimals, SkillAssumptionSettings,
+    SpeedBooster, StartLocationMode, WallJump,
 };
 use crate::spoiler_log::{
     SpoilerLocalState, SpoilerLog, SpoilerRoomLoc, SpoilerRouteEntry, SpoilerStartLocation,
@@ -45,6 +48,7 @@ use maprando_game::{
 use maprando_logic::{GlobalState, Inventory, LocalState};
 use rand::SeedableRng;
 use rand::{Rng, seq::SliceRandom};
+use rand_chacha::ChaCha12Rng;
 use run_speed::{
     get_extra_run_speed_tiles, get_max_extra_run_speed, get_shortcharge_max_extra_run_speed,
     get_shortcharge_min_extra_run_speed,
@@ -99,6 +103,10 @@ pub struct DifficultyConfig {
     pub ridley_proficiency: f32,
     pub botwoon_proficiency: f32,
     pub mother_brain_proficiency: f32,
+    pub kraid_proficiency: f32,
+    pub crocomire_proficiency: f32,
+    pub spore_spawn_proficiency: f32,
+    pub golden_torizo_proficiency: f32,
 }
 
 impl DifficultyConfig {
@@ -195,6 +203,10 @@ impl DifficultyConfig {
             ridley_proficiency: skill.ridley_proficiency,
             botwoon_proficiency: skill.botwoon_proficiency,
             mother_brain_proficiency: skill.mother_brain_proficiency,
+            kraid_proficiency: skill.kraid_proficiency,
+            crocomire_proficiency: skill.crocomire_proficiency,
+            spore_spawn_proficiency: skill.spore_spawn_proficiency,
+            golden_torizo_proficiency: skill.golden_torizo_proficiency,
         }
     }
 
@@ -276,6 +288,19 @@ impl DifficultyConfig {
                 self.mother_brain_proficiency,
                 other.mother_brain_proficiency,
             ),
+            kraid_proficiency: f32::min(self.kraid_proficiency, other.kraid_proficiency),
+            crocomire_proficiency: f32::min(
+                self.crocomire_proficiency,
+                other.crocomire_proficiency,
+            ),
+            spore_spawn_proficiency: f32::min(
+                self.spore_spawn_proficiency,
+                other.spore_spawn_proficiency,
+            ),
+            golden_torizo_proficiency: f32::min(
+                self.golden_torizo_proficiency,
+                other.golden_torizo_proficiency,
+            ),
         }
     }
 }
@@ -300,7 +325,7 @@ pub struct Randomizer<'a> {
     pub next_traversal_number: RefCell<usize>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ItemLocationState {
     pub placed_item: Option<Item>,
     pub placed_tier: Option<usize>,
@@ -311,7 +336,7 @@ pub struct ItemLocationState {
     pub difficulty_tier: Option<usize>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct FlagLocationState {
     pub reachable_traversal: Option<TraversalId>,
     pub reachable_vertex_id: Option<VertexId>,
@@ -319,13 +344,13 @@ pub struct FlagLocationState {
     pub bireachable_vertex_id: Option<VertexId>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct DoorState {
     pub bireachable_traversal: Option<TraversalId>,
     pub bireachable_vertex_id: Option<VertexId>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SaveLocationState {
     pub bireachable_traversal: Option<TraversalId>,
 }
@@ -339,7 +364,7 @@ pub struct LockedDoor {
 }
 
 // The core graph traversal state, which is large so we want to avoid cloning it where possible.
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TraverserPair {
     pub forward: Traverser,
     pub reverse: Traverser,
@@ -347,9 +372,10 @@ pub struct TraverserPair {
 
 // Other randomization state that changes during or across item placement attempts,
 // small enough that cloning it is fine.
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct RandomizationState {
     pub step_num: usize,
+    #[serde(with = "snapshot::json_string")]
     pub start_location: StartLocation,
     pub hub_location: HubLocation,
     pub item_precedence: Vec<Item>, // An ordering of the 21 distinct item names. The game will prioritize placing key items earlier in the list.
@@ -397,13 +423,52 @@ struct SelectItemsOutput {
     other_items: Vec<Item>,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct StartLocationData {
+    #[serde(with = "snapshot::json_string")]
     pub start_location: StartLocation,
     pub hub_location: HubLocation,
+    #[serde(with = "snapshot::json_string")]
     pub hub_obtain_route: Vec<SpoilerRouteEntry>,
+    #[serde(with = "snapshot::json_string")]
     pub hub_return_route: Vec<SpoilerRouteEntry>,
 }
 
+// Full state of a randomization attempt at the start of an item placement step, from which
+// the remaining steps can be replayed deterministically.
+#[derive(Serialize, Deserialize)]
+pub struct RandomizationCheckpoint {
+    pub attempt_num_rando: usize,
+    pub seed: usize,
+    pub display_seed: usize,
+    pub rng_word_pos: u128,
+    pub start_location_data: StartLocationData,
+    pub state: RandomizationState,
+    pub traverser_pair: TraverserPair,
+}
+
+// ChaCha12Rng produces the same stream as `StdRng`, but unlike it exposes
+// its stream position, which we need in order to resume from a checkpoint.
+fn get_checkpoint_rng(seed: usize) -> ChaCha12Rng {
+    let mut rng_seed = [0u8; 32];
+    rng_seed[..8].copy_from_slice(&seed.to_le_bytes());
+    ChaCha12Rng::from_seed(rng_seed)
+}
+
+// Start location used for the "Ship" start location mode, which is not among the random start
+// locations in the game data.
+pub fn get_ship_start_location() -> StartLocation {
+    StartLocation {
+        name: "Ship".to_string(),
+        room_id: 8,
+        node_id: 5,
+        door_load_node_id: Some(2),
+        x: 72.0,
+        y: 69.5,
+        ..StartLocation::default()
+    }
+}
+
 pub fn randomize_map_areas(map: &mut Map, seed: usize) {
     let mut rng_seed = [0u8; 32];
     rng_seed[..8].copy_from_slice(&seed.to_le_bytes());
@@ -513,6 +578,22 @@ fn shuffle_subareas<R: Rng>(
     }
 }
 
+// Rooms which must end up in a given area, from the area assignment settings.
+fn get_fixed_areas(
+    settings: &RandomizerSettings,
+    game_data: &GameData,
+) -> Vec<(RoomGeometryRoomIdx, AreaIdx)> {
+    let area_assignment = &settings.other_settings.area_assignment;
+    let mut fixed = vec![];
+    if area_assignment.ship_in_crateria {
+        fixed.push((game_data.ship_room_idx, 0));
+    }
+    if area_assignment.mother_brain_in_tourian {
+        fixed.push((game_data.mother_brain_room_idx, 5));
+    }
+    fixed
+}
+
 #[must_use]
 pub fn assign_map_areas(
     map: &mut Map,
@@ -528,9 +609,42 @@ pub fn assign_map_areas(
     let mut area_mapping: Vec<isize> = vec![-1; NUM_AREAS];
     let mut reverse_area_mapping: Vec<isize> = vec![-1; NUM_AREAS];
 
-    if settings.map_layout == "Vanilla"
+    if area_assignment.partition != AreaPartition::Map {
+        if let Err(e) = assign_areas(map, game_data, area_assignment.partition, &mut rng) {
+            info!("Area partitioning failed: {e}");
+            return false;
+        }
+        // The new areas must still fit on the pause map without overlapping:
+        if get_map_issues(map, game_data)
+            .iter()
+            .any(|x| x.kind == MapIssueKind::Bounds || x.kind == MapIssueKind::Overlap)
+        {
+            return false;
+        }
+    } else if settings.map_layout == "Vanilla"
         && area_assignment.base_order != AreaAssignmentBaseOrder::Random
     {
+        // The vanilla areas are kept, except as needed to satisfy the constraints:
+        if area_assignment.constraints == AreaConstraints::default() {
+            return true;
+        }
+        let identity_mapping: Vec<AreaIdx> = (0..NUM_AREAS).collect();
+        let area_mapping = match solve_area_mapping(
+            map,
+            game_data,
+            &area_assignment.constraints,
+            &get_fixed_areas(settings, game_data),
+            &identity_mapping,
+        ) {
+            Ok(m) => m,
+            Err(e) => {
+                info!("Area assignment failed: {e}");
+                return false;
+            }
+        };
+        for i in 0..map.area.len() {
+            map.area[i] = area_mapping[map.area[i]];
+        }
         return true;
     }
     if area_assignment.ship_in_crateria {
@@ -598,8 +712,24 @@ pub fn assign_map_areas(
         }
     }
 
+    // Adjust the order as needed to satisfy any further constraints:
+    let preferred_mapping: Vec<AreaIdx> = area_mapping.iter().map(|&x| x as AreaIdx).collect();
+    let area_mapping = match solve_area_mapping(
+        map,
+        game_data,
+        &area_assignment.constraints,
+        &get_fixed_areas(settings, game_data),
+        &preferred_mapping,
+    ) {
+        Ok(m) => m,
+        Err(e) => {
+            info!("Area assignment failed: {e}");
+            return false;
+        }
+    };
+
     for i in 0..map.area.len() {
-        map.area[i] = area_mapping[map.area[i]] as usize;
+        map.area[i] = area_mapping[map.area[i]];
     }
 
     shuffle_subareas(map, settings, game_data, &mut rng);
@@ -3309,10 +3439,8 @@ pub fn randomize_doors(
     let walls = get_walls(map, game_data);
     let door_conns = get_randomizable_door_connections(game_data, map, &walls, objectives);
 
-    // The ability to support more doors is limited by the space for dynamic tiles in bank E4.
-    // That data could possibly be moved out to another bank to free up more space.
-    // For now we limit the amount of ammo/beam doors to 120.
-    let door_count_limit = door_conns.len().min(120);
+    // Each locked door needs its own unlock bit, of which only a limited number are available.
+    let door_count_limit = door_conns.len().min(MAX_LOCKED_DOORS);
     if door_types.len() > door_count_limit {
         let mut keep_doors_idx =
             rand::seq::index::sample(&mut rng, door_types.len(), door_count_limit).into_vec();
@@ -5024,15 +5152,7 @@ impl<'r> Randomizer<'r> {
         let cost_config = simple_cost_config();
 
         if self.settings.start_location_settings.mode == StartLocationMode::Ship {
-            let ship_start = StartLocation {
-                name: "Ship".to_string(),
-                room_id: 8,
-                node_id: 5,
-                door_load_node_id: Some(2),
-                x: 72.0,
-                y: 69.5,
-                ..StartLocation::default()
-            };
+            let ship_start = get_ship_start_location();
 
             let ship_hub = HubLocation {
                 room_id: 8,
@@ -5428,9 +5548,27 @@ impl<'r> Randomizer<'r> {
         display_seed: usize,
         rebuild_traversals: bool,
     ) -> Result<(Randomization, SpoilerLog)> {
-        let mut rng_seed = [0u8; 32];
-        rng_seed[..8].copy_from_slice(&seed.to_le_bytes());
-        let mut rng = rand::rngs::StdRng::from_seed(rng_seed);
+        self.randomize_with_checkpoints(
+            attempt_num_rando,
+            seed,
+            display_seed,
+            rebuild_traversals,
+            &mut |_| Ok(()),
+        )
+    }
+
+    /// Performs the randomization, calling `on_checkpoint` at the start of each item placement step
+    /// with the full randomization state, which can be saved (see `snapshot::encode_snapshot`) in order
+    /// to later replay the remaining steps using `resume_from_checkpoint`.
+    pub fn randomize_with_checkpoints(
+        &self,
+        attempt_num_rando: usize,
+        seed: usize,
+        display_seed: usize,
+        rebuild_traversals: bool,
+        on_checkpoint: &mut dyn FnMut(&RandomizationCheckpoint) -> Result<()>,
+    ) -> Result<(Randomization, SpoilerLog)> {
+        let mut rng = get_checkpoint_rng(seed);
         if self.settings.start_location_settings.mode == StartLocationMode::Escape {
             return self.dummy_randomize(seed, display_seed, &mut rng);
         }
@@ -5542,7 +5680,44 @@ impl<'r> Randomizer<'r> {
         {
             bail!("[attempt {attempt_num_rando}] No initially bireachable item locations");
         }
+        let checkpoint = RandomizationCheckpoint {
+            attempt_num_rando,
+            seed,
+            display_seed,
+            rng_word_pos: 0,
+            start_location_data,
+            state,
+            traverser_pair,
+        };
+        self.run_steps(checkpoint, rng, rebuild_traversals, on_checkpoint)
+    }
+
+    /// Resumes a randomization from a checkpoint, continuing deterministically
+    /// as the original randomization did from that point.
+    pub fn resume_from_checkpoint(
+        &self,
+        checkpoint: RandomizationCheckpoint,
+        rebuild_traversals: bool,
+        on_checkpoint: &mut dyn FnMut(&RandomizationCheckpoint) -> Result<()>,
+    ) -> Result<(Randomization, SpoilerLog)> {
+        let mut rng = get_checkpoint_rng(checkpoint.seed);
+        rng.set_word_pos(checkpoint.rng_word_pos);
+        self.run_steps(checkpoint, rng, rebuild_traversals, on_checkpoint)
+    }
+
+    fn run_steps(
+        &self,
+        mut checkpoint: RandomizationCheckpoint,
+        mut rng: ChaCha12Rng,
+        rebuild_traversals: bool,
+        on_checkpoint: &mut dyn FnMut(&RandomizationCheckpoint) -> Result<()>,
+    ) -> Result<(Randomization, SpoilerLog)> {
+        let attempt_num_rando = checkpoint.attempt_num_rando;
         loop {
+            checkpoint.rng_word_pos = rng.get_word_pos();
+            on_checkpoint(&checkpoint)?;
+            let state = &mut checkpoint.state;
+            let traverser_pair = &mut checkpoint.traverser_pair;
             if self.settings.item_progression_settings.random_tank {
                 self.rerandomize_tank_precedence(&mut state.item_precedence, &mut rng);
             }
@@ -5556,8 +5731,7 @@ impl<'r> Randomizer<'r> {
                 .iter()
                 .filter(|x| x.bireachable_traversal.is_some())
                 .count();
-            let is_early_stop =
-                self.step(attempt_num_rando, &mut state, &mut traverser_pair, &mut rng)?;
+            let is_early_stop = self.step(attempt_num_rando, state, traverser_pair, &mut rng)?;
             let cnt_collected = state
                 .item_location_state
                 .iter()
@@ -5599,7 +5773,7 @@ impl<'r> Randomizer<'r> {
                 // No further progress was made on the last step. So we are done with this attempt: either we have
                 // succeeded or we have failed.
 
-                if !self.is_game_beatable(&state) {
+                if !self.is_game_beatable(state) {
                     bail!("[attempt {attempt_num_rando}] Attempt failed: Game not beatable");
                 }
 
@@ -5658,14 +5832,14 @@ impl<'r> Randomizer<'r> {
                 bail!("[attempt {attempt_num_rando}] Attempt failed: no accessible save location");
             }
         }
-        self.finish(attempt_num_rando, &mut state, &mut rng);
+        self.finish(attempt_num_rando, &mut checkpoint.state, &mut rng);
         self.get_randomization(
-            &state,
-            seed,
-            display_seed,
+            &checkpoint.state,
+            checkpoint.seed,
+            checkpoint.display_seed,
             &mut rng,
-            &mut traverser_pair,
-            &start_location_data,
+            &mut checkpoint.traverser_pair,
+            &checkpoint.start_location_data,
             false,
             rebuild_traversals,
         )
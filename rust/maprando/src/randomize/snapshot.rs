@@ -0,0 +1,107 @@
+use super::{LockedDoor, RandomizationCheckpoint};
+use crate::settings::{Objective, RandomizerSettings};
+use anyhow::{Context, Result, bail};
+use maprando_game::Map;
+use serde_derive::{Deserialize, Serialize};
+use std::path::Path;
+
+const SNAPSHOT_MAGIC: &[u8; 6] = b"MRSNAP";
+
+// Increment this whenever a change is made to any of the serialized structures
+// (randomization state, traversers, settings, etc.), so that stale snapshots are
+// rejected rather than misinterpreted.
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+// Bincode can't represent fields that are omitted depending on their value
+// (`skip_serializing_if`) or that need a self-describing format (`serde_json::Value`),
+// as in start locations and spoiler routes; such fields are embedded as JSON strings instead.
+pub mod json_string {
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<T: Serialize, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let s = serde_json::to_string(value).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&s)
+    }
+
+    pub fn deserialize<'de, T: DeserializeOwned, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<T, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        serde_json::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+// A self-contained dump of a randomization in progress: together with the game data,
+// this is everything needed to reconstruct the `Randomizer` and resume the checkpoint.
+#[derive(Deserialize)]
+pub struct RandomizationSnapshot {
+    pub settings: RandomizerSettings,
+    pub map: Map,
+    pub objectives: Vec<Objective>,
+    pub locked_doors: Vec<LockedDoor>,
+    pub checkpoint: RandomizationCheckpoint,
+}
+
+#[derive(Serialize)]
+struct RandomizationSnapshotRef<'a> {
+    settings: &'a RandomizerSettings,
+    map: &'a Map,
+    objectives: &'a [Objective],
+    locked_doors: &'a [LockedDoor],
+    checkpoint: &'a RandomizationCheckpoint,
+}
+
+pub fn encode_snapshot(
+    settings: &RandomizerSettings,
+    map: &Map,
+    objectives: &[Objective],
+    locked_doors: &[LockedDoor],
+    checkpoint: &RandomizationCheckpoint,
+) -> Result<Vec<u8>> {
+    let snapshot = RandomizationSnapshotRef {
+        settings,
+        map,
+        objectives,
+        locked_doors,
+        checkpoint,
+    };
+    let mut data = Vec::new();
+    data.extend_from_slice(SNAPSHOT_MAGIC);
+    data.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+    bincode::serialize_into(&mut data, &snapshot)?;
+    Ok(data)
+}
+
+pub fn decode_snapshot(data: &[u8]) -> Result<RandomizationSnapshot> {
+    let header_len = SNAPSHOT_MAGIC.len() + 4;
+    if data.len() < header_len || &data[..SNAPSHOT_MAGIC.len()] != SNAPSHOT_MAGIC {
+        bail!("Not a randomization snapshot");
+    }
+    let version = u32::from_le_bytes(data[SNAPSHOT_MAGIC.len()..header_len].try_into()?);
+    if version != SNAPSHOT_VERSION {
+        bail!("Unsupported randomization snapshot version {version} (expected {SNAPSHOT_VERSION})");
+    }
+    let snapshot = bincode::deserialize(&data[header_len..])?;
+    Ok(snapshot)
+}
+
+pub fn save_snapshot(
+    path: &Path,
+    settings: &RandomizerSettings,
+    map: &Map,
+    objectives: &[Objective],
+    locked_doors: &[LockedDoor],
+    checkpoint: &RandomizationCheckpoint,
+) -> Result<()> {
+    let data = encode_snapshot(settings, map, objectives, locked_doors, checkpoint)?;
+    std::fs::write(path, data).with_context(|| format!("Unable to write {}", path.display()))
+}
+
+pub fn load_snapshot(path: &Path) -> Result<RandomizationSnapshot> {
+    let data = std::fs::read(path).with_context(|| format!("Unable to read {}", path.display()))?;
+    decode_snapshot(&data).with_context(|| format!("Unable to load {}", path.display()))
+}
@@ -5,6 +5,7 @@ use hashbrown::HashMap;
 use maprando_game::{Item, NotableId, RoomId, TechId};
 use serde::{Deserialize, Serialize};
 
+use crate::map_index::MapQuery;
 use crate::preset::PresetData;
 
 const VERSION: usize = include!("../../VERSION");
@@ -18,6 +19,9 @@ pub struct RandomizerSettings {
     pub quality_of_life_settings: QualityOfLifeSettings,
     pub objective_settings: ObjectiveSettings,
     pub map_layout: String,
+    // Restricts which maps of the `map_layout` repository may be used.
+    #[serde(default)]
+    pub map_query: Option<MapQuery>,
     pub doors_settings: DoorsSettings,
     pub start_location_settings: StartLocationSettings,
     pub save_animals: SaveAnimals,
@@ -64,6 +68,10 @@ pub struct SkillAssumptionSettings {
     pub ridley_proficiency: f32,
     pub botwoon_proficiency: f32,
     pub mother_brain_proficiency: f32,
+    pub kraid_proficiency: f32,
+    pub crocomire_proficiency: f32,
+    pub spore_spawn_proficiency: f32,
+    pub golden_torizo_proficiency: f32,
     pub escape_timer_multiplier: f32,
     pub tech_settings: Vec<TechSetting>,
     pub notable_settings: Vec<NotableSetting>,
@@ -138,6 +146,22 @@ impl SkillPreset {
     pub fn to_byte(self) -> u8 {
         self as u8
     }
+
+    pub fn from_byte(b: u8) -> Option<Self> {
+        match b {
+            1 => Some(Self::Basic),
+            2 => Some(Self::Medium),
+            3 => Some(Self::Hard),
+            4 => Some(Self::VeryHard),
+            5 => Some(Self::Expert),
+            6 => Some(Self::ExpertPlus),
+            7 => Some(Self::Extreme),
+            8 => Some(Self::ExtremePlus),
+            9 => Some(Self::Insane),
+            10 => Some(Self::InsanePlus),
+            _ => None,
+        }
+    }
 }
 
 #[repr(u8)]
@@ -165,6 +189,17 @@ impl QolPreset {
     pub fn to_byte(self) -> u8 {
         self as u8
     }
+
+    pub fn from_byte(b: u8) -> Option<Self> {
+        match b {
+            1 => Some(Self::Off),
+            2 => Some(Self::Low),
+            3 => Some(Self::Default),
+            4 => Some(Self::High),
+            5 => Some(Self::Max),
+            _ => None,
+        }
+    }
 }
 
 #[repr(u8)]
@@ -196,6 +231,19 @@ impl ObjPreset {
     pub fn to_byte(self) -> u8 {
         self as u8
     }
+
+    pub fn from_byte(b: u8) -> Option<Self> {
+        match b {
+            1 => Some(Self::None),
+            2 => Some(Self::Bosses),
+            3 => Some(Self::MiniBosses),
+            4 => Some(Self::Chozos),
+            5 => Some(Self::Pirates),
+            6 => Some(Self::Metroids),
+            7 => Some(Self::Random),
+            _ => None,
+        }
+    }
 }
 
 #[repr(u8)]
@@ -223,6 +271,17 @@ impl ProgressionPreset {
     pub fn to_byte(self) -> u8 {
         self as u8
     }
+
+    pub fn from_byte(b: u8) -> Option<Self> {
+        match b {
+            1 => Some(Self::Normal),
+            2 => Some(Self::Tricky),
+            3 => Some(Self::Technical),
+            4 => Some(Self::Challenge),
+            5 => Some(Self::Desolate),
+            _ => None,
+        }
+    }
 }
 
 #[repr(u8)]
@@ -248,6 +307,16 @@ impl MapPreset {
     pub fn to_byte(self) -> u8 {
         self as u8
     }
+
+    pub fn from_byte(b: u8) -> Option<Self> {
+        match b {
+            1 => Some(Self::Vanilla),
+            2 => Some(Self::Small),
+            3 => Some(Self::Standard),
+            4 => Some(Self::Wild),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq)]
@@ -759,12 +828,60 @@ pub enum AreaAssignmentBaseOrder {
     Random,
 }
 
-#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+// How rooms are grouped into areas, before the areas are ordered by `base_order`.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Default)]
+pub enum AreaPartition {
+    // Use the areas stored with the map.
+    #[default]
+    Map,
+    // Regroup the rooms into contiguous areas of similar size (in map tiles).
+    Size,
+    // Regroup the rooms into contiguous areas with similar numbers of item locations.
+    Items,
+}
+
+// A room which must end up in the given area (by name, e.g. "Brinstar").
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct AreaPin {
+    pub room: String,
+    pub area: String,
+}
+
+// A room whose area must be neither the given area nor adjacent to it (i.e. connected to it
+// by a door).
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct AreaSeparation {
+    pub room: String,
+    pub area: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct AreaRoomLimit {
+    pub area: String,
+    pub min_rooms: Option<usize>,
+    pub max_rooms: Option<usize>,
+}
+
+// Constraints on which area each of the map's areas becomes. Rooms which aren't in the map
+// are ignored.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Default)]
+#[serde(default)]
+pub struct AreaConstraints {
+    pub pinned_rooms: Vec<AreaPin>,
+    pub separated_rooms: Vec<AreaSeparation>,
+    pub room_limits: Vec<AreaRoomLimit>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub struct AreaAssignment {
     pub preset: Option<AreaAssignmentPreset>,
     pub base_order: AreaAssignmentBaseOrder,
     pub ship_in_crateria: bool,
     pub mother_brain_in_tourian: bool,
+    #[serde(default)]
+    pub partition: AreaPartition,
+    #[serde(default)]
+    pub constraints: AreaConstraints,
 }
 
 impl AreaAssignment {
@@ -775,24 +892,32 @@ impl AreaAssignment {
                 base_order: AreaAssignmentBaseOrder::Size,
                 ship_in_crateria: true,
                 mother_brain_in_tourian: true,
+                partition: AreaPartition::Map,
+                constraints: AreaConstraints::default(),
             },
             AreaAssignmentPreset::Size => AreaAssignment {
                 preset: Some(preset),
                 base_order: AreaAssignmentBaseOrder::Size,
                 ship_in_crateria: false,
                 mother_brain_in_tourian: false,
+                partition: AreaPartition::Map,
+                constraints: AreaConstraints::default(),
             },
             AreaAssignmentPreset::Depth => AreaAssignment {
                 preset: Some(preset),
                 base_order: AreaAssignmentBaseOrder::Depth,
                 ship_in_crateria: false,
                 mother_brain_in_tourian: false,
+                partition: AreaPartition::Map,
+                constraints: AreaConstraints::default(),
             },
             AreaAssignmentPreset::Random => AreaAssignment {
                 preset: Some(preset),
                 base_order: AreaAssignmentBaseOrder::Random,
                 ship_in_crateria: false,
                 mother_brain_in_tourian: false,
+                partition: AreaPartition::Map,
+                constraints: AreaConstraints::default(),
             },
         }
     }
@@ -1096,6 +1221,22 @@ fn upgrade_other_skill_settings(settings: &mut serde_json::Value) -> Result<()>
     if !skill_assumption_settings.contains_key("crystal_spark_leniency") {
         skill_assumption_settings.insert("crystal_spark_leniency".to_string(), 8.into());
     }
+    // Presets from before the Kraid, Crocomire, Spore Spawn, and Golden Torizo fights were modeled:
+    // take their proficiencies from the most comparable of the existing boss proficiencies.
+    for (key, base_key) in [
+        ("kraid_proficiency", "phantoon_proficiency"),
+        ("crocomire_proficiency", "phantoon_proficiency"),
+        ("spore_spawn_proficiency", "phantoon_proficiency"),
+        ("golden_torizo_proficiency", "ridley_proficiency"),
+    ] {
+        if !skill_assumption_settings.contains_key(key) {
+            let value = skill_assumption_settings
+                .get(base_key)
+                .cloned()
+                .unwrap_or(0.into());
+            skill_assumption_settings.insert(key.to_string(), value);
+        }
+    }
 
     Ok(())
 }
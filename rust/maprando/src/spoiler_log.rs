@@ -171,10 +171,10 @@ pub struct SpoilerNode {
 
 #[derive(Serialize, Deserialize)]
 pub struct SpoilerGameData {
-    rooms: Vec<SpoilerRoom>,
-    nodes: Vec<SpoilerNode>,
-    vertices: Vec<VertexKey>,
-    links: Vec<SpoilerLink>,
+    pub rooms: Vec<SpoilerRoom>,
+    pub nodes: Vec<SpoilerNode>,
+    pub vertices: Vec<VertexKey>,
+    pub links: Vec<SpoilerLink>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -22,8 +22,9 @@ use maprando_game::{
 use maprando_logic::{
     GlobalState, Inventory, LocalState, ResourceLevel,
     boss_requirements::{
-        apply_botwoon_requirement, apply_draygon_requirement, apply_mother_brain_2_requirement,
-        apply_phantoon_requirement, apply_ridley_requirement,
+        apply_botwoon_requirement, apply_crocomire_requirement, apply_draygon_requirement,
+        apply_golden_torizo_requirement, apply_kraid_requirement, apply_mother_brain_2_requirement,
+        apply_phantoon_requirement, apply_ridley_requirement, apply_spore_spawn_requirement,
     },
     helpers::suit_damage_factor,
 };
@@ -102,7 +103,7 @@ fn apply_enemy_kill_requirement(
 }
 
 // Ended up abandoning the original purpose of introducing this struct, but keeping it for possible future use.
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct CostConfig {}
 
 pub const NUM_COST_METRICS: usize = 4;
@@ -934,7 +935,7 @@ pub fn apply_farm_requirement(
 // We parametrize the "trail_id" type. In the Traverser data structure, an i32 is used;
 // but during internal requirement processing (e.g. resolving `or`s) a unit type is used
 // as we do not create trails at that level of detail.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LocalStateReducer<T: Copy + Debug> {
     pub local: ArrayVec<LocalState, NUM_COST_METRICS>,
     pub trail_ids: ArrayVec<T, NUM_COST_METRICS>,
@@ -1940,6 +1941,62 @@ fn apply_requirement_simple(
             )
             .into()
         }
+        Requirement::KraidFight {
+            can_be_patient_tech_idx,
+            can_be_very_patient_tech_idx,
+            can_be_extremely_patient_tech_idx,
+        } => apply_kraid_requirement(
+            &cx.global.inventory,
+            local,
+            cx.difficulty.kraid_proficiency,
+            cx.difficulty.tech[*can_be_patient_tech_idx],
+            cx.difficulty.tech[*can_be_very_patient_tech_idx],
+            cx.difficulty.tech[*can_be_extremely_patient_tech_idx],
+            cx.reverse,
+        )
+        .into(),
+        Requirement::CrocomireFight {
+            can_be_patient_tech_idx,
+            can_be_very_patient_tech_idx,
+            can_be_extremely_patient_tech_idx,
+        } => apply_crocomire_requirement(
+            &cx.global.inventory,
+            local,
+            cx.difficulty.crocomire_proficiency,
+            cx.difficulty.tech[*can_be_patient_tech_idx],
+            cx.difficulty.tech[*can_be_very_patient_tech_idx],
+            cx.difficulty.tech[*can_be_extremely_patient_tech_idx],
+            cx.reverse,
+        )
+        .into(),
+        Requirement::SporeSpawnFight {
+            can_be_patient_tech_idx,
+            can_be_very_patient_tech_idx,
+            can_be_extremely_patient_tech_idx,
+        } => apply_spore_spawn_requirement(
+            &cx.global.inventory,
+            local,
+            cx.difficulty.spore_spawn_proficiency,
+            cx.difficulty.tech[*can_be_patient_tech_idx],
+            cx.difficulty.tech[*can_be_very_patient_tech_idx],
+            cx.difficulty.tech[*can_be_extremely_patient_tech_idx],
+            cx.reverse,
+        )
+        .into(),
+        Requirement::GoldenTorizoFight {
+            can_be_patient_tech_idx,
+            can_be_very_patient_tech_idx,
+            can_be_extremely_patient_tech_idx,
+        } => apply_golden_torizo_requirement(
+            &cx.global.inventory,
+            local,
+            cx.difficulty.golden_torizo_proficiency,
+            cx.difficulty.tech[*can_be_patient_tech_idx],
+            cx.difficulty.tech[*can_be_very_patient_tech_idx],
+            cx.difficulty.tech[*can_be_extremely_patient_tech_idx],
+            cx.reverse,
+        )
+        .into(),
         Requirement::SpeedBall { used_tiles, heated } => {
             if !cx.difficulty.tech[cx.game_data.speed_ball_tech_idx]
                 || !cx.global.inventory.items[Item::Morph as usize]
@@ -2576,13 +2633,13 @@ pub struct StepTrail {
     pub local_state: LocalState,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TraversalUpdate {
     pub vertex_id: VertexId,
     pub old_lsr: LocalStateReducer<StepTrailId>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TraversalStep {
     pub updates: Vec<TraversalUpdate>,
     pub start_step_trail_idx: usize,
@@ -2590,7 +2647,7 @@ pub struct TraversalStep {
     pub global_state: GlobalState,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Traverser {
     pub reverse: bool,
     pub initial_local_state: LocalState,
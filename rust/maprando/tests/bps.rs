@@ -0,0 +1,73 @@
+use maprando::patch::bps::{BPSEncoder, BPSPatch, create_bps_patch, get_changed_ranges};
+use maprando::patch::suffix_tree::SuffixTree;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+// A pseudo-random "ROM" and a modified copy of it, extended to a larger size as seed ROMs are.
+fn make_roms() -> (Vec<u8>, Vec<u8>) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let old_rom: Vec<u8> = (0..0x3000).map(|_| rng.gen_range(0..=255)).collect();
+    let mut new_rom = old_rom.clone();
+    new_rom.resize(0x4000, 0);
+    for _ in 0..50 {
+        let i = rng.gen_range(0..new_rom.len());
+        new_rom[i] = new_rom[i].wrapping_add(1);
+    }
+    // Relocated data, which the suffix-tree encoder can copy from the source:
+    new_rom.copy_within(0x100..0x200, 0x3800);
+    new_rom[0x3000..0x3010].fill(0xFF);
+    (old_rom, new_rom)
+}
+
+/// Test that encoding a BPS patch and applying it to the source reproduces the target.
+#[test]
+fn test_bps_round_trip() {
+    let (old_rom, new_rom) = make_roms();
+    let patch_bytes = create_bps_patch(&old_rom, &new_rom);
+    let patch = BPSPatch::new(patch_bytes).unwrap();
+    assert_eq!(patch.src_size, old_rom.len());
+    assert_eq!(patch.dst_size, new_rom.len());
+    assert_eq!(patch.apply_checked(&old_rom).unwrap(), new_rom);
+
+    let source_suffix_tree = SuffixTree::new(&old_rom);
+    let modified_ranges = get_changed_ranges(&old_rom, &new_rom);
+    let mut encoder = BPSEncoder::new(&source_suffix_tree, &new_rom, &modified_ranges);
+    encoder.encode();
+    let patch = BPSPatch::new(encoder.patch_bytes).unwrap();
+    assert_eq!(patch.apply_checked(&old_rom).unwrap(), new_rom);
+}
+
+/// Test that a BPS patch refuses to apply to a different source, or when corrupted.
+#[test]
+fn test_bps_checksums() {
+    let (old_rom, new_rom) = make_roms();
+    let patch_bytes = create_bps_patch(&old_rom, &new_rom);
+
+    let patch = BPSPatch::new(patch_bytes.clone()).unwrap();
+    let mut wrong_rom = old_rom.clone();
+    wrong_rom[0x1234] ^= 1;
+    assert!(patch.apply_checked(&wrong_rom).is_err());
+    assert!(patch.apply_checked(&old_rom[..0x2000]).is_err());
+
+    let mut corrupt_patch = patch_bytes;
+    let mid = corrupt_patch.len() / 2;
+    corrupt_patch[mid] ^= 1;
+    assert!(BPSPatch::new(corrupt_patch).is_err());
+}
+
+/// Test that the written ranges of a BPS patch cover every byte that it changes.
+#[test]
+fn test_bps_written_ranges() {
+    let (old_rom, new_rom) = make_roms();
+    let patch = BPSPatch::new(create_bps_patch(&old_rom, &new_rom)).unwrap();
+    let written_ranges = patch.get_written_ranges();
+    for (i, x) in new_rom.iter().enumerate() {
+        if old_rom.get(i) != Some(x) {
+            assert!(
+                written_ranges
+                    .iter()
+                    .any(|&(start, end)| (start..end).contains(&i)),
+                "byte {i:#x} changed outside the written ranges"
+            );
+        }
+    }
+}
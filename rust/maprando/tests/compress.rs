@@ -0,0 +1,70 @@
+use maprando::patch::Rom;
+use maprando::patch::compress::compress;
+use maprando::patch::decompress::decompress;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+fn round_trip(data: &[u8]) -> Vec<u8> {
+    let compressed = compress(data);
+    let decompressed = decompress(&Rom::new(compressed.clone()), 0).unwrap();
+    assert_eq!(decompressed, data);
+    compressed
+}
+
+// Data made of the kinds of runs that each block type encodes.
+fn make_data(rng: &mut StdRng, len: usize) -> Vec<u8> {
+    let mut data: Vec<u8> = vec![];
+    while data.len() < len {
+        let count = rng.gen_range(1..600);
+        match rng.gen_range(0..6) {
+            0 => data.extend((0..count).map(|_| rng.gen_range(0..=255u8))),
+            1 => data.extend(vec![rng.gen_range(0..=255u8); count]),
+            2 => {
+                let word = [rng.gen_range(0..=255u8), rng.gen_range(0..=255u8)];
+                data.extend((0..count).map(|i| word[i % 2]));
+            }
+            3 => {
+                let start = rng.gen_range(0..=255u8);
+                data.extend((0..count).map(|i| start.wrapping_add(i as u8)));
+            }
+            k if !data.is_empty() => {
+                // Copy (or XOR copy) of earlier data, possibly overlapping itself:
+                let src = rng.gen_range(0..data.len());
+                let xor = if k == 4 { 0x00 } else { 0xFF };
+                for i in 0..count {
+                    data.push(data[src + i] ^ xor);
+                }
+            }
+            _ => {}
+        }
+    }
+    data
+}
+
+/// Test that compressed data decompresses back to the original.
+#[test]
+fn test_compress_round_trip() {
+    let mut rng = StdRng::seed_from_u64(0);
+    round_trip(&[]);
+    round_trip(&[0xFF]);
+    for _ in 0..20 {
+        let len = rng.gen_range(1..4000);
+        round_trip(&make_data(&mut rng, len));
+    }
+}
+
+/// Test that back-references and fills are used where they give a smaller output.
+#[test]
+fn test_compress_block_types() {
+    // Long runs need multiple blocks, each limited to 1024 bytes:
+    assert_eq!(round_trip(&[0; 3000]).len(), 10);
+    // Incrementing sequence:
+    let inc: Vec<u8> = (0..=255).collect();
+    assert_eq!(round_trip(&inc).len(), 4);
+    // Repeated tile, and its complement, as a copy from earlier data:
+    let mut rng = StdRng::seed_from_u64(1);
+    let tile: Vec<u8> = (0..32).map(|_| rng.gen_range(0..=255u8)).collect();
+    let mut data = tile.clone();
+    data.extend(tile.iter().map(|x| x ^ 0xFF));
+    data.extend(&tile);
+    assert_eq!(round_trip(&data).len(), 1 + 32 + 3 + 2 + 1);
+}
@@ -0,0 +1,48 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use maprando_game::data_source::{
+    DataSource, DirSource, MemorySource, MountSource, default_data_source,
+};
+
+/// Test that files can be bundled into an archive and read back, and that mounted sources
+/// resolve paths to the most specific mount.
+#[test]
+fn test_data_source_archive_and_mounts() -> Result<()> {
+    let source = default_data_source(Path::new(".."));
+    let mut bundle = MemorySource::new();
+    bundle.add_from(
+        source.as_ref(),
+        &["data/presets/skill-assumptions", "room_geometry.json"],
+    )?;
+    let archive = MemorySource::from_archive_bytes(&bundle.to_archive_bytes()?)?;
+
+    let skill_presets = source.list("data/presets/skill-assumptions")?;
+    assert!(!skill_presets.is_empty());
+    assert_eq!(
+        archive.list("data/presets/skill-assumptions")?,
+        skill_presets
+    );
+    for path in &skill_presets {
+        assert_eq!(archive.read(path)?, source.read(path)?);
+    }
+    assert_eq!(
+        archive.read("room_geometry.json")?,
+        source.read("room_geometry.json")?
+    );
+    assert!(archive.read("data/tech_data.json").is_err());
+
+    let mounted = MountSource::new()
+        .mount("", Arc::new(archive))
+        .mount("data", Arc::new(DirSource::new(Path::new("../data"))));
+    assert_eq!(
+        mounted.read("data/tech_data.json")?,
+        source.read("data/tech_data.json")?
+    );
+    assert_eq!(
+        mounted.read("room_geometry.json")?,
+        source.read("room_geometry.json")?
+    );
+    Ok(())
+}
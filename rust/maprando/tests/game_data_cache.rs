@@ -0,0 +1,25 @@
+use std::path::Path;
+
+use anyhow::Result;
+use maprando_game::GameData;
+
+/// Test that game data loaded from the cache matches game data loaded from the source files.
+#[test]
+fn test_game_data_cache_roundtrip() -> Result<()> {
+    let base_path = Path::new("..");
+    let cache_path = base_path.join("../tmp/test_game_data.cache");
+    let _ = std::fs::remove_file(&cache_path);
+
+    let uncached = GameData::load_with_cache(base_path, None)?;
+    // The first load builds and writes the cache, and the second one reads it back.
+    GameData::load_with_cache(base_path, Some(&cache_path))?;
+    assert!(cache_path.exists());
+    let cached = GameData::load_with_cache(base_path, Some(&cache_path))?;
+
+    // Compare the full serialized form. The bytes themselves can't be compared, since the hash
+    // maps are serialized in their iteration order, but `serde_value` maps are ordered.
+    assert!(serde_value::to_value(&uncached)? == serde_value::to_value(&cached)?);
+
+    std::fs::remove_file(&cache_path)?;
+    Ok(())
+}
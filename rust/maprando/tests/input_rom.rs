@@ -0,0 +1,55 @@
+use maprando::patch::input_rom::{InvalidRom, normalize_input_rom};
+
+// Stand-in for a Super Metroid ROM: zero-filled apart from the internal header.
+fn make_rom(title: &str, region: u8) -> Vec<u8> {
+    let mut data = vec![0; 0x300000];
+    data[0x7FC0..0x7FD5].copy_from_slice(format!("{title:21}").as_bytes());
+    data[0x7FD9] = region;
+    data
+}
+
+fn check(data: &[u8]) -> Option<InvalidRom> {
+    normalize_input_rom(data).err()
+}
+
+/// Test that each kind of wrong input ROM is identified.
+#[test]
+fn test_invalid_input_roms() {
+    let rom = make_rom("Super Metroid", 1);
+    let crc32 = |data: &[u8]| {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(data);
+        hasher.finalize()
+    };
+    // The stand-in does not match the vanilla checksums:
+    assert_eq!(
+        check(&rom),
+        Some(InvalidRom::Modified { crc32: crc32(&rom) })
+    );
+
+    // A copier header is removed before the checks:
+    let mut headered = vec![0; 0x200];
+    headered.extend(&rom);
+    assert_eq!(
+        check(&headered),
+        Some(InvalidRom::Modified { crc32: crc32(&rom) })
+    );
+
+    let swapped: Vec<u8> = rom.chunks(2).flat_map(|x| [x[1], x[0]]).collect();
+    assert_eq!(check(&swapped), Some(InvalidRom::ByteSwapped));
+
+    let mut interleaved = rom[0x8000..0x10000].to_vec();
+    interleaved.extend(&rom[0..0x8000]);
+    interleaved.extend(&rom[0x10000..]);
+    assert_eq!(check(&interleaved), Some(InvalidRom::Interleaved));
+
+    assert_eq!(check(&make_rom("Super Metroid", 2)), Some(InvalidRom::Pal));
+    assert_eq!(
+        check(&make_rom("SUPERMETROID MAPRANDO", 1)),
+        Some(InvalidRom::AlreadyRandomized)
+    );
+    assert_eq!(
+        check(&make_rom("SUPER MARIOWORLD", 1)),
+        Some(InvalidRom::NotSuperMetroid { size: 0x300000 })
+    );
+}
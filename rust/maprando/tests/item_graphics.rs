@@ -0,0 +1,64 @@
+use std::path::Path;
+
+use anyhow::Result;
+use image::{Rgba, RgbaImage};
+use maprando::patch::item_graphics::{ItemPalette, encode_item_graphics, load_item_graphics};
+use maprando_game::data_source::default_data_source;
+
+/// Test that sprite sheet colors are quantized to the nearest color of each tile's palette row.
+#[test]
+fn test_encode_item_graphics() -> Result<()> {
+    let mut palette_img = RgbaImage::new(16, 4);
+    palette_img.put_pixel(2, 0, Rgba([248, 0, 0, 255]));
+    palette_img.put_pixel(5, 0, Rgba([0, 0, 248, 255]));
+    palette_img.put_pixel(7, 1, Rgba([248, 248, 248, 255]));
+    let palette = ItemPalette::from_image(&palette_img)?;
+
+    let mut img = RgbaImage::new(32, 16);
+    img.put_pixel(0, 0, Rgba([200, 40, 30, 255]));
+    img.put_pixel(1, 0, Rgba([10, 20, 180, 255]));
+    // A color that is not in the row is mapped to the nearest one:
+    img.put_pixel(8, 0, Rgba([90, 90, 90, 255]));
+    // Partially transparent pixels are treated as transparent:
+    img.put_pixel(16, 8, Rgba([248, 0, 0, 100]));
+    img.put_pixel(17, 8, Rgba([248, 0, 0, 200]));
+
+    let tiles = encode_item_graphics(&img, &palette, &[0, 1, 0, 0, 0, 0, 0, 0])?;
+    assert_eq!(tiles.len(), 8);
+    assert_eq!(tiles[0][0][..3], [2, 5, 0]);
+    assert_eq!(tiles[1][0][0], 7);
+    assert_eq!(tiles[6][0][..3], [0, 2, 0]);
+
+    // Each tile's palette row must exist:
+    assert!(encode_item_graphics(&img, &palette, &[4, 0, 0, 0, 0, 0, 0, 0]).is_err());
+    assert!(encode_item_graphics(&RgbaImage::new(16, 16), &palette, &[0; 8]).is_err());
+    Ok(())
+}
+
+/// Test that the bundled item sprite sheets load, reproducing the previously hard-coded graphics.
+#[test]
+fn test_load_item_graphics() -> Result<()> {
+    let source = default_data_source(Path::new(".."));
+    let item_graphics = load_item_graphics(source.as_ref())?;
+    for gfx in &item_graphics {
+        assert_eq!(gfx.tiles.len(), 8);
+    }
+
+    let walljump = item_graphics.iter().find(|x| x.name == "WallJump").unwrap();
+    assert_eq!(walljump.snes_addr, 0x899100);
+    assert_eq!(walljump.tiles[0][0], [0, 0, 0, 8, 8, 8, 8, 8]);
+    assert_eq!(walljump.tiles[0][1], [0, 0, 0, 8, 4, 4, 5, 6]);
+    // The second frame shifts the colors of the first:
+    assert_eq!(walljump.tiles[4][1], [0, 0, 0, 7, 12, 12, 4, 5]);
+
+    let bluebooster = item_graphics
+        .iter()
+        .find(|x| x.name == "BlueBooster")
+        .unwrap();
+    assert_eq!(bluebooster.tiles[1][1], [3, 2, 1, 2, 1, 14, 1, 0]);
+    assert_eq!(bluebooster.tiles[2][6], [0, 7, 4, 5, 6, 5, 4, 12]);
+
+    let nothing = item_graphics.iter().find(|x| x.name == "Nothing").unwrap();
+    assert!(nothing.tiles.iter().all(|t| *t == [[0; 8]; 8]));
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use maprando_game::GameData;
+use maprando_game::data_source::{DirSource, MountSource, default_data_source};
+
+/// Test that linting reports a dangling node reference, an unreachable obstacle and a bad strat
+/// reference in fixture rooms and connections, which replace those of sm-json-data.
+#[test]
+fn test_lint_fixtures() -> Result<()> {
+    let data_source = MountSource::new()
+        .mount("", default_data_source(Path::new("..")))
+        .mount(
+            "sm-json-data/region",
+            Arc::new(DirSource::new(Path::new("tests/lint/region"))),
+        )
+        .mount(
+            "sm-json-data/connection",
+            Arc::new(DirSource::new(Path::new("tests/lint/connection"))),
+        );
+    let issues = GameData::lint(Arc::new(data_source))?;
+    let issues: Vec<_> = issues
+        .into_iter()
+        .map(|issue| {
+            (
+                issue.file,
+                issue.room,
+                issue.strat,
+                issue.json_path,
+                issue.message,
+            )
+        })
+        .collect();
+    let some = |s: &str| Some(s.to_string());
+    assert_eq!(
+        issues,
+        vec![
+            (
+                some("region/bad-reference.json"),
+                some("Bad Reference Room"),
+                some("Base"),
+                None,
+                "Processing requirement h_noSuchHelper: Unknown requirement name (not a tech, helper, item, or flag): h_noSuchHelper".to_string(),
+            ),
+            (
+                some("connection/dangling-node.json"),
+                None,
+                None,
+                some("connections[0].nodes[1]"),
+                "connection references unknown node 9 in room 0".to_string(),
+            ),
+            (
+                some("region/unreachable-obstacle.json"),
+                some("Unreachable Obstacle Room"),
+                some("Base"),
+                some("strats[0].requires"),
+                "requires obstacle 'A' to be cleared, but no strat clears it".to_string(),
+            ),
+        ]
+    );
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use std::path::Path;
+
+use anyhow::Result;
+use hashbrown::HashSet;
+use maprando::difficulty::{get_full_global, get_link_difficulty_length};
+use maprando::logic_graph::{
+    LogicGraph, LogicGraphFilter, LogicGraphLink, LogicGraphVertex, get_logic_graph,
+    logic_graph_to_dot, logic_graph_to_graphml,
+};
+use maprando::preset::PresetData;
+use maprando::randomize::{Randomizer, get_difficulty_tiers, get_objectives, randomize_doors};
+use maprando_game::{GameData, Item, Map};
+use rand::{SeedableRng, rngs::StdRng};
+
+fn sample_graph() -> LogicGraph {
+    LogicGraph {
+        vertices: vec![
+            LogicGraphVertex {
+                vertex_id: 0,
+                room: "Landing Site".to_string(),
+                node: "Ship".to_string(),
+                obstacle_mask: 0,
+                actions: vec![],
+            },
+            LogicGraphVertex {
+                vertex_id: 3,
+                room: "Landing Site".to_string(),
+                node: "Top Left Door".to_string(),
+                obstacle_mask: 1,
+                actions: vec!["Exit".to_string()],
+            },
+        ],
+        links: vec![LogicGraphLink {
+            from_vertex_id: 0,
+            to_vertex_id: 3,
+            strat_name: "Base \"Jump\" <&>".to_string(),
+            requirement: "HiJump".to_string(),
+            difficulty: 2,
+            seed_link: true,
+        }],
+    }
+}
+
+/// Test that the GraphML and DOT writers include every vertex and link, with escaping.
+#[test]
+fn test_logic_graph_writers() {
+    let graph = sample_graph();
+
+    let graphml = logic_graph_to_graphml(&graph);
+    assert!(graphml.contains("<node id=\"v0\">"));
+    assert!(graphml.contains("<node id=\"v3\">"));
+    assert!(graphml.contains("<edge source=\"v0\" target=\"v3\">"));
+    assert!(graphml.contains("Base &quot;Jump&quot; &lt;&amp;&gt;"));
+    assert!(graphml.contains("<data key=\"seed_link\">true</data>"));
+
+    let dot = logic_graph_to_dot(&graph);
+    assert!(dot.starts_with("digraph logic {"));
+    assert!(dot.contains("v3 [label=\"Landing Site: Top Left Door [obstacles 0x1] (Exit)\"];"));
+    assert!(dot.contains("v0 -> v3 [label=\"Base \\\"Jump\\\" <&>\""));
+    assert!(dot.contains("style=dashed"));
+}
+
+/// Test that filtering the graph of a seed by items removes the links which need other items,
+/// while keeping the free links and the vertices they touch.
+#[test]
+fn test_get_logic_graph_filter() -> Result<()> {
+    let mut game_data = GameData::load(Path::new(".."))?;
+    let preset_data = PresetData::load(game_data.data_source().as_ref(), &game_data)?;
+    let global = get_full_global(&game_data);
+    game_data.make_links_data(&|link, game_data| {
+        get_link_difficulty_length(link, game_data, &preset_data, &global)
+    });
+    let settings = preset_data.default_preset.clone();
+    let map: Map = serde_json::from_str(
+        &game_data
+            .data_source()
+            .read_to_string("maps/vanilla/vanilla_map.json")?,
+    )?;
+    let difficulty_tiers = get_difficulty_tiers(
+        &settings,
+        &preset_data.difficulty_tiers,
+        &game_data,
+        &preset_data.tech_by_difficulty["Implicit"],
+        &preset_data.notables_by_difficulty["Implicit"],
+    );
+    let mut rng = StdRng::seed_from_u64(0);
+    let objectives = get_objectives(&settings, Some(&map), &game_data, &mut rng);
+    let locked_door_data = randomize_doors(&game_data, &map, &settings, &objectives, 0);
+    let randomizer = Randomizer::new(
+        &map,
+        &locked_door_data,
+        objectives,
+        &settings,
+        &difficulty_tiers,
+        &game_data,
+        &game_data.base_links_data,
+        &mut rng,
+    );
+
+    let full_graph = get_logic_graph(&randomizer, None);
+    let filter = LogicGraphFilter {
+        items: vec![Item::Varia],
+        difficulty: &difficulty_tiers[0],
+    };
+    let filtered_graph = get_logic_graph(&randomizer, Some(&filter));
+    assert!(filtered_graph.links.len() < full_graph.links.len());
+    assert!(filtered_graph.vertices.len() <= full_graph.vertices.len());
+
+    let link_key = |link: &LogicGraphLink| {
+        (
+            link.from_vertex_id,
+            link.to_vertex_id,
+            link.strat_name.clone(),
+        )
+    };
+    let filtered_links: HashSet<_> = filtered_graph.links.iter().map(link_key).collect();
+    let filtered_vertices: HashSet<_> = filtered_graph
+        .vertices
+        .iter()
+        .map(|v| v.vertex_id)
+        .collect();
+    let mut num_removed_links = 0;
+    for link in &full_graph.links {
+        match link.requirement.as_str() {
+            "free" | "Varia" => assert!(filtered_links.contains(&link_key(link))),
+            "Morph" | "Gravity" => {
+                assert!(!filtered_links.contains(&link_key(link)));
+                num_removed_links += 1;
+            }
+            _ => {}
+        }
+    }
+    assert!(num_removed_links > 0);
+    for link in &filtered_graph.links {
+        assert!(filtered_vertices.contains(&link.from_vertex_id));
+        assert!(filtered_vertices.contains(&link.to_vertex_id));
+    }
+    Ok(())
+}
@@ -1,7 +1,8 @@
 use std::{env, path::Path};
 
 use anyhow::{Context, Result, bail};
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
+use json::JsonValue;
 use maprando::{
     randomize::{DifficultyConfig, LockedDoor, Preprocessor, make_locked_door_data},
     settings::{
@@ -13,10 +14,12 @@ use maprando::{
     traverse::{LockedDoorData, Traverser},
 };
 use maprando_game::{
-    BeamType, Capacity, DoorPtrPair, DoorType, GameData, LinksDataGroup, NodeId, NotableId,
+    BeamType, Capacity, DoorPtrPair, DoorType, GameData, Link, LinksDataGroup, NodeId, NotableId,
     ObstacleMask, RoomId, VertexId, VertexKey,
+    data_source::{DataSource, DirSource},
 };
 use maprando_logic::{GlobalState, Inventory, LocalState, ResourceLevel};
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
@@ -39,7 +42,7 @@ struct ScenariosList {
     scenarios: Vec<Scenario>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct Scenario {
     name: String,
@@ -76,7 +79,7 @@ enum TraversalCheck {
     Exact,
 }
 
-#[derive(Default, Debug, Deserialize)]
+#[derive(Clone, Default, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ScenarioSettings {
     shinecharge_tiles: Option<f32>,
@@ -93,7 +96,7 @@ struct ScenarioSettings {
     blue_suit_distance: Option<u8>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ScenarioGlobalState {
     #[serde(default)]
@@ -116,7 +119,7 @@ struct ScenarioGlobalState {
     pool_max_power_bombs: Option<Capacity>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ScenarioLockedDoor {
     room_id: RoomId,
@@ -124,7 +127,7 @@ struct ScenarioLockedDoor {
     type_: ScenarioLockedDoorType,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 enum ScenarioLockedDoorType {
     Red,
@@ -138,14 +141,14 @@ enum ScenarioLockedDoorType {
     Wall,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ScenarioNotable {
     room_id: RoomId,
     notable_id: NotableId,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Default, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ScenarioState {
     energy: Option<ResourceLevel>,
@@ -190,6 +193,10 @@ fn get_settings(scenario: &Scenario) -> Result<RandomizerSettings> {
             ridley_proficiency: 1.0,
             botwoon_proficiency: 1.0,
             mother_brain_proficiency: 1.0,
+            kraid_proficiency: 1.0,
+            crocomire_proficiency: 1.0,
+            spore_spawn_proficiency: 1.0,
+            golden_torizo_proficiency: 1.0,
             escape_timer_multiplier: 1.0,
             // Omitted as we fill the tech in DifficultyConfig directly:
             tech_settings: vec![],
@@ -315,6 +322,7 @@ fn get_settings(scenario: &Scenario) -> Result<RandomizerSettings> {
             objective_screen: maprando::settings::ObjectiveScreen::Disabled,
         },
         map_layout: String::new(),
+        map_query: None,
         doors_settings: DoorsSettings {
             preset: Some("Blue".to_string()),
             red_doors_count: 0,
@@ -613,6 +621,113 @@ fn resource_match(
     (match_weak, match_exact)
 }
 
+/// Compares the local states reached at the end of a traversal against the expected final state,
+/// returning whether there is a weak match and whether there is an exact match.
+fn match_final_states(
+    final_states: &[LocalState],
+    final_local_state: LocalState,
+    inventory: &Inventory,
+    reverse: bool,
+    settings: &RandomizerSettings,
+) -> (bool, bool) {
+    let skill = &settings.skill_assumption_settings;
+    let flash_suit_distance = skill.flash_suit_distance;
+    let blue_suit_distance = skill.blue_suit_distance;
+    let mut exact_success: bool = false;
+    let mut success: bool = false;
+    for &local in final_states {
+        let mut energy_pass = local.energy_available(inventory, true, reverse)
+            >= final_local_state.energy_available(inventory, true, reverse);
+        let mut energy_exact = local.energy() == final_local_state.energy();
+        if reverse {
+            if let ResourceLevel::Remaining(_) = final_local_state.energy()
+                && let ResourceLevel::Consumed(_) = local.energy()
+            {
+                energy_pass = false;
+                energy_exact = false;
+            }
+            if let ResourceLevel::Remaining(_) = final_local_state.reserves()
+                && let ResourceLevel::Consumed(_) = local.reserves()
+            {
+                energy_pass = false;
+                energy_exact = false;
+            }
+        } else {
+            if let ResourceLevel::Consumed(_) = final_local_state.energy()
+                && let ResourceLevel::Remaining(_) = local.energy()
+            {
+                energy_pass = false;
+                energy_exact = false;
+            }
+            if let ResourceLevel::Consumed(_) = final_local_state.reserves()
+                && let ResourceLevel::Remaining(_) = local.reserves()
+            {
+                energy_pass = false;
+                energy_exact = false;
+            }
+        }
+        let (reserves_pass, reserves_exact) = resource_match(
+            local.reserves(),
+            final_local_state.reserves(),
+            inventory.max_reserves,
+            reverse,
+        );
+        let (missiles_pass, missiles_exact) = resource_match(
+            local.missiles(),
+            final_local_state.missiles(),
+            inventory.max_missiles,
+            reverse,
+        );
+        let (supers_pass, supers_exact) = resource_match(
+            local.supers(),
+            final_local_state.supers(),
+            inventory.max_supers,
+            reverse,
+        );
+        let (power_bombs_pass, power_bombs_exact) = resource_match(
+            local.power_bombs(),
+            final_local_state.power_bombs(),
+            inventory.max_power_bombs,
+            reverse,
+        );
+        let shinecharge_frames_pass = local.shinecharge_frames_available(reverse)
+            >= final_local_state.shinecharge_frames_available(reverse);
+        let shinecharge_frames_exact = local.shinecharge_frames_available(reverse)
+            == final_local_state.shinecharge_frames_available(reverse);
+        let flash_suit_pass = local.flash_suit_available(flash_suit_distance, reverse)
+            >= final_local_state.flash_suit_available(flash_suit_distance, reverse);
+        let flash_suit_exact = local.flash_suit_available(flash_suit_distance, reverse)
+            == final_local_state.flash_suit_available(flash_suit_distance, reverse);
+        let blue_suit_pass = local.blue_suit_available(blue_suit_distance, reverse)
+            >= final_local_state.blue_suit_available(blue_suit_distance, reverse);
+        let blue_suit_exact = local.blue_suit_available(blue_suit_distance, reverse)
+            == final_local_state.blue_suit_available(blue_suit_distance, reverse);
+        if energy_pass
+            && reserves_pass
+            && missiles_pass
+            && supers_pass
+            && power_bombs_pass
+            && shinecharge_frames_pass
+            && flash_suit_pass
+            && blue_suit_pass
+        {
+            success = true;
+        }
+        if energy_exact
+            && reserves_exact
+            && missiles_exact
+            && supers_exact
+            && power_bombs_exact
+            && shinecharge_frames_exact
+            && flash_suit_exact
+            && blue_suit_exact
+        {
+            exact_success = true;
+        }
+    }
+    (success, exact_success)
+}
+
 fn test_scenario(
     game_data: &GameData,
     connections: &[Connection],
@@ -755,10 +870,6 @@ fn test_scenario(
             );
         }
     }
-    let skill = &settings.skill_assumption_settings;
-    let flash_suit_distance = skill.flash_suit_distance;
-    let blue_suit_distance = skill.blue_suit_distance;
-
     for reverse in [false, true] {
         println!("reverse: {}", reverse);
         let initial_vertex_id: VertexId;
@@ -799,98 +910,13 @@ fn test_scenario(
             0,
         );
 
-        let mut exact_success: bool = false;
-        let mut success: bool = false;
-        for &local in &traverser.lsr[final_vertex_id].local {
-            let mut energy_pass = local.energy_available(inventory, true, reverse)
-                >= final_local_state.energy_available(inventory, true, reverse);
-            let mut energy_exact = local.energy() == final_local_state.energy();
-            if reverse {
-                if let ResourceLevel::Remaining(_) = final_local_state.energy()
-                    && let ResourceLevel::Consumed(_) = local.energy()
-                {
-                    energy_pass = false;
-                    energy_exact = false;
-                }
-                if let ResourceLevel::Remaining(_) = final_local_state.reserves()
-                    && let ResourceLevel::Consumed(_) = local.reserves()
-                {
-                    energy_pass = false;
-                    energy_exact = false;
-                }
-            } else {
-                if let ResourceLevel::Consumed(_) = final_local_state.energy()
-                    && let ResourceLevel::Remaining(_) = local.energy()
-                {
-                    energy_pass = false;
-                    energy_exact = false;
-                }
-                if let ResourceLevel::Consumed(_) = final_local_state.reserves()
-                    && let ResourceLevel::Remaining(_) = local.reserves()
-                {
-                    energy_pass = false;
-                    energy_exact = false;
-                }
-            }
-            let (reserves_pass, reserves_exact) = resource_match(
-                local.reserves(),
-                final_local_state.reserves(),
-                inventory.max_reserves,
-                reverse,
-            );
-            let (missiles_pass, missiles_exact) = resource_match(
-                local.missiles(),
-                final_local_state.missiles(),
-                inventory.max_missiles,
-                reverse,
-            );
-            let (supers_pass, supers_exact) = resource_match(
-                local.supers(),
-                final_local_state.supers(),
-                inventory.max_supers,
-                reverse,
-            );
-            let (power_bombs_pass, power_bombs_exact) = resource_match(
-                local.power_bombs(),
-                final_local_state.power_bombs(),
-                inventory.max_power_bombs,
-                reverse,
-            );
-            let shinecharge_frames_pass = local.shinecharge_frames_available(reverse)
-                >= final_local_state.shinecharge_frames_available(reverse);
-            let shinecharge_frames_exact = local.shinecharge_frames_available(reverse)
-                == final_local_state.shinecharge_frames_available(reverse);
-            let flash_suit_pass = local.flash_suit_available(flash_suit_distance, reverse)
-                >= final_local_state.flash_suit_available(flash_suit_distance, reverse);
-            let flash_suit_exact = local.flash_suit_available(flash_suit_distance, reverse)
-                == final_local_state.flash_suit_available(flash_suit_distance, reverse);
-            let blue_suit_pass = local.blue_suit_available(blue_suit_distance, reverse)
-                >= final_local_state.blue_suit_available(blue_suit_distance, reverse);
-            let blue_suit_exact = local.blue_suit_available(blue_suit_distance, reverse)
-                == final_local_state.blue_suit_available(blue_suit_distance, reverse);
-            if energy_pass
-                && reserves_pass
-                && missiles_pass
-                && supers_pass
-                && power_bombs_pass
-                && shinecharge_frames_pass
-                && flash_suit_pass
-                && blue_suit_pass
-            {
-                success = true;
-            }
-            if energy_exact
-                && reserves_exact
-                && missiles_exact
-                && supers_exact
-                && power_bombs_exact
-                && shinecharge_frames_exact
-                && flash_suit_exact
-                && blue_suit_exact
-            {
-                exact_success = true;
-            }
-        }
+        let (success, exact_success) = match_final_states(
+            &traverser.lsr[final_vertex_id].local,
+            final_local_state,
+            inventory,
+            reverse,
+            &settings,
+        );
 
         let check = match (
             scenario.fail,
@@ -953,10 +979,12 @@ fn test_logic_scenarios() -> Result<()> {
         let parent_path = scenarios_path.parent().unwrap();
         println!("\n***** {} *****", parent_path.display());
 
-        let room_pattern = parent_path.to_str().unwrap().to_owned() + "/room*.json";
+        let scenario_source = DirSource::new(parent_path);
+        let mut room_paths = scenario_source.list("")?;
+        room_paths.retain(|p| p.starts_with("room") && p.ends_with(".json"));
         let mut game_data = base_game_data.clone();
         let num_rooms = game_data.room_ptrs.len();
-        game_data.load_rooms(&room_pattern)?;
+        game_data.load_room_files(&scenario_source, &room_paths)?;
         game_data.make_links_data(&|_, _| (0, 1));
 
         for &(room_id, node_id) in game_data.node_json_map.keys().clone() {
@@ -1001,3 +1029,622 @@ fn test_logic_scenarios() -> Result<()> {
     }
     Ok(())
 }
+
+// Maximum number of links in a randomly generated fuzz case, before shrinking.
+const FUZZ_MAX_PATH_LINKS: usize = 4;
+
+/// A randomly generated traversal case: a scenario together with the small set of links
+/// (taken from the real game data) that the traversal is restricted to.
+#[derive(Clone)]
+struct FuzzCase {
+    scenario: Scenario,
+    links: Vec<Link>,
+}
+
+/// A violated forward/reverse consistency property, with a scenario that reproduces it.
+struct FuzzFailure {
+    property: &'static str,
+    details: String,
+    scenario: Scenario,
+}
+
+struct FuzzSetup {
+    settings: RandomizerSettings,
+    difficulty: DifficultyConfig,
+    locked_door_data: LockedDoorData,
+    global_state: GlobalState,
+    base_links_data: LinksDataGroup,
+    seed_links_data: LinksDataGroup,
+    start_vertex_id: VertexId,
+    end_vertex_id: VertexId,
+}
+
+impl FuzzSetup {
+    fn new(game_data: &GameData, case: &FuzzCase) -> Result<Self> {
+        let scenario = &case.scenario;
+        let settings = get_settings(scenario)?;
+        let difficulty = get_difficulty(game_data, &settings, scenario)?;
+        let locked_door_data = make_locked_door_data(vec![], game_data);
+        let global_state = get_global_state(game_data, &difficulty, scenario, &locked_door_data)?;
+        let num_vertices = game_data.vertex_isv.keys.len();
+        let base_links_data = LinksDataGroup::new(case.links.clone(), num_vertices, 0);
+        let seed_links_data = LinksDataGroup::new(vec![], num_vertices, case.links.len());
+        let start_vertex_id = get_vertex_id(
+            game_data,
+            scenario.start_room_id,
+            scenario.start_node_id,
+            &scenario.start_obstacles_cleared,
+        )?;
+        let end_vertex_id = get_vertex_id(
+            game_data,
+            scenario.end_room_id,
+            scenario.end_node_id,
+            &scenario.end_obstacles_cleared,
+        )?;
+        Ok(FuzzSetup {
+            settings,
+            difficulty,
+            locked_door_data,
+            global_state,
+            base_links_data,
+            seed_links_data,
+            start_vertex_id,
+            end_vertex_id,
+        })
+    }
+
+    fn traverse(
+        &self,
+        game_data: &GameData,
+        reverse: bool,
+        vertex_id: VertexId,
+        local: LocalState,
+    ) -> Traverser {
+        let door_map = HashMap::new();
+        let objectives = vec![
+            Objective::Kraid,
+            Objective::Phantoon,
+            Objective::Draygon,
+            Objective::Ridley,
+        ];
+        let mut traverser = Traverser::new(
+            game_data.vertex_isv.keys.len(),
+            reverse,
+            local,
+            &self.global_state,
+        );
+        traverser.add_origin(local, &self.global_state.inventory, vertex_id);
+        traverser.traverse(
+            &self.base_links_data,
+            &self.seed_links_data,
+            &self.global_state,
+            &self.settings,
+            &self.difficulty,
+            game_data,
+            &door_map,
+            &self.locked_door_data,
+            &objectives,
+            0,
+        );
+        traverser
+    }
+}
+
+fn get_vertex_id(
+    game_data: &GameData,
+    room_id: RoomId,
+    node_id: NodeId,
+    obstacles: &[String],
+) -> Result<VertexId> {
+    let vertex_key = VertexKey {
+        room_id,
+        node_id,
+        obstacle_mask: get_obstacle_mask(game_data, room_id, obstacles)?,
+        actions: vec![],
+    };
+    game_data
+        .vertex_isv
+        .index_by_key
+        .get(&vertex_key)
+        .copied()
+        .context(format!("Vertex not found: {:?}", vertex_key))
+}
+
+fn get_obstacle_names(game_data: &GameData, room_id: RoomId, mask: ObstacleMask) -> Vec<String> {
+    let mut obstacles: Vec<(usize, String)> = game_data.room_obstacle_idx_map[&room_id]
+        .iter()
+        .filter(|&(_, &idx)| mask & (1 << idx) != 0)
+        .map(|(name, &idx)| (idx, name.clone()))
+        .collect();
+    obstacles.sort();
+    obstacles.into_iter().map(|(_, name)| name).collect()
+}
+
+fn get_scenario_state(local: &LocalState) -> ScenarioState {
+    ScenarioState {
+        energy: Some(local.energy()),
+        reserves: Some(local.reserves()),
+        missiles: Some(local.missiles()),
+        supers: Some(local.supers()),
+        power_bombs: Some(local.power_bombs()),
+        shinecharge_frames_remaining: Some(local.shinecharge_frames_remaining),
+        flash_suit: Some(local.flash_suit),
+        blue_suit: Some(local.blue_suit),
+    }
+}
+
+fn is_valid_fuzz_state(local: &LocalState, inventory: &Inventory) -> bool {
+    (1..=inventory.max_energy).contains(&local.energy_available(inventory, false, false))
+        && (0..=inventory.max_reserves).contains(&local.reserves_available(inventory, false))
+        && (0..=inventory.max_missiles).contains(&local.missiles_available(inventory, false))
+        && (0..=inventory.max_supers).contains(&local.supers_available(inventory, false))
+        && (0..=inventory.max_power_bombs).contains(&local.power_bombs_available(inventory, false))
+        && (0..=180).contains(&local.shinecharge_frames_remaining)
+}
+
+fn fill_resource(level: Option<ResourceLevel>, max_resource: Capacity) -> Option<ResourceLevel> {
+    match level {
+        Some(ResourceLevel::Consumed(_)) => Some(ResourceLevel::Consumed(0)),
+        _ => Some(ResourceLevel::Remaining(max_resource)),
+    }
+}
+
+fn get_filled_state(state: &ScenarioState, inventory: &Inventory) -> ScenarioState {
+    ScenarioState {
+        energy: fill_resource(state.energy, inventory.max_energy),
+        reserves: fill_resource(state.reserves, inventory.max_reserves),
+        missiles: fill_resource(state.missiles, inventory.max_missiles),
+        supers: fill_resource(state.supers, inventory.max_supers),
+        power_bombs: fill_resource(state.power_bombs, inventory.max_power_bombs),
+        ..state.clone()
+    }
+}
+
+fn random_resource_level(
+    rng: &mut StdRng,
+    min_remaining: Capacity,
+    max_resource: Capacity,
+) -> ResourceLevel {
+    let remaining = rng.gen_range(min_remaining..=max_resource);
+    if rng.gen_bool(0.5) {
+        ResourceLevel::Remaining(remaining)
+    } else {
+        ResourceLevel::Consumed(max_resource - remaining)
+    }
+}
+
+/// Generates a random case by walking a few links from a random starting vertex,
+/// and choosing a random inventory and starting state.
+fn generate_fuzz_case(game_data: &GameData, rng: &mut StdRng) -> Option<FuzzCase> {
+    let first_link = game_data.base_links_data.links.choose(rng)?;
+    let start_vertex_key = &game_data.vertex_isv.keys[first_link.from_vertex_id];
+    if !start_vertex_key.actions.is_empty() {
+        return None;
+    }
+
+    // Scenarios can only express vertices without actions, so the path ends at the last such vertex visited.
+    let path_len = rng.gen_range(1..=FUZZ_MAX_PATH_LINKS);
+    let mut links: Vec<Link> = vec![];
+    let mut end_vertex_id: Option<VertexId> = None;
+    let mut link = first_link;
+    loop {
+        links.push(link.clone());
+        if game_data.vertex_isv.keys[link.to_vertex_id]
+            .actions
+            .is_empty()
+        {
+            end_vertex_id = Some(link.to_vertex_id);
+        }
+        if links.len() >= path_len {
+            break;
+        }
+        let Some((_, next_link)) =
+            game_data.base_links_data.links_by_src[link.to_vertex_id].choose(rng)
+        else {
+            break;
+        };
+        link = next_link;
+    }
+    let end_vertex_key = &game_data.vertex_isv.keys[end_vertex_id?];
+
+    let items: Vec<String> = game_data
+        .item_isv
+        .keys
+        .iter()
+        .filter(|_| rng.gen_bool(0.5))
+        .cloned()
+        .collect();
+    let max_energy: Capacity = 99 + 100 * rng.gen_range(0..=14);
+    let max_reserves: Capacity = 100 * rng.gen_range(0..=4);
+    let max_missiles: Capacity = 5 * rng.gen_range(0..=46);
+    let max_supers: Capacity = 5 * rng.gen_range(0..=10);
+    let max_power_bombs: Capacity = 5 * rng.gen_range(0..=10);
+
+    let start_state = ScenarioState {
+        energy: Some(random_resource_level(rng, 1, max_energy)),
+        reserves: Some(random_resource_level(rng, 0, max_reserves)),
+        missiles: Some(random_resource_level(rng, 0, max_missiles)),
+        supers: Some(random_resource_level(rng, 0, max_supers)),
+        power_bombs: Some(random_resource_level(rng, 0, max_power_bombs)),
+        shinecharge_frames_remaining: if first_link.start_with_shinecharge {
+            Some(rng.gen_range(1..=180))
+        } else {
+            None
+        },
+        flash_suit: Some(rng.gen_range(0..=1)),
+        blue_suit: Some(rng.gen_range(0..=1)),
+    };
+
+    let scenario = Scenario {
+        name: String::new(),
+        settings: ScenarioSettings::default(),
+        global_state: Some(ScenarioGlobalState {
+            items,
+            flags: vec![],
+            disabled_tech: vec![],
+            notables: vec![],
+            max_energy: Some(max_energy),
+            max_reserves: Some(max_reserves),
+            max_missiles: Some(max_missiles),
+            max_supers: Some(max_supers),
+            max_power_bombs: Some(max_power_bombs),
+            pool_max_energy: None,
+            pool_max_reserves: None,
+            pool_max_missiles: None,
+            pool_max_supers: None,
+            pool_max_power_bombs: None,
+        }),
+        locked_doors: vec![],
+        start_room_id: start_vertex_key.room_id,
+        start_node_id: start_vertex_key.node_id,
+        start_obstacles_cleared: get_obstacle_names(
+            game_data,
+            start_vertex_key.room_id,
+            start_vertex_key.obstacle_mask,
+        ),
+        start_state: Some(start_state),
+        end_room_id: end_vertex_key.room_id,
+        end_node_id: end_vertex_key.node_id,
+        end_obstacles_cleared: get_obstacle_names(
+            game_data,
+            end_vertex_key.room_id,
+            end_vertex_key.obstacle_mask,
+        ),
+        end_state: None,
+        fail: false,
+        forward: TraversalCheck::Weak,
+        reverse: TraversalCheck::Weak,
+    };
+    Some(FuzzCase { scenario, links })
+}
+
+/// Checks the forward/reverse consistency properties on a case, returning the first violation found.
+fn check_fuzz_case(game_data: &GameData, case: &FuzzCase) -> Result<Option<FuzzFailure>> {
+    let setup = FuzzSetup::new(game_data, case)?;
+    let settings = &setup.settings;
+    let inventory = &setup.global_state.inventory;
+    let start_local_state = get_local_state(&case.scenario.start_state);
+    let end_local_state = get_local_state(&case.scenario.end_state);
+    if !is_valid_fuzz_state(&start_local_state, inventory) {
+        return Ok(None);
+    }
+
+    let forward = setup.traverse(game_data, false, setup.start_vertex_id, start_local_state);
+    let forward_states = &forward.lsr[setup.end_vertex_id].local;
+    let (forward_success, _) =
+        match_final_states(forward_states, end_local_state, inventory, false, settings);
+
+    let reverse = setup.traverse(game_data, true, setup.end_vertex_id, end_local_state);
+    let reverse_states = &reverse.lsr[setup.start_vertex_id].local;
+
+    // A route feasible in the forward direction must also be feasible in reverse,
+    // with the reverse traversal requiring no more than the forward starting state.
+    if forward_success {
+        let (reverse_success, _) =
+            match_final_states(reverse_states, start_local_state, inventory, true, settings);
+        if !reverse_success {
+            let mut scenario = case.scenario.clone();
+            scenario.forward = TraversalCheck::Weak;
+            scenario.reverse = TraversalCheck::Weak;
+            return Ok(Some(FuzzFailure {
+                property: "forward traversal succeeds but reverse traversal fails",
+                details: format!(
+                    "forward final state(s): {:?}, reverse final state(s): {:?}",
+                    forward_states, reverse_states
+                ),
+                scenario,
+            }));
+        }
+    }
+
+    // Any starting state found sufficient by the reverse traversal must be sufficient going forward.
+    for reverse_local in reverse_states {
+        let state = get_scenario_state(reverse_local);
+        let local = get_local_state(&Some(state.clone()));
+        if !is_valid_fuzz_state(&local, inventory) {
+            continue;
+        }
+        let traverser = setup.traverse(game_data, false, setup.start_vertex_id, local);
+        let final_states = &traverser.lsr[setup.end_vertex_id].local;
+        let (success, _) =
+            match_final_states(final_states, end_local_state, inventory, false, settings);
+        if !success {
+            let mut scenario = case.scenario.clone();
+            scenario.start_state = Some(state);
+            scenario.forward = TraversalCheck::Weak;
+            scenario.reverse = TraversalCheck::Disabled;
+            return Ok(Some(FuzzFailure {
+                property: "reverse traversal succeeds but forward traversal fails",
+                details: format!(
+                    "reverse final state: {:?}, forward final state(s): {:?}",
+                    reverse_local, final_states
+                ),
+                scenario,
+            }));
+        }
+    }
+
+    // Starting with more resources can't make a reachable destination unreachable.
+    if forward_success {
+        let start_state = case.scenario.start_state.clone().unwrap_or_default();
+        let state = get_filled_state(&start_state, inventory);
+        let local = get_local_state(&Some(state.clone()));
+        let traverser = setup.traverse(game_data, false, setup.start_vertex_id, local);
+        let final_states = &traverser.lsr[setup.end_vertex_id].local;
+        let (success, _) =
+            match_final_states(final_states, end_local_state, inventory, false, settings);
+        if !success {
+            let mut scenario = case.scenario.clone();
+            scenario.start_state = Some(state);
+            scenario.forward = TraversalCheck::Weak;
+            scenario.reverse = TraversalCheck::Disabled;
+            return Ok(Some(FuzzFailure {
+                property: "forward traversal fails after adding resources",
+                details: format!(
+                    "original final state(s): {:?}, final state(s) with added resources: {:?}",
+                    forward_states, final_states
+                ),
+                scenario,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+fn get_shrink_candidates(case: &FuzzCase) -> Vec<FuzzCase> {
+    let mut candidates = vec![];
+    for i in 0..case.links.len() {
+        let mut c = case.clone();
+        c.links.remove(i);
+        candidates.push(c);
+    }
+    if let Some(global) = &case.scenario.global_state {
+        for i in 0..global.items.len() {
+            let mut c = case.clone();
+            c.scenario.global_state.as_mut().unwrap().items.remove(i);
+            candidates.push(c);
+        }
+        let capacities: [fn(&mut ScenarioGlobalState) -> &mut Option<Capacity>; 5] = [
+            |g| &mut g.max_energy,
+            |g| &mut g.max_reserves,
+            |g| &mut g.max_missiles,
+            |g| &mut g.max_supers,
+            |g| &mut g.max_power_bombs,
+        ];
+        for capacity in capacities {
+            let mut c = case.clone();
+            let field = capacity(c.scenario.global_state.as_mut().unwrap());
+            if field.is_some() {
+                *field = None;
+                candidates.push(c);
+            }
+        }
+    }
+    if let Some(state) = &case.scenario.start_state {
+        let resources: [fn(&mut ScenarioState) -> &mut Option<ResourceLevel>; 5] = [
+            |s| &mut s.energy,
+            |s| &mut s.reserves,
+            |s| &mut s.missiles,
+            |s| &mut s.supers,
+            |s| &mut s.power_bombs,
+        ];
+        for resource in resources {
+            let mut c = case.clone();
+            let field = resource(c.scenario.start_state.as_mut().unwrap());
+            if field.is_some() {
+                *field = None;
+                candidates.push(c);
+            }
+        }
+        if state.shinecharge_frames_remaining.is_some() {
+            let mut c = case.clone();
+            c.scenario
+                .start_state
+                .as_mut()
+                .unwrap()
+                .shinecharge_frames_remaining = None;
+            candidates.push(c);
+        }
+        if state.flash_suit.is_some() {
+            let mut c = case.clone();
+            c.scenario.start_state.as_mut().unwrap().flash_suit = None;
+            candidates.push(c);
+        }
+        if state.blue_suit.is_some() {
+            let mut c = case.clone();
+            c.scenario.start_state.as_mut().unwrap().blue_suit = None;
+            candidates.push(c);
+        }
+    }
+    candidates
+}
+
+/// Greedily simplifies a failing case (dropping links and items, and resetting capacities
+/// and resources to their defaults) for as long as the same property continues to fail.
+fn shrink_fuzz_case(
+    game_data: &GameData,
+    mut case: FuzzCase,
+    mut failure: FuzzFailure,
+) -> Result<(FuzzCase, FuzzFailure)> {
+    'outer: loop {
+        for candidate in get_shrink_candidates(&case) {
+            if let Some(new_failure) = check_fuzz_case(game_data, &candidate)?
+                && new_failure.property == failure.property
+            {
+                case = candidate;
+                failure = new_failure;
+                continue 'outer;
+            }
+        }
+        return Ok((case, failure));
+    }
+}
+
+fn get_scenario_state_json(state: &ScenarioState) -> Result<serde_json::Value> {
+    let mut out = serde_json::Map::new();
+    let fields: [(&str, serde_json::Value); 8] = [
+        ("energy", serde_json::to_value(state.energy)?),
+        ("reserves", serde_json::to_value(state.reserves)?),
+        ("missiles", serde_json::to_value(state.missiles)?),
+        ("supers", serde_json::to_value(state.supers)?),
+        ("powerBombs", serde_json::to_value(state.power_bombs)?),
+        (
+            "shinechargeFramesRemaining",
+            serde_json::to_value(state.shinecharge_frames_remaining)?,
+        ),
+        ("flashSuit", serde_json::to_value(state.flash_suit)?),
+        ("blueSuit", serde_json::to_value(state.blue_suit)?),
+    ];
+    for (key, value) in fields {
+        if !value.is_null() {
+            out.insert(key.to_string(), value);
+        }
+    }
+    Ok(serde_json::Value::Object(out))
+}
+
+fn get_scenario_json(scenario: &Scenario) -> Result<serde_json::Value> {
+    let mut out = serde_json::json!({
+        "name": scenario.name,
+        "startRoomId": scenario.start_room_id,
+        "startNodeId": scenario.start_node_id,
+        "endRoomId": scenario.end_room_id,
+        "endNodeId": scenario.end_node_id,
+    });
+    if let Some(global) = &scenario.global_state {
+        let mut global_json = serde_json::json!({ "items": global.items });
+        let capacities = [
+            ("maxEnergy", global.max_energy),
+            ("maxReserves", global.max_reserves),
+            ("maxMissiles", global.max_missiles),
+            ("maxSupers", global.max_supers),
+            ("maxPowerBombs", global.max_power_bombs),
+        ];
+        for (key, value) in capacities {
+            if let Some(x) = value {
+                global_json[key] = x.into();
+            }
+        }
+        out["globalState"] = global_json;
+    }
+    if !scenario.start_obstacles_cleared.is_empty() {
+        out["startObstaclesCleared"] = serde_json::to_value(&scenario.start_obstacles_cleared)?;
+    }
+    if let Some(state) = &scenario.start_state {
+        out["startState"] = get_scenario_state_json(state)?;
+    }
+    if !scenario.end_obstacles_cleared.is_empty() {
+        out["endObstaclesCleared"] = serde_json::to_value(&scenario.end_obstacles_cleared)?;
+    }
+    if let Some(state) = &scenario.end_state {
+        out["endState"] = get_scenario_state_json(state)?;
+    }
+    for (key, check) in [("forward", scenario.forward), ("reverse", scenario.reverse)] {
+        out[key] = match check {
+            TraversalCheck::Default => continue,
+            TraversalCheck::Disabled => "disabled",
+            TraversalCheck::Weak => "weak",
+            TraversalCheck::Exact => "exact",
+        }
+        .into();
+    }
+    Ok(out)
+}
+
+/// Writes a failing case as a logic scenario directory (a room file containing only the strats
+/// used by the case's links, plus a scenarios file), in the format used under `tests/scenarios`.
+fn write_fuzz_case(
+    game_data: &GameData,
+    case: &FuzzCase,
+    failure: &FuzzFailure,
+    path: &Path,
+) -> Result<()> {
+    std::fs::create_dir_all(path)?;
+
+    let strat_ids: HashSet<usize> = case.links.iter().filter_map(|x| x.strat_id).collect();
+    let mut room_json = game_data.room_json_map[&case.scenario.start_room_id].clone();
+    let strats: Vec<JsonValue> = room_json["strats"]
+        .members()
+        .filter(|x| x["id"].as_usize().is_some_and(|id| strat_ids.contains(&id)))
+        .cloned()
+        .collect();
+    room_json["strats"] = JsonValue::Array(strats);
+    room_json.remove("$schema");
+    std::fs::write(path.join("room.json"), room_json.pretty(2))?;
+
+    let scenarios_json = serde_json::json!({
+        "$schema": "../../schema/scenarios.schema.json",
+        "scenarios": [get_scenario_json(&failure.scenario)?],
+    });
+    std::fs::write(
+        path.join("scenarios.json"),
+        serde_json::to_string_pretty(&scenarios_json)?,
+    )?;
+    Ok(())
+}
+
+/// Randomized test of consistency between forward and reverse traversal, using short sequences of
+/// links sampled from the real game data, with random inventories and starting states. Failing cases
+/// are shrunk and written out as logic scenarios under "tmp/fuzz-scenarios" in the MapRandomizer
+/// directory, from where they can be moved into `tests/scenarios` as regression tests.
+///
+/// This test is marked as ignored by default because it loads the full game data and is time-consuming.
+/// The number of cases and the random seed can be set with the FUZZ_ITERATIONS and FUZZ_SEED
+/// environment variables.
+#[test]
+#[ignore]
+fn fuzz_forward_reverse_consistency() -> Result<()> {
+    let mut game_data = GameData::load(Path::new(".."))?;
+    game_data.make_links_data(&|_, _| (0, 1));
+    let seed: u64 = env::var("FUZZ_SEED").map_or(Ok(0), |x| x.parse())?;
+    let iterations: usize = env::var("FUZZ_ITERATIONS").map_or(Ok(1000), |x| x.parse())?;
+    let output_path = Path::new("../../tmp/fuzz-scenarios");
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut cnt_checked = 0;
+    let mut cnt_fail = 0;
+    for i in 0..iterations {
+        let Some(case) = generate_fuzz_case(&game_data, &mut rng) else {
+            continue;
+        };
+        cnt_checked += 1;
+        let Some(failure) = check_fuzz_case(&game_data, &case)? else {
+            continue;
+        };
+        let (case, mut failure) = shrink_fuzz_case(&game_data, case, failure)?;
+        failure.scenario.name = format!("Fuzz case {}-{}: {}", seed, i, failure.property);
+        let case_path = output_path.join(format!("case-{}-{}", seed, i));
+        write_fuzz_case(&game_data, &case, &failure, &case_path)?;
+        println!(
+            "🔴 FAILED: {}: {} (written to {})",
+            failure.property,
+            failure.details,
+            case_path.display()
+        );
+        cnt_fail += 1;
+    }
+    println!("Checked {} fuzz cases", cnt_checked);
+    if cnt_fail > 0 {
+        bail!("🔴 {} fuzz case(s) failed", cnt_fail);
+    }
+    Ok(())
+}
@@ -0,0 +1,208 @@
+use std::path::Path;
+
+use anyhow::Result;
+use maprando::map_areas::{
+    assign_areas, assign_subareas, solve_area_mapping, validate_area_assignment,
+};
+use maprando::map_validation::{MapIssueKind, get_map_issues};
+use maprando::patch::NUM_AREAS;
+use maprando::preset::PresetData;
+use maprando::randomize::assign_map_areas;
+use maprando::settings::{
+    AreaAssignmentBaseOrder, AreaConstraints, AreaPartition, AreaPin, AreaRoomLimit, AreaSeparation,
+};
+use maprando_game::{GameData, Map};
+use rand::{SeedableRng, rngs::StdRng};
+
+fn load_vanilla_map(game_data: &GameData) -> Result<Map> {
+    let map_str = game_data
+        .data_source()
+        .read_to_string("maps/vanilla/vanilla_map.json")?;
+    Ok(serde_json::from_str(&map_str)?)
+}
+
+/// Test that area assignment on the vanilla layout uses every area, with values in range.
+#[test]
+fn test_assign_areas() -> Result<()> {
+    let game_data = GameData::load(Path::new(".."))?;
+    for partition in [AreaPartition::Size, AreaPartition::Items] {
+        let mut map = load_vanilla_map(&game_data)?;
+        let mut rng = StdRng::seed_from_u64(0);
+        assign_areas(&mut map, &game_data, partition, &mut rng)?;
+
+        for area in 0..NUM_AREAS {
+            for subarea in 0..2 {
+                for subsubarea in 0..2 {
+                    assert!(
+                        (0..map.area.len()).any(|i| map.area[i] == area
+                            && map.subarea[i] == subarea
+                            && map.subsubarea[i] == subsubarea),
+                        "{partition:?}: area {area}/{subarea}/{subsubarea} unused"
+                    );
+                }
+            }
+        }
+        assert_eq!(map.subarea[game_data.ship_room_idx], 0);
+        assert_eq!(map.subsubarea[game_data.ship_room_idx], 0);
+        // The vanilla layout isn't arranged for the new areas, so they may not fit on the pause
+        // map (which is why regrouping is rejected for it in settings), but the other checks
+        // should pass:
+        let issues: Vec<_> = get_map_issues(&map, &game_data)
+            .into_iter()
+            .filter(|x| x.kind != MapIssueKind::Bounds && x.kind != MapIssueKind::Overlap)
+            .collect();
+        assert!(
+            issues.is_empty(),
+            "{partition:?}: unexpected issues: {issues:?}"
+        );
+    }
+    Ok(())
+}
+
+/// Test that subareas can be reassigned while keeping the map's areas.
+#[test]
+fn test_assign_subareas() -> Result<()> {
+    let game_data = GameData::load(Path::new(".."))?;
+    let mut map = load_vanilla_map(&game_data)?;
+    let mut rng = StdRng::seed_from_u64(1);
+    assign_areas(&mut map, &game_data, AreaPartition::Size, &mut rng)?;
+    let areas = map.area.clone();
+    assign_subareas(&mut map, &game_data, &mut rng)?;
+    assert_eq!(map.area, areas);
+    assert!(map.subarea.iter().all(|&x| x < 2));
+    assert!(map.subsubarea.iter().all(|&x| x < 2));
+
+    // Areas must be contiguous, which moving the ship into a far-away area breaks:
+    let mut all_ok = true;
+    for area in 0..NUM_AREAS {
+        map.area[game_data.ship_room_idx] = area;
+        all_ok &= assign_subareas(&mut map, &game_data, &mut rng).is_ok();
+    }
+    assert!(!all_ok);
+    Ok(())
+}
+
+/// Test that area mappings are chosen to satisfy constraints, or reported as infeasible.
+#[test]
+fn test_solve_area_mapping() -> Result<()> {
+    let game_data = GameData::load(Path::new(".."))?;
+    let map = load_vanilla_map(&game_data)?;
+    let identity: Vec<usize> = (0..NUM_AREAS).collect();
+    let preferred = vec![1, 0, 2, 3, 4, 5];
+    let kraid_room_idx = game_data
+        .room_geometry
+        .iter()
+        .position(|x| x.name == "Kraid Room")
+        .unwrap();
+
+    // The preferred mapping is kept when there are no constraints:
+    let mapping = solve_area_mapping(
+        &map,
+        &game_data,
+        &AreaConstraints::default(),
+        &[],
+        &preferred,
+    )?;
+    assert_eq!(mapping, preferred);
+
+    // Pinning Kraid to Brinstar leaves Crateria and Brinstar as in vanilla:
+    let mut constraints = AreaConstraints::default();
+    constraints.pinned_rooms.push(AreaPin {
+        room: "Kraid Room".to_string(),
+        area: "Brinstar".to_string(),
+    });
+    let mapping = solve_area_mapping(&map, &game_data, &constraints, &[], &preferred)?;
+    assert_eq!(mapping[map.area[kraid_room_idx]], 1);
+    assert_eq!(mapping, identity);
+
+    // Limiting Tourian to the size of the smallest area puts the smallest area there:
+    let mut room_counts = [0; NUM_AREAS];
+    for i in 0..map.area.len() {
+        room_counts[map.area[i]] += 1;
+    }
+    let min_count = *room_counts.iter().min().unwrap();
+    constraints.room_limits.push(AreaRoomLimit {
+        area: "Tourian".to_string(),
+        min_rooms: None,
+        max_rooms: Some(min_count),
+    });
+    let mapping = solve_area_mapping(&map, &game_data, &constraints, &[], &identity)?;
+    let tourian_source = mapping.iter().position(|&x| x == 5).unwrap();
+    assert_eq!(room_counts[tourian_source], min_count);
+
+    // The ship can't be pinned to Tourian while also being kept away from it:
+    let mut constraints = AreaConstraints::default();
+    constraints.pinned_rooms.push(AreaPin {
+        room: "Landing Site".to_string(),
+        area: "Tourian".to_string(),
+    });
+    constraints.separated_rooms.push(AreaSeparation {
+        room: "Landing Site".to_string(),
+        area: "Tourian".to_string(),
+    });
+    assert!(solve_area_mapping(&map, &game_data, &constraints, &[], &identity).is_err());
+    Ok(())
+}
+
+/// Test that constraints are applied on the vanilla layout, where the areas are otherwise kept.
+#[test]
+fn test_assign_map_areas_vanilla_constraints() -> Result<()> {
+    let game_data = GameData::load(Path::new(".."))?;
+    let preset_data = PresetData::load(game_data.data_source().as_ref(), &game_data)?;
+    let mut settings = preset_data.default_preset.clone();
+    settings.map_layout = "Vanilla".to_string();
+    let area_assignment = &mut settings.other_settings.area_assignment;
+    area_assignment.partition = AreaPartition::Map;
+    area_assignment.base_order = AreaAssignmentBaseOrder::Size;
+    let kraid_room_idx = game_data
+        .room_geometry
+        .iter()
+        .position(|x| x.name == "Kraid Room")
+        .unwrap();
+
+    let vanilla_map = load_vanilla_map(&game_data)?;
+    let mut map = vanilla_map.clone();
+    assert!(assign_map_areas(&mut map, &settings, 0, &game_data));
+    assert_eq!(map.area, vanilla_map.area);
+
+    settings
+        .other_settings
+        .area_assignment
+        .constraints
+        .pinned_rooms
+        .push(AreaPin {
+            room: "Kraid Room".to_string(),
+            area: "Norfair".to_string(),
+        });
+    let mut map = vanilla_map.clone();
+    assert!(assign_map_areas(&mut map, &settings, 0, &game_data));
+    assert_eq!(map.area[kraid_room_idx], 2);
+    assert_ne!(map.area, vanilla_map.area);
+    Ok(())
+}
+
+/// Test that regrouping the areas is rejected on the Vanilla layout, where the areas would not fit
+/// on the pause map.
+#[test]
+fn test_validate_area_assignment() -> Result<()> {
+    let game_data = GameData::load(Path::new(".."))?;
+    let preset_data = PresetData::load(game_data.data_source().as_ref(), &game_data)?;
+    let mut area_assignment = preset_data
+        .default_preset
+        .other_settings
+        .area_assignment
+        .clone();
+    for partition in [
+        AreaPartition::Map,
+        AreaPartition::Size,
+        AreaPartition::Items,
+    ] {
+        area_assignment.partition = partition;
+        assert!(validate_area_assignment(&area_assignment, "Standard", &game_data).is_ok());
+        assert_eq!(
+            validate_area_assignment(&area_assignment, "Vanilla", &game_data).is_ok(),
+            partition == AreaPartition::Map
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use std::path::Path;
+
+use anyhow::Result;
+use maprando::map_format::{map_from_text, map_to_text, read_avro_maps, write_avro_maps};
+use maprando_game::{GameData, Map};
+
+/// Test that a map converts to Avro and to text and back without changes.
+#[test]
+fn test_map_format_roundtrip() -> Result<()> {
+    let game_data = GameData::load(Path::new(".."))?;
+    let map_str = game_data
+        .data_source()
+        .read_to_string("maps/vanilla/vanilla_map.json")?;
+    let map: Map = serde_json::from_str(&map_str)?;
+    let expected = serde_json::to_string(&map)?;
+
+    let text = map_to_text(&map, &game_data)?;
+    assert!(text.contains("room \"Landing Site\""));
+    let text_map = map_from_text(&text, &game_data)?;
+    assert_eq!(serde_json::to_string(&text_map)?, expected);
+
+    let avro_data = write_avro_maps(&[map.clone(), text_map], &game_data)?;
+    let avro_maps = read_avro_maps(&avro_data, &game_data)?;
+    assert_eq!(avro_maps.len(), 2);
+    for avro_map in &avro_maps {
+        assert_eq!(serde_json::to_string(avro_map)?, expected);
+    }
+
+    let err = map_from_text("room \"Nowhere\" x=0 y=0", &game_data).unwrap_err();
+    assert!(format!("{err:#}").contains("Unknown room"));
+    Ok(())
+}
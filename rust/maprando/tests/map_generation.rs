@@ -0,0 +1,27 @@
+use std::path::Path;
+
+use anyhow::Result;
+use maprando::map_generation::{MapGeneratorSettings, generate_map};
+use maprando::map_validation::get_map_issues;
+use maprando::randomize::Randomizer;
+use maprando_game::GameData;
+use rand::{SeedableRng, rngs::StdRng};
+
+/// Test that a map generated from a fixed seed includes every room and passes validation, with
+/// the Toilet passing through exactly one room.
+#[test]
+fn test_generate_map() -> Result<()> {
+    let game_data = GameData::load(Path::new(".."))?;
+    let settings = MapGeneratorSettings::new(&game_data);
+    let mut rng = StdRng::seed_from_u64(0);
+    let map = generate_map(&settings, &game_data, &mut rng)?;
+
+    assert!(map.room_mask.iter().all(|&x| x));
+    let issues = get_map_issues(&map, &game_data);
+    assert!(issues.is_empty(), "unexpected issues: {issues:?}");
+    assert_eq!(
+        Randomizer::get_toilet_intersections(&map, &game_data).len(),
+        1
+    );
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use std::path::Path;
+
+use anyhow::Result;
+use maprando::map_index::{MapQuery, get_map_properties};
+use maprando::map_repository::MapRepository;
+use maprando::settings::AreaPartition;
+use maprando_game::GameData;
+
+/// Test building the index of the vanilla repository and selecting maps from it.
+#[test]
+fn test_map_index_query() -> Result<()> {
+    let game_data = GameData::load(Path::new(".."))?;
+    let mut repository =
+        MapRepository::new("Vanilla", game_data.data_source().clone(), "maps/vanilla")?;
+    let index = repository.build_index(&game_data)?;
+    assert_eq!(index.entries.len(), 1);
+    let entry = &index.entries[0];
+    assert_eq!(entry.id, "maps-0.avro:0");
+    assert_eq!(entry.properties.num_rooms, game_data.room_geometry.len());
+    assert!(entry.properties.area_size_ratio >= 1.0);
+    assert!(entry.properties.hub_distance > 0);
+    repository.index = Some(index.clone());
+
+    let map = repository.get_map_by_id(&entry.id, &game_data)?;
+    assert_eq!(get_map_properties(&map, &game_data), entry.properties);
+
+    let query = MapQuery {
+        max_hub_distance: Some(entry.properties.hub_distance),
+        ..MapQuery::default()
+    };
+    let (id, _) = repository.select_map(12345, &query, &game_data)?;
+    assert_eq!(id, entry.id);
+
+    let query = MapQuery {
+        max_hub_distance: Some(entry.properties.hub_distance - 1),
+        ..MapQuery::default()
+    };
+    assert!(repository.find_maps(&query)?.is_empty());
+    assert!(repository.select_map(0, &query, &game_data).is_err());
+    assert!(
+        repository
+            .get_map_by_id("maps-0.avro:1", &game_data)
+            .is_err()
+    );
+    Ok(())
+}
+
+/// Test that the area size ratio can't be queried when the rooms are regrouped into new areas,
+/// as it's only known for the areas stored with the maps.
+#[test]
+fn test_map_query_area_partition() {
+    let query = MapQuery::balanced_areas();
+    assert!(query.validate(AreaPartition::Map).is_ok());
+    assert!(query.validate(AreaPartition::Size).is_err());
+    assert!(query.validate(AreaPartition::Items).is_err());
+    let query = MapQuery {
+        max_hub_distance: Some(10),
+        ..MapQuery::default()
+    };
+    assert!(query.validate(AreaPartition::Size).is_ok());
+}
@@ -0,0 +1,56 @@
+use std::path::Path;
+
+use anyhow::Result;
+use maprando::map_validation::{MapIssueKind, get_map_issues};
+use maprando_game::{GameData, Map};
+
+fn issue_kinds(map: &Map, game_data: &GameData) -> Vec<MapIssueKind> {
+    get_map_issues(map, game_data)
+        .into_iter()
+        .map(|x| x.kind)
+        .collect()
+}
+
+/// Test that the vanilla map passes validation, and that breaking it in various ways is caught.
+#[test]
+fn test_map_validation() -> Result<()> {
+    let game_data = GameData::load(Path::new(".."))?;
+    let map_str = game_data
+        .data_source()
+        .read_to_string("maps/vanilla/vanilla_map.json")?;
+    let vanilla_map: Map = serde_json::from_str(&map_str)?;
+    let issues = get_map_issues(&vanilla_map, &game_data);
+    assert!(issues.is_empty(), "unexpected issues: {issues:?}");
+
+    let mut map = vanilla_map.clone();
+    map.doors.pop();
+    let kinds = issue_kinds(&map, &game_data);
+    assert!(kinds.contains(&MapIssueKind::Door));
+
+    let mut map = vanilla_map.clone();
+    let room_idx = (0..map.room_mask.len())
+        .find(|&i| map.room_mask[i])
+        .unwrap();
+    map.area[room_idx] = 7;
+    assert!(issue_kinds(&map, &game_data).contains(&MapIssueKind::AreaRange));
+
+    // Move a room on top of another room in the same area, where both have a tile in their
+    // top-left corner:
+    let mut map = vanilla_map.clone();
+    let other_idx = (0..map.room_mask.len())
+        .find(|&i| {
+            i != room_idx
+                && i != game_data.toilet_room_idx
+                && map.room_mask[i]
+                && map.area[i] == map.area[room_idx]
+                && game_data.room_geometry[i].map[0][0] == 1
+        })
+        .unwrap();
+    map.rooms[room_idx] = map.rooms[other_idx];
+    assert!(issue_kinds(&map, &game_data).contains(&MapIssueKind::Overlap));
+
+    let mut map = vanilla_map.clone();
+    map.room_mask.pop();
+    assert_eq!(issue_kinds(&map, &game_data), vec![MapIssueKind::Shape]);
+    Ok(())
+}
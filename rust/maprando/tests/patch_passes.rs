@@ -0,0 +1,115 @@
+use anyhow::Result;
+use maprando::patch::Rom;
+use maprando::patch::passes::{ConflictPolicy, PassRegistry, PassTarget, RomWriteLog};
+
+struct TestTarget {
+    rom: Rom,
+    write_log: RomWriteLog,
+    order: Vec<&'static str>,
+}
+
+impl PassTarget for TestTarget {
+    fn rom_and_write_log(&mut self) -> (&mut Rom, &mut RomWriteLog) {
+        (&mut self.rom, &mut self.write_log)
+    }
+}
+
+fn make_target(policy: ConflictPolicy) -> TestTarget {
+    TestTarget {
+        rom: Rom::new(vec![0; 0x100]),
+        write_log: RomWriteLog::new(policy),
+        order: vec![],
+    }
+}
+
+/// Test that passes run in dependency order, and that byte ownership and conflicts are recorded.
+#[test]
+fn test_pass_order_and_ownership() -> Result<()> {
+    let mut target = make_target(ConflictPolicy::Warn);
+    let mut passes: PassRegistry<TestTarget> = PassRegistry::default();
+    passes
+        .add("c", |t| {
+            t.order.push("c");
+            t.rom.write_n(0x14, &[3; 8])
+        })
+        .after(&["b"]);
+    passes.add("a", |t| {
+        t.order.push("a");
+        t.rom.write_n(0x00, &[1; 0x10])
+    });
+    passes
+        .add("b", |t| {
+            t.order.push("b");
+            t.rom.write_n(0x08, &[2; 0x10])?;
+            t.write_log.begin(&mut t.rom, "b/nested", &["b"])?;
+            t.rom.write_u16(0x10, 0x1234)?;
+            t.write_log.end(&mut t.rom)
+        })
+        .overrides(&["a"]);
+    passes
+        .add("disabled", |t| t.rom.write_u8(0, 0))
+        .enabled(false);
+    assert_eq!(passes.get_order()?, vec!["a", "b", "c", "disabled"]);
+    passes.run(&mut target)?;
+
+    assert_eq!(target.order, vec!["a", "b", "c"]);
+    assert_eq!(target.write_log.get_owner(0x07), Some("a"));
+    assert_eq!(target.write_log.get_owner(0x08), Some("b"));
+    assert_eq!(target.write_log.get_owner(0x11), Some("b/nested"));
+    assert_eq!(target.write_log.get_owner(0x18), Some("c"));
+    assert_eq!(target.write_log.get_owner(0x20), None);
+
+    // Only "c" overwrote bytes without declaring it:
+    let conflicts = target.write_log.conflicts();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].pass, "c");
+    assert_eq!(conflicts[0].previous_pass, "b");
+    assert_eq!(conflicts[0].ranges, vec![(0x14, 0x18)]);
+
+    let stats = target.write_log.get_stats();
+    let a = stats.iter().find(|s| s.name == "a").unwrap();
+    assert_eq!((a.bytes_written, a.bytes_owned), (0x10, 0x08));
+    assert!(
+        target
+            .write_log
+            .report()
+            .contains("c overwrites 4 bytes from b")
+    );
+    Ok(())
+}
+
+/// Test that writes are not tracked when conflicts are ignored.
+#[test]
+fn test_untracked_passes() -> Result<()> {
+    let mut target = make_target(ConflictPolicy::Ignore);
+    let mut passes: PassRegistry<TestTarget> = PassRegistry::default();
+    passes.add("a", |t| t.rom.write_u8(0x10, 1));
+    passes.add("b", |t| t.rom.write_u8(0x10, 2));
+    passes.run(&mut target)?;
+
+    assert_eq!(target.rom.data[0x10], 2);
+    assert!(!target.rom.track_touched);
+    assert_eq!(target.write_log.get_owner(0x10), None);
+    assert!(target.write_log.conflicts().is_empty());
+    Ok(())
+}
+
+/// Test that undeclared overlapping writes fail under the strict policy, and that bad
+/// dependencies are rejected.
+#[test]
+fn test_pass_errors() {
+    let mut target = make_target(ConflictPolicy::Strict);
+    let mut passes: PassRegistry<TestTarget> = PassRegistry::default();
+    passes.add("a", |t| t.rom.write_u8(0x10, 1));
+    passes.add("b", |t| t.rom.write_u8(0x10, 2));
+    assert!(passes.run(&mut target).is_err());
+
+    let mut passes: PassRegistry<TestTarget> = PassRegistry::default();
+    passes.add("a", |_| Ok(())).after(&["b"]);
+    passes.add("b", |_| Ok(())).after(&["a"]);
+    assert!(passes.get_order().is_err());
+
+    let mut passes: PassRegistry<TestTarget> = PassRegistry::default();
+    passes.add("a", |_| Ok(())).after(&["missing"]);
+    assert!(passes.get_order().is_err());
+}
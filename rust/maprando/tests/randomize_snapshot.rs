@@ -0,0 +1,119 @@
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use maprando::difficulty::{get_full_global, get_link_difficulty_length};
+use maprando::preset::PresetData;
+use maprando::randomize::snapshot::{decode_snapshot, encode_snapshot};
+use maprando::randomize::{
+    RandomizationCheckpoint, Randomizer, get_difficulty_tiers, get_objectives,
+    make_locked_door_data, randomize_doors,
+};
+use maprando_game::{GameData, Map};
+use rand::{SeedableRng, rngs::StdRng};
+
+fn load_data() -> Result<(GameData, PresetData)> {
+    let mut game_data = GameData::load(Path::new(".."))?;
+    let preset_data = PresetData::load(game_data.data_source().as_ref(), &game_data)?;
+    let global = get_full_global(&game_data);
+    game_data.make_links_data(&|link, game_data| {
+        get_link_difficulty_length(link, game_data, &preset_data, &global)
+    });
+    Ok((game_data, preset_data))
+}
+
+/// Test that resuming from a snapshot saved in the middle of a randomization gives the same
+/// result as the uninterrupted randomization.
+#[test]
+fn test_resume_from_snapshot() -> Result<()> {
+    let (game_data, preset_data) = load_data()?;
+    let settings = preset_data.default_preset.clone();
+    let map: Map = serde_json::from_str(
+        &game_data
+            .data_source()
+            .read_to_string("maps/vanilla/vanilla_map.json")?,
+    )?;
+    let implicit_tech = &preset_data.tech_by_difficulty["Implicit"];
+    let implicit_notables = &preset_data.notables_by_difficulty["Implicit"];
+    let difficulty_tiers = get_difficulty_tiers(
+        &settings,
+        &preset_data.difficulty_tiers,
+        &game_data,
+        implicit_tech,
+        implicit_notables,
+    );
+    let mut rng = StdRng::seed_from_u64(1);
+    let objectives = get_objectives(&settings, Some(&map), &game_data, &mut rng);
+    let locked_door_data = randomize_doors(&game_data, &map, &settings, &objectives, 1);
+    let randomizer = Randomizer::new(
+        &map,
+        &locked_door_data,
+        objectives.clone(),
+        &settings,
+        &difficulty_tiers,
+        &game_data,
+        &game_data.base_links_data,
+        &mut rng,
+    );
+
+    let mut result = None;
+    let mut snapshots: Vec<Vec<u8>> = vec![];
+    for attempt_num in 0..20 {
+        snapshots.clear();
+        let mut on_checkpoint = |checkpoint: &RandomizationCheckpoint| -> Result<()> {
+            snapshots.push(encode_snapshot(
+                &settings,
+                &map,
+                &objectives,
+                &locked_door_data.locked_doors,
+                checkpoint,
+            )?);
+            Ok(())
+        };
+        if let Ok(r) = randomizer.randomize_with_checkpoints(
+            attempt_num,
+            1 + attempt_num,
+            1,
+            true,
+            &mut on_checkpoint,
+        ) {
+            result = Some(r);
+            break;
+        }
+    }
+    let Some((randomization, spoiler_log)) = result else {
+        bail!("Randomization failed");
+    };
+    assert!(snapshots.len() >= 2);
+
+    let snapshot = decode_snapshot(&snapshots[snapshots.len() / 2])?;
+    let resumed_difficulty_tiers = get_difficulty_tiers(
+        &snapshot.settings,
+        &preset_data.difficulty_tiers,
+        &game_data,
+        implicit_tech,
+        implicit_notables,
+    );
+    let resumed_locked_door_data = make_locked_door_data(snapshot.locked_doors, &game_data);
+    let resumed_randomizer = Randomizer::new(
+        &snapshot.map,
+        &resumed_locked_door_data,
+        snapshot.objectives,
+        &snapshot.settings,
+        &resumed_difficulty_tiers,
+        &game_data,
+        &game_data.base_links_data,
+        &mut StdRng::seed_from_u64(0),
+    );
+    let (resumed_randomization, resumed_spoiler_log) =
+        resumed_randomizer.resume_from_checkpoint(snapshot.checkpoint, true, &mut |_| Ok(()))?;
+
+    assert_eq!(
+        serde_json::to_string(&resumed_randomization)?,
+        serde_json::to_string(&randomization)?
+    );
+    assert_eq!(
+        serde_json::to_string(&resumed_spoiler_log)?,
+        serde_json::to_string(&spoiler_log)?
+    );
+    Ok(())
+}
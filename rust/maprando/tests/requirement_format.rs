@@ -0,0 +1,142 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use maprando::difficulty::{get_full_global, get_link_difficulty_length};
+use maprando::preset::PresetData;
+use maprando::randomize::{Preprocessor, get_difficulty_tiers};
+use maprando_game::data_source::DirSource;
+use maprando_game::{GameData, Item, ItemId, Link, Map, Requirement};
+
+// Scenario rooms with a known requirement on their "Base" strat from node 1 to node 2, and the
+// expected JSON and compact forms of the link requirement. The strats don't set
+// `blueSuitChecked`, which adds `noBlueSuit`.
+const KNOWN_LINKS: [(&str, &str, &str); 3] = [
+    (
+        "tests/scenarios/item",
+        r#"{"and":["Morph",{"noBlueSuit":{}}]}"#,
+        "Morph & noBlueSuit()",
+    ),
+    (
+        "tests/scenarios/heat-frames",
+        r#"{"and":[{"heatFrames":9},{"noBlueSuit":{}}]}"#,
+        "heatFrames(9) & noBlueSuit()",
+    ),
+    (
+        "tests/scenarios/and/order",
+        r#"{"and":[{"ammo":{"type":"Missile","count":1}},{"refill":["Missile"]},{"noBlueSuit":{}}]}"#,
+        "ammo(type=Missile, count=1) & refill(Missile) & noBlueSuit()",
+    ),
+];
+
+fn check_round_trip(link: &Link, game_data: &GameData) -> Result<()> {
+    let req_json = link.requirement.to_json(game_data);
+    let req_json = json::parse(&req_json.dump())?;
+    let req = Requirement::from_json(&req_json, game_data)
+        .with_context(|| format!("{}: {}", link.strat_name, req_json.dump()))?;
+    assert_eq!(
+        req,
+        link.requirement,
+        "{}: {}",
+        link.strat_name,
+        req_json.dump()
+    );
+    Ok(())
+}
+
+/// Test that link requirements are serialized to the expected JSON and compact strings, for
+/// known links in scenario rooms and for a hand-built requirement.
+#[test]
+fn test_serialize_link_requirements() -> Result<()> {
+    let base_game_data = GameData::load_minimal(Path::new(".."))?;
+    for (room_path, expected_json, expected_str) in KNOWN_LINKS {
+        let mut game_data = base_game_data.clone();
+        game_data.load_room_files(
+            &DirSource::new(Path::new(room_path)),
+            &["room.json".to_string()],
+        )?;
+        let link = game_data
+            .links
+            .iter()
+            .find(|link| {
+                let from_key = &game_data.vertex_isv.keys[link.from_vertex_id];
+                let to_key = &game_data.vertex_isv.keys[link.to_vertex_id];
+                link.strat_name == "Base"
+                    && (from_key.room_id, from_key.node_id) == (0, 1)
+                    && (to_key.room_id, to_key.node_id) == (0, 2)
+            })
+            .with_context(|| format!("{room_path}: link not found"))?;
+        assert_eq!(
+            link.requirement.to_json(&game_data).dump(),
+            expected_json,
+            "{room_path}"
+        );
+        assert_eq!(
+            link.requirement.to_compact_string(&game_data),
+            expected_str,
+            "{room_path}"
+        );
+    }
+
+    let game_data = GameData::load(Path::new("..")).context("Unable to load game data")?;
+    let req = Requirement::make_and(vec![
+        Requirement::Item(Item::Morph as ItemId),
+        Requirement::make_or(vec![
+            Requirement::HeatFrames(120.into()),
+            Requirement::Item(Item::Varia as ItemId),
+        ]),
+        Requirement::Missiles(5.into()),
+        Requirement::MissileRefill(9999.into()),
+    ]);
+    assert_eq!(
+        req.to_json(&game_data).dump(),
+        r#"{"and":["Morph",{"or":[{"heatFrames":120},"Varia"]},{"ammo":{"type":"Missile","count":5}},{"refill":["Missile"]}]}"#
+    );
+    assert_eq!(
+        req.to_compact_string(&game_data),
+        "Morph & (heatFrames(120) | Varia) & ammo(type=Missile, count=5) & refill(Missile)"
+    );
+    Ok(())
+}
+
+/// Test that every link requirement within rooms is rebuilt exactly when its JSON form is parsed
+/// back.
+#[test]
+fn test_round_trip_link_requirements() -> Result<()> {
+    let game_data = GameData::load(Path::new("..")).context("Unable to load game data")?;
+    for link in &game_data.links {
+        check_round_trip(link, &game_data)?;
+    }
+    Ok(())
+}
+
+/// Test that the door links of the vanilla map, whose requirements are built from the
+/// cross-room requirements of entrance and exit conditions, are rebuilt exactly when their JSON
+/// form is parsed back.
+#[test]
+fn test_round_trip_door_link_requirements() -> Result<()> {
+    let mut game_data = GameData::load(Path::new("..")).context("Unable to load game data")?;
+    let preset_data = PresetData::load(game_data.data_source().as_ref(), &game_data)?;
+    let global = get_full_global(&game_data);
+    game_data.make_links_data(&|link, game_data| {
+        get_link_difficulty_length(link, game_data, &preset_data, &global)
+    });
+    let map: Map = serde_json::from_str(
+        &game_data
+            .data_source()
+            .read_to_string("maps/vanilla/vanilla_map.json")?,
+    )?;
+    let difficulty_tiers = get_difficulty_tiers(
+        &preset_data.default_preset,
+        &preset_data.difficulty_tiers,
+        &game_data,
+        &preset_data.tech_by_difficulty["Implicit"],
+        &preset_data.notables_by_difficulty["Implicit"],
+    );
+    let preprocessor = Preprocessor::new(&game_data, &map, &difficulty_tiers[0]);
+    let door_links = preprocessor.get_all_door_links();
+    assert!(!door_links.is_empty());
+    for link in &door_links {
+        check_round_trip(link, &game_data)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,252 @@
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use hashbrown::HashSet;
+use maprando::customize::{CustomizeSettings, TileTheme};
+use maprando::difficulty::{get_full_global, get_link_difficulty_length};
+use maprando::patch::input_rom::load_input_rom;
+use maprando::patch::inspect::{
+    inspect_rom, is_map_rando_rom, read_autotracker_settings, read_door_connections,
+    read_item_placement, read_locked_doors, read_map, read_objectives, read_seed_identifiers,
+    read_start_location,
+};
+use maprando::patch::{Rom, get_objective_flag_bit, make_rom, snes2pc};
+use maprando::preset::PresetData;
+use maprando::randomize::{
+    LockedDoor, Randomization, Randomizer, get_difficulty_tiers, get_objectives, randomize_doors,
+};
+use maprando::settings::{
+    MapPreset, Objective, RandomizerSettings, SkillPreset, StartLocationMode,
+};
+use maprando_game::{DoorPtrPair, DoorType, GameData, Item, Map};
+use rand::{SeedableRng, rngs::StdRng};
+
+/// Test reading back the objectives, seed identifiers, and autotracker settings from a stand-in ROM.
+#[test]
+fn test_read_rom_settings() -> Result<()> {
+    let mut rom = Rom::new(vec![0; 0x300000]);
+    assert!(!is_map_rando_rom(&rom));
+    rom.write_n(0x7FC0, "SUPERMETROID MAPRANDO".as_bytes())?;
+    assert!(is_map_rando_rom(&rom));
+
+    let objectives = [
+        Objective::Kraid,
+        Objective::GoldenTorizo,
+        Objective::MetroidRoom3,
+    ];
+    // Objective count, with the fast pause menu flag set:
+    rom.write_u16(snes2pc(0x82FFFC), 0x8000 | objectives.len() as isize)?;
+    for (i, &obj) in objectives.iter().enumerate() {
+        let (addr, mask) = get_objective_flag_bit(obj);
+        rom.write_u16(snes2pc(0x8FEBC0) + i * 2, addr)?;
+        rom.write_u16(snes2pc(0x8FEBE8) + i * 2, mask)?;
+    }
+    assert_eq!(read_objectives(&rom)?, objectives);
+
+    rom.write_n(snes2pc(0xDFFEF0), "abc123".as_bytes())?;
+    rom.write_n(snes2pc(0xDFFF00), &0x12345678u32.to_le_bytes())?;
+    assert_eq!(
+        read_seed_identifiers(&rom)?,
+        ("abc123".to_string(), 0x12345678)
+    );
+
+    rom.write_u8(snes2pc(0xDFFF09), SkillPreset::Expert.to_byte() as isize)?;
+    rom.write_u8(snes2pc(0xDFFF0D), MapPreset::Wild.to_byte() as isize)?;
+    let autotracker_settings = read_autotracker_settings(&rom)?;
+    assert!(autotracker_settings.skill_preset == Some(SkillPreset::Expert));
+    assert!(autotracker_settings.item_progression_preset.is_none());
+    assert!(autotracker_settings.map_layout == Some(MapPreset::Wild));
+    Ok(())
+}
+
+fn randomize(
+    settings: &RandomizerSettings,
+    game_data: &GameData,
+    preset_data: &PresetData,
+    seed: usize,
+) -> Result<Randomization> {
+    let map: Map = serde_json::from_str(
+        &game_data
+            .data_source()
+            .read_to_string("maps/vanilla/vanilla_map.json")?,
+    )?;
+    let difficulty_tiers = get_difficulty_tiers(
+        settings,
+        &preset_data.difficulty_tiers,
+        game_data,
+        &preset_data.tech_by_difficulty["Implicit"],
+        &preset_data.notables_by_difficulty["Implicit"],
+    );
+    let mut rng = StdRng::seed_from_u64(seed as u64);
+    let objectives = get_objectives(settings, Some(&map), game_data, &mut rng);
+    let locked_door_data = randomize_doors(game_data, &map, settings, &objectives, seed);
+    let randomizer = Randomizer::new(
+        &map,
+        &locked_door_data,
+        objectives,
+        settings,
+        &difficulty_tiers,
+        game_data,
+        &game_data.base_links_data,
+        &mut rng,
+    );
+    for attempt_num in 0..20 {
+        if let Ok((randomization, _)) =
+            randomizer.randomize(attempt_num, seed + attempt_num, 1, true)
+        {
+            return Ok(randomization);
+        }
+    }
+    bail!("Randomization failed for seed {seed}");
+}
+
+// Doors as unordered pairs, since the direction of a bidirectional door is not recoverable.
+fn normalize_doors(
+    doors: &[(DoorPtrPair, DoorPtrPair, bool)],
+) -> HashSet<(DoorPtrPair, DoorPtrPair, bool)> {
+    doors
+        .iter()
+        .map(|&(src, dst, bidirectional)| {
+            if bidirectional && dst < src {
+                (dst, src, bidirectional)
+            } else {
+                (src, dst, bidirectional)
+            }
+        })
+        .collect()
+}
+
+fn normalize_locked_doors(
+    locked_doors: &[LockedDoor],
+) -> Vec<(DoorPtrPair, DoorPtrPair, DoorType, bool)> {
+    let mut out: Vec<_> = locked_doors
+        .iter()
+        .filter(|d| d.door_type != DoorType::Gray)
+        .map(|d| {
+            let (src, dst) = if d.bidirectional && d.dst_ptr_pair < d.src_ptr_pair {
+                (d.dst_ptr_pair, d.src_ptr_pair)
+            } else {
+                (d.src_ptr_pair, d.dst_ptr_pair)
+            };
+            (src, dst, d.door_type, d.bidirectional)
+        })
+        .collect();
+    out.sort_by_key(|x| (x.0, x.1));
+    out
+}
+
+// Room positions relative to the top-left of the map.
+fn normalize_positions(map: &Map) -> Vec<Option<(usize, usize)>> {
+    let masked = || (0..map.rooms.len()).filter(|&i| map.room_mask[i]);
+    let min_x = masked().map(|i| map.rooms[i].0).min().unwrap_or(0);
+    let min_y = masked().map(|i| map.rooms[i].1).min().unwrap_or(0);
+    (0..map.rooms.len())
+        .map(|i| map.room_mask[i].then(|| (map.rooms[i].0 - min_x, map.rooms[i].1 - min_y)))
+        .collect()
+}
+
+/// Round-trip test: generate a ROM from a randomization, and check that inspecting the ROM
+/// recovers the randomization. This test is marked as ignored by default because it requires the
+/// vanilla ROM, which cannot be distributed. (The stand-in ROM lacks the room headers, door data,
+/// and graphics needed to write the map.)
+///
+/// The test assumes the vanilla ROM is present at "roms/vanilla.sfc" in the MapRandomizer directory.
+#[test]
+#[ignore]
+fn test_inspect_round_trip() -> Result<()> {
+    let orig_rom = load_input_rom(Path::new("../../roms/vanilla.sfc"))?;
+    let mut game_data = GameData::load(Path::new(".."))?;
+    let preset_data = PresetData::load(game_data.data_source().as_ref(), &game_data)?;
+    let global = get_full_global(&game_data);
+    game_data.make_links_data(&|link, game_data| {
+        get_link_difficulty_length(link, game_data, &preset_data, &global)
+    });
+
+    // Use locked doors, a random start, and fewer items than locations so that some are "Nothing":
+    let mut settings = preset_data.default_preset.clone();
+    settings.doors_settings = preset_data.doors_presets.last().unwrap().clone();
+    settings.start_location_settings.mode = StartLocationMode::Random;
+    for x in &mut settings.item_progression_settings.item_pool {
+        if x.item == Item::Missile {
+            x.count -= 10;
+        }
+    }
+    let randomization = randomize(&settings, &game_data, &preset_data, 1)?;
+    assert!(randomization.item_placement.contains(&Item::Nothing));
+    assert!(
+        randomization
+            .locked_doors
+            .iter()
+            .any(|d| d.door_type != DoorType::Gray)
+    );
+
+    let customize_settings = CustomizeSettings {
+        samus_sprite: None,
+        tile_theme: TileTheme::Vanilla,
+        ..CustomizeSettings::default()
+    };
+    let rom = make_rom(
+        &orig_rom,
+        &settings,
+        &customize_settings,
+        &randomization,
+        &game_data,
+        &[],
+        &[],
+    )?;
+
+    assert_eq!(
+        read_item_placement(&rom, &game_data)?,
+        randomization.item_placement
+    );
+
+    let connections = read_door_connections(&rom, &orig_rom, &game_data)?;
+    let connection_set: HashSet<(DoorPtrPair, DoorPtrPair)> = connections.into_iter().collect();
+    for &(src, dst, bidirectional) in &randomization.map.doors {
+        if src.0.is_some() && dst.1.is_some() {
+            assert!(connection_set.contains(&(src, dst)), "{src:?} -> {dst:?}");
+        }
+        if bidirectional && dst.0.is_some() && src.1.is_some() {
+            assert!(connection_set.contains(&(dst, src)), "{dst:?} -> {src:?}");
+        }
+    }
+
+    let map = read_map(&rom, &orig_rom, &game_data)?;
+    assert_eq!(map.room_mask, randomization.map.room_mask);
+    assert_eq!(map.area, randomization.map.area);
+    assert_eq!(
+        normalize_positions(&map),
+        normalize_positions(&randomization.map)
+    );
+    assert_eq!(
+        normalize_doors(&map.doors),
+        normalize_doors(&randomization.map.doors)
+    );
+
+    let locked_doors = read_locked_doors(&rom, &game_data, &map)?;
+    assert_eq!(
+        normalize_locked_doors(&locked_doors),
+        normalize_locked_doors(&randomization.locked_doors)
+    );
+
+    let start_location = read_start_location(&rom, &game_data)?;
+    assert_eq!(start_location.room_id, randomization.start_location.room_id);
+    assert_eq!(start_location.node_id, randomization.start_location.node_id);
+
+    let inspection = inspect_rom(&rom, &orig_rom, &game_data)?;
+    let inspected = &inspection.randomization;
+    assert_eq!(inspected.item_placement, randomization.item_placement);
+    assert_eq!(inspected.objectives, randomization.objectives);
+    assert_eq!(inspected.map.area, randomization.map.area);
+    assert_eq!(
+        normalize_locked_doors(&inspected.locked_doors),
+        normalize_locked_doors(&randomization.locked_doors)
+    );
+    assert_eq!(
+        inspected.start_location.name,
+        randomization.start_location.name
+    );
+    assert_eq!(inspected.display_seed, randomization.display_seed);
+    assert_eq!(inspected.seed_name, randomization.seed_name);
+    Ok(())
+}
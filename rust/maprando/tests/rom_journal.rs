@@ -0,0 +1,224 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+use maprando::customize::CustomizeSettings;
+use maprando::difficulty::{get_full_global, get_link_difficulty_length};
+use maprando::patch::passes::ConflictPolicy;
+use maprando::patch::{MAX_LOCKED_DOORS, make_rom_journal, pc2snes};
+use maprando::preset::PresetData;
+use maprando::randomize::{
+    Randomization, Randomizer, get_difficulty_tiers, get_objectives, randomize_doors,
+};
+use maprando::settings::RandomizerSettings;
+use maprando_game::{GameData, Map};
+use rand::{SeedableRng, rngs::StdRng};
+
+// Passes whose writes are compared against the snapshots:
+const JOURNAL_PASSES: [&str; 5] = [
+    "place_items",
+    "write_door_data",
+    "apply_map_tile_patches",
+    "apply_locked_doors",
+    "apply_credits",
+];
+
+fn randomize(
+    settings: &RandomizerSettings,
+    game_data: &GameData,
+    preset_data: &PresetData,
+    seed: usize,
+) -> Result<Randomization> {
+    let map: Map = serde_json::from_str(
+        &game_data
+            .data_source()
+            .read_to_string("maps/vanilla/vanilla_map.json")?,
+    )?;
+    let difficulty_tiers = get_difficulty_tiers(
+        settings,
+        &preset_data.difficulty_tiers,
+        game_data,
+        &preset_data.tech_by_difficulty["Implicit"],
+        &preset_data.notables_by_difficulty["Implicit"],
+    );
+    let mut rng = StdRng::seed_from_u64(seed as u64);
+    let objectives = get_objectives(settings, Some(&map), game_data, &mut rng);
+    let locked_door_data = randomize_doors(game_data, &map, settings, &objectives, seed);
+    let randomizer = Randomizer::new(
+        &map,
+        &locked_door_data,
+        objectives,
+        settings,
+        &difficulty_tiers,
+        game_data,
+        &game_data.base_links_data,
+        &mut rng,
+    );
+    for attempt_num in 0..20 {
+        if let Ok((randomization, _)) =
+            randomizer.randomize(attempt_num, seed + attempt_num, 1, true)
+        {
+            return Ok(randomization);
+        }
+    }
+    bail!("Randomization failed for seed {seed}");
+}
+
+// Compare against the snapshot. Set MAPRANDO_UPDATE_GOLDEN=1 to write the snapshots, when adding a
+// scenario or after an intended change.
+fn check_golden(name: &str, journal_text: &str) -> Result<()> {
+    let path = PathBuf::from("tests/golden/rom_journal").join(format!("{name}.txt"));
+    if std::env::var("MAPRANDO_UPDATE_GOLDEN").is_ok_and(|x| x == "1") {
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(&path, journal_text)?;
+        return Ok(());
+    }
+    if !path.exists() {
+        bail!(
+            "{name}: missing snapshot {} (run with MAPRANDO_UPDATE_GOLDEN=1 to create it)",
+            path.display()
+        );
+    }
+    let expected = std::fs::read_to_string(&path)?;
+    for (i, (a, b)) in expected.lines().zip(journal_text.lines()).enumerate() {
+        if a != b {
+            bail!(
+                "{name}: journal differs from {} at line {}:\n  expected: {a}\n  actual:   {b}",
+                path.display(),
+                i + 1
+            );
+        }
+    }
+    if expected.lines().count() != journal_text.lines().count() {
+        bail!(
+            "{name}: journal has {} lines, expected {}",
+            journal_text.lines().count(),
+            expected.lines().count()
+        );
+    }
+    Ok(())
+}
+
+fn load_data() -> Result<(GameData, PresetData)> {
+    let mut game_data = GameData::load(Path::new(".."))?;
+    let preset_data = PresetData::load(game_data.data_source().as_ref(), &game_data)?;
+    let global = get_full_global(&game_data);
+    game_data.make_links_data(&|link, game_data| {
+        get_link_difficulty_length(link, game_data, &preset_data, &global)
+    });
+    Ok((game_data, preset_data))
+}
+
+/// Golden test of the ROM writes for fixed seeds and settings, using a zero-filled stand-in ROM
+/// so that it runs without the vanilla ROM.
+#[test]
+fn test_rom_journal() -> Result<()> {
+    let (game_data, preset_data) = load_data()?;
+
+    let mut scenarios = vec![("default".to_string(), preset_data.default_preset.clone())];
+    if let Some(doors) = preset_data.doors_presets.last() {
+        let mut settings = preset_data.default_preset.clone();
+        settings.doors_settings = doors.clone();
+        let name = doors.preset.clone().unwrap_or("custom".to_string());
+        scenarios.push((format!("doors-{}", name.to_lowercase()), settings));
+    }
+
+    for (name, settings) in &scenarios {
+        let randomization = randomize(settings, &game_data, &preset_data, 1)?;
+        let journal = make_rom_journal(
+            settings,
+            &CustomizeSettings::default(),
+            &randomization,
+            &game_data,
+            ConflictPolicy::Warn,
+        )?;
+        // A snapshot of failed passes alone would be meaningless, so these must have written data:
+        for pass in [
+            "write_door_data",
+            "apply_map_tile_patches",
+            "apply_locked_doors",
+        ] {
+            if let Some((_, err)) = journal.failed_passes.iter().find(|(p, _)| p == pass) {
+                bail!("{name}: {pass} failed: {err}");
+            }
+            if !journal.writes.iter().any(|w| w.pass == pass) {
+                bail!("{name}: {pass} wrote nothing");
+            }
+        }
+        check_golden(name, &journal.to_text(Some(&JOURNAL_PASSES)))?;
+    }
+    Ok(())
+}
+
+/// Test patching with a lock on every randomizable door connection, beyond the former limit of
+/// 120 locked doors.
+#[test]
+fn test_all_doors_locked() -> Result<()> {
+    let (game_data, preset_data) = load_data()?;
+    let settings = preset_data.default_preset.clone();
+    let mut randomization = randomize(&settings, &game_data, &preset_data, 1)?;
+
+    let mut all_doors_settings = settings.clone();
+    all_doors_settings.doors_settings.red_doors_count = 10000;
+    let objectives = randomization.objectives.clone();
+    let locked_door_data = randomize_doors(
+        &game_data,
+        &randomization.map,
+        &all_doors_settings,
+        &objectives,
+        1,
+    );
+    let num_locked_doors = locked_door_data.locked_doors.len();
+    assert!(num_locked_doors > 120);
+    assert!(num_locked_doors <= MAX_LOCKED_DOORS);
+    randomization.locked_doors = locked_door_data.locked_doors;
+
+    let journal = make_rom_journal(
+        &settings,
+        &CustomizeSettings::default(),
+        &randomization,
+        &game_data,
+        ConflictPolicy::Warn,
+    )?;
+    for (pass, err) in &journal.failed_passes {
+        if [
+            "apply_locked_doors",
+            "apply_map_tile_patches",
+            "apply_extra_setup_asm",
+        ]
+        .contains(&pass.as_str())
+        {
+            bail!("{pass} failed: {err}");
+        }
+    }
+    // Door unlock tiles are in the dynamic tile data region of bank $EA:
+    assert!(
+        journal
+            .writes
+            .iter()
+            .any(|w| w.pass == "apply_map_tile_patches"
+                && (0xEAAE00..0xEB0000).contains(&pc2snes(w.addr)))
+    );
+    Ok(())
+}
+
+/// Test that ROM generation has no undeclared overlapping writes between patch passes, using the
+/// strict conflict policy (under which a conflict fails the pass that causes it).
+#[test]
+fn test_no_patch_conflicts() -> Result<()> {
+    let (game_data, preset_data) = load_data()?;
+    let settings = preset_data.default_preset.clone();
+    let randomization = randomize(&settings, &game_data, &preset_data, 1)?;
+    let journal = make_rom_journal(
+        &settings,
+        &CustomizeSettings::default(),
+        &randomization,
+        &game_data,
+        ConflictPolicy::Strict,
+    )?;
+    for (pass, err) in &journal.failed_passes {
+        if err.contains(" overwrites ") {
+            bail!("{pass} failed: {err}");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use maprando::patch::snes2pc;
+use maprando::patch::symbols::SymbolTable;
+
+/// Test loading labels from an asar WLA symbol file, skipping anonymous labels and RAM.
+#[test]
+fn test_load_wla() {
+    let text = "\
+; wla symbolic information file
+; generated by asar
+
+[labels]
+84:f380 spawn_plm
+84:f390 spawn_plm.loop
+84:f3a0 :pos_1_0
+7e:1f00 ram_var
+
+[source files]
+0000 abcd1234 door.asm
+";
+    let mut symbols = SymbolTable::default();
+    symbols.load_wla(text, "gray_doors").unwrap();
+    let names: Vec<(&str, usize)> = symbols
+        .symbols
+        .iter()
+        .map(|s| (s.name.as_str(), s.addr))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("gray_doors_spawn_plm", snes2pc(0x84F380)),
+            ("gray_doors_spawn_plm_loop", snes2pc(0x84F390)),
+        ]
+    );
+}
+
+/// Test that symbols are written in address order in both output formats.
+#[test]
+fn test_symbol_formats() {
+    let mut symbols = SymbolTable::default();
+    symbols.add("extra_setup_asm_7D408", snes2pc(0xB89000), 0x10);
+    symbols.add("objective_count", snes2pc(0x82FFFC), 2);
+    symbols.add("label", snes2pc(0x8F8000), 0);
+
+    assert_eq!(
+        symbols.to_sym(),
+        "; generated by Map Rando\n\n[labels]\n\
+         82:fffc objective_count\n\
+         8f:8000 label\n\
+         b8:9000 extra_setup_asm_7D408\n"
+    );
+    assert_eq!(
+        symbols.to_mlb(),
+        "SnesPrgRom:17FFC-17FFD:objective_count\n\
+         SnesPrgRom:78000:label\n\
+         SnesPrgRom:1C1000-1C100F:extra_setup_asm_7D408\n"
+    );
+}
@@ -0,0 +1,94 @@
+use std::collections::HashSet;
+use std::io::Cursor;
+use std::path::Path;
+
+use anyhow::Result;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageOutputFormat, Rgb, RgbImage};
+use maprando::patch::title::{TITLE_BACKGROUND_DIM, load_title_background};
+use maprando_game::data_source::default_data_source;
+use ndarray::Array3;
+
+fn encode_png(img: RgbImage) -> Vec<u8> {
+    let mut out = Cursor::new(vec![]);
+    DynamicImage::ImageRgb8(img)
+        .write_to(&mut out, ImageOutputFormat::Png)
+        .unwrap();
+    out.into_inner()
+}
+
+fn get_colors_and_tiles(img: &Array3<u8>) -> (HashSet<[u8; 3]>, HashSet<Vec<[u8; 3]>>) {
+    let (height, width, _) = img.dim();
+    let mut colors: HashSet<[u8; 3]> = HashSet::new();
+    let mut tiles: HashSet<Vec<[u8; 3]>> = HashSet::new();
+    for tile_y in 0..height / 8 {
+        for tile_x in 0..width / 8 {
+            let mut tile = vec![];
+            for y in tile_y * 8..tile_y * 8 + 8 {
+                for x in tile_x * 8..tile_x * 8 + 8 {
+                    let c = [img[[y, x, 0]], img[[y, x, 1]], img[[y, x, 2]]];
+                    colors.insert(c);
+                    tile.push(c);
+                }
+            }
+            tiles.insert(tile);
+        }
+    }
+    (colors, tiles)
+}
+
+/// Test that a user-supplied title image is resized and reduced to the title screen palette, and
+/// that data which isn't an image is rejected.
+#[test]
+fn test_load_title_background() -> Result<()> {
+    let (height, width) = TITLE_BACKGROUND_DIM;
+    let map_station = Array3::<u8>::zeros((height, width, 3));
+
+    // A horizontal gradient has many colors but few distinct tiles:
+    let gradient = RgbImage::from_fn(300, 100, |x, _| {
+        Rgb([(x % 256) as u8, 100, 255 - (x % 256) as u8])
+    });
+    let img = load_title_background(&encode_png(gradient), &map_station)?;
+    assert_eq!(img.dim(), (height, width, 3));
+    let (colors, _) = get_colors_and_tiles(&img);
+    assert!(colors.len() < 128);
+
+    assert!(load_title_background(b"not an image", &map_station).is_err());
+    Ok(())
+}
+
+/// Test that a detailed image, with many more distinct tiles than the title screen supports, is
+/// reduced to fit while staying close to the original.
+#[test]
+fn test_load_detailed_title_background() -> Result<()> {
+    let (height, width) = TITLE_BACKGROUND_DIM;
+    let source = default_data_source(Path::new(".."));
+    // Resampling by a non-integer factor breaks up the tile grid of the original artwork:
+    let art = image::load_from_memory(&source.read("gfx/title/Title3.png")?)?
+        .resize_exact(300, 262, FilterType::Triangle)
+        .to_rgb8();
+    let expected = DynamicImage::ImageRgb8(art.clone())
+        .resize_to_fill(width as u32, height as u32, FilterType::Lanczos3)
+        .to_rgb8();
+    let mut expected_img = Array3::<u8>::zeros((height, width, 3));
+    for (x, y, pixel) in expected.enumerate_pixels() {
+        for c in 0..3 {
+            expected_img[[y as usize, x as usize, c]] = pixel[c];
+        }
+    }
+    assert!(get_colors_and_tiles(&expected_img).1.len() > 500);
+
+    let map_station = Array3::<u8>::zeros((height, width, 3));
+    let img = load_title_background(&encode_png(art), &map_station)?;
+    let (colors, tiles) = get_colors_and_tiles(&img);
+    assert!(colors.len() <= 128);
+    assert!(tiles.len() <= 256);
+    let total_error: u64 = img
+        .iter()
+        .zip(expected_img.iter())
+        .map(|(&a, &b)| (a as i64 - b as i64).unsigned_abs())
+        .sum();
+    let mean_error = total_error as f64 / img.len() as f64;
+    assert!(mean_error < 8.0, "mean error {mean_error}");
+    Ok(())
+}
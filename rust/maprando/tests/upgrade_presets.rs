@@ -10,10 +10,7 @@ use maprando_game::GameData;
 fn test_upgrade_old_presets() -> Result<()> {
     let base_path = Path::new("..");
     let game_data = GameData::load(base_path).context("Unable to load game data")?;
-    let tech_path = base_path.join("data/tech_data.json");
-    let notable_path = base_path.join("data/notable_data.json");
-    let presets_path = base_path.join("data/presets");
-    let preset_data = PresetData::load(&tech_path, &notable_path, &presets_path, &game_data)?;
+    let preset_data = PresetData::load(game_data.data_source().as_ref(), &game_data)?;
 
     for entry in std::fs::read_dir("tests/presets")? {
         let entry = entry?;
@@ -46,10 +43,7 @@ fn test_upgrade_old_presets() -> Result<()> {
 fn test_upgrade_current_presets() -> Result<()> {
     let base_path = Path::new("..");
     let game_data = GameData::load(base_path).context("Unable to load game data")?;
-    let tech_path = base_path.join("data/tech_data.json");
-    let notable_path = base_path.join("data/notable_data.json");
-    let presets_path = base_path.join("data/presets");
-    let preset_data = PresetData::load(&tech_path, &notable_path, &presets_path, &game_data)?;
+    let preset_data = PresetData::load(game_data.data_source().as_ref(), &game_data)?;
 
     for preset in preset_data.full_presets.iter() {
         println!("Checking preset: {}", preset.name.as_ref().unwrap());
@@ -97,17 +97,29 @@ async function prepareCustomize(form) {
     }
 
     let romData = await localforage.getItem('vanillaRomData');
-    let hashBuffer = await window.crypto.subtle.digest("SHA-256", romData);
-    const hashArray = Array.from(new Uint8Array(hashBuffer)); // convert buffer to byte array
-    const hashHex = hashArray
-        .map((b) => b.toString(16).padStart(2, "0"))
-        .join(""); // convert bytes to hex string
+    if (romData !== null && romData.byteLength % 0x8000 == 512) {
+        // Strip the copier header, as the server does.
+        romData = romData.slice(512);
+    }
+    let hashHex = "";
+    if (romData !== null) {
+        let hashBuffer = await window.crypto.subtle.digest("SHA-256", romData);
+        const hashArray = Array.from(new Uint8Array(hashBuffer)); // convert buffer to byte array
+        hashHex = hashArray
+            .map((b) => b.toString(16).padStart(2, "0"))
+            .join(""); // convert bytes to hex string
+    }
     if (hashHex != "12b77c4bc9c1832cee8881244659065ee1d84c70c3d29e6eaf92e6798cc2ca72") {
         console.log("ROM hash: " + hashHex);
+        // Forget the cached ROM, so that it isn't loaded again on the next visit.
+        await localforage.removeItem('vanillaRomName');
+        await localforage.removeItem('vanillaRomData');
+        romEl.value = "";
         inputRomModal.show();
         document.getElementById("romInvalid").classList.remove("d-none");
-        return;
+        return false;
     }
+    document.getElementById("romInvalid").classList.add("d-none");
     form.submit();
 }
 window.onload = (event) => {